@@ -138,6 +138,18 @@ build_exceptions! {
 
     /// ParquetFileInvalid is used when given parquet file is invalid.
     ParquetFileInvalid(1201),
+    /// ParquetSchemaMismatch is used when parquet files read together
+    /// have different schemas.
+    ParquetSchemaMismatch(1202),
+    /// ParquetUnsupported is used when a parquet file uses an encoding
+    /// or a compression codec that we can't decode.
+    ParquetUnsupported(1203),
+    /// ParquetDecryptionFailed is used when given parquet file is encrypted
+    /// and can't be decrypted.
+    ParquetDecryptionFailed(1204),
+    /// ParquetChecksumMismatch is used when the CRC checksum of a page
+    /// doesn't match its data.
+    ParquetChecksumMismatch(1205),
 
     // Table related errors starts here.
 
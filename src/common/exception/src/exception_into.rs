@@ -165,6 +165,7 @@ impl From<opendal::Error> for ErrorCode {
             opendal::ErrorKind::ObjectPermissionDenied => {
                 ErrorCode::StoragePermissionDenied(error.to_string())
             }
+            _ if error.is_temporary() => ErrorCode::StorageUnavailable(error.to_string()),
             _ => ErrorCode::StorageOther(error.to_string()),
         }
     }
@@ -179,6 +180,10 @@ impl From<std::io::Error> for ErrorCode {
         match error.kind() {
             ErrorKind::NotFound => ErrorCode::StorageNotFound(msg),
             ErrorKind::PermissionDenied => ErrorCode::StoragePermissionDenied(msg),
+            ErrorKind::TimedOut
+            | ErrorKind::Interrupted
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted => ErrorCode::StorageUnavailable(msg),
             _ => ErrorCode::StorageOther(msg),
         }
     }
@@ -136,3 +136,25 @@ fn test_from_and_to_status() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_from_storage_error() {
+    use common_exception::exception::*;
+
+    let e: ErrorCode = opendal::Error::new(opendal::ErrorKind::ObjectNotFound, "foo").into();
+    assert_eq!(ErrorCode::STORAGE_NOT_FOUND, e.code());
+
+    let e: ErrorCode = opendal::Error::new(opendal::ErrorKind::Unexpected, "foo")
+        .set_temporary()
+        .into();
+    assert_eq!(ErrorCode::STORAGE_UNAVAILABLE, e.code());
+
+    let e: ErrorCode = opendal::Error::new(opendal::ErrorKind::Unexpected, "foo").into();
+    assert_eq!(ErrorCode::STORAGE_OTHER, e.code());
+
+    let e: ErrorCode = std::io::Error::from(std::io::ErrorKind::TimedOut).into();
+    assert_eq!(ErrorCode::STORAGE_UNAVAILABLE, e.code());
+
+    let e: ErrorCode = std::io::Error::from(std::io::ErrorKind::NotFound).into();
+    assert_eq!(ErrorCode::STORAGE_NOT_FOUND, e.code());
+}
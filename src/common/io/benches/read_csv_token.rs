@@ -0,0 +1,104 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[macro_use]
+extern crate criterion;
+
+use std::borrow::Cow;
+use std::io::Cursor;
+
+use common_io::cursor_ext::BufferReadStringExt;
+use common_io::cursor_ext::ReadBytesExt;
+use criterion::Criterion;
+
+const ROWS: usize = 10_000;
+
+/// Benchmark reading the fields of a typical CSV as borrowed tokens by `read_csv_token`,
+/// and as copies by `read_csv_field`.
+///
+/// Most fields are unquoted or quoted without escapes and are borrowed, the ones with
+/// doubled quotas are owned. How often each happens is printed first.
+fn bench_read_csv_token(c: &mut Criterion) {
+    let csv = typical_csv();
+    let (borrowed, owned) = count_tokens(&csv);
+    println!(
+        "read_csv_token: {} borrowed, {} owned ({:.1}% borrowed)",
+        borrowed,
+        owned,
+        borrowed as f64 * 100.0 / (borrowed + owned) as f64
+    );
+
+    let mut group = c.benchmark_group("read_csv_token");
+    group.bench_function("token", |b| b.iter(|| count_tokens(&csv)));
+    group.bench_function("field", |b| b.iter(|| read_fields(&csv)));
+    group.finish();
+}
+
+/// Rows of an id, a name, a quoted address with a delimiter, and a quoted comment which
+/// quotes someone every tenth row.
+fn typical_csv() -> Vec<u8> {
+    let mut csv = String::new();
+    for row in 0..ROWS {
+        let comment = if row % 10 == 0 {
+            format!(r#""said ""hello {}""""#, row)
+        } else {
+            format!(r#""comment {}""#, row)
+        };
+        csv.push_str(&format!(
+            "{},name_{},\"{} Main St, Springfield\",{}\n",
+            row, row, row, comment
+        ));
+    }
+    csv.into_bytes()
+}
+
+/// The numbers of borrowed and owned tokens of `csv`.
+fn count_tokens(csv: &[u8]) -> (usize, usize) {
+    let mut cursor = Cursor::new(csv);
+    let (mut borrowed, mut owned) = (0, 0);
+    while !cursor.eof() {
+        loop {
+            match cursor.read_csv_token(b'"', b',').unwrap() {
+                Cow::Borrowed(_) => borrowed += 1,
+                Cow::Owned(_) => owned += 1,
+            }
+            if !cursor.ignore_byte(b',') {
+                break;
+            }
+        }
+        cursor.ignore_byte(b'\n');
+    }
+    (borrowed, owned)
+}
+
+/// The number of bytes of the fields of `csv`.
+fn read_fields(csv: &[u8]) -> usize {
+    let mut cursor = Cursor::new(csv);
+    let mut bytes = 0;
+    while !cursor.eof() {
+        loop {
+            let mut buf = vec![];
+            cursor.read_csv_field(&mut buf, b'"', b',').unwrap();
+            bytes += buf.len();
+            if !cursor.ignore_byte(b',') {
+                break;
+            }
+        }
+        cursor.ignore_byte(b'\n');
+    }
+    bytes
+}
+
+criterion_group!(benches, bench_read_csv_token);
+criterion_main!(benches);
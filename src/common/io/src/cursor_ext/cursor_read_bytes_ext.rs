@@ -27,6 +27,13 @@ pub trait ReadBytesExt {
     fn ignore_white_spaces(&mut self) -> bool;
     fn until(&mut self, delim: u8, buf: &mut Vec<u8>) -> usize;
     fn keep_read(&mut self, buf: &mut Vec<u8>, f: impl Fn(u8) -> bool) -> usize;
+    /// Skip `n` records terminated by `delim`, without copying them. The `delim`s in the
+    /// fields quoted by `"` don't terminate the records, a field is quoted only if it starts
+    /// by `"`.
+    ///
+    /// The last record may end by the end of the buffer instead. Returns the number of
+    /// records skipped, less than `n` if the buffer ends before.
+    fn skip_records(&mut self, n: usize, delim: u8) -> usize;
     fn eof(&mut self) -> bool;
     fn must_eof(&mut self) -> Result<()>;
     fn must_ignore(&mut self, f: impl Fn(u8) -> bool) -> Result<()> {
@@ -41,15 +48,7 @@ pub trait ReadBytesExt {
 
     fn must_ignore_byte(&mut self, b: u8) -> Result<()>;
 
-    fn must_ignore_bytes(&mut self, bs: &[u8]) -> Result<()> {
-        if !self.ignore_bytes(bs) {
-            return Err(std::io::Error::new(
-                ErrorKind::InvalidData,
-                format!("Expected to have bytes {:?}", bs),
-            ));
-        }
-        Ok(())
-    }
+    fn must_ignore_bytes(&mut self, bs: &[u8]) -> Result<()>;
 
     fn must_ignore_insensitive_bytes(&mut self, bs: &[u8]) -> Result<()> {
         if !self.ignore_insensitive_bytes(bs) {
@@ -147,6 +146,23 @@ where T: AsRef<[u8]>
         Ok(())
     }
 
+    fn must_ignore_bytes(&mut self, bs: &[u8]) -> Result<()> {
+        if !self.ignore_bytes(bs) {
+            let available = self.remaining_slice();
+            let found = &available[..bs.len().min(available.len())];
+            return Err(std::io::Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "Expected to have bytes '{}', got '{}' at pos {}",
+                    String::from_utf8_lossy(bs),
+                    String::from_utf8_lossy(found),
+                    self.position()
+                ),
+            ));
+        }
+        Ok(())
+    }
+
     fn ignore_insensitive_bytes(&mut self, bs: &[u8]) -> bool {
         let available = self.remaining_slice();
         let len = bs.len();
@@ -191,4 +207,38 @@ where T: AsRef<[u8]>
         self.consume(to_read);
         to_read
     }
+
+    fn skip_records(&mut self, n: usize, delim: u8) -> usize {
+        let remaining_slice = self.remaining_slice();
+        let mut skipped = 0;
+        let mut to_skip = 0;
+        let mut record_end = 0;
+        let mut quoted = false;
+        // Only a quote at the start of a field, or right after the closing quote of a
+        // doubled one, enters a quoted field. The others are a part of the unquoted ones.
+        let mut may_quote = true;
+        while skipped < n && to_skip < remaining_slice.len() {
+            let b = remaining_slice[to_skip];
+            to_skip += 1;
+            if quoted {
+                if b == b'"' {
+                    quoted = false;
+                    may_quote = true;
+                }
+                continue;
+            }
+            if b == b'"' && may_quote {
+                quoted = true;
+            } else if b == delim {
+                skipped += 1;
+                record_end = to_skip;
+            }
+            may_quote = b == delim || b == b',';
+        }
+        if skipped < n && to_skip > record_end {
+            skipped += 1;
+        }
+        self.consume(to_skip);
+        skipped
+    }
 }
@@ -19,11 +19,72 @@ use common_exception::ErrorCode;
 use common_exception::Result;
 use lexical_core::FromLexical;
 
+/// The radix prefixes of the integers read by [`ReadNumberExt::read_radix_int_text`], which
+/// are matched in any case.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RadixPrefix {
+    /// `0x`, hexadecimal.
+    Hex,
+    /// `0o`, octal.
+    Octal,
+    /// `0b`, binary.
+    Binary,
+}
+
+impl RadixPrefix {
+    /// All the prefixes.
+    pub const ALL: [RadixPrefix; 3] = [RadixPrefix::Hex, RadixPrefix::Octal, RadixPrefix::Binary];
+
+    fn prefix(&self) -> &'static str {
+        match self {
+            RadixPrefix::Hex => "0x",
+            RadixPrefix::Octal => "0o",
+            RadixPrefix::Binary => "0b",
+        }
+    }
+
+    pub fn radix(&self) -> u32 {
+        match self {
+            RadixPrefix::Hex => 16,
+            RadixPrefix::Octal => 8,
+            RadixPrefix::Binary => 2,
+        }
+    }
+}
+
 pub trait ReadNumberExt {
     fn read_int_text<T: FromLexical>(&mut self) -> Result<T>;
     fn read_float_text<T: FromLexical>(&mut self) -> Result<T>;
 
     fn read_num_text_exact<T: FromLexical>(&mut self) -> Result<T>;
+
+    /// Read an unquoted decimal field, up to the `delimiter` or the end of the line which
+    /// are not consumed, and append it to `buf` normalized for `DECIMAL(precision, scale)`.
+    ///
+    /// The field is `[+-]digits[.digits]`, with at most `precision - scale` digits before
+    /// the point and `scale` digits after it. It is normalized without the `+` sign and the
+    /// leading zeros, and with `scale` digits after the point, `+01.5` is `1.50` for
+    /// `DECIMAL(5, 2)`. The error of a malformed field has the position of the first byte
+    /// in error, and nothing is consumed.
+    fn read_decimal_text(
+        &mut self,
+        buf: &mut Vec<u8>,
+        precision: usize,
+        scale: usize,
+        delimiter: u8,
+    ) -> Result<()>;
+
+    /// Read an unquoted integer, up to the `delimiter` or the end of the line which are not
+    /// consumed, in decimal or in the radix of one of `prefixes`.
+    ///
+    /// The integer is `[+-][prefix]digits`, `-0x1F` is `-31` if [`RadixPrefix::Hex`] is
+    /// accepted. The error of an invalid digit has its position, the one of a value out of
+    /// the range of `T` the position of the integer, and nothing is consumed.
+    fn read_radix_int_text<T: TryFrom<i128>>(
+        &mut self,
+        prefixes: &[RadixPrefix],
+        delimiter: u8,
+    ) -> Result<T>;
 }
 
 pub fn collect_number(buffer: &[u8]) -> (usize, usize) {
@@ -98,6 +159,141 @@ fn read_num_text_exact<T: FromLexical>(buf: &[u8]) -> Result<T> {
     }
 }
 
+/// Normalize the decimal `field` for `DECIMAL(precision, scale)`, see
+/// [`ReadNumberExt::read_decimal_text`].
+///
+/// Returns the position in `field` of the first byte in error, with the cause.
+fn normalize_decimal(
+    field: &[u8],
+    precision: usize,
+    scale: usize,
+) -> std::result::Result<Vec<u8>, (usize, String)> {
+    let len = field.len();
+    let mut pos = 0;
+    let negative = match field.first() {
+        Some(b'-') => {
+            pos += 1;
+            true
+        }
+        Some(b'+') => {
+            pos += 1;
+            false
+        }
+        _ => false,
+    };
+
+    let int_start = pos;
+    while pos < len && field[pos].is_ascii_digit() {
+        pos += 1;
+    }
+    let int_digits = &field[int_start..pos];
+    let mut frac_start = pos;
+    let mut frac_digits: &[u8] = &[];
+    if pos < len && field[pos] == b'.' {
+        pos += 1;
+        frac_start = pos;
+        while pos < len && field[pos].is_ascii_digit() {
+            pos += 1;
+        }
+        frac_digits = &field[frac_start..pos];
+    }
+    if int_digits.is_empty() && frac_digits.is_empty() {
+        return Err((pos, "expect digits".to_string()));
+    }
+    if pos < len {
+        return Err((pos, format!("unexpected byte {:?}", field[pos] as char)));
+    }
+
+    let leading_zeros = int_digits.iter().take_while(|b| **b == b'0').count();
+    let int_digits = &int_digits[leading_zeros..];
+    if int_digits.len() > precision - scale {
+        return Err((
+            int_start + leading_zeros,
+            format!("more than {} integral digits", precision - scale),
+        ));
+    }
+    if frac_digits.len() > scale {
+        return Err((
+            frac_start + scale,
+            format!("more than {} fractional digits", scale),
+        ));
+    }
+
+    let mut decimal = Vec::with_capacity(precision + 3);
+    let is_zero = int_digits.is_empty() && frac_digits.iter().all(|b| *b == b'0');
+    if negative && !is_zero {
+        decimal.push(b'-');
+    }
+    if int_digits.is_empty() {
+        decimal.push(b'0');
+    } else {
+        decimal.extend_from_slice(int_digits);
+    }
+    if scale > 0 {
+        decimal.push(b'.');
+        decimal.extend_from_slice(frac_digits);
+        decimal.resize(decimal.len() + scale - frac_digits.len(), b'0');
+    }
+    Ok(decimal)
+}
+
+/// Parse the integer `token`, see [`ReadNumberExt::read_radix_int_text`].
+///
+/// Returns the position in `token` of the first byte in error, with the cause.
+fn parse_radix_int<T: TryFrom<i128>>(
+    token: &[u8],
+    prefixes: &[RadixPrefix],
+) -> std::result::Result<T, (usize, String)> {
+    let mut pos = 0;
+    let negative = match token.first() {
+        Some(b'-') => {
+            pos += 1;
+            true
+        }
+        Some(b'+') => {
+            pos += 1;
+            false
+        }
+        _ => false,
+    };
+    let radix = match prefixes.iter().find(|prefix| {
+        let prefix = prefix.prefix().as_bytes();
+        token.len() >= pos + prefix.len()
+            && token[pos..pos + prefix.len()].eq_ignore_ascii_case(prefix)
+    }) {
+        Some(prefix) => {
+            pos += prefix.prefix().len();
+            prefix.radix()
+        }
+        None => 10,
+    };
+    if pos == token.len() {
+        return Err((pos, "expect digits".to_string()));
+    }
+
+    // The magnitude is bounded by the one of `i128::MIN`, larger than the ones of `T`.
+    let out_of_range = || (0, format!("out of range, in radix {}", radix));
+    let mut magnitude: u128 = 0;
+    for (i, byte) in token.iter().enumerate().skip(pos) {
+        let digit = (*byte as char).to_digit(radix).ok_or_else(|| {
+            (
+                i,
+                format!("unexpected byte {:?} in radix {}", *byte as char, radix),
+            )
+        })?;
+        magnitude = magnitude
+            .checked_mul(radix as u128)
+            .and_then(|magnitude| magnitude.checked_add(digit as u128))
+            .filter(|magnitude| *magnitude <= i128::MIN.unsigned_abs())
+            .ok_or_else(out_of_range)?;
+    }
+    let value = match negative {
+        true => (magnitude as i128).wrapping_neg(),
+        false => i128::try_from(magnitude).map_err(|_| out_of_range())?,
+    };
+    T::try_from(value).map_err(|_| out_of_range())
+}
+
 impl<B> ReadNumberExt for Cursor<B>
 where B: AsRef<[u8]>
 {
@@ -120,4 +316,63 @@ where B: AsRef<[u8]>
         let buf = self.remaining_slice();
         read_num_text_exact(buf)
     }
+
+    fn read_decimal_text(
+        &mut self,
+        buf: &mut Vec<u8>,
+        precision: usize,
+        scale: usize,
+        delimiter: u8,
+    ) -> Result<()> {
+        if precision == 0 || scale > precision {
+            return Err(ErrorCode::BadArguments(format!(
+                "Invalid DECIMAL({}, {}), expect 0 < precision and scale <= precision",
+                precision, scale
+            )));
+        }
+        let start = self.position() as usize;
+        let remaining = self.remaining_slice();
+        let len = remaining
+            .iter()
+            .position(|b| *b == delimiter || *b == b'\n' || *b == b'\r')
+            .unwrap_or(remaining.len());
+        let field = &remaining[..len];
+        let decimal = normalize_decimal(field, precision, scale).map_err(|(pos, cause)| {
+            ErrorCode::BadBytes(format!(
+                "Cannot parse value:{:?} to DECIMAL({}, {}) at position {}, cause: {}",
+                String::from_utf8_lossy(field),
+                precision,
+                scale,
+                start + pos,
+                cause
+            ))
+        })?;
+        buf.extend_from_slice(&decimal);
+        self.consume(len);
+        Ok(())
+    }
+
+    fn read_radix_int_text<T: TryFrom<i128>>(
+        &mut self,
+        prefixes: &[RadixPrefix],
+        delimiter: u8,
+    ) -> Result<T> {
+        let start = self.position() as usize;
+        let remaining = self.remaining_slice();
+        let len = remaining
+            .iter()
+            .position(|b| *b == delimiter || *b == b'\n' || *b == b'\r')
+            .unwrap_or(remaining.len());
+        let token = &remaining[..len];
+        let value = parse_radix_int(token, prefixes).map_err(|(pos, cause)| {
+            ErrorCode::BadBytes(format!(
+                "Cannot parse value:{:?} to integer at position {}, cause: {}",
+                String::from_utf8_lossy(token),
+                start + pos,
+                cause
+            ))
+        })?;
+        self.consume(len);
+        Ok(value)
+    }
 }
@@ -12,119 +12,370 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::borrow::Cow;
 use std::io::BufRead;
 use std::io::Cursor;
 use std::io::ErrorKind;
-use std::io::Read;
 use std::io::Result;
 
 use crate::cursor_ext::cursor_read_bytes_ext::ReadBytesExt;
 
+/// How [`BufferReadStringExt::read_escaped_string_text_with`] treats the NUL bytes of a
+/// text.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NulBytes {
+    /// Keep them in the string.
+    #[default]
+    Allow,
+    /// Fail on them, raw or escaped by `\0` or `\x00`. They often mean that binary data is
+    /// read as text.
+    Reject,
+}
+
+/// The spellings of the booleans read by [`BufferReadStringExt::read_bool_text`], which
+/// are matched in any case.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BoolSpelling {
+    /// `true` and `false`.
+    TrueFalse,
+    /// `t` and `f`.
+    TrueFalseInitials,
+    /// `1` and `0`.
+    OneZero,
+    /// `yes` and `no`.
+    YesNo,
+}
+
+impl BoolSpelling {
+    /// All the spellings.
+    pub const ALL: [BoolSpelling; 4] = [
+        BoolSpelling::TrueFalse,
+        BoolSpelling::TrueFalseInitials,
+        BoolSpelling::OneZero,
+        BoolSpelling::YesNo,
+    ];
+
+    /// The spelling of `true` and the one of `false`.
+    fn words(&self) -> (&'static str, &'static str) {
+        match self {
+            BoolSpelling::TrueFalse => ("true", "false"),
+            BoolSpelling::TrueFalseInitials => ("t", "f"),
+            BoolSpelling::OneZero => ("1", "0"),
+            BoolSpelling::YesNo => ("yes", "no"),
+        }
+    }
+
+    /// The boolean spelled by `token` in any case, if it is spelled this way.
+    fn parse(&self, token: &[u8]) -> Option<bool> {
+        let (true_word, false_word) = self.words();
+        if token.eq_ignore_ascii_case(true_word.as_bytes()) {
+            Some(true)
+        } else if token.eq_ignore_ascii_case(false_word.as_bytes()) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
+
 pub trait BufferReadStringExt {
-    fn read_quoted_text(&mut self, buf: &mut Vec<u8>, quota: u8) -> Result<()>;
-    fn read_escaped_string_text(&mut self, buf: &mut Vec<u8>) -> Result<()>;
+    /// Read a string literal quoted by `quota`, unescaping its escapes.
+    ///
+    /// The literal can span lines: only the closing `quota` ends it, the new lines before it
+    /// are part of the value.
+    ///
+    /// Fails with [`ErrorKind::UnexpectedEof`] if the input ends before the opening or the
+    /// closing `quota`: an empty input is not an empty string, which is two `quota`s.
+    fn read_quoted_text(&mut self, buf: &mut Vec<u8>, quota: u8) -> Result<()> {
+        self.read_quoted_text_with(buf, quota, usize::MAX)
+    }
+    /// Read a string literal like [`Self::read_quoted_text`], of at most `max_len` bytes
+    /// between its quotas, so that a missing closing `quota` doesn't read the rest of the
+    /// input into the value.
+    ///
+    /// A longer literal fails with [`ErrorKind::InvalidData`] and the position of its
+    /// opening `quota`, which the cursor is moved back to. Nothing is added to `buf` then.
+    fn read_quoted_text_with(
+        &mut self,
+        buf: &mut Vec<u8>,
+        quota: u8,
+        max_len: usize,
+    ) -> Result<()>;
+    /// Read a CSV field like [`Self::read_csv_field`], borrowed from the buffer if it has
+    /// nothing to unescape, owned if its escapes or doubled `quota`s had to be rewritten.
+    fn read_csv_token(&mut self, quota: u8, delimiter: u8) -> Result<Cow<'_, [u8]>>;
+    /// Read an escaped string text up to a tab, a new line or the end of the input.
+    ///
+    /// An empty input is an empty field, read as an empty string. An input ending by an
+    /// unfinished escape fails with [`ErrorKind::UnexpectedEof`].
+    fn read_escaped_string_text(&mut self, buf: &mut Vec<u8>) -> Result<()> {
+        self.read_escaped_string_text_with(buf, NulBytes::Allow)
+    }
+    /// Read an escaped string text like [`Self::read_escaped_string_text`], with its NUL
+    /// bytes treated as `nul_bytes` says.
+    fn read_escaped_string_text_with(
+        &mut self,
+        buf: &mut Vec<u8>,
+        nul_bytes: NulBytes,
+    ) -> Result<()>;
+    /// Read a CSV field, quoted by `quota` or not, up to the `delimiter` or the end of
+    /// the line, which are not consumed.
+    ///
+    /// A doubled `quota` in a quoted field is a `quota` of the value.
+    fn read_csv_field(&mut self, buf: &mut Vec<u8>, quota: u8, delimiter: u8) -> Result<()>;
+    /// Read an unquoted boolean, up to the `delimiter` or the end of the line which are not
+    /// consumed, spelled in any case as one of `spellings`.
+    ///
+    /// Any other token fails with [`ErrorKind::InvalidData`] and the position of its first
+    /// byte, and nothing is consumed.
+    fn read_bool_text(&mut self, spellings: &[BoolSpelling], delimiter: u8) -> Result<bool>;
 }
 
 impl<T> BufferReadStringExt for Cursor<T>
 where T: AsRef<[u8]>
 {
-    fn read_quoted_text(&mut self, buf: &mut Vec<u8>, quota: u8) -> Result<()> {
+    fn read_quoted_text_with(
+        &mut self,
+        buf: &mut Vec<u8>,
+        quota: u8,
+        max_len: usize,
+    ) -> Result<()> {
+        if self.eof() {
+            return Err(std::io::Error::new(
+                ErrorKind::UnexpectedEof,
+                format!(
+                    "Unexpected EOF, expected a string literal quoted by {:?}",
+                    quota as char
+                ),
+            ));
+        }
+        let start = self.position();
+        let buf_len = buf.len();
         self.must_ignore_byte(quota)?;
 
         loop {
-            self.keep_read(buf, |b| b != quota && b != b'\\');
+            // Stop at the first byte over `max_len`, before copying it.
+            let remaining = self.remaining_slice();
+            let len = remaining
+                .iter()
+                .position(|b| *b == quota || *b == b'\\')
+                .unwrap_or(remaining.len());
+            let read = (self.position() - start - 1) as usize;
+            if read.saturating_add(len) > max_len {
+                buf.truncate(buf_len);
+                self.set_position(start);
+                return Err(std::io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "String literal quoted by {:?} at position {} is longer than {} bytes, its closing quota may be missing",
+                        quota as char, start, max_len
+                    ),
+                ));
+            }
+            buf.extend_from_slice(&remaining[..len]);
+            self.consume(len);
             if self.ignore_byte(quota) {
                 return Ok(());
             } else if self.ignore_byte(b'\\') {
                 let b = self.remaining_slice();
                 if b.is_empty() {
                     return Err(std::io::Error::new(
-                        ErrorKind::InvalidData,
+                        ErrorKind::UnexpectedEof,
                         "Expected to have terminated string literal after escaped char '\' ."
                             .to_string(),
                     ));
                 }
-                let c = b[0];
-                self.ignore_byte(c);
-
-                match c {
-                    b'n' => buf.push(b'\n'),
-                    b't' => buf.push(b'\t'),
-                    b'r' => buf.push(b'\r'),
-                    b'0' => buf.push(b'\0'),
-                    b'\'' => buf.push(b'\''),
-                    b'\\' => buf.push(b'\\'),
-                    b'\"' => buf.push(b'\"'),
-                    _ => {
-                        buf.push(b'\\');
-                        buf.push(c);
-                    }
-                }
+                let len = decode_escape(b, buf);
+                self.consume(len);
             } else {
                 break;
             }
         }
         Err(std::io::Error::new(
-            ErrorKind::InvalidData,
+            ErrorKind::UnexpectedEof,
             format!(
-                "Expected to have terminated string literal after quota {:?}, while consumed buf: {:?}",
+                "Unexpected EOF, expected to have terminated string literal after quota {:?}, while consumed buf: {:?}",
                 quota as char, buf
             ),
         ))
     }
 
-    fn read_escaped_string_text(&mut self, buf: &mut Vec<u8>) -> Result<()> {
+    fn read_csv_token(&mut self, quota: u8, delimiter: u8) -> Result<Cow<'_, [u8]>> {
+        let start = self.position() as usize;
+        let remaining = self.remaining_slice();
+        if remaining.first() != Some(&quota) {
+            let len = remaining
+                .iter()
+                .position(|b| *b == delimiter || *b == b'\n' || *b == b'\r')
+                .unwrap_or(remaining.len());
+            self.consume(len);
+            return Ok(Cow::Borrowed(&self.get_ref().as_ref()[start..start + len]));
+        }
+
+        // The value of a quoted field is borrowed up to the closing quota, unless an escape
+        // or a doubled quota comes first.
+        let len = remaining[1..].iter().position(|b| *b == quota || *b == b'\\');
+        match len {
+            Some(len) if remaining[1 + len] == quota && remaining.get(2 + len) != Some(&quota) => {
+                self.consume(len + 2);
+                Ok(Cow::Borrowed(&self.get_ref().as_ref()[start + 1..start + 1 + len]))
+            }
+            _ => {
+                let mut buf = vec![];
+                self.read_csv_field(&mut buf, quota, delimiter)?;
+                Ok(Cow::Owned(buf))
+            }
+        }
+    }
+
+    fn read_csv_field(&mut self, buf: &mut Vec<u8>, quota: u8, delimiter: u8) -> Result<()> {
+        if self.remaining_slice().first() != Some(&quota) {
+            self.keep_read(buf, |b| b != delimiter && b != b'\n' && b != b'\r');
+            return Ok(());
+        }
+
+        self.read_quoted_text(buf, quota)?;
+        // The quota following the closing one is doubled, the value goes on after it.
+        while self.remaining_slice().first() == Some(&quota) {
+            buf.push(quota);
+            self.read_quoted_text(buf, quota)?;
+        }
+        Ok(())
+    }
+
+    fn read_bool_text(&mut self, spellings: &[BoolSpelling], delimiter: u8) -> Result<bool> {
+        let start = self.position();
+        let remaining = self.remaining_slice();
+        let len = remaining
+            .iter()
+            .position(|b| *b == delimiter || *b == b'\n' || *b == b'\r')
+            .unwrap_or(remaining.len());
+        let token = &remaining[..len];
+        match spellings.iter().find_map(|spelling| spelling.parse(token)) {
+            Some(value) => {
+                self.consume(len);
+                Ok(value)
+            }
+            None => {
+                let expected = spellings
+                    .iter()
+                    .map(|spelling| {
+                        let (true_word, false_word) = spelling.words();
+                        format!("{}/{}", true_word, false_word)
+                    })
+                    .collect::<Vec<_>>();
+                Err(std::io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "Cannot parse value:{:?} to boolean at position {}, expect one of {}",
+                        String::from_utf8_lossy(token),
+                        start,
+                        expected.join(", ")
+                    ),
+                ))
+            }
+        }
+    }
+
+    fn read_escaped_string_text_with(
+        &mut self,
+        buf: &mut Vec<u8>,
+        nul_bytes: NulBytes,
+    ) -> Result<()> {
+        let start = buf.len();
         loop {
             self.keep_read(buf, |f| f != b'\t' && f != b'\n' && f != b'\\');
             if self.ignore_byte(b'\\') {
                 let buffer = self.remaining_slice();
-                let c = buffer[0];
-                match c {
-                    b'\'' | b'\"' | b'\\' | b'/' | b'`' => {
-                        buf.push(c);
-                        self.consume(1);
-                    }
-                    b'N' => {
-                        self.consume(1);
-                    }
-                    b'x' => {
-                        self.consume(1);
-                        let mut b = [0u8; 2];
-                        self.read_exact(&mut b[..])?;
-                        let high = hex_char_to_digit(b[0]);
-                        let low = hex_char_to_digit(b[1]);
-                        let c = high * 0x10 + low;
-                        buf.push(c);
-                    }
-                    _ => {
-                        let e = unescape(c);
-                        if !is_control_ascii(e) {
-                            buf.push(b'\\');
-                        }
-                        buf.push(e);
-                        self.consume(1);
-                    }
+                if buffer.is_empty() {
+                    return Err(std::io::Error::new(
+                        ErrorKind::UnexpectedEof,
+                        format!(
+                            "Unexpected EOF after escape char '\\' of string text, while consumed buf: {:?}",
+                            &buf[start..]
+                        ),
+                    ));
                 }
+                // The text formats mark NULL by `\N`, it is not part of the value.
+                if buffer[0] == b'N' {
+                    self.consume(1);
+                    continue;
+                }
+                let len = decode_escape(buffer, buf);
+                self.consume(len);
             } else {
                 break;
             }
         }
+        if nul_bytes == NulBytes::Reject {
+            if let Some(pos) = buf[start..].iter().position(|b| *b == b'\0') {
+                return Err(std::io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "Unexpected NUL byte at position {} of string text, while consumed buf: {:?}",
+                        pos,
+                        &buf[start..]
+                    ),
+                ));
+            }
+        }
         Ok(())
     }
 }
 
-fn unescape(c: u8) -> u8 {
+/// Decode the escape sequence at the start of `escaped`, which follows a `\`, into `buf`,
+/// returning the number of bytes of `escaped` it takes. Nothing is taken from an empty
+/// `escaped`.
+///
+/// This is the escape policy of both [`BufferReadStringExt::read_quoted_text`] and
+/// [`BufferReadStringExt::read_escaped_string_text`]:
+/// - `\n`, `\t`, `\r`, `\0`, `\a`, `\b`, `\v`, `\f` and `\e` are the control characters
+///   of C.
+/// - `\\`, `\'`, `\"`, ``\` `` and `\/` are the escaped characters.
+/// - `\xHH` is the byte of the hexadecimal digits `HH`.
+/// - `\N` is kept as it is. It is the NULL of the text formats,
+///   which [`BufferReadStringExt::read_escaped_string_text`] drops before.
+/// - A `\` before a control character, like a tab or a new line, is the character.
+/// - Any other escape is kept as it is, `\` included.
+pub fn decode_escape(escaped: &[u8], buf: &mut Vec<u8>) -> usize {
+    let c = match escaped.first() {
+        Some(c) => *c,
+        None => return 0,
+    };
     match c {
-        b'a' => b'\x07', // \a in c
-        b'b' => b'\x08', // \b in c
-        b'v' => b'\x0B', // \v in c
-        b'f' => b'\x0C', // \e in c
-        b'e' => b'\x1B', // \e in c
-        b'n' => b'\n',
-        b'r' => b'\r',
-        b't' => b'\t',
-        b'0' => b'\0',
-        _ => c,
+        b'\'' | b'\"' | b'\\' | b'/' | b'`' => buf.push(c),
+        b'x' => match escaped.get(1..3).and_then(|hex| hex_to_byte(hex[0], hex[1])) {
+            Some(b) => {
+                buf.push(b);
+                return 3;
+            }
+            None => buf.extend_from_slice(b"\\x"),
+        },
+        c if is_control_ascii(c) => buf.push(c),
+        _ => match unescape(c) {
+            Some(e) => buf.push(e),
+            None => {
+                buf.push(b'\\');
+                buf.push(c);
+            }
+        },
+    }
+    1
+}
+
+/// The control character of C escaped by `c`, if any.
+fn unescape(c: u8) -> Option<u8> {
+    match c {
+        b'a' => Some(b'\x07'),
+        b'b' => Some(b'\x08'),
+        b'v' => Some(b'\x0B'),
+        b'f' => Some(b'\x0C'),
+        b'e' => Some(b'\x1B'),
+        b'n' => Some(b'\n'),
+        b'r' => Some(b'\r'),
+        b't' => Some(b'\t'),
+        b'0' => Some(b'\0'),
+        _ => None,
     }
 }
 
@@ -133,12 +384,9 @@ fn is_control_ascii(c: u8) -> bool {
     c <= 31
 }
 
+/// The byte of the hexadecimal digits `high` and `low`, if they are.
 #[inline]
-fn hex_char_to_digit(c: u8) -> u8 {
-    match c {
-        b'A'..=b'F' => c - b'A' + 10,
-        b'a'..=b'f' => c - b'a' + 10,
-        b'0'..=b'9' => c - b'0',
-        _ => 0xff,
-    }
+fn hex_to_byte(high: u8, low: u8) -> Option<u8> {
+    let digit = |c: u8| (c as char).to_digit(16).map(|d| d as u8);
+    Some(digit(high)? * 0x10 + digit(low)?)
 }
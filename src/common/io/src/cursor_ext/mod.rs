@@ -22,5 +22,9 @@ pub use cursor_checkpoint_ext::ReadCheckPointExt;
 pub use cursor_read_bytes_ext::ReadBytesExt;
 pub use cursor_read_datetime_ext::BufferReadDateTimeExt;
 pub use cursor_read_number_ext::collect_number;
+pub use cursor_read_number_ext::RadixPrefix;
 pub use cursor_read_number_ext::ReadNumberExt;
+pub use cursor_read_string_ext::decode_escape;
+pub use cursor_read_string_ext::BoolSpelling;
 pub use cursor_read_string_ext::BufferReadStringExt;
+pub use cursor_read_string_ext::NulBytes;
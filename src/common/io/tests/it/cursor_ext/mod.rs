@@ -15,3 +15,4 @@
 mod read_bytes_ext;
 mod read_datetime_ext;
 mod read_number_ext;
+mod read_string_ext;
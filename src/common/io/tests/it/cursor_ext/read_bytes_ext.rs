@@ -47,3 +47,66 @@ fn test_read_ext() {
     let bs = cursor.remaining_slice();
     assert_eq!(String::from_utf8_lossy(bs), "bytes   helloworld");
 }
+
+#[test]
+fn test_must_ignore_bytes() {
+    let mut cursor = Cursor::new("NULL,true".as_bytes());
+    cursor.must_ignore_bytes(b"NULL").unwrap();
+    cursor.must_ignore_byte(b',').unwrap();
+    cursor.must_ignore_bytes(b"true").unwrap();
+    assert!(cursor.eof());
+
+    // mismatch before EOF, nothing is consumed.
+    let mut cursor = Cursor::new("NULx,".as_bytes());
+    let err = cursor.must_ignore_bytes(b"NULL").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Expected to have bytes 'NULL', got 'NULx' at pos 0"
+    );
+    assert_eq!(cursor.position(), 0);
+
+    // reach EOF in the middle of the literal.
+    let mut cursor = Cursor::new("1,tr".as_bytes());
+    cursor.must_ignore_bytes(b"1,").unwrap();
+    let err = cursor.must_ignore_bytes(b"true").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Expected to have bytes 'true', got 'tr' at pos 2"
+    );
+    assert_eq!(cursor.remaining_slice(), b"tr");
+
+    // already at EOF.
+    let err = cursor
+        .must_ignore_bytes(b"tr")
+        .and_then(|_| cursor.must_ignore_bytes(b"ue"));
+    assert_eq!(
+        err.unwrap_err().to_string(),
+        "Expected to have bytes 'ue', got '' at pos 4"
+    );
+}
+
+#[test]
+fn test_skip_records() {
+    let text = "id,name\n1,\"a\nb\"\n2,\"c,\"\"d\"\"\n\"\n3,e\n4,f";
+    let mut cursor = Cursor::new(text.as_bytes());
+
+    // The delimiters in the quoted fields are skipped with them.
+    assert_eq!(cursor.skip_records(1, b'\n'), 1);
+    assert_eq!(cursor.remaining_slice(), b"1,\"a\nb\"\n2,\"c,\"\"d\"\"\n\"\n3,e\n4,f");
+    assert_eq!(cursor.skip_records(2, b'\n'), 2);
+    assert_eq!(cursor.remaining_slice(), b"3,e\n4,f");
+    assert_eq!(cursor.skip_records(0, b'\n'), 0);
+    assert_eq!(cursor.remaining_slice(), b"3,e\n4,f");
+
+    // The last record ends by the end of the buffer.
+    assert_eq!(cursor.skip_records(5, b'\n'), 2);
+    assert!(cursor.eof());
+    assert_eq!(cursor.skip_records(1, b'\n'), 0);
+
+    // A quote inside an unquoted field doesn't quote the rest of the record.
+    let mut cursor = Cursor::new("5\" pipe,x\n6,\"y\"\"\",z\n7,w".as_bytes());
+    assert_eq!(cursor.skip_records(1, b'\n'), 1);
+    assert_eq!(cursor.remaining_slice(), b"6,\"y\"\"\",z\n7,w");
+    assert_eq!(cursor.skip_records(1, b'\n'), 1);
+    assert_eq!(cursor.remaining_slice(), b"7,w");
+}
@@ -78,3 +78,119 @@ fn test_read_float() -> Result<()> {
     assert_eq!(res, expected);
     Ok(())
 }
+
+#[test]
+fn test_read_decimal() -> Result<()> {
+    let mut reader = Cursor::new("1.5,+0012.25,-3,.5,-0.00,99.99\n".as_bytes());
+    let expected = vec!["1.50", "12.25", "-3.00", "0.50", "0.00", "99.99"];
+    let mut res = vec![];
+    for _ in 0..expected.len() {
+        let mut buf = vec![];
+        reader.read_decimal_text(&mut buf, 4, 2, b',')?;
+        res.push(String::from_utf8(buf).unwrap());
+        let _ = reader.ignore_byte(b',');
+    }
+    assert_eq!(res, expected);
+    assert_eq!(reader.remaining_slice(), b"\n");
+
+    let mut reader = Cursor::new("-0012|x".as_bytes());
+    let mut buf = vec![];
+    reader.read_decimal_text(&mut buf, 3, 0, b'|')?;
+    assert_eq!(buf, b"-12");
+
+    // Over the precision or the scale, the position of the first digit in excess is
+    // reported and nothing is consumed.
+    let cases = [("123.4", 0), ("1.234", 4), ("a,123.4", 2)];
+    for (text, position) in cases {
+        let mut reader = Cursor::new(text.as_bytes());
+        let _ = reader.ignore_bytes(b"a,");
+        let start = reader.position();
+        let err = reader
+            .read_decimal_text(&mut vec![], 4, 2, b',')
+            .unwrap_err();
+        assert!(
+            err.message().contains(&format!("at position {},", position)),
+            "{}",
+            err.message()
+        );
+        assert_eq!(reader.position(), start);
+    }
+
+    // Malformed.
+    for text in ["", "-", "1.2.3", "1e3", "12a", " 1"] {
+        let mut reader = Cursor::new(text.as_bytes());
+        assert!(reader.read_decimal_text(&mut vec![], 10, 2, b',').is_err());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_read_radix_int() -> Result<()> {
+    let prefixes = RadixPrefix::ALL;
+    let mut reader = Cursor::new("42,-17,+0,0x1F,-0XfF,0o17,0b101\n".as_bytes());
+    let expected = vec![42, -17, 0, 31, -255, 15, 5];
+    let mut res = vec![];
+    for _ in 0..expected.len() {
+        res.push(reader.read_radix_int_text::<i64>(&prefixes, b',')?);
+        let _ = reader.ignore_byte(b',');
+    }
+    assert_eq!(res, expected);
+    assert_eq!(reader.remaining_slice(), b"\n");
+
+    // The bounds of the types.
+    let mut reader = Cursor::new("-0x8000000000000000".as_bytes());
+    assert_eq!(reader.read_radix_int_text::<i64>(&prefixes, b',')?, i64::MIN);
+    let mut reader = Cursor::new("0xffffffffffffffff".as_bytes());
+    assert_eq!(reader.read_radix_int_text::<u64>(&prefixes, b',')?, u64::MAX);
+
+    // Out of range, the position of the integer is reported and nothing is consumed.
+    let cases = [
+        ("9223372036854775808", false),
+        ("a,-0x8000000000000001", false),
+        ("18446744073709551616", true),
+        ("a,-1", true),
+        ("a,340282366920938463463374607431768211456", true),
+    ];
+    for (text, unsigned) in cases {
+        let mut reader = Cursor::new(text.as_bytes());
+        let _ = reader.ignore_bytes(b"a,");
+        let start = reader.position();
+        let err = if unsigned {
+            reader.read_radix_int_text::<u64>(&prefixes, b',').err()
+        } else {
+            reader.read_radix_int_text::<i64>(&prefixes, b',').err()
+        }
+        .unwrap();
+        assert!(
+            err.message().contains(&format!("at position {},", start)),
+            "{}",
+            err.message()
+        );
+        assert!(err.message().contains("out of range"), "{}", err.message());
+        assert_eq!(reader.position(), start);
+    }
+
+    // Invalid digits, their positions are reported. The prefixes are only the accepted ones.
+    let cases = [
+        ("12a", &prefixes[..], 2),
+        ("0x1g", &prefixes[..], 3),
+        ("0o8", &prefixes[..], 2),
+        ("0x1F", &[RadixPrefix::Octal][..], 1),
+        ("-", &prefixes[..], 1),
+        ("0x", &prefixes[..], 2),
+        ("", &prefixes[..], 0),
+    ];
+    for (text, prefixes, position) in cases {
+        let mut reader = Cursor::new(text.as_bytes());
+        let err = reader
+            .read_radix_int_text::<i64>(prefixes, b',')
+            .unwrap_err();
+        assert!(
+            err.message().contains(&format!("at position {},", position)),
+            "{}",
+            err.message()
+        );
+        assert_eq!(reader.position(), 0);
+    }
+    Ok(())
+}
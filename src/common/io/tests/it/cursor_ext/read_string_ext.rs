@@ -0,0 +1,340 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::borrow::Cow;
+use std::io::Cursor;
+use std::io::ErrorKind;
+
+use common_exception::Result;
+use common_io::cursor_ext::*;
+
+#[test]
+fn test_read_csv_field() -> Result<()> {
+    let mut cursor = Cursor::new(r#"1,"a,b",say,"say ""hi""","",x"#.as_bytes());
+    let mut fields = vec![];
+    loop {
+        let mut buf = vec![];
+        cursor.read_csv_field(&mut buf, b'"', b',')?;
+        fields.push(String::from_utf8_lossy(&buf).to_string());
+        if !cursor.ignore_byte(b',') {
+            break;
+        }
+    }
+    assert_eq!(fields, vec!["1", "a,b", "say", r#"say "hi""#, "", "x"]);
+    assert!(cursor.remaining_slice().is_empty());
+
+    // An unquoted field ends at the end of the line.
+    let mut cursor = Cursor::new("abc\ndef".as_bytes());
+    let mut buf = vec![];
+    cursor.read_csv_field(&mut buf, b'"', b',')?;
+    assert_eq!(buf, b"abc");
+    assert_eq!(cursor.remaining_slice(), b"\ndef");
+
+    // A quoted field must be terminated.
+    let mut cursor = Cursor::new(r#""abc,def"#.as_bytes());
+    let mut buf = vec![];
+    assert!(cursor.read_csv_field(&mut buf, b'"', b',').is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_read_csv_token() -> Result<()> {
+    let mut cursor = Cursor::new(r#"1,"a,b",say,"say ""hi""","",x,"a\"b""#.as_bytes());
+    let mut tokens = vec![];
+    loop {
+        let token = cursor.read_csv_token(b'"', b',')?;
+        let borrowed = matches!(token, Cow::Borrowed(_));
+        tokens.push((String::from_utf8_lossy(&token).to_string(), borrowed));
+        if !cursor.ignore_byte(b',') {
+            break;
+        }
+    }
+    // Borrowed unless the escapes or the doubled quotas are rewritten.
+    assert_eq!(tokens, vec![
+        ("1".to_string(), true),
+        ("a,b".to_string(), true),
+        ("say".to_string(), true),
+        (r#"say "hi""#.to_string(), false),
+        ("".to_string(), true),
+        ("x".to_string(), true),
+        (r#"a"b"#.to_string(), false),
+    ]);
+    assert!(cursor.remaining_slice().is_empty());
+
+    // An unquoted token ends at the end of the line.
+    let mut cursor = Cursor::new("abc\r\ndef".as_bytes());
+    assert_eq!(cursor.read_csv_token(b'"', b',')?.as_ref(), b"abc");
+    assert_eq!(cursor.remaining_slice(), b"\r\ndef");
+
+    // A quoted token must be terminated, escaped or not.
+    for text in [r#""abc,def"#, r#""abc\"def"#] {
+        let mut cursor = Cursor::new(text.as_bytes());
+        assert!(cursor.read_csv_token(b'"', b',').is_err());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_read_escaped_string_text_nul_bytes() -> Result<()> {
+    let texts: [&[u8]; 3] = [b"a\\0b\tc", b"a\\x00b\tc", b"a\0b\tc"];
+    for text in texts {
+        // The NUL bytes are kept by default.
+        let mut cursor = Cursor::new(text);
+        let mut buf = vec![];
+        cursor.read_escaped_string_text(&mut buf)?;
+        assert_eq!(buf, b"a\0b");
+        assert_eq!(cursor.remaining_slice(), b"\tc");
+
+        let mut cursor = Cursor::new(text);
+        let mut buf = vec![];
+        cursor.read_escaped_string_text_with(&mut buf, NulBytes::Allow)?;
+        assert_eq!(buf, b"a\0b");
+
+        let mut cursor = Cursor::new(text);
+        let mut buf = vec![];
+        assert!(
+            cursor
+                .read_escaped_string_text_with(&mut buf, NulBytes::Reject)
+                .is_err()
+        );
+    }
+
+    let mut cursor = Cursor::new(b"a\\tb\tc");
+    let mut buf = vec![];
+    cursor.read_escaped_string_text_with(&mut buf, NulBytes::Reject)?;
+    assert_eq!(buf, b"a\tb");
+
+    Ok(())
+}
+
+#[test]
+fn test_read_quoted_text_empty_input() -> Result<()> {
+    // An empty string is quoted, the input ending before a quota is not one.
+    let mut cursor = Cursor::new(b"''");
+    let mut buf = vec![];
+    cursor.read_quoted_text(&mut buf, b'\'')?;
+    assert!(buf.is_empty());
+    assert!(cursor.eof());
+
+    for text in [&b""[..], b"'", b"'ab", b"'ab\\"] {
+        let mut cursor = Cursor::new(text);
+        let mut buf = vec![];
+        let err = cursor.read_quoted_text(&mut buf, b'\'').unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof, "{:?}", text);
+    }
+    let err = Cursor::new(b"").read_quoted_text(&mut vec![], b'"').unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Unexpected EOF, expected a string literal quoted by '\"'"
+    );
+
+    // Not a quoted string.
+    let mut cursor = Cursor::new(b"ab");
+    let err = cursor.read_quoted_text(&mut vec![], b'\'').unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+    Ok(())
+}
+
+#[test]
+fn test_read_quoted_text_multiline() -> Result<()> {
+    // The new lines within the quotas are part of the value.
+    let mut cursor = Cursor::new(b"\"line 1\nline 2\r\n\nline \\\"4\\\"\n\",next\n");
+    let mut buf = vec![];
+    cursor.read_quoted_text(&mut buf, b'"')?;
+    assert_eq!(buf, b"line 1\nline 2\r\n\nline \"4\"\n");
+    assert_eq!(cursor.remaining_slice(), b",next\n");
+
+    // The same under a cap fitting the value.
+    let mut cursor = Cursor::new(b"\"a\nb\nc\",next");
+    let mut buf = vec![];
+    cursor.read_quoted_text_with(&mut buf, b'"', 5)?;
+    assert_eq!(buf, b"a\nb\nc");
+    assert_eq!(cursor.remaining_slice(), b",next");
+
+    // A missing closing quota reads no further than the cap, and nothing is consumed.
+    let text = b"x,\"abc\nd,e\nf,g\nh,i\n";
+    let mut cursor = Cursor::new(text);
+    cursor.set_position(2);
+    let mut buf = b"kept".to_vec();
+    let err = cursor.read_quoted_text_with(&mut buf, b'"', 8).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert_eq!(
+        err.to_string(),
+        "String literal quoted by '\"' at position 2 is longer than 8 bytes, its closing quota may be missing"
+    );
+    assert_eq!(buf, b"kept");
+    assert_eq!(cursor.position(), 2);
+
+    // The escapes count as the bytes they are written with.
+    let mut cursor = Cursor::new(b"'\\n\\n'");
+    let err = cursor
+        .read_quoted_text_with(&mut vec![], b'\'', 3)
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+    // Without a cap, the missing quota is only found at the end of the input.
+    let mut cursor = Cursor::new(text);
+    cursor.set_position(2);
+    let err = cursor.read_quoted_text(&mut vec![], b'"').unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+
+    Ok(())
+}
+
+#[test]
+fn test_read_escaped_string_text_empty_input() -> Result<()> {
+    // An empty field, alone or followed by the next one.
+    for text in [&b""[..], b"\tb"] {
+        let mut cursor = Cursor::new(text);
+        let mut buf = vec![];
+        cursor.read_escaped_string_text(&mut buf)?;
+        assert!(buf.is_empty());
+    }
+
+    let mut cursor = Cursor::new(b"ab\\");
+    let mut buf = vec![];
+    let err = cursor.read_escaped_string_text(&mut buf).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+
+    Ok(())
+}
+
+#[test]
+fn test_read_escapes() -> Result<()> {
+    let escapes: [(&[u8], &[u8]); 21] = [
+        (b"\\n", b"\n"),
+        (b"\\t", b"\t"),
+        (b"\\r", b"\r"),
+        (b"\\0", b"\0"),
+        (b"\\a", b"\x07"),
+        (b"\\b", b"\x08"),
+        (b"\\v", b"\x0B"),
+        (b"\\f", b"\x0C"),
+        (b"\\e", b"\x1B"),
+        (b"\\\\", b"\\"),
+        (b"\\'", b"'"),
+        (b"\\\"", b"\""),
+        (b"\\`", b"`"),
+        (b"\\/", b"/"),
+        (b"\\x41", b"A"),
+        (b"\\x4g", b"\\x4g"),
+        (b"\\x4", b"\\x4"),
+        (b"\\q", b"\\q"),
+        (b"\\\t", b"\t"),
+        (b"\\\n", b"\n"),
+        (b"a\\zb\\nc", b"a\\zb\nc"),
+    ];
+    for (escaped, expected) in escapes {
+        let mut quoted = b"'".to_vec();
+        quoted.extend_from_slice(escaped);
+        quoted.push(b'\'');
+        let mut cursor = Cursor::new(quoted.as_slice());
+        let mut quoted_buf = vec![];
+        cursor.read_quoted_text(&mut quoted_buf, b'\'')?;
+        assert!(cursor.eof());
+
+        let mut cursor = Cursor::new(escaped);
+        let mut escaped_buf = vec![];
+        cursor.read_escaped_string_text(&mut escaped_buf)?;
+        assert!(cursor.eof());
+
+        // Both readers decode the escapes by `decode_escape`.
+        assert_eq!(quoted_buf, escaped_buf, "{:?}", escaped);
+        assert_eq!(quoted_buf, expected, "{:?}", escaped);
+    }
+
+    let mut buf = vec![];
+    assert_eq!(decode_escape(b"x41x", &mut buf), 3);
+    assert_eq!(decode_escape(b"", &mut buf), 0);
+    assert_eq!(buf, b"A");
+
+    Ok(())
+}
+
+#[test]
+fn test_read_null_escape() -> Result<()> {
+    // `\N` is kept in the string literals.
+    let literals: [(&[u8], &[u8]); 2] = [
+        (b"'a\\Nb'", b"a\\Nb"),
+        (b"'C:\\New'", b"C:\\New"),
+    ];
+    for (quoted, expected) in literals {
+        let mut cursor = Cursor::new(quoted);
+        let mut buf = vec![];
+        cursor.read_quoted_text(&mut buf, b'\'')?;
+        assert_eq!(buf, expected);
+    }
+
+    // The escaped texts drop it, it marks their NULLs.
+    let mut cursor = Cursor::new(b"a\\Nb");
+    let mut buf = vec![];
+    cursor.read_escaped_string_text(&mut buf)?;
+    assert_eq!(buf, b"ab");
+
+    Ok(())
+}
+
+#[test]
+fn test_read_bool_text() -> Result<()> {
+    let cases = [
+        (BoolSpelling::TrueFalse, "true,FALSE,True,false"),
+        (BoolSpelling::TrueFalseInitials, "t,F,T,f"),
+        (BoolSpelling::OneZero, "1,0,1,0"),
+        (BoolSpelling::YesNo, "yes,NO,Yes,no"),
+    ];
+    for (spelling, text) in cases {
+        let mut cursor = Cursor::new(text.as_bytes());
+        let mut values = vec![];
+        loop {
+            values.push(cursor.read_bool_text(&[spelling], b',')?);
+            if !cursor.ignore_byte(b',') {
+                break;
+            }
+        }
+        assert_eq!(values, vec![true, false, true, false], "{}", text);
+        assert!(cursor.eof());
+
+        // The other spellings are not accepted.
+        let mut cursor = Cursor::new("maybe".as_bytes());
+        assert!(cursor.read_bool_text(&[spelling], b',').is_err());
+    }
+
+    // The token ends at the delimiter or the end of the line, which are not consumed.
+    let mut cursor = Cursor::new("1|t\nno".as_bytes());
+    assert!(cursor.read_bool_text(&BoolSpelling::ALL, b'|')?);
+    assert!(cursor.ignore_byte(b'|'));
+    assert!(cursor.read_bool_text(&BoolSpelling::ALL, b'|')?);
+    assert!(cursor.ignore_byte(b'\n'));
+    assert!(!cursor.read_bool_text(&BoolSpelling::ALL, b'|')?);
+
+    // A rejected token is reported with its position, and nothing is consumed.
+    for (text, spellings) in [
+        ("true,yes", &[BoolSpelling::TrueFalse][..]),
+        ("true,tru", &BoolSpelling::ALL[..]),
+        ("true,", &BoolSpelling::ALL[..]),
+    ] {
+        let mut cursor = Cursor::new(text.as_bytes());
+        assert!(cursor.read_bool_text(spellings, b',')?);
+        assert!(cursor.ignore_byte(b','));
+        let err = cursor.read_bool_text(spellings, b',').unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert!(err.to_string().contains("at position 5"), "{}", err);
+        assert_eq!(cursor.position(), 5);
+    }
+
+    Ok(())
+}
@@ -51,6 +51,15 @@ impl ColumnLeaves {
                 }
                 ColumnLeaf::new(field.clone(), child_leaf_ids, Some(child_column_leaves))
             }
+            // The leaf columns of a list are the ones of its items, like the fields of the
+            // structs in an array of structs. They are read together, as the whole list.
+            ArrowType::List(inner_field)
+            | ArrowType::LargeList(inner_field)
+            | ArrowType::FixedSizeList(inner_field, _)
+            | ArrowType::Map(inner_field, _) => {
+                let inner_column_leaf = Self::traverse_fields_dfs(inner_field, leaf_id);
+                ColumnLeaf::new(field.clone(), inner_column_leaf.leaf_ids, None)
+            }
             _ => {
                 let column_leaf = ColumnLeaf::new(field.clone(), vec![*leaf_id], None);
                 *leaf_id += 1;
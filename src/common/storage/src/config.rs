@@ -46,16 +46,40 @@ use super::utils::mask_string;
 /// [storage.temperary]
 /// type = "s3"
 /// ```
-#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct StorageConfig {
     pub num_cpus: u64,
     pub allow_insecure: bool,
+    /// The directory the parquet table functions read local files under without
+    /// `allow_insecure`, empty for none. The sessions can narrow it to a directory under it by
+    /// the setting `parquet_trusted_root`.
+    pub parquet_trusted_root: String,
+    /// The connection parameters the parquet table functions read the files under path
+    /// prefixes with, as `<prefix>:<key>=<value>,...` entries separated by `;`, empty for
+    /// none. The values are secrets.
+    pub parquet_credentials: String,
 
     pub params: StorageParams,
 
     pub cache: CacheConfig,
 }
 
+impl Debug for StorageConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StorageConfig")
+            .field("num_cpus", &self.num_cpus)
+            .field("allow_insecure", &self.allow_insecure)
+            .field("parquet_trusted_root", &self.parquet_trusted_root)
+            .field(
+                "parquet_credentials",
+                &mask_string(&self.parquet_credentials, 3),
+            )
+            .field("params", &self.params)
+            .field("cache", &self.cache)
+            .finish()
+    }
+}
+
 /// Config for cache backend.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CacheConfig {
@@ -51,6 +51,7 @@ use dyn_clone::DynClone;
 use crate::database::Database;
 use crate::table::Table;
 use crate::table_args::TableArgs;
+use crate::table_context::TableContext;
 use crate::table_function::TableFunction;
 
 #[derive(Default, Clone)]
@@ -172,11 +173,12 @@ pub trait Catalog: DynClone + Send + Sync {
 
     /// Table function
 
-    // Get function by name.
+    // Get function by name, created within the query context `ctx`.
     fn get_table_function(
         &self,
         _func_name: &str,
         _tbl_args: TableArgs,
+        _ctx: &dyn TableContext,
     ) -> Result<Arc<dyn TableFunction>> {
         Err(ErrorCode::Unimplemented(
             "'get_table_function' not implemented",
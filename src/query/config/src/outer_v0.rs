@@ -254,6 +254,18 @@ pub struct StorageConfig {
     #[clap(long = "storage-allow-insecure")]
     pub allow_insecure: bool,
 
+    /// The directory the parquet table functions read local files under without
+    /// `allow_insecure`, empty for none. The sessions can narrow it to a directory under it by
+    /// the setting `parquet_trusted_root`.
+    #[clap(long = "storage-parquet-trusted-root", default_value_t)]
+    pub parquet_trusted_root: String,
+
+    /// The connection parameters the parquet table functions read the files under path
+    /// prefixes with, as `<prefix>:<key>=<value>,...` entries separated by `;`, like
+    /// `s3://bucket/a/:access_key_id=...,secret_access_key=...`, empty for none.
+    #[clap(long = "storage-parquet-credentials", default_value_t)]
+    pub parquet_credentials: String,
+
     // Fs storage backend config.
     #[clap(flatten)]
     pub fs: FsStorageConfig,
@@ -298,6 +310,8 @@ impl From<InnerStorageConfig> for StorageConfig {
             storage_num_cpus: inner.num_cpus,
             storage_type: "".to_string(),
             allow_insecure: inner.allow_insecure,
+            parquet_trusted_root: inner.parquet_trusted_root,
+            parquet_credentials: inner.parquet_credentials,
             fs: Default::default(),
             gcs: Default::default(),
             s3: Default::default(),
@@ -356,6 +370,8 @@ impl TryInto<InnerStorageConfig> for StorageConfig {
         Ok(InnerStorageConfig {
             num_cpus: self.storage_num_cpus,
             allow_insecure: self.allow_insecure,
+            parquet_trusted_root: self.parquet_trusted_root,
+            parquet_credentials: self.parquet_credentials,
             params: {
                 match self.storage_type.as_str() {
                     "azblob" => StorageParams::Azblob(self.azblob.try_into()?),
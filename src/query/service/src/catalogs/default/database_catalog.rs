@@ -54,6 +54,7 @@ use crate::catalogs::catalog::Catalog;
 use crate::catalogs::default::ImmutableCatalog;
 use crate::catalogs::default::MutableCatalog;
 use crate::databases::Database;
+use crate::sessions::TableContext;
 use crate::storages::StorageDescription;
 use crate::storages::Table;
 use crate::table_functions::TableArgs;
@@ -474,8 +475,9 @@ impl Catalog for DatabaseCatalog {
         &self,
         func_name: &str,
         tbl_args: TableArgs,
+        ctx: &dyn TableContext,
     ) -> Result<Arc<dyn TableFunction>> {
-        self.table_function_factory.get(func_name, tbl_args)
+        self.table_function_factory.get(func_name, tbl_args, ctx)
     }
 
     fn get_table_engines(&self) -> Vec<StorageDescription> {
@@ -99,7 +99,7 @@ impl QueryContext {
             catalog.get_table_by_info(table_info)
         } else {
             Ok(catalog
-                .get_table_function(&table_info.name, table_args)?
+                .get_table_function(&table_info.name, table_args, self)?
                 .as_table())
         }
     }
@@ -15,15 +15,22 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use common_config::GlobalConfig;
 use common_datavalues::DataValue;
 use common_exception::ErrorCode;
 use common_exception::Result;
 use common_meta_types::MetaId;
+use common_storage::StorageConfig;
+use common_storages_parquet::narrow_trusted_root;
+use common_storages_parquet::ParquetFeaturesTable;
+use common_storages_parquet::ParquetSchemaDiffTable;
+use common_storages_parquet::ParquetSchemasCompatibleTable;
 use common_storages_parquet::ParquetTable;
 use parking_lot::RwLock;
 
 use crate::catalogs::SYS_TBL_FUC_ID_END;
 use crate::catalogs::SYS_TBL_FUNC_ID_BEGIN;
+use crate::sessions::TableContext;
 use crate::storages::fuse::table_functions::ClusteringInformationTable;
 use crate::storages::fuse::table_functions::FuseBlockTable;
 use crate::storages::fuse::table_functions::FuseSegmentTable;
@@ -44,6 +51,7 @@ pub trait TableFunctionCreator: Send + Sync {
         tbl_func_name: &str,
         tbl_id: MetaId,
         arg: TableArgs,
+        ctx: &dyn TableContext,
     ) -> Result<Arc<dyn TableFunction>>;
 }
 
@@ -58,11 +66,42 @@ where
         tbl_func_name: &str,
         tbl_id: MetaId,
         arg: TableArgs,
+        _ctx: &dyn TableContext,
     ) -> Result<Arc<dyn TableFunction>> {
         self(db_name, tbl_func_name, tbl_id, arg)
     }
 }
 
+/// The creator of a table function reading files with the config `storage`: the local
+/// files are read without `allow_insecure` under the directory of
+/// `storage.parquet_trusted_root`, and the files under the path prefixes of
+/// `storage.parquet_credentials` with their connection parameters.
+///
+/// The session setting `parquet_trusted_root` narrows the trusted root of the config to a
+/// directory under it, see [`narrow_trusted_root`].
+struct StorageConfigCreator<T>(T);
+
+impl<T> TableFunctionCreator for StorageConfigCreator<T>
+where
+    T: Fn(&str, &str, MetaId, TableArgs, &StorageConfig) -> Result<Arc<dyn TableFunction>>,
+    T: Send + Sync,
+{
+    fn try_create(
+        &self,
+        db_name: &str,
+        tbl_func_name: &str,
+        tbl_id: MetaId,
+        arg: TableArgs,
+        ctx: &dyn TableContext,
+    ) -> Result<Arc<dyn TableFunction>> {
+        let mut config = GlobalConfig::instance().storage.clone();
+        let session_root = ctx.get_settings().get_parquet_trusted_root()?;
+        config.parquet_trusted_root =
+            narrow_trusted_root(&config.parquet_trusted_root, &session_root)?;
+        (self.0)(db_name, tbl_func_name, tbl_id, arg, &config)
+    }
+}
+
 #[derive(Default)]
 pub struct TableFunctionFactory {
     creators: TableFunctionCreators,
@@ -134,7 +173,42 @@ impl TableFunctionFactory {
 
         creators.insert(
             "read_parquet".to_string(),
-            (next_id(), Arc::new(ParquetTable::create)),
+            (
+                next_id(),
+                Arc::new(StorageConfigCreator(ParquetTable::create_with_config)),
+            ),
+        );
+
+        creators.insert(
+            "read_parquet_sample".to_string(),
+            (
+                next_id(),
+                Arc::new(StorageConfigCreator(ParquetTable::create_sample_with_config)),
+            ),
+        );
+
+        creators.insert(
+            "parquet_schema_diff".to_string(),
+            (
+                next_id(),
+                Arc::new(StorageConfigCreator(ParquetSchemaDiffTable::create_with_config)),
+            ),
+        );
+
+        creators.insert(
+            "parquet_schemas_compatible".to_string(),
+            (
+                next_id(),
+                Arc::new(StorageConfigCreator(ParquetSchemasCompatibleTable::create_with_config)),
+            ),
+        );
+
+        creators.insert(
+            "read_parquet_features".to_string(),
+            (
+                next_id(),
+                Arc::new(StorageConfigCreator(ParquetFeaturesTable::create_with_config)),
+            ),
         );
 
         TableFunctionFactory {
@@ -142,13 +216,18 @@ impl TableFunctionFactory {
         }
     }
 
-    pub fn get(&self, func_name: &str, tbl_args: TableArgs) -> Result<Arc<dyn TableFunction>> {
+    pub fn get(
+        &self,
+        func_name: &str,
+        tbl_args: TableArgs,
+        ctx: &dyn TableContext,
+    ) -> Result<Arc<dyn TableFunction>> {
         let lock = self.creators.read();
         let func_name = func_name.to_lowercase();
         let (id, factory) = lock.get(&func_name).ok_or_else(|| {
             ErrorCode::UnknownTable(format!("Unknown table function {}", func_name))
         })?;
-        let func = factory.try_create("", &func_name, *id, tbl_args)?;
+        let func = factory.try_create("", &func_name, *id, tbl_args, ctx)?;
         Ok(func)
     }
 }
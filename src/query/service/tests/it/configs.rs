@@ -114,6 +114,7 @@ rpc_tls_meta_service_domain_name = "localhost"
 type = "fs"
 num_cpus = 0
 allow_insecure = false
+parquet_trusted_root = ""
 
 [storage.fs]
 data_path = "_data"
@@ -13,3 +13,4 @@
 //  limitations under the License.W
 
 mod numbers_table;
+mod read_parquet_table;
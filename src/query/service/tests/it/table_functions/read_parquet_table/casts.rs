@@ -0,0 +1,906 @@
+//  Copyright 2021 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::collections::BTreeMap;
+
+use chrono::TimeZone;
+use chrono_tz::Tz;
+use common_arrow::arrow::array::DictionaryArray;
+use common_arrow::arrow::array::FixedSizeBinaryArray;
+use common_arrow::arrow::array::PrimitiveArray;
+use common_arrow::arrow::array::Utf8Array;
+use common_arrow::arrow::bitmap::Bitmap;
+use common_arrow::arrow::chunk::Chunk;
+use common_arrow::arrow::datatypes::DataType as ArrowType;
+use common_arrow::arrow::datatypes::Field as ArrowField;
+use common_arrow::arrow::datatypes::IntegerType;
+use common_arrow::arrow::datatypes::Schema as ArrowSchema;
+use common_arrow::arrow::datatypes::TimeUnit;
+use common_arrow::arrow::io::parquet::write::FileWriter;
+use common_arrow::arrow::io::parquet::write::RowGroupIterator;
+use common_arrow::arrow::io::parquet::write::WriteOptions;
+use common_arrow::parquet::compression::CompressionOptions;
+use common_arrow::parquet::encoding::Encoding;
+use common_arrow::parquet::thrift_format::ConvertedType;
+use common_arrow::parquet::thrift_format::DecimalType;
+use common_arrow::parquet::thrift_format::LogicalType;
+use common_arrow::parquet::write::Version;
+use common_base::base::tokio;
+use common_catalog::plan::Projection;
+use common_catalog::plan::PushDownInfo;
+use common_catalog::table::Table;
+use common_datablocks::serialize_data_blocks;
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_sql::executor::table_read_plan::ToReadDataSourcePlan;
+use common_storages_parquet::ParquetTable;
+use common_storages_parquet::WarningKind;
+use databend_query::sessions::TableContext;
+use databend_query::stream::ReadDataBlockStream;
+use futures::TryStreamExt;
+
+use super::table_test_fixture::append_footer;
+use super::table_test_fixture::build_table;
+use super::table_test_fixture::chunks_file;
+use super::table_test_fixture::int_row_groups_file;
+use super::table_test_fixture::memory_operator;
+use super::table_test_fixture::nullable_int_columns_file;
+use super::table_test_fixture::strip_footer;
+
+#[tokio::test]
+async fn test_read_parquet_cast() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+
+    let schema = DataSchemaRefExt::create(vec![
+        DataField::new("id", i32::to_data_type()),
+        DataField::new("value", i64::to_data_type()),
+    ]);
+    let block = DataBlock::create(schema.clone(), vec![
+        Series::from_data(vec![1i32, 2, 3]),
+        Series::from_data(vec![10i64, 200, 300]),
+    ]);
+    let mut buf = vec![];
+    serialize_data_blocks(vec![block], &schema, &mut buf)?;
+    let operator = memory_operator()?;
+    operator.object("data/t.parquet").write(buf).await?;
+
+    let table = build_table(&operator, &[
+        "data/t.parquet",
+        "cast=id:double, value:string",
+    ])?;
+    let table_schema = table.get_table_info().schema();
+    assert_eq!(table_schema.field(0).data_type(), &f64::to_data_type());
+    assert_eq!(table_schema.field(1).data_type(), &Vu8::to_data_type());
+
+    let source_plan = table
+        .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+        .await?;
+    ctx.try_set_partitions(source_plan.parts.clone())?;
+    let stream = table
+        .read_data_block_stream(ctx.clone(), &source_plan)
+        .await?;
+    let result = stream.try_collect::<Vec<_>>().await?;
+    let ids = result[0].column(0);
+    let ids = (0..ids.len()).map(|i| ids.get(i)).collect::<Vec<_>>();
+    assert_eq!(ids, vec![
+        DataValue::Float64(1.0),
+        DataValue::Float64(2.0),
+        DataValue::Float64(3.0)
+    ]);
+    let values = result[0].column(1);
+    assert_eq!(values.get(2), DataValue::String(b"300".to_vec()));
+
+    // Casts to nested types are rejected while planning.
+    let err = build_table(&operator, &["data/t.parquet", "cast=id:array(int32)"])
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+
+    // 200 and 300 overflow int8, they are reported while reading.
+    let table = build_table(&operator, &["data/t.parquet", "cast=value:int8"])?;
+    let source_plan = table
+        .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+        .await?;
+    ctx.try_set_partitions(source_plan.parts.clone())?;
+    let stream = table.read_data_block_stream(ctx, &source_plan).await?;
+    let err = stream.try_collect::<Vec<_>>().await.err().unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_DATA_VALUE_TYPE);
+
+    Ok(())
+}
+
+/// A parquet file with a TIMESTAMP column `utc_ts` adjusted to UTC, and a TIMESTAMP column
+/// `local_ts` which is not, both holding the microseconds `micros`.
+fn timestamps_file(micros: i64) -> Result<Vec<u8>> {
+    let utc_type = ArrowType::Timestamp(TimeUnit::Microsecond, Some("+00:00".to_string()));
+    let local_type = ArrowType::Timestamp(TimeUnit::Microsecond, None);
+    let fields = vec![
+        ArrowField::new("utc_ts", utc_type.clone(), false),
+        ArrowField::new("local_ts", local_type.clone(), false),
+    ];
+    let chunk = Chunk::new(vec![
+        PrimitiveArray::<i64>::from_vec(vec![micros]).to(utc_type).boxed(),
+        PrimitiveArray::<i64>::from_vec(vec![micros]).to(local_type).boxed(),
+    ]);
+    let (buf, meta) = chunks_file(fields, vec![chunk])?;
+    let logical_types = meta.schema[1..]
+        .iter()
+        .map(|element| element.logical_type.clone())
+        .collect::<Vec<_>>();
+    assert!(matches!(
+        &logical_types[..],
+        [Some(LogicalType::TIMESTAMP(utc)), Some(LogicalType::TIMESTAMP(local))]
+            if utc.is_adjusted_to_u_t_c && !local.is_adjusted_to_u_t_c
+    ));
+    Ok(buf)
+}
+
+/// The timestamps of `utc_ts` and `local_ts` of the first row of `blocks`, rendered in
+/// `timezone`.
+fn rendered_timestamps(blocks: &[DataBlock], timezone: Tz) -> Vec<String> {
+    (0..2)
+        .map(|index| match blocks[0].column(index).get(0) {
+            DataValue::Int64(micros) => timezone
+                .timestamp(micros.div_euclid(1_000_000), 0)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string(),
+            value => panic!("unexpected timestamp value {:?}", value),
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_read_parquet_local_timestamps() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+    ctx.get_settings()
+        .set_settings("timezone".to_string(), "Asia/Shanghai".to_string(), false)?;
+
+    // Written as 2022-01-01 08:00:00.
+    let operator = memory_operator()?;
+    operator
+        .object("data/t.parquet")
+        .write(timestamps_file(1_641_024_000_000_000)?)
+        .await?;
+
+    // The UTC timestamps are instants, rendered in the session timezone. The local times
+    // are taken in the session timezone, so they are rendered as they are written.
+    let table = build_table(&operator, &["data/t.parquet"])?;
+    let source_plan = table
+        .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+        .await?;
+    ctx.try_set_partitions(source_plan.parts.clone())?;
+    let stream = table
+        .read_data_block_stream(ctx.clone(), &source_plan)
+        .await?;
+    let blocks = stream.try_collect::<Vec<_>>().await?;
+    assert_eq!(rendered_timestamps(&blocks, Tz::Asia__Shanghai), vec![
+        "2022-01-01 16:00:00",
+        "2022-01-01 08:00:00",
+    ]);
+    assert_eq!(rendered_timestamps(&blocks, Tz::UTC), vec![
+        "2022-01-01 08:00:00",
+        "2022-01-01 00:00:00",
+    ]);
+
+    // The option takes precedence over the session timezone.
+    let table = build_table(&operator, &["data/t.parquet", "timezone=UTC"])?;
+    let source_plan = table
+        .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+        .await?;
+    ctx.try_set_partitions(source_plan.parts.clone())?;
+    let stream = table
+        .read_data_block_stream(ctx.clone(), &source_plan)
+        .await?;
+    let blocks = stream.try_collect::<Vec<_>>().await?;
+    assert_eq!(rendered_timestamps(&blocks, Tz::UTC), vec![
+        "2022-01-01 08:00:00",
+        "2022-01-01 08:00:00",
+    ]);
+
+    // Without a session, the local times are taken in UTC unless the option is set.
+    let table = build_table(&operator, &["data/t.parquet"])?;
+    let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+    assert_eq!(rendered_timestamps(&blocks, Tz::UTC), vec![
+        "2022-01-01 08:00:00",
+        "2022-01-01 08:00:00",
+    ]);
+    let table = build_table(&operator, &["data/t.parquet", "timezone=America/New_York"])?;
+    let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+    assert_eq!(rendered_timestamps(&blocks, Tz::America__New_York), vec![
+        "2022-01-01 03:00:00",
+        "2022-01-01 08:00:00",
+    ]);
+
+    let err = build_table(&operator, &["data/t.parquet", "timezone=Mars/Olympus"])
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+
+    Ok(())
+}
+
+/// A parquet file written by arrow with the arrow schema embedded, holding a TIMESTAMP
+/// column `ts` of milliseconds in the timezone Asia/Shanghai, a DATE column `day` stored
+/// as INT32 and a dictionary encoded STRING column `name`.
+fn embedded_arrow_schema_file(millis: i64, days: i32) -> Result<Vec<u8>> {
+    let ts_type = ArrowType::Timestamp(TimeUnit::Millisecond, Some("Asia/Shanghai".to_string()));
+    let day_metadata = BTreeMap::from([(ARROW_EXTENSION_NAME.to_string(), "Date".to_string())]);
+    let name_type = ArrowType::Dictionary(IntegerType::Int32, Box::new(ArrowType::Utf8), false);
+    let arrow_schema = ArrowSchema::from(vec![
+        ArrowField::new("ts", ts_type.clone(), false),
+        ArrowField::new("day", ArrowType::Int32, false).with_metadata(day_metadata),
+        ArrowField::new("name", name_type, false),
+    ]);
+    let names = DictionaryArray::<i32>::try_from_keys(
+        PrimitiveArray::from_vec(vec![0]),
+        Utf8Array::<i32>::from_slice(["databend"]).boxed(),
+    )?;
+    let chunk = Chunk::new(vec![
+        PrimitiveArray::<i64>::from_vec(vec![millis]).to(ts_type).boxed(),
+        PrimitiveArray::<i32>::from_vec(vec![days]).boxed(),
+        names.boxed(),
+    ]);
+    let options = WriteOptions {
+        write_statistics: true,
+        compression: CompressionOptions::Uncompressed,
+        version: Version::V2,
+    };
+    let row_groups = RowGroupIterator::try_new(
+        vec![Ok(chunk)].into_iter(),
+        &arrow_schema,
+        options,
+        vec![
+            vec![Encoding::Plain],
+            vec![Encoding::Plain],
+            vec![Encoding::RleDictionary],
+        ],
+    )?;
+    let mut buf = vec![];
+    let mut writer = FileWriter::try_new(&mut buf, arrow_schema, options)?;
+    for row_group in row_groups {
+        writer.write(row_group?)?;
+    }
+    writer.end(None)?;
+    Ok(buf)
+}
+
+#[tokio::test]
+async fn test_read_parquet_embedded_arrow_schema() -> Result<()> {
+    let operator = memory_operator()?;
+    // 2022-01-01 08:00:00 in Asia/Shanghai.
+    let millis = 1_641_024_000_000;
+    let days = 18993;
+    operator
+        .object("data/arrow.parquet")
+        .write(embedded_arrow_schema_file(millis, days)?)
+        .await?;
+
+    let table = build_table(&operator, &["data/arrow.parquet"])?;
+    let schema = table.schema();
+    let types = schema
+        .fields()
+        .iter()
+        .map(|field| remove_nullable(field.data_type()).data_type_id())
+        .collect::<Vec<_>>();
+    assert_eq!(types, vec![TypeID::Timestamp, TypeID::Date, TypeID::String]);
+
+    let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+    let values = (0..3)
+        .map(|index| blocks[0].column(index).get(0))
+        .collect::<Vec<_>>();
+    assert_eq!(values, vec![
+        DataValue::Int64(millis * 1000),
+        DataValue::Int64(days as i64),
+        DataValue::String(b"databend".to_vec()),
+    ]);
+
+    Ok(())
+}
+
+/// A file of a TIMESTAMP(MILLIS) column `ms` and a TIMESTAMP(NANOS) column `ns`, with a
+/// row of `millis` and `nanos` after a row of zeros.
+fn timestamp_units_file(millis: i64, nanos: i64) -> Result<Vec<u8>> {
+    let ms_type = ArrowType::Timestamp(TimeUnit::Millisecond, Some("+00:00".to_string()));
+    let ns_type = ArrowType::Timestamp(TimeUnit::Nanosecond, Some("+00:00".to_string()));
+    let fields = vec![
+        ArrowField::new("ms", ms_type.clone(), false),
+        ArrowField::new("ns", ns_type.clone(), false),
+    ];
+    let chunk = Chunk::new(vec![
+        PrimitiveArray::<i64>::from_vec(vec![0, millis]).to(ms_type).boxed(),
+        PrimitiveArray::<i64>::from_vec(vec![0, nanos]).to(ns_type).boxed(),
+    ]);
+    let (buf, _) = chunks_file(fields, vec![chunk])?;
+    Ok(buf)
+}
+
+#[tokio::test]
+async fn test_read_parquet_timestamp_overflow() -> Result<()> {
+    let operator = memory_operator()?;
+    // The extreme nanoseconds are scaled down to microseconds and always fit, the
+    // milliseconds overflow once scaled up, or fall out of the range of the timestamps.
+    operator
+        .object("data/max.parquet")
+        .write(timestamp_units_file(i64::MAX / 1000 + 1, i64::MAX)?)
+        .await?;
+    operator
+        .object("data/min.parquet")
+        .write(timestamp_units_file(-300_000_000_000_000, i64::MIN)?)
+        .await?;
+    let values = |blocks: &[DataBlock]| {
+        (0..2)
+            .map(|index| blocks[0].column(index).get(1))
+            .collect::<Vec<_>>()
+    };
+
+    for location in ["data/max.parquet", "data/min.parquet"] {
+        for policy in ["", "on_timestamp_overflow=error"] {
+            let mut args = vec![location];
+            if !policy.is_empty() {
+                args.push(policy);
+            }
+            let table = build_table(&operator, &args)?;
+            let err = match table.read_blocks()?.collect::<Result<Vec<_>>>() {
+                Ok(_) => panic!("the overflow of {} should be an error", location),
+                Err(e) => e,
+            };
+            assert!(err.message().contains(location), "{}", err.message());
+            assert!(err.message().contains("column 'ms' at row 1"), "{}", err.message());
+        }
+    }
+
+    let table = build_table(&operator, &["data/max.parquet", "on_timestamp_overflow=saturate"])?;
+    let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+    assert_eq!(values(&blocks), vec![
+        DataValue::Int64(TIMESTAMP_MAX),
+        DataValue::Int64(i64::MAX / 1000),
+    ]);
+    assert_eq!(blocks[0].column(0).get(0), DataValue::Int64(0));
+
+    let table = build_table(&operator, &["data/min.parquet", "on_timestamp_overflow=saturate"])?;
+    let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+    assert_eq!(values(&blocks), vec![
+        DataValue::Int64(TIMESTAMP_MIN),
+        DataValue::Int64(i64::MIN / 1000),
+    ]);
+
+    let err = build_table(&operator, &["data/max.parquet", "on_timestamp_overflow=wrap"])
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+
+    Ok(())
+}
+
+/// A file of a TIMESTAMP column `ts` of `unit`, adjusted to UTC.
+fn timestamp_file(unit: TimeUnit, values: Vec<i64>) -> Result<Vec<u8>> {
+    let ts_type = ArrowType::Timestamp(unit, Some("+00:00".to_string()));
+    let fields = vec![ArrowField::new("ts", ts_type.clone(), false)];
+    let chunk = Chunk::new(vec![
+        PrimitiveArray::<i64>::from_vec(values).to(ts_type).boxed(),
+    ]);
+    let (buf, _) = chunks_file(fields, vec![chunk])?;
+    Ok(buf)
+}
+
+#[tokio::test]
+async fn test_read_parquet_mixed_time_units() -> Result<()> {
+    let operator = memory_operator()?;
+    // The same instants, in microseconds then in milliseconds.
+    operator
+        .object("data/a.parquet")
+        .write(timestamp_file(TimeUnit::Microsecond, vec![
+            1_600_000_000_000_000,
+            1_600_000_000_123_000,
+        ])?)
+        .await?;
+    operator
+        .object("data/b.parquet")
+        .write(timestamp_file(TimeUnit::Millisecond, vec![
+            1_600_000_000_000,
+            1_600_000_000_123,
+        ])?)
+        .await?;
+
+    // The values are normalized to microseconds, and the mix is reported.
+    let table = build_table(&operator, &["data/*.parquet", "warnings=collect"])?;
+    let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+    let mut values = blocks
+        .iter()
+        .flat_map(|block| (0..block.num_rows()).map(|i| block.column(0).get(i)))
+        .collect::<Vec<_>>();
+    values.sort();
+    assert_eq!(values, vec![
+        DataValue::Int64(1_600_000_000_000_000),
+        DataValue::Int64(1_600_000_000_000_000),
+        DataValue::Int64(1_600_000_000_123_000),
+        DataValue::Int64(1_600_000_000_123_000),
+    ]);
+    let warnings = table.warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].kind, WarningKind::MixedTimeUnits);
+    assert_eq!(warnings[0].location.as_deref(), Some("data/b.parquet"));
+    assert_eq!(warnings[0].column.as_deref(), Some("ts"));
+
+    // Or rejected, naming the files.
+    let table = build_table(&operator, &["data/*.parquet", "on_mixed_time_units=error"])?;
+    let err = table.read_blocks().err().unwrap();
+    assert_eq!(err.code(), ErrorCode::PARQUET_SCHEMA_MISMATCH);
+    assert!(err.message().contains("'data/a.parquet'"), "{}", err.message());
+    assert!(err.message().contains("'data/b.parquet'"), "{}", err.message());
+
+    // The files of the same unit are read either way.
+    let table = build_table(&operator, &["data/a.parquet", "on_mixed_time_units=error"])?;
+    let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+    assert_eq!(blocks.iter().map(|b| b.num_rows()).sum::<usize>(), 2);
+
+    let err = build_table(&operator, &["data/*.parquet", "on_mixed_time_units=scale"])
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_nullability() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+    let operator = memory_operator()?;
+    let buf = nullable_int_columns_file(&[
+        ("a", vec![Some(1), Some(2), Some(3)]),
+        ("b", vec![Some(1), None, Some(3)]),
+        ("c", vec![Some(1), Some(2), Some(3)]),
+    ])?;
+    operator.object("data/t.parquet").write(buf).await?;
+    // `c` holds a NULL in another file only.
+    let buf = nullable_int_columns_file(&[
+        ("a", vec![Some(4)]),
+        ("b", vec![Some(4)]),
+        ("c", vec![None]),
+    ])?;
+    operator.object("data/u.parquet").write(buf).await?;
+
+    let read = |args: Vec<&'static str>| {
+        let ctx = ctx.clone();
+        let operator = operator.clone();
+        async move {
+            let table = build_table(&operator, &args)?;
+            let source_plan = table
+                .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+                .await?;
+            ctx.try_set_partitions(source_plan.parts.clone())?;
+            let stream = table.read_data_block_stream(ctx, &source_plan).await?;
+            let blocks = stream.try_collect::<Vec<_>>().await?;
+            Ok::<_, ErrorCode>((table.schema(), blocks))
+        }
+    };
+    // The nullability of the columns of a block, which is the one of the table schema.
+    let nullability = |schema: &DataSchemaRef, block: &DataBlock| {
+        (0..block.num_columns())
+            .map(|i| {
+                let is_nullable = block.schema().field(i).is_nullable();
+                assert_eq!(block.column(i).is_nullable(), is_nullable);
+                assert_eq!(schema.field(i).is_nullable(), is_nullable);
+                is_nullable
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // The declared nullability is kept by default, whatever the values.
+    for args in [vec!["data/t.parquet"], vec!["data/t.parquet", "nullability=declared"]] {
+        let (schema, blocks) = read(args).await?;
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(nullability(&schema, &blocks[0]), vec![true, true, true]);
+    }
+
+    // The optional columns without NULL in the file are tightened in the table schema, the
+    // one with NULL is kept nullable.
+    let (schema, blocks) = read(vec!["data/t.parquet", "nullability=observed"]).await?;
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(nullability(&schema, &blocks[0]), vec![false, true, false]);
+    let expected = vec![
+        "+---+------+---+",
+        "| a | b    | c |",
+        "+---+------+---+",
+        "| 1 | 1    | 1 |",
+        "| 2 | NULL | 2 |",
+        "| 3 | 3    | 3 |",
+        "+---+------+---+",
+    ];
+    common_datablocks::assert_blocks_sorted_eq(expected, blocks.as_slice());
+
+    // The nullability is observed over all the files, the same for all the blocks.
+    let (schema, blocks) = read(vec!["data/*.parquet", "nullability=observed"]).await?;
+    assert_eq!(blocks.len(), 2);
+    for block in &blocks {
+        assert_eq!(nullability(&schema, block), vec![false, true, true]);
+    }
+
+    let err = read(vec!["data/t.parquet", "nullability=tight"])
+        .await
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_literals() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+    let operator = memory_operator()?;
+    operator
+        .object("data/a.parquet")
+        .write(int_row_groups_file(&[vec![1, 2]])?)
+        .await?;
+    operator
+        .object("data/b.parquet")
+        .write(int_row_groups_file(&[vec![3]])?)
+        .await?;
+
+    // An int and a string literal, typed by their values, after the columns of the files.
+    let table = build_table(&operator, &[
+        "data/*.parquet",
+        "literals=batch:42, Source:daily",
+    ])?;
+    let schema = table.schema();
+    let types = schema
+        .fields()
+        .iter()
+        .map(|field| (field.name().as_str(), field.data_type().data_type_id()))
+        .collect::<Vec<_>>();
+    assert_eq!(types, vec![
+        ("id", TypeID::Int32),
+        ("batch", TypeID::Int64),
+        ("source", TypeID::String),
+    ]);
+
+    // They are materialized in the blocks of the selected columns.
+    let push_downs = PushDownInfo {
+        projection: Some(Projection::Columns(vec![2, 0, 1])),
+        ..Default::default()
+    };
+    let source_plan = table.read_plan(ctx.clone(), Some(push_downs)).await?;
+    ctx.try_set_partitions(source_plan.parts.clone())?;
+    let stream = table.read_data_block_stream(ctx.clone(), &source_plan).await?;
+    let blocks = stream.try_collect::<Vec<_>>().await?;
+    let expected = vec![
+        "+--------+----+-------+",
+        "| source | id | batch |",
+        "+--------+----+-------+",
+        "| daily  | 1  | 42    |",
+        "| daily  | 2  | 42    |",
+        "| daily  | 3  | 42    |",
+        "+--------+----+-------+",
+    ];
+    common_datablocks::assert_blocks_sorted_eq(expected, blocks.as_slice());
+
+    // The type can be given, the value is cast to it.
+    let table = build_table(&operator, &["data/*.parquet", "literals=batch:42::UInt8"])?;
+    let field = table.schema().field(1).clone();
+    assert_eq!(field.data_type().data_type_id(), TypeID::UInt8);
+    let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+    assert!(blocks
+        .iter()
+        .all(|block| block.column(1).get(0) == DataValue::UInt64(42)));
+
+    let cases = [
+        "literals=batch",
+        "literals=:42",
+        "literals=batch:42::",
+        "literals=batch:1,batch:2",
+        "literals=id:1",
+        "literals=batch:x::UInt8",
+        "literals=batch:42::Nothing",
+    ];
+    for option in cases {
+        let err = build_table(&operator, &["data/*.parquet", option])
+            .err()
+            .unwrap();
+        assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS, "{}", option);
+    }
+
+    Ok(())
+}
+
+/// A file of the columns `day` (DATE), `count` (UINT_32), `name` (UTF8) and `ts`
+/// (TIMESTAMP_MILLIS) annotated only by their converted types, like the files written
+/// before the logical types, with a row of `days`, `count`, `name` and `millis`.
+fn converted_types_file(days: i32, count: u32, name: &str, millis: i64) -> Result<Vec<u8>> {
+    let ts_type = ArrowType::Timestamp(TimeUnit::Millisecond, Some("+00:00".to_string()));
+    let fields = vec![
+        ArrowField::new("day", ArrowType::Date32, false),
+        ArrowField::new("count", ArrowType::UInt32, false),
+        ArrowField::new("name", ArrowType::Utf8, false),
+        ArrowField::new("ts", ts_type.clone(), false),
+    ];
+    let chunk = Chunk::new(vec![
+        PrimitiveArray::<i32>::from_vec(vec![days]).to(ArrowType::Date32).boxed(),
+        PrimitiveArray::<u32>::from_vec(vec![count]).boxed(),
+        Utf8Array::<i32>::from_slice([name]).boxed(),
+        PrimitiveArray::<i64>::from_vec(vec![millis]).to(ts_type).boxed(),
+    ]);
+    let (mut buf, mut meta) = chunks_file(fields, vec![chunk])?;
+
+    // Rewrite the footer without the logical types, nor the embedded arrow schema.
+    strip_footer(&mut buf);
+    for element in meta.schema[1..].iter_mut() {
+        assert!(element.converted_type.is_some());
+        element.logical_type = None;
+    }
+    meta.key_value_metadata = None;
+    append_footer(&mut buf, &meta)?;
+    Ok(buf)
+}
+
+#[tokio::test]
+async fn test_read_parquet_converted_types() -> Result<()> {
+    let operator = memory_operator()?;
+    let days = 18993;
+    let count = 4_000_000_000;
+    let millis = 1_641_024_000_000;
+    operator
+        .object("data/legacy.parquet")
+        .write(converted_types_file(days, count, "databend", millis)?)
+        .await?;
+    let types = |table: &ParquetTable| {
+        table
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| remove_nullable(field.data_type()).data_type_id())
+            .collect::<Vec<_>>()
+    };
+    let values = |table: &ParquetTable| -> Result<Vec<DataValue>> {
+        let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+        Ok((0..4).map(|index| blocks[0].column(index).get(0)).collect())
+    };
+
+    // The types are taken from the converted types by default.
+    for args in [
+        vec!["data/legacy.parquet"],
+        vec!["data/legacy.parquet", "converted_types=fallback"],
+    ] {
+        let table = build_table(&operator, &args)?;
+        assert_eq!(types(&table), vec![
+            TypeID::Date,
+            TypeID::UInt32,
+            TypeID::String,
+            TypeID::Timestamp,
+        ]);
+        assert_eq!(values(&table)?, vec![
+            DataValue::Int64(days as i64),
+            DataValue::UInt64(count as u64),
+            DataValue::String(b"databend".to_vec()),
+            DataValue::Int64(millis * 1000),
+        ]);
+    }
+
+    // Ignored, the columns are read as their physical types.
+    let table = build_table(&operator, &["data/legacy.parquet", "converted_types=ignore"])?;
+    assert_eq!(types(&table), vec![TypeID::Int32, TypeID::Int32, TypeID::String, TypeID::Int64]);
+    assert_eq!(values(&table)?, vec![
+        DataValue::Int64(days as i64),
+        DataValue::Int64(count as i32 as i64),
+        DataValue::String(b"databend".to_vec()),
+        DataValue::Int64(millis),
+    ]);
+
+    let err = build_table(&operator, &["data/legacy.parquet", "converted_types=drop"])
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+
+    Ok(())
+}
+
+/// A file of a DECIMAL(10, 2) column `price` of fixed length byte arrays of 5 bytes, whose
+/// rows are the unscaled values `values`, big-endian as by the format unless
+/// `little_endian`.
+fn flba_decimals_file(values: &[Option<i64>], little_endian: bool) -> Result<Vec<u8>> {
+    let size = 5;
+    let bytes = values
+        .iter()
+        .flat_map(|value| {
+            let value = value.unwrap_or_default() as i128;
+            if little_endian {
+                value.to_le_bytes()[..size].to_vec()
+            } else {
+                value.to_be_bytes()[16 - size..].to_vec()
+            }
+        })
+        .collect::<Vec<_>>();
+    let validity = Bitmap::from_iter(values.iter().map(Option::is_some));
+    let data_type = ArrowType::FixedSizeBinary(size);
+    let fields = vec![ArrowField::new("price", data_type.clone(), true)];
+    let array = FixedSizeBinaryArray::new(data_type, bytes.into(), Some(validity));
+    let (mut buf, mut meta) = chunks_file(fields, vec![Chunk::new(vec![array.boxed()])])?;
+
+    // Rewrite the footer with the column annotated as DECIMAL, without the embedded arrow
+    // schema.
+    strip_footer(&mut buf);
+    let element = &mut meta.schema[1];
+    element.converted_type = Some(ConvertedType::DECIMAL);
+    element.precision = Some(10);
+    element.scale = Some(2);
+    element.logical_type = Some(LogicalType::DECIMAL(DecimalType {
+        precision: 10,
+        scale: 2,
+    }));
+    meta.key_value_metadata = None;
+    append_footer(&mut buf, &meta)?;
+    Ok(buf)
+}
+
+#[tokio::test]
+async fn test_read_parquet_flba_decimals() -> Result<()> {
+    let operator = memory_operator()?;
+    let values = [Some(1234567), Some(-250), None, Some(-549_755_813_888)];
+    operator
+        .object("data/big_endian.parquet")
+        .write(flba_decimals_file(&values, false)?)
+        .await?;
+    operator
+        .object("data/little_endian.parquet")
+        .write(flba_decimals_file(&values, true)?)
+        .await?;
+    let prices = |table: &ParquetTable| -> Result<Vec<DataValue>> {
+        let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+        let column = blocks[0].column(0);
+        Ok((0..column.len()).map(|row| column.get(row)).collect())
+    };
+    // The smallest value of 5 bytes is negative, with all the other bits unset.
+    let expected = vec![
+        DataValue::Float64(12345.67),
+        DataValue::Float64(-2.5),
+        DataValue::Null,
+        DataValue::Float64(-5_497_558_138.88),
+    ];
+
+    // The values are big-endian by default, and read as FLOAT64 values.
+    let table = build_table(&operator, &["data/big_endian.parquet"])?;
+    let field = table.schema().field(0).clone();
+    assert_eq!(remove_nullable(field.data_type()).data_type_id(), TypeID::Float64);
+    assert_eq!(prices(&table)?, expected);
+    let table = build_table(&operator, &[
+        "data/big_endian.parquet",
+        "decimal_byte_order=big_endian",
+    ])?;
+    assert_eq!(prices(&table)?, expected);
+
+    // Little-endian values are misread unless the byte order is set.
+    let table = build_table(&operator, &["data/little_endian.parquet"])?;
+    assert_ne!(prices(&table)?, expected);
+    let table = build_table(&operator, &[
+        "data/little_endian.parquet",
+        "decimal_byte_order=little_endian",
+    ])?;
+    assert_eq!(prices(&table)?, expected);
+
+    let err = build_table(&operator, &["data/big_endian.parquet", "decimal_byte_order=native"])
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_decimal_rounding() -> Result<()> {
+    let operator = memory_operator()?;
+    // DECIMAL(10, 2) values, with halves at both scales.
+    let values = [
+        Some(125),
+        Some(135),
+        Some(-125),
+        Some(-135),
+        Some(124),
+        Some(126),
+        Some(-129),
+        Some(250),
+        Some(-250),
+        None,
+    ];
+    operator
+        .object("data/t.parquet")
+        .write(flba_decimals_file(&values, false)?)
+        .await?;
+    operator
+        .object("data/max.parquet")
+        .write(flba_decimals_file(&[Some(9995)], false)?)
+        .await?;
+    let prices = |args: &[&str]| -> Result<Vec<DataValue>> {
+        let table = build_table(&operator, args)?;
+        let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+        let column = blocks[0].column(0);
+        Ok((0..column.len()).map(|row| column.get(row)).collect())
+    };
+    let floats = |values: &[f64]| {
+        let mut values = values
+            .iter()
+            .map(|value| DataValue::Float64(*value))
+            .collect::<Vec<_>>();
+        values.push(DataValue::Null);
+        values
+    };
+
+    for (rounding, scale_1, scale_0) in [
+        (
+            "half_up",
+            [1.3, 1.4, -1.3, -1.4, 1.2, 1.3, -1.3, 2.5, -2.5],
+            [1.0, 1.0, -1.0, -1.0, 1.0, 1.0, -1.0, 3.0, -3.0],
+        ),
+        (
+            "half_even",
+            [1.2, 1.4, -1.2, -1.4, 1.2, 1.3, -1.3, 2.5, -2.5],
+            [1.0, 1.0, -1.0, -1.0, 1.0, 1.0, -1.0, 2.0, -2.0],
+        ),
+        (
+            "truncate",
+            [1.2, 1.3, -1.2, -1.3, 1.2, 1.2, -1.2, 2.5, -2.5],
+            [1.0, 1.0, -1.0, -1.0, 1.0, 1.0, -1.0, 2.0, -2.0],
+        ),
+    ] {
+        let option = format!("decimal_rounding={}", rounding);
+        let args = ["data/t.parquet", "cast=price:decimal(10, 1)", option.as_str()];
+        assert_eq!(prices(&args)?, floats(&scale_1), "{}", rounding);
+        let args = ["data/t.parquet", "cast=price:DECIMAL(10)", option.as_str()];
+        assert_eq!(prices(&args)?, floats(&scale_0), "{}", rounding);
+    }
+
+    // The values of the scale of the cast or a smaller one are kept as they are, without a
+    // rounding.
+    let expected = prices(&["data/t.parquet"])?;
+    assert_eq!(prices(&["data/t.parquet", "cast=price:decimal(12, 4)"])?, expected);
+
+    // The values of a larger scale are not rounded implicitly.
+    let err = prices(&["data/t.parquet", "cast=price:decimal(10, 1)"])
+        .err()
+        .unwrap();
+    assert!(err.message().contains("decimal_rounding"), "{}", err.message());
+
+    // The values rounded beyond the precision are rejected.
+    for rounding in ["half_up", "half_even"] {
+        let option = format!("decimal_rounding={}", rounding);
+        let err = prices(&["data/max.parquet", "cast=price:decimal(3, 1)", option.as_str()])
+            .err()
+            .unwrap();
+        assert!(
+            err.message().contains("without losing precision"),
+            "{}",
+            err.message()
+        );
+    }
+    assert_eq!(
+        prices(&[
+            "data/max.parquet",
+            "cast=price:decimal(3, 1)",
+            "decimal_rounding=truncate"
+        ])?,
+        vec![DataValue::Float64(99.9)]
+    );
+
+    for args in [
+        ["data/t.parquet", "cast=price:decimal(1, 2)"],
+        ["data/t.parquet", "cast=price:decimal(10, x)"],
+        ["data/t.parquet", "decimal_rounding=half_down"],
+    ] {
+        let err = build_table(&operator, &args).err().unwrap();
+        assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS, "{:?}", args);
+    }
+
+    Ok(())
+}
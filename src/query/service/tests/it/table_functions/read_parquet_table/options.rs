@@ -0,0 +1,1953 @@
+//  Copyright 2021 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::io::Cursor;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use common_arrow::arrow::chunk::Chunk;
+use common_arrow::arrow::datatypes::DataType as ArrowType;
+use common_arrow::arrow::datatypes::Field as ArrowField;
+use common_arrow::arrow::io::parquet::read::read_metadata;
+use common_arrow::parquet::compression::CompressionOptions;
+use common_arrow::parquet::thrift_format::thrift::protocol::TCompactInputProtocol;
+use common_arrow::parquet::thrift_format::PageHeader;
+use common_base::base::tokio;
+use common_catalog::plan::Expression;
+use common_catalog::plan::PrewhereInfo;
+use common_catalog::plan::Projection;
+use common_catalog::plan::PushDownInfo;
+use common_catalog::table::Table;
+use common_datablocks::serialize_data_blocks;
+use common_datablocks::serialize_data_blocks_with_compression;
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_sql::executor::table_read_plan::ToReadDataSourcePlan;
+use common_storages_parquet::resolve_files;
+use common_storages_parquet::FileOperators;
+use common_storages_parquet::ParquetPartInfo;
+use common_storages_parquet::ParquetTable;
+use common_storages_parquet::ParquetTableBuilder;
+use common_storages_parquet::ParquetWarnings;
+use common_storages_parquet::ReadAheadWindow;
+use common_storages_parquet::WarningKind;
+use databend_query::sessions::QueryContext;
+use databend_query::sessions::TableContext;
+use databend_query::stream::ReadDataBlockStream;
+use futures::TryStreamExt;
+use opendal::Operator;
+use tempfile::TempDir;
+
+use super::table_test_fixture::build_table;
+use super::table_test_fixture::chunks_file;
+use super::table_test_fixture::id_equals;
+use super::table_test_fixture::id_name_block;
+use super::table_test_fixture::int_column_file;
+use super::table_test_fixture::int_fields_file;
+use super::table_test_fixture::int_row_groups_file;
+use super::table_test_fixture::matched_ids;
+use super::table_test_fixture::memory_operator;
+use super::table_test_fixture::nullable_int_columns_file;
+
+#[tokio::test]
+async fn test_read_parquet_max_block_rows() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+
+    let schema = DataSchemaRefExt::create(vec![
+        DataField::new("id", i32::to_data_type()),
+        DataField::new("name", Vu8::to_data_type()),
+    ]);
+    let block = DataBlock::create(schema.clone(), vec![
+        Series::from_data(vec![1i32, 2, 3, 4, 5]),
+        Series::from_data(vec!["a", "b", "c", "d", "e"]),
+    ]);
+    let mut buf = vec![];
+    serialize_data_blocks(vec![block], &schema, &mut buf)?;
+
+    let operator = memory_operator()?;
+    operator.object("data/t.parquet").write(buf).await?;
+
+    // The row group of 5 rows is read as blocks of 2, 2 and 1 rows.
+    let table = build_table(&operator, &["data/t.parquet", "max_block_rows=2"])?;
+    let source_plan = table
+        .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+        .await?;
+    ctx.try_set_partitions(source_plan.parts.clone())?;
+
+    let stream = table.read_data_block_stream(ctx, &source_plan).await?;
+    let result = stream.try_collect::<Vec<_>>().await?;
+    let num_rows = result.iter().map(|b| b.num_rows()).collect::<Vec<_>>();
+    assert_eq!(num_rows, vec![2, 2, 1]);
+
+    let expected = vec![
+        "+----+------+",
+        "| id | name |",
+        "+----+------+",
+        "| 1  | a    |",
+        "| 2  | b    |",
+        "| 3  | c    |",
+        "| 4  | d    |",
+        "| 5  | e    |",
+        "+----+------+",
+    ];
+    common_datablocks::assert_blocks_sorted_eq(expected, result.as_slice());
+
+    // A block size of 0 is rejected.
+    let err = build_table(&operator, &["data/t.parquet", "max_block_rows=0"])
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+    assert_eq!(
+        err.message(),
+        "read_parquet option 'max_block_rows' must be greater than 0"
+    );
+
+    // The choices of the options are case insensitive, and listed when none matches.
+    build_table(&operator, &["data/t.parquet", "file_order=Name_Desc"])?;
+    let err = build_table(&operator, &["data/t.parquet", "file_order=random"])
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+    assert_eq!(
+        err.message(),
+        "invalid value 'random' for read_parquet option 'file_order': expect 'name', 'name_desc', 'mtime' or 'as_listed'"
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_batch_size() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+    // A single source reads the parts one after the other.
+    ctx.get_settings().set_max_threads(1)?;
+    ctx.get_settings().set_max_storage_io_requests(1)?;
+    let operator = memory_operator()?;
+    operator
+        .object("data/a.parquet")
+        .write(int_row_groups_file(&[(0..7).collect(), (7..10).collect()])?)
+        .await?;
+    operator
+        .object("data/b.parquet")
+        .write(int_row_groups_file(&[(10..25).collect()])?)
+        .await?;
+
+    let read = |batch_size: usize| {
+        let ctx = ctx.clone();
+        let option = format!("batch_size={}", batch_size);
+        let args = ["data/*.parquet", option.as_str(), "row_group_index=true"];
+        let table = build_table(&operator, &args);
+        async move {
+            let table = table?;
+            let source_plan = table.read_plan(ctx.clone(), None).await?;
+            ctx.try_set_partitions(source_plan.parts.clone())?;
+            let stream = table.read_data_block_stream(ctx, &source_plan).await?;
+            stream.try_collect::<Vec<_>>().await
+        }
+    };
+
+    // The blocks have the rows of the batch size across the row groups and the files,
+    // but the last one.
+    let blocks = read(4).await?;
+    let num_rows = blocks.iter().map(|b| b.num_rows()).collect::<Vec<_>>();
+    assert_eq!(num_rows, vec![4, 4, 4, 4, 4, 4, 1]);
+    let ids = blocks
+        .iter()
+        .flat_map(|block| (0..block.num_rows()).map(|i| block.column(0).get(i)))
+        .collect::<Vec<_>>();
+    assert_eq!(ids, (0..25).map(DataValue::Int64).collect::<Vec<_>>());
+    // The values of the columns not stored in the files follow their rows.
+    let row_groups = blocks
+        .iter()
+        .flat_map(|block| (0..block.num_rows()).map(|i| block.column(1).get(i)))
+        .collect::<Vec<_>>();
+    let expected = (0..25).map(|id| DataValue::UInt64((7..10).contains(&id) as u64));
+    assert_eq!(row_groups, expected.collect::<Vec<_>>());
+
+    let blocks = read(5).await?;
+    let num_rows = blocks.iter().map(|b| b.num_rows()).collect::<Vec<_>>();
+    assert_eq!(num_rows, vec![5, 5, 5, 5, 5]);
+    let blocks = read(100).await?;
+    let num_rows = blocks.iter().map(|b| b.num_rows()).collect::<Vec<_>>();
+    assert_eq!(num_rows, vec![25]);
+
+    let err = build_table(&operator, &["data/*.parquet", "batch_size=0"])
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_columns() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+
+    let schema = DataSchemaRefExt::create(vec![
+        DataField::new("c0", i32::to_data_type()),
+        DataField::new("c1", Vu8::to_data_type()),
+        DataField::new("c2", i64::to_data_type()),
+    ]);
+    let block = DataBlock::create(schema.clone(), vec![
+        Series::from_data(vec![1i32, 2]),
+        Series::from_data(vec!["a", "b"]),
+        Series::from_data(vec![10i64, 20]),
+    ]);
+    let mut buf = vec![];
+    serialize_data_blocks(vec![block], &schema, &mut buf)?;
+
+    let operator = memory_operator()?;
+    operator.object("data/t.parquet").write(buf).await?;
+
+    let table = build_table(&operator, &["data/t.parquet", "columns=id, Name,value"])?;
+    let source_plan = table
+        .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+        .await?;
+    ctx.try_set_partitions(source_plan.parts.clone())?;
+
+    let stream = table.read_data_block_stream(ctx, &source_plan).await?;
+    let result = stream.try_collect::<Vec<_>>().await?;
+
+    let expected = vec![
+        "+----+------+-------+",
+        "| id | name | value |",
+        "+----+------+-------+",
+        "| 1  | a    | 10    |",
+        "| 2  | b    | 20    |",
+        "+----+------+-------+",
+    ];
+    common_datablocks::assert_blocks_sorted_eq(expected, result.as_slice());
+
+    // The number of names must match the number of columns.
+    let err = build_table(&operator, &["data/t.parquet", "columns=id,name"])
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_page_null_counts() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+
+    // Write the file with statistics, so that it has column indexes.
+    let schema = DataSchemaRefExt::create(vec![DataField::new_nullable("id", i32::to_data_type())]);
+    let block = DataBlock::create(schema.clone(), vec![Series::from_data(vec![
+        Some(1i32),
+        None,
+        Some(3),
+        None,
+        None,
+    ])]);
+    let (buf, meta) = chunks_file(schema.to_arrow().fields, vec![Chunk::try_from(block)?])?;
+    assert!(meta.row_groups[0].columns[0].column_index_offset.is_some());
+
+    let operator = memory_operator()?;
+    operator.object("data/t.parquet").write(buf).await?;
+
+    // No statistics without the option.
+    let table = build_table(&operator, &["data/t.parquet"])?;
+    let provider = table.column_statistics_provider().await?;
+    assert!(provider.column_statistics(0).is_none());
+
+    let table = build_table(&operator, &["data/t.parquet", "page_null_counts=true"])?;
+    let provider = table.column_statistics_provider().await?;
+    let null_count = provider.column_statistics(0).unwrap().null_count;
+
+    // The same as the number of nulls found by a full scan.
+    let source_plan = table
+        .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+        .await?;
+    ctx.try_set_partitions(source_plan.parts.clone())?;
+    let stream = table.read_data_block_stream(ctx, &source_plan).await?;
+    let result = stream.try_collect::<Vec<_>>().await?;
+    let scanned_null_count = result
+        .iter()
+        .map(|block| {
+            let column = block.column(0);
+            (0..column.len()).filter(|i| column.null_at(*i)).count()
+        })
+        .sum::<usize>();
+    assert_eq!(null_count, 3);
+    assert_eq!(null_count as usize, scanned_null_count);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_max_scan_bytes() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+
+    let operator = memory_operator()?;
+    for location in ["data/a.parquet", "data/b.parquet"] {
+        let (buf, _) = int_column_file("id")?;
+        operator.object(location).write(buf).await?;
+    }
+
+    let table = build_table(&operator, &["data/*.parquet"])?;
+    let source_plan = table
+        .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+        .await?;
+    let read_bytes = source_plan.statistics.read_bytes;
+    assert!(read_bytes > 0);
+
+    // Just under the limit.
+    let max_scan_bytes = format!("max_scan_bytes={}", read_bytes);
+    let table = build_table(&operator, &["data/*.parquet", &max_scan_bytes])?;
+    let source_plan = table
+        .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+        .await?;
+    assert_eq!(source_plan.statistics.read_bytes, read_bytes);
+
+    // Exceeding the limit, the error is raised before any file is scanned.
+    let max_scan_bytes = format!("max_scan_bytes={}", read_bytes - 1);
+    let table = build_table(&operator, &["data/*.parquet", &max_scan_bytes])?;
+    let err = table
+        .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+        .await
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_no_prune() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+
+    let row_groups = (0..10)
+        .map(|i| (i * 10..i * 10 + 10).collect::<Vec<i32>>())
+        .collect::<Vec<_>>();
+    let operator = memory_operator()?;
+    operator
+        .object("data/t.parquet")
+        .write(int_row_groups_file(&row_groups)?)
+        .await?;
+
+    let pruned = build_table(&operator, &["data/t.parquet"])?;
+    let unpruned = build_table(&operator, &["data/t.parquet", "no_prune=true"])?;
+    for id in [0, 55, 99, 100] {
+        let (pruned_rows, pruned_ids) = matched_ids(ctx.clone(), pruned.clone(), id).await?;
+        let (unpruned_rows, unpruned_ids) = matched_ids(ctx.clone(), unpruned.clone(), id).await?;
+        // All the row groups are read, with the same results.
+        assert!(pruned_rows <= 10);
+        assert_eq!(unpruned_rows, 100);
+        assert_eq!(pruned_ids, unpruned_ids);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_sample() -> Result<()> {
+    let tmp_dir = TempDir::new().unwrap();
+    let root = tmp_dir.path().to_str().unwrap();
+    let conf = crate::tests::ConfigBuilder::create()
+        .parquet_trusted_root(root)
+        .build();
+    let (_guard, ctx) = crate::tests::create_query_context_with_config(conf, None).await?;
+
+    // 4 files of 25 row groups of 40 rows, 4000 rows.
+    let operator = memory_operator()?;
+    for file in 0..4 {
+        let row_groups = (0..25)
+            .map(|i| {
+                let first = file * 1000 + i * 40;
+                (first..first + 40).collect::<Vec<i32>>()
+            })
+            .collect::<Vec<_>>();
+        operator
+            .object(&format!("data/{}.parquet", file))
+            .write(int_row_groups_file(&row_groups)?)
+            .await?;
+    }
+
+    for fraction in [0.01, 0.1, 0.5, 1.0] {
+        let option = format!("fraction={}", fraction);
+        let table = build_table(&operator, &["data/*.parquet", &option])?;
+        assert!(!table.has_exact_total_row_count());
+        let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+
+        // Whole row groups are read, within half a row group of the fraction of the rows.
+        let rows = blocks.iter().map(|block| block.num_rows()).sum::<usize>();
+        assert_eq!(rows % 40, 0);
+        assert!((rows as f64 - 4000.0 * fraction).abs() <= 20.0, "{}", option);
+
+        // They are spread over the files.
+        let mut file_rows = vec![0; 4];
+        for block in &blocks {
+            match block.column(0).get(0) {
+                DataValue::Int64(id) => file_rows[id as usize / 1000] += block.num_rows(),
+                value => panic!("expect an id, but got {:?}", value),
+            }
+        }
+        if fraction >= 0.1 {
+            for rows in file_rows {
+                assert!((rows as f64 - 1000.0 * fraction).abs() <= 40.0, "{}", option);
+            }
+        }
+    }
+
+    for option in ["fraction=0", "fraction=1.5", "fraction=half"] {
+        let err = build_table(&operator, &["data/*.parquet", option])
+            .err()
+            .unwrap();
+        assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+    }
+
+    // The table function `read_parquet_sample` needs the fraction.
+    std::fs::write(tmp_dir.path().join("a.parquet"), int_column_file("a")?.0)?;
+    let path = format!("{}/a.parquet", root);
+    let create = |args: &[&str]| {
+        let table_args = args
+            .iter()
+            .map(|arg| DataValue::String(arg.as_bytes().to_vec()))
+            .collect();
+        ctx.get_catalog("default")?
+            .get_table_function("read_parquet_sample", Some(table_args), ctx.as_ref())
+    };
+    let err = create(&[&path]).err().unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+    let table = create(&[&path, "fraction=1"])?.as_table();
+    let source_plan = table
+        .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+        .await?;
+    assert_eq!(source_plan.statistics.read_rows, 3);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_warnings() -> Result<()> {
+    let operator = memory_operator()?;
+    let buf = int_row_groups_file(&[vec![1, 2, 3]])?;
+    operator.object("data/t.parquet").write(buf).await?;
+    operator.object("data/tiny.parquet").write(vec![0u8; 4]).await?;
+
+    let args = ["data/*", "min_file_size=16", "cast=id:double", "warnings=collect"];
+    let table = build_table(&operator, &args)?;
+    let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+    assert_eq!(blocks.iter().map(|b| b.num_rows()).sum::<usize>(), 3);
+
+    // The coerced column and the skipped file are collected, along with the result.
+    let warnings = table.warnings();
+    assert_eq!(warnings.len(), 2);
+    assert_eq!(warnings[0].kind, WarningKind::SkippedFile);
+    assert_eq!(warnings[0].location.as_deref(), Some("data/tiny.parquet"));
+    assert_eq!(warnings[1].kind, WarningKind::Coercion);
+    assert_eq!(warnings[1].column.as_deref(), Some("id"));
+    assert_eq!(
+        warnings[1].message,
+        "Column 'id' is read as Float64 instead of Int32 by the option 'cast'"
+    );
+
+    let block = table.warnings_block();
+    assert_eq!(block.schema(), &ParquetWarnings::schema());
+    assert_eq!(block.num_rows(), 2);
+    assert_eq!(block.column(0).get(1), DataValue::String(b"coercion".to_vec()));
+    assert_eq!(block.column(1).get(1), DataValue::Null);
+    assert_eq!(block.column(3).get(1), DataValue::String(b"id".to_vec()));
+
+    // They are only logged by default.
+    let table = build_table(&operator, &["data/*", "min_file_size=16", "cast=id:double"])?;
+    assert!(table.warnings().is_empty());
+
+    let err = build_table(&operator, &["data/t.parquet", "warnings=return"])
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_skip_corrupt_row_groups() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+
+    let row_groups = (0..3)
+        .map(|i| (i * 10..i * 10 + 10).collect::<Vec<i32>>())
+        .collect::<Vec<_>>();
+    let mut buf = int_row_groups_file(&row_groups)?;
+
+    // Overwrite the first page header of the second row group with garbage.
+    let meta = read_metadata(&mut Cursor::new(&buf))?;
+    let offset = meta.row_groups[1].columns()[0].byte_range().0 as usize;
+    buf[offset..offset + 8].fill(0xff);
+    let operator = memory_operator()?;
+    operator.object("data/t.parquet").write(buf).await?;
+
+    // Aborts by default.
+    let table = build_table(&operator, &["data/t.parquet"])?;
+    let source_plan = table
+        .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+        .await?;
+    ctx.try_set_partitions(source_plan.parts.clone())?;
+    let stream = table
+        .read_data_block_stream(ctx.clone(), &source_plan)
+        .await?;
+    assert!(stream.try_collect::<Vec<_>>().await.is_err());
+
+    // The other row groups are still read, the skipped rows are counted as scanned.
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+    let table = build_table(&operator, &["data/t.parquet", "on_error=skip"])?;
+    let source_plan = table
+        .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+        .await?;
+    ctx.try_set_partitions(source_plan.parts.clone())?;
+    let stream = table
+        .read_data_block_stream(ctx.clone(), &source_plan)
+        .await?;
+    let result = stream.try_collect::<Vec<_>>().await?;
+    let mut ids = result
+        .iter()
+        .flat_map(|block| {
+            let column = block.column(0);
+            (0..column.len()).map(|i| column.get(i)).collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    ids.sort();
+    let expected = (0..10)
+        .chain(20..30)
+        .map(DataValue::Int64)
+        .collect::<Vec<_>>();
+    assert_eq!(ids, expected);
+    assert_eq!(ctx.get_scan_progress_value().rows, 30);
+
+    let err = build_table(&operator, &["data/t.parquet", "on_error=continue"])
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_skip_corrupt_row_group_blocks() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+
+    // A row group large enough to be written in several pages.
+    let rows = 400_000;
+    let mut buf = int_row_groups_file(&[(0..rows as i32).collect()])?;
+
+    // Overwrite the header of the second page with garbage.
+    let meta = read_metadata(&mut Cursor::new(&buf))?;
+    let offset = meta.row_groups[0].columns()[0].byte_range().0 as usize;
+    let mut page = &buf[offset..];
+    let header = {
+        let mut prot = TCompactInputProtocol::new(&mut page, 1024);
+        PageHeader::read_from_in_protocol(&mut prot)
+            .map_err(|e| ErrorCode::ParquetFileInvalid(e.to_string()))?
+    };
+    let first_page_rows = header.data_page_header_v2.unwrap().num_rows as usize;
+    assert!(first_page_rows < rows);
+    let offset = buf.len() - page.len() + header.compressed_page_size as usize;
+    buf[offset..offset + 8].fill(0xff);
+    let operator = memory_operator()?;
+    operator.object("data/t.parquet").write(buf).await?;
+
+    // The blocks read before the corrupt page are kept, the rest of the row group is
+    // skipped and still counted as scanned, once.
+    let table = build_table(&operator, &[
+        "data/t.parquet",
+        "on_error=skip",
+        "max_block_rows=1000",
+        "warnings=collect",
+    ])?;
+    let source_plan = table
+        .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+        .await?;
+    ctx.try_set_partitions(source_plan.parts.clone())?;
+    let stream = table
+        .read_data_block_stream(ctx.clone(), &source_plan)
+        .await?;
+    let result = stream.try_collect::<Vec<_>>().await?;
+    let ids = result
+        .iter()
+        .flat_map(|block| {
+            let column = block.column(0);
+            (0..column.len()).map(|i| column.get(i)).collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let rows_read = ids.len();
+    assert!(rows_read > 0 && rows_read <= first_page_rows);
+    let expected = (0..rows_read as i64)
+        .map(DataValue::Int64)
+        .collect::<Vec<_>>();
+    assert_eq!(ids, expected);
+    assert_eq!(ctx.get_scan_progress_value().rows, rows);
+
+    let warnings = table.warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].kind, WarningKind::SkippedRowGroup);
+    assert!(warnings[0].message.starts_with(&format!(
+        "Skip the last {} rows of row group 0 of parquet file 'data/t.parquet' ({} rows, the first {} are read)",
+        rows - rows_read,
+        rows,
+        rows_read
+    )));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_name_transform() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+
+    let operator = memory_operator()?;
+    for (location, names) in [
+        ("data/t.parquet", ["User Id", " Name "]),
+        ("data/dup.parquet", ["User Id", "user_id"]),
+    ] {
+        let schema = DataSchemaRefExt::create(vec![
+            DataField::new(names[0], i32::to_data_type()),
+            DataField::new(names[1], Vu8::to_data_type()),
+        ]);
+        let block = DataBlock::create(schema.clone(), vec![
+            Series::from_data(vec![1i32, 2]),
+            Series::from_data(vec!["a", "b"]),
+        ]);
+        let mut buf = vec![];
+        serialize_data_blocks(vec![block], &schema, &mut buf)?;
+        operator.object(location).write(buf).await?;
+    }
+    let names = |table: &ParquetTable| {
+        let schema = table.get_table_info().schema();
+        schema
+            .fields()
+            .iter()
+            .map(|field| field.name().clone())
+            .collect::<Vec<_>>()
+    };
+
+    let table = build_table(&operator, &["data/t.parquet", "name_transform=snake"])?;
+    assert_eq!(names(&table), vec!["user_id", "name"]);
+    let source_plan = table
+        .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+        .await?;
+    assert_eq!(source_plan.statistics.read_rows, 2);
+
+    // The closure of the builder takes the lower cased names.
+    let table = ParquetTableBuilder::create("system", "read_parquet", 1, vec![
+        DataValue::String(b"data/t.parquet".to_vec()),
+    ])
+    .with_operator(operator.clone())
+    .with_name_transform(|name| name.trim().replace(' ', "-"))
+    .build()?;
+    assert_eq!(names(&table), vec!["user-id", "name"]);
+
+    // `User Id` and `user_id` collide after the transform.
+    let err = build_table(&operator, &["data/dup.parquet", "name_transform=snake"])
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_max_string_block_bytes() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+
+    // One row group of 1000 distinct strings of about 1KB.
+    let schema = DataSchemaRefExt::create(vec![DataField::new("name", Vu8::to_data_type())]);
+    let names = (0..1000)
+        .map(|i| format!("{:04}{}", i, "x".repeat(1000)))
+        .collect::<Vec<_>>();
+    let block = DataBlock::create(schema.clone(), vec![Series::from_data(names.clone())]);
+    let mut buf = vec![];
+    serialize_data_blocks(vec![block], &schema, &mut buf)?;
+    let operator = memory_operator()?;
+    operator.object("data/t.parquet").write(buf).await?;
+
+    let read = |args: &'static [&'static str]| {
+        let ctx = ctx.clone();
+        let operator = operator.clone();
+        async move {
+            let table = build_table(&operator, args)?;
+            let source_plan = table
+                .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+                .await?;
+            ctx.try_set_partitions(source_plan.parts.clone())?;
+            let stream = table.read_data_block_stream(ctx, &source_plan).await?;
+            stream.try_collect::<Vec<_>>().await
+        }
+    };
+    let values = |blocks: &[DataBlock]| {
+        blocks
+            .iter()
+            .flat_map(|block| {
+                let column = block.column(0);
+                (0..column.len()).map(|i| column.get(i)).collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // The string column is below the default bound, it is decoded at once.
+    let whole = read(&["data/t.parquet"]).await?;
+    assert_eq!(whole.len(), 1);
+
+    // Decoded into blocks of about 100KB of strings, with the same values. The bound is
+    // checked on the sizes of the blocks, the peak RSS of the process is not measured.
+    let bounded = read(&["data/t.parquet", "max_string_block_bytes=100000"]).await?;
+    assert!(bounded.len() >= 10);
+    assert!(bounded.iter().all(|block| block.memory_size() <= 110_000));
+    assert_eq!(values(&bounded), values(&whole));
+    let expected = names
+        .into_iter()
+        .map(|name| DataValue::String(name.into_bytes()))
+        .collect::<Vec<_>>();
+    assert_eq!(values(&bounded), expected);
+
+    // The smaller of the two bounds is taken.
+    let bounded = read(&[
+        "data/t.parquet",
+        "max_string_block_bytes=100000",
+        "max_block_rows=10",
+    ])
+    .await?;
+    assert_eq!(bounded.len(), 100);
+
+    Ok(())
+}
+
+fn id_compares(comparisons: &[(&str, i64)]) -> PushDownInfo {
+    let filters = comparisons
+        .iter()
+        .map(|(op, id)| Expression::Function {
+            name: op.to_string(),
+            args: vec![
+                Expression::IndexedVariable {
+                    name: "id".to_string(),
+                    data_type: i32::to_data_type(),
+                },
+                Expression::Constant {
+                    value: DataValue::Int64(*id),
+                    data_type: i64::to_data_type(),
+                },
+            ],
+            return_type: bool::to_data_type(),
+        })
+        .collect();
+    PushDownInfo {
+        filters,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_read_parquet_sorted_by() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+
+    // 10 files of 10 ids, sorted by their paths.
+    let operator = memory_operator()?;
+    for i in 0..10 {
+        let ids = (i * 10..i * 10 + 10).collect::<Vec<i32>>();
+        operator
+            .object(&format!("data/{:02}.parquet", i))
+            .write(int_row_groups_file(&[ids])?)
+            .await?;
+    }
+
+    let table = build_table(&operator, &["data/*.parquet", "sorted_by=id"])?;
+    for (push_down, expected) in [
+        (id_equals(15), vec![1]),
+        (id_compares(&[(">=", 30), ("<=", 49)]), vec![3, 4]),
+        (id_compares(&[(">", 85)]), vec![8, 9]),
+        (id_compares(&[("<", 5)]), vec![0]),
+        (id_compares(&[(">=", 100)]), vec![]),
+    ] {
+        let source_plan = table.read_plan(ctx.clone(), Some(push_down)).await?;
+        assert_eq!(source_plan.statistics.partitions_scanned, expected.len());
+        assert_eq!(source_plan.statistics.read_rows, expected.len() * 10);
+    }
+
+    // The footers of the files which are neither probed nor candidates are not read.
+    operator
+        .object("data/09.parquet")
+        .write(vec![0; 16])
+        .await?;
+    let source_plan = table.read_plan(ctx.clone(), Some(id_equals(15))).await?;
+    assert_eq!(source_plan.statistics.partitions_scanned, 1);
+    let unsorted = build_table(&operator, &["data/*.parquet"])?;
+    assert!(unsorted.read_plan(ctx.clone(), Some(id_equals(15))).await.is_err());
+
+    // Files and row groups not sorted as hinted are detected.
+    let operator = memory_operator()?;
+    operator
+        .object("data/a.parquet")
+        .write(int_row_groups_file(&[(20..30).collect()])?)
+        .await?;
+    operator
+        .object("data/b.parquet")
+        .write(int_row_groups_file(&[(0..10).collect()])?)
+        .await?;
+    operator
+        .object("data/c.parquet")
+        .write(int_row_groups_file(&[(20..30).collect(), (0..10).collect()])?)
+        .await?;
+    for pattern in ["data/[ab].parquet", "data/c.parquet"] {
+        let table = build_table(&operator, &[pattern, "sorted_by=id"])?;
+        let err = table
+            .read_plan(ctx.clone(), Some(id_equals(5)))
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+    }
+
+    let err = build_table(&operator, &["data/a.parquet", "sorted_by=name"])
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_merge_by() -> Result<()> {
+    let operator = memory_operator()?;
+    for (location, columns) in [
+        ("data/a.parquet", vec![
+            ("id", vec![Some(1), Some(2)]),
+            ("v", vec![Some(10), None]),
+        ]),
+        ("data/b.parquet", vec![
+            ("v", vec![Some(30)]),
+            ("id", vec![Some(3)]),
+            ("w", vec![Some(300)]),
+        ]),
+        ("data/c.parquet", vec![("v", vec![Some(40)]), ("id", vec![Some(4)])]),
+    ] {
+        operator
+            .object(location)
+            .write(nullable_int_columns_file(&columns)?)
+            .await?;
+    }
+
+    // By name, the columns are matched whatever their order, the missing ones are NULL.
+    let table = build_table(&operator, &["data/*.parquet", "merge_by=name"])?;
+    let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+    let expected = vec![
+        "+----+------+------+",
+        "| id | v    | w    |",
+        "+----+------+------+",
+        "| 1  | 10   | NULL |",
+        "| 2  | NULL | NULL |",
+        "| 3  | 30   | 300  |",
+        "| 4  | 40   | NULL |",
+        "+----+------+------+",
+    ];
+    common_datablocks::assert_blocks_sorted_eq(expected, blocks.as_slice());
+
+    // By position, the columns take the names of the first file.
+    let table = build_table(&operator, &[
+        "data/a.parquet",
+        "data/c.parquet",
+        "merge_by=position",
+    ])?;
+    let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+    let expected = vec![
+        "+----+------+",
+        "| id | v    |",
+        "+----+------+",
+        "| 1  | 10   |",
+        "| 2  | NULL |",
+        "| 40 | 4    |",
+        "+----+------+",
+    ];
+    common_datablocks::assert_blocks_sorted_eq(expected, blocks.as_slice());
+
+    // By position, the files must have as many columns.
+    let err = build_table(&operator, &["data/*.parquet", "merge_by=position"])
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::PARQUET_SCHEMA_MISMATCH);
+
+    // The names by position of `columns` can't be matched by name.
+    let err = build_table(&operator, &["data/*.parquet", "merge_by=name", "columns=a,b,c"])
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+
+    let err = build_table(&operator, &["data/*.parquet", "merge_by=type"])
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_column_order() -> Result<()> {
+    let operator = memory_operator()?;
+    for (location, columns) in [
+        ("data/x.parquet", vec![("v", vec![Some(10), None]), ("id", vec![Some(1), Some(2)])]),
+        ("data/y.parquet", vec![("id", vec![Some(3)]), ("v", vec![Some(30)])]),
+    ] {
+        operator
+            .object(location)
+            .write(nullable_int_columns_file(&columns)?)
+            .await?;
+    }
+
+    let column_names = |table: &ParquetTable| {
+        let schema = table.schema();
+        schema
+            .fields()
+            .iter()
+            .map(|field| field.name().clone())
+            .collect::<Vec<_>>()
+    };
+
+    // The columns follow the first file by default.
+    let table = build_table(&operator, &["data/y.parquet", "data/x.parquet", "merge_by=name"])?;
+    assert_eq!(column_names(&table), vec!["id", "v"]);
+    let table = build_table(&operator, &["data/x.parquet", "data/y.parquet", "merge_by=name"])?;
+    assert_eq!(column_names(&table), vec!["v", "id"]);
+
+    // Sorted by name, the columns are in the same order whatever the order of the files,
+    // with or without `merge_by`.
+    let expected = vec![
+        "+----+------+",
+        "| id | v    |",
+        "+----+------+",
+        "| 1  | 10   |",
+        "| 2  | NULL |",
+        "| 3  | 30   |",
+        "+----+------+",
+    ];
+    let orders = [
+        ["data/x.parquet", "data/y.parquet"],
+        ["data/y.parquet", "data/x.parquet"],
+    ];
+    for merge_by in [Some("merge_by=name"), None] {
+        for paths in orders {
+            let mut args = paths.to_vec();
+            args.extend(merge_by);
+            args.push("column_order=name");
+            let table = build_table(&operator, &args)?;
+            assert_eq!(column_names(&table), vec!["id", "v"]);
+            let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+            common_datablocks::assert_blocks_sorted_eq(expected.clone(), blocks.as_slice());
+        }
+    }
+
+    // By position, the columns can't be found by name.
+    let err = build_table(&operator, &[
+        "data/*.parquet",
+        "merge_by=position",
+        "column_order=name",
+    ])
+    .err()
+    .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+
+    let err = build_table(&operator, &["data/*.parquet", "column_order=random"])
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+
+    Ok(())
+}
+
+/// The sorted values of the column `index` of `table`, read alone.
+async fn column_values(
+    ctx: Arc<QueryContext>,
+    table: Arc<ParquetTable>,
+    index: usize,
+) -> Result<Vec<DataValue>> {
+    let push_down = PushDownInfo {
+        projection: Some(Projection::Columns(vec![index])),
+        ..Default::default()
+    };
+    let source_plan = table.read_plan(ctx.clone(), Some(push_down)).await?;
+    ctx.try_set_partitions(source_plan.parts.clone())?;
+    let stream = table.read_data_block_stream(ctx, &source_plan).await?;
+    let blocks = stream.try_collect::<Vec<_>>().await?;
+    let mut values = blocks
+        .iter()
+        .flat_map(|block| {
+            let column = block.column(0);
+            (0..column.len()).map(|i| column.get(i)).collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    values.sort();
+    Ok(values)
+}
+
+#[tokio::test]
+async fn test_read_parquet_on_missing_columns() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+    let operator = memory_operator()?;
+    for (location, columns) in [
+        ("data/a.parquet", vec![("id", vec![Some(1), Some(2)]), ("v", vec![Some(10), None])]),
+        ("data/b.parquet", vec![("id", vec![Some(3)])]),
+    ] {
+        operator
+            .object(location)
+            .write(nullable_int_columns_file(&columns)?)
+            .await?;
+    }
+
+    // Merged by name, `v` is NULL in `b.parquet`, by default or by the option.
+    for args in [
+        vec!["data/*.parquet", "merge_by=name"],
+        vec!["data/*.parquet", "merge_by=name", "on_missing_columns=null"],
+    ] {
+        let table = build_table(&operator, &args)?;
+        assert_eq!(column_values(ctx.clone(), table.clone(), 1).await?, vec![
+            DataValue::Int64(10),
+            DataValue::Null,
+            DataValue::Null,
+        ]);
+        let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+        assert_eq!(blocks.iter().map(|b| b.num_rows()).sum::<usize>(), 3);
+    }
+
+    // Rejected by the option, only if `v` is read.
+    let table = build_table(&operator, &[
+        "data/*.parquet",
+        "merge_by=name",
+        "on_missing_columns=error",
+    ])?;
+    assert_eq!(column_values(ctx.clone(), table.clone(), 0).await?, vec![
+        DataValue::Int64(1),
+        DataValue::Int64(2),
+        DataValue::Int64(3),
+    ]);
+    let err = column_values(ctx, table.clone(), 1).await.err().unwrap();
+    assert_eq!(err.code(), ErrorCode::PARQUET_SCHEMA_MISMATCH);
+    assert!(err.message().contains("data/b.parquet"), "{}", err.message());
+    let err = table.read_blocks().err().unwrap();
+    assert_eq!(err.code(), ErrorCode::PARQUET_SCHEMA_MISMATCH);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_on_duplicate() -> Result<()> {
+    let operator = memory_operator()?;
+    let file = int_fields_file(vec![
+        ArrowField::new("ID", ArrowType::Int32, false),
+        ArrowField::new("id", ArrowType::Int32, false),
+        ArrowField::new("id_1", ArrowType::Int32, false),
+    ])?;
+    operator.object("data/t.parquet").write(file).await?;
+
+    // Both are named `id` once lower cased.
+    let err = build_table(&operator, &["data/t.parquet"]).err().unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+    let err = build_table(&operator, &["data/t.parquet", "on_duplicate=error"])
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+
+    // The suffixes skip the names already taken.
+    let table = build_table(&operator, &["data/t.parquet", "on_duplicate=suffix"])?;
+    let schema = table.schema();
+    let names = schema
+        .fields()
+        .iter()
+        .map(|field| field.name().as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["id", "id_2", "id_1"]);
+    let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+    assert_eq!(blocks[0].num_rows(), 1);
+
+    let err = build_table(&operator, &["data/t.parquet", "on_duplicate=keep"])
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_read_ahead() -> Result<()> {
+    let ms = Duration::from_millis;
+
+    // The window doubles while the reads are slower than the decoding, up to the max.
+    let mut window = ReadAheadWindow::create(1, 6);
+    assert_eq!(window.size(), 1);
+    for expected in [2, 4, 6, 6] {
+        window.observe(ms(10), ms(1));
+        assert_eq!(window.size(), expected);
+    }
+    // It stays while the decoding takes up to twice the time of the reads.
+    window.observe(ms(10), ms(20));
+    assert_eq!(window.size(), 6);
+    // It shrinks by one row group when the decoding is the bottleneck, down to the min.
+    let mut window = ReadAheadWindow::create(2, 3);
+    window.observe(ms(10), ms(1));
+    assert_eq!(window.size(), 3);
+    for expected in [2, 2] {
+        window.observe(ms(1), ms(10));
+        assert_eq!(window.size(), expected);
+    }
+    assert!(ReadAheadWindow::create(4, 4).is_fixed());
+
+    let operator = memory_operator()?;
+    operator
+        .object("data/t.parquet")
+        .write(int_row_groups_file(&[vec![1, 2], vec![3], vec![4, 5]])?)
+        .await?;
+    let table = build_table(&operator, &[
+        "data/t.parquet",
+        "min_read_ahead=1",
+        "max_read_ahead=4",
+    ])?;
+    let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+    assert_eq!(blocks.iter().map(|b| b.num_rows()).sum::<usize>(), 5);
+
+    for args in [
+        ["data/t.parquet", "min_read_ahead=0"],
+        ["data/t.parquet", "max_read_ahead=0"],
+        ["data/t.parquet", "max_read_ahead=many"],
+    ] {
+        let err = build_table(&operator, &args).err().unwrap();
+        assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+    }
+    let err = build_table(&operator, &[
+        "data/t.parquet",
+        "max_read_ahead=2",
+        "min_read_ahead=4",
+    ])
+    .err()
+    .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_file_order() -> Result<()> {
+    // Written in the order c, a, b.
+    let tmp_dir = TempDir::new().unwrap();
+    let data_dir = tmp_dir.path().join("data");
+    std::fs::create_dir_all(&data_dir)?;
+    for name in ["c", "a", "b"] {
+        std::fs::write(data_dir.join(format!("{}.parquet", name)), int_column_file("id")?.0)?;
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    let mut builder = opendal::services::fs::Builder::default();
+    builder.root(tmp_dir.path().to_str().unwrap());
+    let operator = Operator::new(builder.build()?);
+
+    let resolve = |order: Option<&str>| {
+        let mut args = vec!["data/b.parquet".to_string(), "data/[ac].parquet".to_string()];
+        if let Some(order) = order {
+            args.push(format!("file_order={}", order));
+        }
+        let table_args = args
+            .into_iter()
+            .map(|arg| DataValue::String(arg.into_bytes()))
+            .collect::<Vec<_>>();
+        resolve_files(&table_args, Some(operator.clone()), &[]).map(|(_, files)| files)
+    };
+    let names = |files: Vec<String>| {
+        files
+            .iter()
+            .map(|file| file.trim_start_matches("data/").trim_end_matches(".parquet"))
+            .collect::<String>()
+    };
+
+    // The files matched by each path are sorted by name by default.
+    assert_eq!(names(resolve(None)?), "bac");
+    assert_eq!(names(resolve(Some("name"))?), "abc");
+    assert_eq!(names(resolve(Some("name_desc"))?), "cba");
+    assert_eq!(names(resolve(Some("mtime"))?), "cab");
+    let mut as_listed = names(resolve(Some("as_listed"))?).into_bytes();
+    assert_eq!(as_listed[0], b'b');
+    as_listed[1..].sort();
+    assert_eq!(as_listed, b"bac");
+
+    let err = resolve(Some("size")).err().unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+    let table_args = ["data/*.parquet", "sorted_by=id", "file_order=mtime"]
+        .iter()
+        .map(|arg| DataValue::String(arg.as_bytes().to_vec()))
+        .collect::<Vec<_>>();
+    let err = resolve_files(&table_args, Some(operator), &[])
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_mmap() -> Result<()> {
+    let tmp_dir = TempDir::new().unwrap();
+    let path = tmp_dir.path().join("t.parquet");
+    std::fs::write(&path, int_row_groups_file(&[vec![1, 2, 3], vec![4, 5]])?)?;
+    let location = path.to_str().unwrap();
+
+    let read = |args: &[&str]| -> Result<String> {
+        let table_args = args
+            .iter()
+            .map(|arg| DataValue::String(arg.as_bytes().to_vec()))
+            .collect::<Vec<_>>();
+        let table = ParquetTableBuilder::create("system", "read_parquet", 1, table_args).build()?;
+        let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+        common_datablocks::pretty_format_blocks(&blocks)
+    };
+    assert_eq!(read(&[location, "mmap=true"])?, read(&[location])?);
+
+    // Only the local files are mapped.
+    let table_args = vec![
+        DataValue::String(location.as_bytes().to_vec()),
+        DataValue::String(b"mmap=true".to_vec()),
+    ];
+    let (operators, files) = resolve_files(&table_args, None, &[])?;
+    let mapped = operators.mapped_file(&files[0])?.unwrap();
+    assert_eq!(mapped.len() as u64, std::fs::metadata(&path)?.len());
+    let (operators, files) = resolve_files(&table_args[..1], None, &[])?;
+    assert!(operators.mapped_file(&files[0])?.is_none());
+
+    let operator = memory_operator()?;
+    operator
+        .object("data/t.parquet")
+        .write(int_row_groups_file(&[vec![1, 2, 3]])?)
+        .await?;
+    let table_args = vec![
+        DataValue::String(b"data/t.parquet".to_vec()),
+        DataValue::String(b"mmap=true".to_vec()),
+    ];
+    let (operators, files) = resolve_files(&table_args, Some(operator.clone()), &[])?;
+    assert!(operators.mapped_file(&files[0])?.is_none());
+    let table = ParquetTableBuilder::create("system", "read_parquet", 1, table_args)
+        .with_operator(operator)
+        .build()?;
+    let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+    assert_eq!(blocks.iter().map(|b| b.num_rows()).sum::<usize>(), 3);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_coalesce_file_size() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+    let operator = memory_operator()?;
+    for id in 0..1000 {
+        operator
+            .object(&format!("data/{:04}.parquet", id))
+            .write(int_row_groups_file(&[vec![id]])?)
+            .await?;
+    }
+
+    let table = build_table(&operator, &["data/*.parquet"])?;
+    let source_plan = table
+        .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+        .await?;
+    assert_eq!(source_plan.parts.len(), 1000);
+
+    // The tiny files are read by parts of about 1KB.
+    let table = build_table(&operator, &["data/*.parquet", "coalesce_file_size=1024"])?;
+    let source_plan = table
+        .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+        .await?;
+    let parts = &source_plan.parts.partitions;
+    assert!(parts.len() > 1 && parts.len() < 100, "{} parts", parts.len());
+    let files = parts
+        .iter()
+        .map(|part| Ok(ParquetPartInfo::from_part(part)?.coalesced.len().max(1)))
+        .sum::<Result<usize>>()?;
+    assert_eq!(files, 1000);
+
+    ctx.try_set_partitions(source_plan.parts.clone())?;
+    let stream = table.read_data_block_stream(ctx, &source_plan).await?;
+    let result = stream.try_collect::<Vec<_>>().await?;
+    assert_eq!(result.iter().map(|b| b.num_rows()).sum::<usize>(), 1000);
+    let mut ids = result
+        .iter()
+        .flat_map(|block| {
+            let column = block.column(0);
+            (0..column.len()).map(|i| column.get(i)).collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    ids.sort();
+    assert_eq!(ids, (0..1000).map(DataValue::Int64).collect::<Vec<_>>());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_identifier_policy() -> Result<()> {
+    let operator = memory_operator()?;
+    let names = [" Select ", "1st", "id", "user name"];
+    let schema = DataSchemaRefExt::create(
+        names
+            .iter()
+            .map(|name| DataField::new(name, i32::to_data_type()))
+            .collect(),
+    );
+    let columns = names
+        .iter()
+        .map(|_| Series::from_data(vec![1i32]))
+        .collect();
+    let mut buf = vec![];
+    serialize_data_blocks_with_compression(
+        vec![DataBlock::create(schema.clone(), columns)],
+        &schema,
+        &mut buf,
+        CompressionOptions::Uncompressed,
+    )?;
+    operator.object("data/t.parquet").write(buf).await?;
+    let column_names = |args: &[&str]| -> Result<Vec<String>> {
+        let table = build_table(&operator, args)?;
+        let schema = table.schema();
+        Ok(schema.fields().iter().map(|f| f.name().clone()).collect())
+    };
+
+    // The names are kept as they are by default.
+    let names = column_names(&["data/t.parquet", "name_transform=trim"])?;
+    assert_eq!(names, vec!["select", "1st", "id", "user name"]);
+
+    // The keyword and the name starting with a digit are prefixed, after the transform,
+    // the name with a space has to be quoted anyway.
+    let names = column_names(&[
+        "data/t.parquet",
+        "name_transform=trim",
+        "identifier_policy=prefix",
+    ])?;
+    assert_eq!(names, vec!["_select", "_1st", "id", "user name"]);
+
+    let err = column_names(&["data/t.parquet", "identifier_policy=quote"])
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_memory_budget() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+
+    // One large row group of 10000 rows.
+    let ids = (0..10000).collect::<Vec<i32>>();
+    let names = ids
+        .iter()
+        .map(|id| format!("name_{:08}", id))
+        .collect::<Vec<_>>();
+    let block = id_name_block(ids, Some(names.iter().map(String::as_str).collect()));
+    let mut buf = vec![];
+    serialize_data_blocks(vec![block.clone()], block.schema(), &mut buf)?;
+    let columns = read_metadata(&mut Cursor::new(&buf))?.row_groups[0]
+        .columns()
+        .to_vec();
+    let chunk_bytes = columns
+        .iter()
+        .map(|column| column.compressed_size() as usize)
+        .sum::<usize>();
+    let decoded_bytes = columns
+        .iter()
+        .map(|column| column.uncompressed_size() as usize)
+        .sum::<usize>();
+    let operator = memory_operator()?;
+    operator.object("data/t.parquet").write(buf).await?;
+
+    let read = |args: Vec<String>| {
+        let ctx = ctx.clone();
+        let operator = operator.clone();
+        async move {
+            let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+            let table = build_table(&operator, &args)?;
+            let source_plan = table
+                .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+                .await?;
+            ctx.try_set_partitions(source_plan.parts.clone())?;
+            let stream = table.read_data_block_stream(ctx, &source_plan).await?;
+            stream.try_collect::<Vec<_>>().await
+        }
+    };
+    let budget = |bytes: usize| format!("memory_budget={}", bytes);
+
+    // Within the budget, the row group is decoded at once.
+    let blocks = read(vec![
+        "data/t.parquet".to_string(),
+        budget(2 * (chunk_bytes + decoded_bytes)),
+    ])
+    .await?;
+    assert_eq!(blocks.len(), 1);
+
+    // Above it, in blocks of about a quarter of the rows, with the same rows.
+    let blocks = read(vec![
+        "data/t.parquet".to_string(),
+        budget(chunk_bytes + decoded_bytes / 4),
+    ])
+    .await?;
+    assert!(blocks.len() >= 4, "{} blocks", blocks.len());
+    assert!(blocks.iter().all(|block| block.num_rows() <= 2500));
+    let expected = (0..10000).map(DataValue::Int64).collect::<Vec<_>>();
+    let values = blocks
+        .iter()
+        .flat_map(|block| {
+            let column = block.column(0);
+            (0..column.len()).map(|i| column.get(i)).collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(values, expected);
+
+    // Or rejected with `on_memory_budget=error`.
+    let err = read(vec![
+        "data/t.parquet".to_string(),
+        budget(chunk_bytes + decoded_bytes / 4),
+        "on_memory_budget=error".to_string(),
+    ])
+    .await
+    .err()
+    .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+    assert!(err.message().contains("exceeds memory budget"), "{}", err.message());
+
+    // The compressed chunks alone don't fit, whatever the block size.
+    let err = read(vec!["data/t.parquet".to_string(), budget(chunk_bytes / 2)])
+        .await
+        .err()
+        .unwrap();
+    assert!(err.message().contains("exceeds memory budget"), "{}", err.message());
+
+    Ok(())
+}
+
+/// A prewhere of `id > min_id` on the columns of `id_name_block`, with `name` read after
+/// the filter if `remain`, or along with `id`.
+fn id_greater_prewhere(min_id: i64, remain: bool) -> PushDownInfo {
+    let (prewhere_columns, remain_columns) = if remain {
+        (vec![0], vec![1])
+    } else {
+        (vec![0, 1], vec![])
+    };
+    let filter = Expression::Function {
+        name: ">".to_string(),
+        args: vec![
+            Expression::IndexedVariable {
+                name: "id".to_string(),
+                data_type: NullableType::new_impl(i32::to_data_type()),
+            },
+            Expression::Constant {
+                value: DataValue::Int64(min_id),
+                data_type: i64::to_data_type(),
+            },
+        ],
+        return_type: NullableType::new_impl(bool::to_data_type()),
+    };
+    PushDownInfo {
+        projection: Some(Projection::Columns(vec![0, 1])),
+        prewhere: Some(PrewhereInfo {
+            output_columns: Projection::Columns(vec![0, 1]),
+            prewhere_columns: Projection::Columns(prewhere_columns),
+            remain_columns: Projection::Columns(remain_columns),
+            filter,
+        }),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_read_parquet_block_rows_with_prewhere() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+
+    // One row group of 10 rows.
+    let ids = (0..10).collect::<Vec<i32>>();
+    let names = ids
+        .iter()
+        .map(|id| format!("name_{}", id))
+        .collect::<Vec<_>>();
+    let block = id_name_block(ids, Some(names.iter().map(String::as_str).collect()));
+    let mut buf = vec![];
+    serialize_data_blocks(vec![block.clone()], block.schema(), &mut buf)?;
+    let columns = read_metadata(&mut Cursor::new(&buf))?.row_groups[0]
+        .columns()
+        .to_vec();
+    let chunk_bytes = columns
+        .iter()
+        .map(|column| column.compressed_size() as usize)
+        .sum::<usize>();
+    let decoded_bytes = columns
+        .iter()
+        .map(|column| column.uncompressed_size() as usize)
+        .sum::<usize>();
+    let operator = memory_operator()?;
+    operator.object("data/t.parquet").write(buf).await?;
+
+    let read = |args: Vec<String>, push_downs: PushDownInfo| {
+        let ctx = ctx.clone();
+        let operator = operator.clone();
+        async move {
+            let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+            let table = build_table(&operator, &args)?;
+            let source_plan = table.read_plan(ctx.clone(), Some(push_downs)).await?;
+            ctx.try_set_partitions(source_plan.parts.clone())?;
+            let stream = table.read_data_block_stream(ctx, &source_plan).await?;
+            stream.try_collect::<Vec<_>>().await
+        }
+    };
+    let expected = vec![
+        "+----+--------+",
+        "| id | name   |",
+        "+----+--------+",
+        "| 5  | name_5 |",
+        "| 6  | name_6 |",
+        "| 7  | name_7 |",
+        "| 8  | name_8 |",
+        "| 9  | name_9 |",
+        "+----+--------+",
+    ];
+
+    // The row group is split before the filter, whether the remain columns are read after
+    // the prewhere columns or along with them. All its rows are counted as scanned.
+    for remain in [true, false] {
+        let scanned = ctx.get_scan_progress_value().rows;
+        let args = vec!["data/t.parquet".to_string(), "max_block_rows=3".to_string()];
+        let blocks = read(args, id_greater_prewhere(4, remain)).await?;
+        assert!(blocks.len() >= 3, "{} blocks", blocks.len());
+        assert!(blocks.iter().all(|block| block.num_rows() <= 3));
+        common_datablocks::assert_blocks_sorted_eq(expected.clone(), blocks.as_slice());
+        assert_eq!(ctx.get_scan_progress_value().rows - scanned, 10);
+    }
+
+    // The memory budget applies to the prewhere and the remain columns together.
+    let budget = format!("memory_budget={}", chunk_bytes + decoded_bytes / 4);
+    let args = vec!["data/t.parquet".to_string(), budget.clone()];
+    let blocks = read(args, id_greater_prewhere(4, true)).await?;
+    assert!(blocks.len() >= 4, "{} blocks", blocks.len());
+    common_datablocks::assert_blocks_sorted_eq(expected, blocks.as_slice());
+
+    let args = vec![
+        "data/t.parquet".to_string(),
+        budget,
+        "on_memory_budget=error".to_string(),
+    ];
+    let err = read(args, id_greater_prewhere(4, true))
+        .await
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+    assert!(err.message().contains("exceeds memory budget"), "{}", err.message());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_max_concurrent_requests() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+    let operator = memory_operator()?;
+    for i in 0..8 {
+        let (buf, _) = int_column_file("id")?;
+        operator
+            .object(&format!("data/t_{}.parquet", i))
+            .write(buf)
+            .await?;
+    }
+
+    // The reads of all the files share the limit.
+    let table = build_table(&operator, &["data/*.parquet", "max_concurrent_requests=2"])?;
+    let source_plan = table
+        .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+        .await?;
+    ctx.try_set_partitions(source_plan.parts.clone())?;
+    let stream = table.read_data_block_stream(ctx, &source_plan).await?;
+    let blocks = stream.try_collect::<Vec<_>>().await?;
+    assert_eq!(blocks.iter().map(|b| b.num_rows()).sum::<usize>(), 8 * 3);
+    let peak = table.peak_concurrent_requests().unwrap();
+    assert!(peak > 0 && peak <= 2, "peak of {} requests", peak);
+
+    // The requests are not limited by default.
+    let table = build_table(&operator, &["data/*.parquet"])?;
+    assert_eq!(table.peak_concurrent_requests(), None);
+
+    let err = build_table(&operator, &["data/*.parquet", "max_concurrent_requests=0"])
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_file_operators_request_permits() -> Result<()> {
+    let mut operators = FileOperators::create(memory_operator()?);
+    operators.limit_requests(2);
+    let in_flight = Arc::new(AtomicUsize::new(0));
+
+    // Many requests at once, of which never more than 2 hold a permit.
+    let tasks = (0..8)
+        .map(|_| {
+            let operators = operators.clone();
+            let in_flight = in_flight.clone();
+            tokio::spawn(async move {
+                let _permit = operators.permit().await?;
+                let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                assert!(current <= 2, "{} requests in flight", current);
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                Result::Ok(())
+            })
+        })
+        .collect::<Vec<_>>();
+    for task in tasks {
+        task.await.unwrap()?;
+    }
+    assert_eq!(operators.peak_requests(), Some(2));
+
+    // The blocking permits share the same limit.
+    let permit = operators.blocking_permit()?;
+    let other = operators.blocking_permit()?;
+    drop(permit);
+    let _permit = operators.blocking_permit()?;
+    drop(other);
+    assert_eq!(operators.peak_requests(), Some(2));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_max_open_files() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+    let operator = memory_operator()?;
+    for i in 0..64 {
+        let (buf, _) = int_column_file("id")?;
+        operator
+            .object(&format!("data/t_{}.parquet", i))
+            .write(buf)
+            .await?;
+    }
+
+    // The files of the coalesced parts and of the parts read ahead share the limit.
+    for (args, max_open_files) in [
+        (vec!["data/*.parquet", "max_open_files=2"], 2),
+        (vec!["data/*.parquet", "max_open_files=2", "coalesce_file_size=1048576"], 2),
+        (vec!["data/*.parquet", "max_open_files=1", "max_read_ahead=4"], 1),
+    ] {
+        let table = build_table(&operator, &args)?;
+        let source_plan = table
+            .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+            .await?;
+        ctx.try_set_partitions(source_plan.parts.clone())?;
+        let stream = table
+            .read_data_block_stream(ctx.clone(), &source_plan)
+            .await?;
+        let blocks = stream.try_collect::<Vec<_>>().await?;
+        assert_eq!(blocks.iter().map(|b| b.num_rows()).sum::<usize>(), 64 * 3);
+        let peak = table.peak_open_files().unwrap();
+        assert!(peak > 0 && peak <= max_open_files, "peak of {} files", peak);
+    }
+
+    // The open files are not limited by default.
+    let table = build_table(&operator, &["data/*.parquet"])?;
+    assert_eq!(table.peak_open_files(), None);
+
+    let err = build_table(&operator, &["data/*.parquet", "max_open_files=0"])
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+
+    // The readers of a file share its slot, the other files wait for it to be closed.
+    let mut operators = FileOperators::create(memory_operator()?);
+    operators.limit_open_files(1);
+    let file = operators.blocking_open_file("a")?;
+    let reader = operators.open_file("a").await?;
+    drop(file);
+    let waiting = tokio::time::timeout(Duration::from_millis(10), operators.open_file("b"));
+    assert!(waiting.await.is_err());
+    drop(reader);
+    let _file = operators.open_file("b").await?;
+    assert_eq!(operators.peak_open_files(), Some(1));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_cluster_by() -> Result<()> {
+    // The ranges of the ids of the files are in the reverse order of their paths.
+    let operator = memory_operator()?;
+    for (name, ids) in [("a", 20..30), ("b", 10..20), ("c", 0..10)] {
+        let ids = ids.collect::<Vec<i32>>();
+        operator
+            .object(&format!("data/clustered/{}.parquet", name))
+            .write(int_row_groups_file(&[ids[..5].to_vec(), ids[5..].to_vec()])?)
+            .await?;
+    }
+    let read_ids = |table: &ParquetTable| -> Result<Vec<DataValue>> {
+        let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+        Ok(blocks
+            .iter()
+            .flat_map(|block| {
+                let column = block.column(0);
+                (0..column.len()).map(|i| column.get(i)).collect::<Vec<_>>()
+            })
+            .collect())
+    };
+
+    // The files are read in the order of the key, which is the cluster key of the table.
+    let table = build_table(&operator, &["data/clustered/*.parquet", "cluster_by=id"])?;
+    let expected = (0..30).map(DataValue::Int64).collect::<Vec<_>>();
+    assert_eq!(read_ids(&table)?, expected);
+    let cluster_keys = table.cluster_keys();
+    assert_eq!(cluster_keys.len(), 1);
+    assert_eq!(cluster_keys[0].column_name(), "id");
+    let table = build_table(&operator, &["data/clustered/*.parquet"])?;
+    assert!(table.cluster_keys().is_empty());
+
+    // The files whose ranges overlap, or whose row groups are out of order, are rejected.
+    operator
+        .object("data/overlapping/a.parquet")
+        .write(int_row_groups_file(&[(0..10).collect()])?)
+        .await?;
+    operator
+        .object("data/overlapping/b.parquet")
+        .write(int_row_groups_file(&[(5..15).collect()])?)
+        .await?;
+    operator
+        .object("data/unsorted/a.parquet")
+        .write(int_row_groups_file(&[(10..20).collect(), (0..10).collect()])?)
+        .await?;
+    for (pattern, expected) in [
+        ("data/overlapping/*.parquet", "overlap"),
+        ("data/unsorted/*.parquet", "not sorted"),
+    ] {
+        let err = build_table(&operator, &[pattern, "cluster_by=id"])
+            .err()
+            .unwrap();
+        assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+        assert!(err.message().contains(expected), "{}", err.message());
+        assert!(err.message().contains("cluster_by"), "{}", err.message());
+    }
+
+    // Or read in the order of their paths without a cluster key, with a warning.
+    let table = build_table(&operator, &[
+        "data/overlapping/*.parquet",
+        "cluster_by=id",
+        "on_unclustered=warn",
+    ])?;
+    assert!(table.cluster_keys().is_empty());
+    let expected = (0..10).chain(5..15).map(DataValue::Int64).collect::<Vec<_>>();
+    assert_eq!(read_ids(&table)?, expected);
+
+    for args in [
+        &["data/clustered/*.parquet", "cluster_by=name"][..],
+        &["data/clustered/*.parquet", "cluster_by=id", "file_order=name"],
+        &["data/clustered/*.parquet", "cluster_by=id", "on_unclustered=ignore"],
+    ] {
+        let err = build_table(&operator, args).err().unwrap();
+        assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_row_group_index() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+    let operator = memory_operator()?;
+    operator
+        .object("data/a.parquet")
+        .write(int_row_groups_file(&[vec![1, 2], vec![3], vec![4, 5, 6]])?)
+        .await?;
+    operator
+        .object("data/b.parquet")
+        .write(int_row_groups_file(&[vec![7]])?)
+        .await?;
+
+    // The virtual column is only added by the option, after the columns of the files.
+    let table = build_table(&operator, &["data/*.parquet"])?;
+    assert_eq!(table.schema().num_fields(), 1);
+    let table = build_table(&operator, &["data/*.parquet", "row_group_index=true"])?;
+    let schema = table.schema();
+    let types = schema
+        .fields()
+        .iter()
+        .map(|field| (field.name().as_str(), field.data_type().data_type_id()))
+        .collect::<Vec<_>>();
+    assert_eq!(types, vec![
+        ("id", TypeID::Int32),
+        ("_row_group_index", TypeID::UInt64),
+    ]);
+
+    // Each row has the index of its row group in its file.
+    let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+    let expected = vec![
+        "+----+------------------+",
+        "| id | _row_group_index |",
+        "+----+------------------+",
+        "| 1  | 0                |",
+        "| 2  | 0                |",
+        "| 3  | 1                |",
+        "| 4  | 2                |",
+        "| 5  | 2                |",
+        "| 6  | 2                |",
+        "| 7  | 0                |",
+        "+----+------------------+",
+    ];
+    common_datablocks::assert_blocks_sorted_eq(expected, blocks.as_slice());
+
+    // The column can be projected alone, or left out.
+    let values = column_values(ctx.clone(), table.clone(), 1).await?;
+    let expected = [0, 0, 0, 1, 2, 2, 2].map(DataValue::UInt64);
+    assert_eq!(values, expected);
+    let values = column_values(ctx, table, 0).await?;
+    assert_eq!(values, (1..=7).map(DataValue::Int64).collect::<Vec<_>>());
+
+    let err = build_table(&operator, &["data/*.parquet", "row_group_index=maybe"])
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+
+    Ok(())
+}
+
+/// The names of the files of the parts planned for `table`, in order, with their sizes.
+async fn part_files(
+    ctx: Arc<QueryContext>,
+    table: Arc<ParquetTable>,
+) -> Result<(String, Vec<usize>)> {
+    let source_plan = table.read_plan(ctx, None).await?;
+    let mut names = String::new();
+    let mut sizes = vec![];
+    for part in source_plan.parts.partitions.iter() {
+        let part = ParquetPartInfo::from_part(part)?;
+        let name = part.location.trim_start_matches("data/");
+        names.push_str(name.trim_end_matches(".parquet"));
+        sizes.push(part.size());
+    }
+    Ok((names, sizes))
+}
+
+#[tokio::test]
+async fn test_read_parquet_part_order() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+    let operator = memory_operator()?;
+    // The files have one row group each, of 100, 1000 and 10 rows.
+    for (name, num_rows) in [("a", 100), ("b", 1000), ("c", 10)] {
+        operator
+            .object(&format!("data/{}.parquet", name))
+            .write(int_row_groups_file(&[(0..num_rows).collect()])?)
+            .await?;
+    }
+
+    // The parts follow the files by default.
+    let table = build_table(&operator, &["data/*.parquet"])?;
+    let (names, _) = part_files(ctx.clone(), table).await?;
+    assert_eq!(names, "abc");
+    let table = build_table(&operator, &["data/*.parquet", "part_order=sequential"])?;
+    let (names, _) = part_files(ctx.clone(), table).await?;
+    assert_eq!(names, "abc");
+
+    let table = build_table(&operator, &["data/*.parquet", "part_order=size_desc"])?;
+    let (names, sizes) = part_files(ctx.clone(), table).await?;
+    assert_eq!(names, "bac");
+    assert!(sizes.windows(2).all(|w| w[0] > w[1]));
+    let table = build_table(&operator, &["data/*.parquet", "part_order=SIZE_ASC"])?;
+    let (names, sizes) = part_files(ctx.clone(), table).await?;
+    assert_eq!(names, "cab");
+    assert!(sizes.windows(2).all(|w| w[0] < w[1]));
+
+    let cases = [
+        vec!["data/*.parquet", "part_order=random"],
+        vec!["data/*.parquet", "cluster_by=id", "part_order=size_desc"],
+    ];
+    for args in cases {
+        let err = build_table(&operator, &args).err().unwrap();
+        assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS, "{:?}", args);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_columns_like() -> Result<()> {
+    let operator = memory_operator()?;
+    // A wide file of `id`, 50 `metric_<i>` columns and 50 `other_<i>` columns.
+    let mut columns = vec![("id".to_string(), vec![Some(1)])];
+    for i in 0..50 {
+        columns.push((format!("metric_{}", i), vec![Some(i)]));
+        columns.push((format!("other_{}", i), vec![Some(-i)]));
+    }
+    let columns = columns
+        .iter()
+        .map(|(name, values)| (name.as_str(), values.clone()))
+        .collect::<Vec<_>>();
+    operator
+        .object("data/a.parquet")
+        .write(nullable_int_columns_file(&columns)?)
+        .await?;
+    // A file with only two of the metrics.
+    operator
+        .object("data/b.parquet")
+        .write(nullable_int_columns_file(&[
+            ("id", vec![Some(2)]),
+            ("metric_0", vec![Some(100)]),
+            ("metric_1", vec![Some(101)]),
+        ])?)
+        .await?;
+    let names = |table: &ParquetTable| {
+        table
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| field.name().clone())
+            .collect::<Vec<_>>()
+    };
+
+    let table = build_table(&operator, &["data/a.parquet", "columns_like=METRIC_%"])?;
+    let metrics = (0..50).map(|i| format!("metric_{}", i)).collect::<Vec<_>>();
+    assert_eq!(names(&table), metrics);
+    let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+    let values = (0..50)
+        .map(|index| blocks[0].column(index).get(0))
+        .collect::<Vec<_>>();
+    assert_eq!(values, (0..50).map(DataValue::Int64).collect::<Vec<_>>());
+
+    // `_` matches a single character.
+    let table = build_table(&operator, &["data/a.parquet", "columns_like=metric_1_"])?;
+    assert_eq!(names(&table), metrics[10..20].to_vec());
+
+    // The file missing metrics is rejected, unless they are read as NULL.
+    let table = build_table(&operator, &["data/*.parquet", "columns_like=metric_%"])?;
+    let err = table
+        .read_blocks()
+        .and_then(|blocks| blocks.collect::<Result<Vec<_>>>())
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::PARQUET_SCHEMA_MISMATCH);
+    let table = build_table(&operator, &[
+        "data/*.parquet",
+        "columns_like=metric_%",
+        "on_missing_columns=null",
+    ])?;
+    let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+    let values = (0..3)
+        .map(|index| blocks[1].column(index).get(0))
+        .collect::<Vec<_>>();
+    assert_eq!(values, vec![DataValue::Int64(100), DataValue::Int64(101), DataValue::Null]);
+
+    for args in [
+        vec!["data/a.parquet", "columns_like=nothing_%"],
+        vec!["data/a.parquet", "columns_like=metric_%", "merge_by=position"],
+        vec!["data/a.parquet", "on_missing_columns=zero"],
+    ] {
+        let err = build_table(&operator, &args).err().unwrap();
+        assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS, "{:?}", args);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,1934 @@
+//  Copyright 2021 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::ops::Range;
+use std::sync::Arc;
+
+use chrono::TimeZone;
+use chrono::Utc;
+use common_arrow::arrow::array::Array;
+use common_arrow::arrow::array::BinaryArray;
+use common_arrow::arrow::array::DictionaryArray;
+use common_arrow::arrow::array::ListArray;
+use common_arrow::arrow::array::PrimitiveArray;
+use common_arrow::arrow::array::StructArray;
+use common_arrow::arrow::array::Utf8Array;
+use common_arrow::arrow::bitmap::Bitmap;
+use common_arrow::arrow::chunk::Chunk;
+use common_arrow::arrow::datatypes::DataType as ArrowType;
+use common_arrow::arrow::datatypes::Field as ArrowField;
+use common_arrow::arrow::datatypes::Schema as ArrowSchema;
+use common_arrow::arrow::io::parquet::read::read_metadata;
+use common_arrow::arrow::io::parquet::write::RowGroupIterator;
+use common_arrow::arrow::io::parquet::write::WriteOptions;
+use common_arrow::parquet::bloom_filter;
+use common_arrow::parquet::compression::CompressionOptions;
+use common_arrow::parquet::encoding::Encoding;
+use common_arrow::parquet::metadata::ThriftFileMetaData;
+use common_arrow::parquet::thrift_format::thrift::protocol::TCompactInputProtocol;
+use common_arrow::parquet::thrift_format::thrift::protocol::TCompactOutputProtocol;
+use common_arrow::parquet::thrift_format::BloomFilterAlgorithm;
+use common_arrow::parquet::thrift_format::BloomFilterCompression;
+use common_arrow::parquet::thrift_format::BloomFilterHash;
+use common_arrow::parquet::thrift_format::BloomFilterHeader;
+use common_arrow::parquet::thrift_format::SplitBlockAlgorithm;
+use common_arrow::parquet::thrift_format::Uncompressed;
+use common_arrow::parquet::thrift_format::XxHash;
+use common_arrow::parquet::write::Version;
+use common_arrow::parquet::write::WriteOptions as FileWriteOptions;
+use common_arrow::write_parquet_file;
+use common_base::base::tokio;
+use common_catalog::plan::Expression;
+use common_catalog::plan::PartStatistics;
+use common_catalog::plan::Projection;
+use common_catalog::plan::PushDownInfo;
+use common_catalog::table::Table;
+use common_datablocks::serialize_data_blocks;
+use common_datablocks::serialize_data_blocks_with_compression;
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_sql::executor::table_read_plan::ToReadDataSourcePlan;
+use common_storage::StorageConfig;
+use common_storages_parquet::column_bloom_filters;
+use common_storages_parquet::file_features;
+use common_storages_parquet::schema_diff;
+use common_storages_parquet::schema_incompatibility;
+use common_storages_parquet::testing::ParquetFixture;
+use common_storages_parquet::BloomFilterParams;
+use common_storages_parquet::ColumnBloomFilter;
+use common_storages_parquet::ColumnDifference;
+use common_storages_parquet::DifferenceKind;
+use common_storages_parquet::FeatureKind;
+use common_storages_parquet::FileColumnStatistics;
+use common_storages_parquet::FileStatistics;
+use common_storages_parquet::ParquetFeature;
+use common_storages_parquet::ParquetFeaturesTable;
+use common_storages_parquet::ParquetPartInfo;
+use common_storages_parquet::ParquetScanCursor;
+use common_storages_parquet::ParquetScanProgressValues;
+use common_storages_parquet::ParquetSchemaDiffTable;
+use common_storages_parquet::ParquetStatisticsProvider;
+use common_storages_parquet::ParquetTable;
+use common_storages_parquet::ParquetTableBuilder;
+use databend_query::sessions::QueryContext;
+use databend_query::sessions::TableContext;
+use databend_query::stream::ReadDataBlockStream;
+use futures::TryStreamExt;
+use tempfile::TempDir;
+
+use super::table_test_fixture::append_footer;
+use super::table_test_fixture::build_table;
+use super::table_test_fixture::chunks_file;
+use super::table_test_fixture::id_equals;
+use super::table_test_fixture::id_name_block;
+use super::table_test_fixture::int_column_file;
+use super::table_test_fixture::int_fields_file;
+use super::table_test_fixture::int_row_groups_file;
+use super::table_test_fixture::matched_ids;
+use super::table_test_fixture::memory_operator;
+use super::table_test_fixture::nullable_int_columns_file;
+use super::table_test_fixture::row_groups_file;
+use super::table_test_fixture::strip_footer;
+
+#[tokio::test]
+async fn test_read_parquet_created_on() -> Result<()> {
+    let (buf, _) = int_column_file("id")?;
+    let operator = memory_operator()?;
+    operator.object("data/t.parquet").write(buf).await?;
+
+    // The timestamps are fixed to the epoch by default.
+    let table = build_table(&operator, &["data/t.parquet"])?;
+    let meta = &table.get_table_info().meta;
+    assert_eq!(meta.created_on.timestamp(), 0);
+    assert_eq!(meta.updated_on.timestamp(), 0);
+
+    let created_on = Utc.timestamp(1_600_000_000, 0);
+    let updated_on = Utc.timestamp(1_700_000_000, 0);
+    let table_args = vec![DataValue::String("data/t.parquet".as_bytes().to_vec())];
+    let table = ParquetTableBuilder::create("system", "read_parquet", 1, table_args)
+        .with_operator(operator)
+        .with_created_on(created_on)
+        .with_updated_on(updated_on)
+        .build()?;
+    let meta = &table.get_table_info().meta;
+    assert_eq!(meta.created_on, created_on);
+    assert_eq!(meta.updated_on, updated_on);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_read_blocks() -> Result<()> {
+    let schema = DataSchemaRefExt::create(vec![
+        DataField::new("id", i32::to_data_type()),
+        DataField::new("name", Vu8::to_data_type()),
+    ]);
+    let operator = memory_operator()?;
+    for (location, ids, names) in [
+        ("data/b.parquet", vec![3i32], vec!["c"]),
+        ("data/a.parquet", vec![1i32, 2], vec!["a", "b"]),
+    ] {
+        let block = DataBlock::create(schema.clone(), vec![
+            Series::from_data(ids),
+            Series::from_data(names),
+        ]);
+        let mut buf = vec![];
+        serialize_data_blocks(vec![block], &schema, &mut buf)?;
+        operator.object(location).write(buf).await?;
+    }
+
+    // No query context is needed, the blocks follow the order of the files.
+    let table = build_table(&operator, &["data/b.parquet", "data/a.parquet"])?;
+    let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+    let num_rows = blocks.iter().map(|b| b.num_rows()).collect::<Vec<_>>();
+    assert_eq!(num_rows, vec![1, 2]);
+
+    let expected = vec![
+        "+----+------+",
+        "| id | name |",
+        "+----+------+",
+        "| 3  | c    |",
+        "| 1  | a    |",
+        "| 2  | b    |",
+        "+----+------+",
+    ];
+    common_datablocks::assert_blocks_eq(expected, blocks.as_slice());
+
+    Ok(())
+}
+
+/// Like [`int_row_groups_file`], with a UINT64 column `id`.
+fn uint64_row_groups_file(row_groups: &[Vec<u64>]) -> Result<Vec<u8>> {
+    let schema = DataSchemaRefExt::create(vec![DataField::new("id", u64::to_data_type())]);
+    let columns = row_groups
+        .iter()
+        .map(|ids| Series::from_data(ids.clone()))
+        .collect();
+    row_groups_file(schema, columns)
+}
+
+#[tokio::test]
+async fn test_read_parquet_point_lookup() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+
+    // 1000 rows sorted on `id`, in 100 row groups of 10 rows.
+    let sorted = (0..100)
+        .map(|i| (i * 10..i * 10 + 10).collect::<Vec<i32>>())
+        .collect::<Vec<_>>();
+    let operator = memory_operator()?;
+    operator
+        .object("data/sorted.parquet")
+        .write(int_row_groups_file(&sorted)?)
+        .await?;
+
+    // Only the row group holding the value is read.
+    let table = build_table(&operator, &["data/sorted.parquet"])?;
+    let source_plan = table.read_plan(ctx.clone(), Some(id_equals(555))).await?;
+    assert_eq!(source_plan.parts.len(), 1);
+    assert_eq!(source_plan.statistics.read_rows, 10);
+
+    ctx.try_set_partitions(source_plan.parts.clone())?;
+    let stream = table
+        .read_data_block_stream(ctx.clone(), &source_plan)
+        .await?;
+    let result = stream.try_collect::<Vec<_>>().await?;
+    let ids = result[0].column(0);
+    let ids = (0..ids.len()).map(|i| ids.get(i)).collect::<Vec<_>>();
+    assert_eq!(ids, (550..560).map(DataValue::Int64).collect::<Vec<_>>());
+
+    // No row group holds the value.
+    let source_plan = table.read_plan(ctx.clone(), Some(id_equals(1000))).await?;
+    assert!(source_plan.parts.is_empty());
+
+    // The row groups are not sorted, their statistics are checked one by one.
+    let mut unsorted = sorted;
+    unsorted.reverse();
+    operator
+        .object("data/unsorted.parquet")
+        .write(int_row_groups_file(&unsorted)?)
+        .await?;
+    let table = build_table(&operator, &["data/unsorted.parquet"])?;
+    let source_plan = table.read_plan(ctx.clone(), Some(id_equals(555))).await?;
+    assert_eq!(source_plan.parts.len(), 1);
+    assert_eq!(source_plan.statistics.read_rows, 10);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_read_cell() -> Result<()> {
+    let row_groups = (0..3)
+        .map(|i| (i * 10..i * 10 + 10).collect::<Vec<i32>>())
+        .collect::<Vec<_>>();
+    let operator = memory_operator()?;
+    operator
+        .object("data/t.parquet")
+        .write(int_row_groups_file(&row_groups)?)
+        .await?;
+
+    let table = build_table(&operator, &["data/t.parquet"])?;
+    let value = table.read_cell("data/t.parquet", 1, 3, "id")?;
+    assert_eq!(value, DataValue::Int64(13));
+    let value = table.read_cell("data/t.parquet", 2, 9, "id")?;
+    assert_eq!(value, DataValue::Int64(29));
+
+    // Out of range row groups and rows, and unknown columns.
+    for (row_group, row_index, column) in [(3, 0, "id"), (0, 10, "id"), (0, 0, "name")] {
+        let err = table
+            .read_cell("data/t.parquet", row_group, row_index, column)
+            .err()
+            .unwrap();
+        assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_close() -> Result<()> {
+    let operator = memory_operator()?;
+    for (location, row_groups) in [
+        ("data/a.parquet", vec![vec![1i32, 2]]),
+        ("data/b.parquet", vec![vec![3i32]]),
+    ] {
+        operator
+            .object(location)
+            .write(int_row_groups_file(&row_groups)?)
+            .await?;
+    }
+
+    let table = build_table(&operator, &["data/a.parquet", "data/b.parquet"])?;
+    let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+    assert_eq!(blocks.len(), 2);
+    assert!(!table.is_closed());
+
+    // The readers created before closing share the operators of the table.
+    let mut blocks = table.read_blocks()?;
+    assert_eq!(blocks.next().unwrap()?.num_rows(), 2);
+    table.close();
+    assert!(table.is_closed());
+    let err = blocks.next().unwrap().err().unwrap();
+    assert_eq!(err.code(), ErrorCode::STORAGE_UNAVAILABLE);
+
+    let err = table.read_blocks().err().unwrap();
+    assert_eq!(err.code(), ErrorCode::STORAGE_UNAVAILABLE);
+    let err = table.read_cell("data/a.parquet", 0, 0, "id").err().unwrap();
+    assert_eq!(err.code(), ErrorCode::STORAGE_UNAVAILABLE);
+
+    // Closing again does nothing.
+    table.close();
+    assert!(table.is_closed());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_parquet_schema_diff() -> Result<()> {
+    let point = |fields| ArrowField::new("point", ArrowType::Struct(fields), false);
+    let a = int_fields_file(vec![
+        ArrowField::new("id", ArrowType::Int32, false),
+        ArrowField::new("name", ArrowType::Int32, false),
+        point(vec![
+            ArrowField::new("x", ArrowType::Int32, false),
+            ArrowField::new("y", ArrowType::Int32, false),
+        ]),
+    ])?;
+    let b = int_fields_file(vec![
+        ArrowField::new("id", ArrowType::Int64, false),
+        point(vec![
+            ArrowField::new("x", ArrowType::Int32, false),
+            ArrowField::new("y", ArrowType::Int32, true),
+            ArrowField::new("z", ArrowType::Int32, false),
+        ]),
+        ArrowField::new("extra", ArrowType::Int32, true),
+    ])?;
+    let operator = memory_operator()?;
+    operator.object("data/a.parquet").write(a).await?;
+    operator.object("data/b.parquet").write(b).await?;
+
+    let a = build_table(&operator, &["data/a.parquet"])?.schema();
+    let b = build_table(&operator, &["data/b.parquet"])?.schema();
+    let int = i32::to_data_type();
+    let nullable_int = NullableType::new_impl(int.clone());
+    let difference = |column: &str, kind, a_type: Option<&DataTypeImpl>, b_type| {
+        let sql_name = |t: Option<&DataTypeImpl>| t.map(|t| t.sql_name());
+        ColumnDifference {
+            column: column.to_string(),
+            kind,
+            a_type: sql_name(a_type),
+            b_type: sql_name(b_type),
+        }
+    };
+    assert_eq!(schema_diff(&a, &b), vec![
+        difference("id", DifferenceKind::Type, Some(&int), Some(&i64::to_data_type())),
+        difference("name", DifferenceKind::OnlyInA, Some(&int), None),
+        difference("point.y", DifferenceKind::Nullability, Some(&int), Some(&nullable_int)),
+        difference("point.z", DifferenceKind::OnlyInB, None, Some(&int)),
+        difference("extra", DifferenceKind::OnlyInB, None, Some(&nullable_int)),
+    ]);
+    assert!(schema_diff(&a, &a).is_empty());
+
+    // Exactly two files are compared.
+    let table_args = vec![DataValue::String(b"data/a.parquet".to_vec())];
+    let err = ParquetSchemaDiffTable::create("system", "parquet_schema_diff", 1, Some(table_args))
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_resume_blocks() -> Result<()> {
+    let operator = memory_operator()?;
+    for (location, row_groups) in [
+        ("data/a.parquet", vec![vec![1i32, 2], vec![3]]),
+        ("data/b.parquet", vec![vec![4i32], vec![5, 6]]),
+    ] {
+        operator
+            .object(location)
+            .write(int_row_groups_file(&row_groups)?)
+            .await?;
+    }
+    let table = build_table(&operator, &["data/a.parquet", "data/b.parquet"])?;
+    let ids = |blocks: &[DataBlock]| {
+        blocks
+            .iter()
+            .flat_map(|block| {
+                let column = block.column(0);
+                (0..column.len()).map(move |i| column.get(i))
+            })
+            .collect::<Vec<_>>()
+    };
+    let all_blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+
+    // Interrupted after three of the four row groups are read.
+    let mut blocks = table.read_blocks()?;
+    let mut read = vec![];
+    for _ in 0..3 {
+        read.push(blocks.next().unwrap()?);
+    }
+    let saved = blocks.cursor().to_json()?;
+    drop(blocks);
+    assert_eq!(
+        saved,
+        r#"{"version":1,"completed":[{"location":"data/a.parquet","row_group":0},{"location":"data/a.parquet","row_group":1},{"location":"data/b.parquet","row_group":0}]}"#
+    );
+
+    // The resumed read skips the completed parts.
+    let cursor = ParquetScanCursor::from_json(&saved)?;
+    let mut blocks = table.resume_blocks(cursor)?;
+    for block in blocks.by_ref() {
+        read.push(block?);
+    }
+    assert_eq!(read.len(), all_blocks.len());
+    assert_eq!(ids(&read), ids(&all_blocks));
+    assert_eq!(blocks.cursor().completed().count(), 4);
+
+    // Nothing is left once all the parts are completed.
+    let cursor = blocks.cursor().clone();
+    assert_eq!(table.resume_blocks(cursor)?.count(), 0);
+
+    let err = ParquetScanCursor::from_json(r#"{"version":2,"completed":[]}"#)
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+
+    Ok(())
+}
+
+/// A parquet file with the columns `arrays` of `fields`, in one row group.
+fn arrays_file(fields: Vec<ArrowField>, arrays: Vec<Box<dyn Array>>) -> Result<Vec<u8>> {
+    let (buf, _) = chunks_file(fields, vec![Chunk::new(arrays)])?;
+    Ok(buf)
+}
+
+#[tokio::test]
+async fn test_read_parquet_string_offsets() -> Result<()> {
+    // Written with 32-bit and 64-bit offsets, all read as strings with 64-bit ones.
+    let operator = memory_operator()?;
+    let file = arrays_file(
+        vec![
+            ArrowField::new("utf8", ArrowType::Utf8, false),
+            ArrowField::new("binary", ArrowType::Binary, false),
+            ArrowField::new("large_utf8", ArrowType::LargeUtf8, false),
+        ],
+        vec![
+            Utf8Array::<i32>::from_slice(["a", "bc"]).boxed(),
+            BinaryArray::<i32>::from_slice([b"d", b"ef"]).boxed(),
+            Utf8Array::<i64>::from_slice(["g", "hi"]).boxed(),
+        ],
+    )?;
+    operator.object("data/t.parquet").write(file).await?;
+
+    let table = build_table(&operator, &["data/t.parquet"])?;
+    let schema = table.schema();
+    for field in schema.fields() {
+        assert_eq!(field.data_type(), &Vu8::to_data_type());
+    }
+    let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+    let expected = vec![
+        "+------+--------+------------+",
+        "| utf8 | binary | large_utf8 |",
+        "+------+--------+------------+",
+        "| a    | d      | g          |",
+        "| bc   | ef     | hi         |",
+        "+------+--------+------------+",
+    ];
+    common_datablocks::assert_blocks_sorted_eq(expected, blocks.as_slice());
+
+    Ok(())
+}
+
+/// The types of the events of the array of structs `column`, like `events[].type`.
+fn event_types(column: &ColumnRef) -> Vec<Option<Vec<DataValue>>> {
+    (0..column.len())
+        .map(|i| match column.get(i) {
+            DataValue::Array(events) => Some(
+                events
+                    .iter()
+                    .map(|event| match event {
+                        DataValue::Struct(fields) => fields[0].clone(),
+                        value => panic!("expect a struct, but got {:?}", value),
+                    })
+                    .collect(),
+            ),
+            DataValue::Null => None,
+            value => panic!("expect an array, but got {:?}", value),
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_read_parquet_array_of_structs() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+
+    // The fields of the structs of `events` are two leaf columns, before the one of `id`.
+    let event_type = ArrowType::Struct(vec![
+        ArrowField::new("type", ArrowType::Utf8, false),
+        ArrowField::new("ts", ArrowType::Int64, false),
+    ]);
+    let events_type =
+        ArrowType::List(Box::new(ArrowField::new("item", event_type.clone(), false)));
+    let events = StructArray::new(
+        event_type,
+        vec![
+            Utf8Array::<i32>::from_slice(["click", "view", "buy"]).boxed(),
+            PrimitiveArray::<i64>::from_vec(vec![1, 2, 3]).boxed(),
+        ],
+        None,
+    );
+    // [{click, 1}, {view, 2}], [], NULL, [{buy, 3}]
+    let events = ListArray::<i32>::new(
+        events_type.clone(),
+        vec![0, 2, 2, 2, 3].into(),
+        events.boxed(),
+        Some(Bitmap::from_iter([true, true, false, true])),
+    );
+    let file = arrays_file(
+        vec![
+            ArrowField::new("events", events_type, true),
+            ArrowField::new("id", ArrowType::Int32, false),
+        ],
+        vec![
+            events.boxed(),
+            PrimitiveArray::<i32>::from_vec(vec![1, 2, 3, 4]).boxed(),
+        ],
+    )?;
+    let operator = memory_operator()?;
+    operator.object("data/events.parquet").write(file).await?;
+    let table = build_table(&operator, &["data/events.parquet"])?;
+
+    let string = |s: &str| DataValue::String(s.as_bytes().to_vec());
+    let expected_types = vec![
+        Some(vec![string("click"), string("view")]),
+        Some(vec![]),
+        None,
+        Some(vec![string("buy")]),
+    ];
+    let expected_ids = (1..=4).map(DataValue::Int64).collect::<Vec<_>>();
+    let ids = |column: &ColumnRef| (0..column.len()).map(|i| column.get(i)).collect::<Vec<_>>();
+
+    let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(event_types(blocks[0].column(0)), expected_types);
+    assert_eq!(ids(blocks[0].column(1)), expected_ids);
+
+    // Projected alone, each column is read from its own leaf columns.
+    let mut columns = vec![];
+    for index in [0, 1] {
+        let push_down = PushDownInfo {
+            projection: Some(Projection::Columns(vec![index])),
+            ..Default::default()
+        };
+        let source_plan = table.read_plan(ctx.clone(), Some(push_down)).await?;
+        ctx.try_set_partitions(source_plan.parts.clone())?;
+        let stream = table.read_data_block_stream(ctx.clone(), &source_plan).await?;
+        let blocks = stream.try_collect::<Vec<_>>().await?;
+        assert_eq!(blocks.len(), 1);
+        columns.push(blocks[0].column(0).clone());
+    }
+    assert_eq!(event_types(&columns[0]), expected_types);
+    assert_eq!(ids(&columns[1]), expected_ids);
+
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "writes and reads more than 2GB of strings"]
+async fn test_read_parquet_strings_over_i32_offsets() -> Result<()> {
+    // The strings of the column chunk add up to more than i32::MAX bytes.
+    const VALUE_SIZE: usize = 600 * 1024 * 1024;
+    let operator = memory_operator()?;
+    let values = (0..4u8)
+        .map(|i| vec![b'a' + i; VALUE_SIZE])
+        .collect::<Vec<_>>();
+    let file = arrays_file(
+        vec![ArrowField::new("s", ArrowType::LargeBinary, false)],
+        vec![BinaryArray::<i64>::from_slice(&values).boxed()],
+    )?;
+    drop(values);
+    operator.object("data/t.parquet").write(file).await?;
+
+    // Decoded as a whole, instead of block by block.
+    let table = build_table(&operator, &[
+        "data/t.parquet",
+        "max_string_block_bytes=4294967296",
+    ])?;
+    let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+    assert_eq!(blocks.len(), 1);
+    let column = blocks[0].column(0);
+    assert_eq!(column.len(), 4);
+    for i in 0..4 {
+        match column.get(i) {
+            DataValue::String(value) => {
+                assert_eq!(value.len(), VALUE_SIZE);
+                assert!(value.iter().all(|b| *b == b'a' + i as u8));
+            }
+            value => panic!("expect a string, but got {:?}", value),
+        }
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_deserialize_selected() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+    let schema = DataSchemaRefExt::create(vec![
+        DataField::new("id", i32::to_data_type()),
+        DataField::new_nullable("name", Vu8::to_data_type()),
+    ]);
+    let names = (0..10)
+        .map(|i| (i % 3 != 0).then(|| format!("name_{}", i).into_bytes()))
+        .collect::<Vec<_>>();
+    let block = DataBlock::create(schema.clone(), vec![
+        Series::from_data((0..10).collect::<Vec<i32>>()),
+        Series::from_data(names),
+    ]);
+    let mut buf = vec![];
+    serialize_data_blocks(vec![block], &schema, &mut buf)?;
+    let operator = memory_operator()?;
+    operator.object("data/t.parquet").write(buf).await?;
+
+    let table = build_table(&operator, &["data/t.parquet"])?;
+    let (_, partitions) = table.read_partitions(ctx.clone(), None).await?;
+    let part = partitions.partitions[0].clone();
+    let reader = table.create_reader(Projection::Columns(vec![0, 1]))?;
+    let chunks = reader.sync_read_columns_data(part.clone())?;
+
+    // Decoding the selected rows is the same as filtering the decoded ones.
+    for selected in [
+        (0..10).map(|i| i % 2 == 0).collect::<Vec<_>>(),
+        (0..10).map(|i| (3..7).contains(&i)).collect(),
+        vec![true; 10],
+        vec![false; 10],
+    ] {
+        let selection = Bitmap::from_iter(selected.iter().copied());
+        let block = reader.deserialize_selected(part.clone(), chunks.clone(), &selection)?;
+        let expected = DataBlock::filter_block(
+            reader.deserialize(part.clone(), chunks.clone())?,
+            &Series::from_data(selected.clone()),
+        )?;
+        assert_eq!(block.num_rows(), selected.iter().filter(|s| **s).count());
+        assert_eq!(
+            common_datablocks::pretty_format_blocks(&[block])?,
+            common_datablocks::pretty_format_blocks(&[expected])?
+        );
+    }
+
+    // The selection must have a bit per row.
+    let selection = Bitmap::from_iter(vec![true; 3]);
+    let err = reader
+        .deserialize_selected(part, chunks, &selection)
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_point_lookup_signedness() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+
+    // The values of the last row group are over i64::MAX, negative once taken as i64.
+    let max = i64::MAX as u64;
+    let row_groups = vec![vec![0, 1], vec![max - 1, max], vec![max + 1, u64::MAX]];
+    let operator = memory_operator()?;
+    operator
+        .object("data/t.parquet")
+        .write(uint64_row_groups_file(&row_groups)?)
+        .await?;
+    let table = build_table(&operator, &["data/t.parquet"])?;
+
+    let id_equals = |value: DataValue| {
+        let data_type = value.data_type();
+        PushDownInfo {
+            filters: vec![Expression::Function {
+                name: "=".to_string(),
+                args: vec![
+                    Expression::IndexedVariable {
+                        name: "id".to_string(),
+                        data_type: u64::to_data_type(),
+                    },
+                    Expression::Constant { value, data_type },
+                ],
+                return_type: bool::to_data_type(),
+            }],
+            ..Default::default()
+        }
+    };
+    let cases = [
+        (DataValue::Int64(1), vec![0]),
+        (DataValue::Int64(i64::MAX), vec![1]),
+        (DataValue::UInt64(max + 1), vec![2]),
+        (DataValue::UInt64(u64::MAX), vec![2]),
+        (DataValue::Int64(-1), vec![]),
+    ];
+    for (value, expected) in cases {
+        let source_plan = table
+            .read_plan(ctx.clone(), Some(id_equals(value.clone())))
+            .await?;
+        let row_groups = source_plan
+            .parts
+            .partitions
+            .iter()
+            .map(|part| Ok(ParquetPartInfo::from_part(part)?.row_group))
+            .collect::<Result<Vec<_>>>()?;
+        assert_eq!(row_groups, expected, "id = {:?}", value);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_arrow_schema() -> Result<()> {
+    let operator = memory_operator()?;
+    let fields = vec![
+        ArrowField::new("ID", ArrowType::Int32, false),
+        ArrowField::new("amount", ArrowType::Int64, false),
+    ];
+    let build = |location: &str| {
+        let table_args = vec![DataValue::String(location.as_bytes().to_vec())];
+        ParquetTableBuilder::create("system", "read_parquet", 1, table_args)
+            .with_operator(operator.clone())
+            .with_arrow_schema(ArrowSchema::from(fields.clone()))
+            .build()
+    };
+
+    // No file is read to build the table, the file is only written after.
+    let table = build("data/t.parquet")?;
+    let schema = table.schema();
+    let names = schema
+        .fields()
+        .iter()
+        .map(|field| field.name().as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["id", "amount"]);
+
+    operator
+        .object("data/t.parquet")
+        .write(int_fields_file(fields.clone())?)
+        .await?;
+    let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+    assert_eq!(blocks.iter().map(|b| b.num_rows()).sum::<usize>(), 1);
+
+    // The files are checked against the schema once read.
+    operator
+        .object("data/other.parquet")
+        .write(int_fields_file(vec![ArrowField::new(
+            "id",
+            ArrowType::Int32,
+            false,
+        )])?)
+        .await?;
+    let table = build("data/other.parquet")?;
+    let err = table
+        .read_blocks()
+        .and_then(|blocks| blocks.collect::<Result<Vec<_>>>())
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::PARQUET_SCHEMA_MISMATCH);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_features() -> Result<()> {
+    let file = arrays_file(
+        vec![
+            ArrowField::new("name", ArrowType::Utf8, false),
+            ArrowField::new("price", ArrowType::Decimal(9, 2), false),
+        ],
+        vec![
+            Utf8Array::<i32>::from_slice(["a"]).boxed(),
+            PrimitiveArray::<i128>::from_vec(vec![100])
+                .to(ArrowType::Decimal(9, 2))
+                .boxed(),
+        ],
+    )?;
+    let meta = read_metadata(&mut Cursor::new(file))?;
+    let features = file_features(&meta);
+    let feature = |kind, name: &str| {
+        features
+            .iter()
+            .find(|feature| feature.kind == kind && feature.name == name)
+            .cloned()
+    };
+    let expected = |kind, name: &str, supported, note: &str| ParquetFeature {
+        kind,
+        name: name.to_string(),
+        supported,
+        note: note.to_string(),
+    };
+
+    assert_eq!(
+        feature(FeatureKind::Compression, "Uncompressed"),
+        Some(expected(
+            FeatureKind::Compression,
+            "Uncompressed",
+            true,
+            "used by name, price"
+        ))
+    );
+    assert!(feature(FeatureKind::Encoding, "Plain").unwrap().supported);
+    assert_eq!(
+        feature(FeatureKind::LogicalType, "STRING"),
+        Some(expected(FeatureKind::LogicalType, "STRING", true, "used by name"))
+    );
+    assert_eq!(
+        feature(FeatureKind::LogicalType, "DECIMAL"),
+        Some(expected(FeatureKind::LogicalType, "DECIMAL", true, "used by price"))
+    );
+    assert_eq!(
+        feature(FeatureKind::Encryption, "none"),
+        Some(expected(FeatureKind::Encryption, "none", true, ""))
+    );
+    assert_eq!(
+        features
+            .iter()
+            .filter(|feature| feature.kind == FeatureKind::Encryption)
+            .count(),
+        1
+    );
+
+    // The features of exactly one file are reported.
+    let table_args = ["data/a.parquet", "data/b.parquet"]
+        .iter()
+        .map(|location| DataValue::String(location.as_bytes().to_vec()))
+        .collect();
+    let err = ParquetFeaturesTable::create("system", "read_parquet_features", 1, Some(table_args))
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+
+    Ok(())
+}
+
+fn v_is_not_null(negated: bool) -> PushDownInfo {
+    let is_not_null = Expression::Function {
+        name: "is_not_null".to_string(),
+        args: vec![Expression::IndexedVariable {
+            name: "v".to_string(),
+            data_type: NullableType::new_impl(i32::to_data_type()),
+        }],
+        return_type: bool::to_data_type(),
+    };
+    let filter = if negated {
+        Expression::Function {
+            name: "not".to_string(),
+            args: vec![is_not_null],
+            return_type: bool::to_data_type(),
+        }
+    } else {
+        is_not_null
+    };
+    PushDownInfo {
+        filters: vec![filter],
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_read_parquet_null_counts() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+
+    // `v` is all null in the first row group, and has no null in the last one.
+    let schema =
+        DataSchemaRefExt::create(vec![DataField::new_nullable("v", i32::to_data_type())]);
+    let row_groups = vec![
+        Series::from_data(vec![None::<i32>, None, None]),
+        Series::from_data(vec![Some(1i32), None, Some(3)]),
+        Series::from_data(vec![Some(4i32), Some(5)]),
+    ];
+    let operator = memory_operator()?;
+    operator
+        .object("data/nulls.parquet")
+        .write(row_groups_file(schema, row_groups)?)
+        .await?;
+    let table = build_table(&operator, &["data/nulls.parquet"])?;
+
+    // WHERE v IS NOT NULL
+    let source_plan = table.read_plan(ctx.clone(), Some(v_is_not_null(false))).await?;
+    let row_groups = source_plan
+        .parts
+        .partitions
+        .iter()
+        .map(|part| Ok(ParquetPartInfo::from_part(part)?.row_group))
+        .collect::<Result<Vec<_>>>()?;
+    assert_eq!(row_groups, vec![1, 2]);
+    assert_eq!(source_plan.statistics.read_rows, 5);
+
+    // WHERE v IS NULL
+    let source_plan = table.read_plan(ctx.clone(), Some(v_is_not_null(true))).await?;
+    let row_groups = source_plan
+        .parts
+        .partitions
+        .iter()
+        .map(|part| Ok(ParquetPartInfo::from_part(part)?.row_group))
+        .collect::<Result<Vec<_>>>()?;
+    assert_eq!(row_groups, vec![0, 1]);
+    assert_eq!(source_plan.statistics.read_rows, 6);
+
+    // All the row groups are read without pruning.
+    let table = build_table(&operator, &["data/nulls.parquet", "no_prune=true"])?;
+    let source_plan = table.read_plan(ctx, Some(v_is_not_null(false))).await?;
+    assert_eq!(source_plan.parts.len(), 3);
+
+    Ok(())
+}
+
+/// The statistics of the scan of `table` by `push_down`, with the numbers of rows and of
+/// nulls in the first column of the blocks read.
+async fn scanned_nulls(
+    ctx: Arc<QueryContext>,
+    table: Arc<ParquetTable>,
+    push_down: PushDownInfo,
+) -> Result<(PartStatistics, usize, usize)> {
+    let source_plan = table.read_plan(ctx.clone(), Some(push_down)).await?;
+    ctx.try_set_partitions(source_plan.parts.clone())?;
+    let stream = table.read_data_block_stream(ctx, &source_plan).await?;
+    let blocks = stream.try_collect::<Vec<_>>().await?;
+    let rows = blocks.iter().map(|block| block.num_rows()).sum();
+    let nulls = blocks
+        .iter()
+        .flat_map(|block| {
+            let column = block.column(0);
+            (0..column.len()).map(|i| column.get(i)).collect::<Vec<_>>()
+        })
+        .filter(|value| value.is_null())
+        .count();
+    Ok((source_plan.statistics, rows, nulls))
+}
+
+#[tokio::test]
+async fn test_read_parquet_null_count_shortcut() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+
+    let schema =
+        DataSchemaRefExt::create(vec![DataField::new_nullable("v", i32::to_data_type())]);
+    let row_groups = vec![
+        Series::from_data(vec![None::<i32>, None, None]),
+        Series::from_data(vec![Some(1i32), None, Some(3)]),
+        Series::from_data(vec![Some(4i32), Some(5)]),
+    ];
+    let buf = row_groups_file(schema, row_groups)?;
+
+    // The same file, with its footer rewritten without the statistics of the columns.
+    let (mut meta, _) = footer_and_chunk(&buf)?;
+    let mut stripped = buf.clone();
+    strip_footer(&mut stripped);
+    for column in meta
+        .row_groups
+        .iter_mut()
+        .flat_map(|rg| rg.columns.iter_mut())
+    {
+        column.meta_data.as_mut().unwrap().statistics = None;
+    }
+    append_footer(&mut stripped, &meta)?;
+
+    let operator = memory_operator()?;
+    operator.object("data/nulls.parquet").write(buf).await?;
+    operator.object("data/stripped.parquet").write(stripped).await?;
+
+    // SELECT count(*) ... WHERE v IS NULL, which only reads `v`.
+    let push_down = PushDownInfo {
+        projection: Some(Projection::Columns(vec![0])),
+        ..v_is_not_null(true)
+    };
+
+    // The nulls are counted by the footer, no data is read.
+    let table = build_table(&operator, &["data/nulls.parquet"])?;
+    let (statistics, rows, nulls) = scanned_nulls(ctx.clone(), table, push_down.clone()).await?;
+    assert_eq!((statistics.read_rows, statistics.read_bytes), (4, 0));
+    assert!(statistics.is_exact);
+    assert_eq!((rows, nulls), (4, 4));
+
+    // The same nulls are found by scanning the data.
+    let table = build_table(&operator, &["data/nulls.parquet", "no_prune=true"])?;
+    let (statistics, rows, nulls) = scanned_nulls(ctx.clone(), table, push_down.clone()).await?;
+    assert!(statistics.read_bytes > 0);
+    assert!(!statistics.is_exact);
+    assert_eq!((rows, nulls), (8, 4));
+
+    // Without null counts, the row groups are scanned.
+    let table = build_table(&operator, &["data/stripped.parquet"])?;
+    let (statistics, rows, nulls) = scanned_nulls(ctx.clone(), table, push_down).await?;
+    assert!(statistics.read_bytes > 0);
+    assert!(!statistics.is_exact);
+    assert_eq!((rows, nulls), (8, 4));
+
+    // Without a projection, all the columns are read by the scan.
+    let table = build_table(&operator, &["data/nulls.parquet"])?;
+    let (statistics, rows, _) = scanned_nulls(ctx, table, v_is_not_null(true)).await?;
+    assert!(!statistics.is_exact);
+    assert_eq!(rows, 6);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_column_chunk() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+    let buf = nullable_int_columns_file(&[
+        ("a", vec![Some(1), None, Some(3)]),
+        ("b", vec![Some(4), Some(5), None]),
+    ])?;
+    let operator = memory_operator()?;
+    operator.object("data/t.parquet").write(buf.clone()).await?;
+
+    let file_meta = read_metadata(&mut Cursor::new(&buf))?;
+    let table = build_table(&operator, &["data/t.parquet"])?;
+    let source_plan = table
+        .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+        .await?;
+    assert_eq!(source_plan.parts.len(), 1);
+    let part = &source_plan.parts.partitions[0];
+    for (leaf_id, column) in file_meta.row_groups[0].columns().iter().enumerate() {
+        let (start, length) = column.byte_range();
+        let (start, end) = (start as usize, (start + length) as usize);
+        assert_eq!(table.read_column_chunk(part, leaf_id)?, &buf[start..end]);
+    }
+
+    let err = table.read_column_chunk(part, 2).err().unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_scan_progress() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+    let operator = memory_operator()?;
+    let files = [
+        ("data/a.parquet", vec![vec![1, 2]]),
+        ("data/b.parquet", vec![vec![3], vec![4, 5]]),
+        ("data/c.parquet", vec![vec![6], vec![7], vec![8, 9]]),
+    ];
+    let mut bytes_total = 0;
+    for (location, row_groups) in files.iter() {
+        let buf = int_row_groups_file(row_groups)?;
+        let file_meta = read_metadata(&mut Cursor::new(&buf))?;
+        bytes_total += file_meta
+            .row_groups
+            .iter()
+            .flat_map(|row_group| row_group.columns())
+            .map(|column| column.compressed_size() as usize)
+            .sum::<usize>();
+        operator.object(location).write(buf).await?;
+    }
+
+    let table = build_table(&operator, &["data/*.parquet"])?;
+    assert_eq!(table.scan_progress(), None);
+    let source_plan = table
+        .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+        .await?;
+    ctx.try_set_partitions(source_plan.parts.clone())?;
+    let mut stream = table.read_data_block_stream(ctx, &source_plan).await?;
+
+    // The totals come from the footers, the completed values only grow.
+    let mut last = table.scan_progress().unwrap();
+    assert_eq!(last.files_total, 3);
+    assert_eq!(last.bytes_total, bytes_total);
+    while stream.try_next().await?.is_some() {
+        let progress = table.scan_progress().unwrap();
+        assert_eq!(progress.files_total, last.files_total);
+        assert_eq!(progress.bytes_total, last.bytes_total);
+        assert!(progress.files_completed >= last.files_completed);
+        assert!(progress.files_completed <= progress.files_total);
+        assert!(progress.bytes_read >= last.bytes_read);
+        assert!(progress.bytes_read <= progress.bytes_total);
+        last = progress;
+    }
+    assert_eq!(last.files_completed, 3);
+    assert_eq!(last.bytes_read, bytes_total);
+
+    Ok(())
+}
+
+/// The final statistics of a scan of `table` with `push_down`, checked against the
+/// estimates of its plan, with the rows of the blocks read.
+async fn final_scan_statistics(
+    ctx: Arc<QueryContext>,
+    table: Arc<ParquetTable>,
+    push_down: PushDownInfo,
+) -> Result<(ParquetScanProgressValues, usize)> {
+    let source_plan = table.read_plan(ctx.clone(), Some(push_down)).await?;
+    ctx.try_set_partitions(source_plan.parts.clone())?;
+    let stream = table.read_data_block_stream(ctx, &source_plan).await?;
+    let blocks = stream.try_collect::<Vec<_>>().await?;
+    let rows = blocks.iter().map(|block| block.num_rows()).sum();
+
+    let statistics = table.final_scan_statistics().unwrap();
+    assert_eq!(statistics.rows_total, source_plan.statistics.read_rows);
+    assert_eq!(statistics.files_total, source_plan.statistics.partitions_scanned);
+    assert_eq!(
+        statistics.files_total + statistics.files_pruned,
+        source_plan.statistics.partitions_total
+    );
+    Ok((statistics, rows))
+}
+
+#[tokio::test]
+async fn test_read_parquet_final_scan_statistics() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+    let operator = memory_operator()?;
+    let files = [
+        ("data/a.parquet", vec![vec![1, 2]]),
+        ("data/b.parquet", vec![vec![3], vec![4, 5]]),
+        ("data/c.parquet", vec![vec![6], vec![7], vec![8, 9]]),
+    ];
+    for (location, row_groups) in files.iter() {
+        operator
+            .object(location)
+            .write(int_row_groups_file(row_groups)?)
+            .await?;
+    }
+
+    // A full scan reads all the rows of the footers, and prunes nothing.
+    let unpruned = build_table(&operator, &["data/*.parquet", "no_prune=true"])?;
+    assert_eq!(unpruned.final_scan_statistics(), None);
+    let (full, full_rows) =
+        final_scan_statistics(ctx.clone(), unpruned, PushDownInfo::default()).await?;
+    assert_eq!(full_rows, 9);
+    assert_eq!(full, ParquetScanProgressValues {
+        files_completed: 3,
+        files_total: 3,
+        bytes_read: full.bytes_total,
+        bytes_total: full.bytes_total,
+        rows_read: 9,
+        rows_total: 9,
+        parts_completed: 6,
+        parts_total: 6,
+        files_pruned: 0,
+        row_groups_pruned: 0,
+    });
+
+    // Only the row group of c.parquet holding 7 is read, the files and row groups pruned
+    // hold the other rows of the full scan.
+    let table = build_table(&operator, &["data/*.parquet"])?;
+    let (pruned, _) = final_scan_statistics(ctx.clone(), table, id_equals(7)).await?;
+    assert_eq!(pruned.files_completed, 1);
+    assert_eq!(pruned.files_pruned, 2);
+    assert_eq!(pruned.parts_completed, 1);
+    assert_eq!(pruned.row_groups_pruned, 2);
+    assert_eq!(pruned.rows_read, 1);
+    assert_eq!(pruned.bytes_read, pruned.bytes_total);
+    // a.parquet and b.parquet, then the first and the last row groups of c.parquet.
+    let pruned_rows = 2 + 3 + 1 + 2;
+    assert_eq!(pruned.rows_read + pruned_rows, full.rows_read);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_parquet_fixture_decode() -> Result<()> {
+    // The blocks are the row groups of the file.
+    let table = ParquetFixture::create()?
+        .with_compression(CompressionOptions::Snappy)
+        .with_file("data/a.parquet", vec![
+            id_name_block(vec![1, 2], Some(vec!["a", "b"])),
+            id_name_block(vec![3], Some(vec!["c"])),
+        ])?
+        .with_file("data/b.parquet", vec![id_name_block(
+            vec![4, 5, 6],
+            Some(vec!["d", "e", "f"]),
+        )])?
+        .build()?;
+
+    let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+    let num_rows = blocks.iter().map(|b| b.num_rows()).collect::<Vec<_>>();
+    assert_eq!(num_rows, vec![2, 1, 3]);
+    let expected = vec![
+        "+----+------+",
+        "| id | name |",
+        "+----+------+",
+        "| 1  | a    |",
+        "| 2  | b    |",
+        "| 3  | c    |",
+        "| 4  | d    |",
+        "| 5  | e    |",
+        "| 6  | f    |",
+        "+----+------+",
+    ];
+    common_datablocks::assert_blocks_eq(expected, blocks.as_slice());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_parquet_fixture_merge_by_name() -> Result<()> {
+    let table = ParquetFixture::create()?
+        .with_file("data/a.parquet", vec![id_name_block(vec![1, 2], Some(vec!["a", "b"]))])?
+        .with_file("data/b.parquet", vec![id_name_block(vec![3], None)])?
+        .with_option("merge_by=name")
+        .build()?;
+
+    let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+    let expected = vec![
+        "+----+------+",
+        "| id | name |",
+        "+----+------+",
+        "| 1  | a    |",
+        "| 2  | b    |",
+        "| 3  | NULL |",
+        "+----+------+",
+    ];
+    common_datablocks::assert_blocks_sorted_eq(expected, blocks.as_slice());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_parquet_fixture_prune() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+    let row_groups = || {
+        (0..4)
+            .map(|i| id_name_block((i * 10..i * 10 + 10).collect(), None))
+            .collect::<Vec<_>>()
+    };
+
+    // The row groups are pruned by their statistics, all of them are read without.
+    let with_statistics = ParquetFixture::create()?
+        .with_file("data/t.parquet", row_groups())?
+        .build()?;
+    let without_statistics = ParquetFixture::create()?
+        .with_statistics(false)
+        .with_file("data/t.parquet", row_groups())?
+        .build()?;
+    for id in [0, 25, 39] {
+        let (rows, ids) = matched_ids(ctx.clone(), with_statistics.clone(), id).await?;
+        assert_eq!(rows, 10);
+        assert_eq!(ids, vec![DataValue::Int64(id)]);
+        let (rows, ids) = matched_ids(ctx.clone(), without_statistics.clone(), id).await?;
+        assert_eq!(rows, 40);
+        assert_eq!(ids, vec![DataValue::Int64(id)]);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_mixed_page_versions() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+    let blocks = |first: i32| {
+        vec![
+            id_name_block(vec![first, first + 1], Some(vec!["a", "b"])),
+            id_name_block(vec![first + 2], Some(vec!["c"])),
+        ]
+    };
+    let fixture = ParquetFixture::create()?
+        .with_version(Version::V1)
+        .with_file("data/v1.parquet", blocks(1))?
+        .with_version(Version::V2)
+        .with_file("data/v2.parquet", blocks(4))?;
+    let operator = fixture.operator();
+
+    // Each file is decoded by the versions of its pages, in one call.
+    for options in [vec![], vec!["verify_checksums=true"]] {
+        let mut args = vec!["data/*.parquet"];
+        args.extend(options);
+        let table = build_table(&operator, &args)?;
+        let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+        let expected = vec![
+            "+----+------+",
+            "| id | name |",
+            "+----+------+",
+            "| 1  | a    |",
+            "| 2  | b    |",
+            "| 3  | c    |",
+            "| 4  | a    |",
+            "| 5  | b    |",
+            "| 6  | c    |",
+            "+----+------+",
+        ];
+        common_datablocks::assert_blocks_sorted_eq(expected, blocks.as_slice());
+
+        for id in [2, 5] {
+            let (_, ids) = matched_ids(ctx.clone(), table.clone(), id).await?;
+            assert_eq!(ids, vec![DataValue::Int64(id)]);
+        }
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_empty_column_names() -> Result<()> {
+    let schema = DataSchemaRefExt::create(vec![
+        DataField::new_nullable("", i32::to_data_type()),
+        DataField::new_nullable("", Vu8::to_data_type()),
+        DataField::new_nullable("id", i32::to_data_type()),
+    ]);
+    let block = DataBlock::create(schema, vec![
+        Series::from_data(vec![Some(1i32), Some(2)]),
+        Series::from_data(vec![Some("a"), Some("b")]),
+        Series::from_data(vec![Some(10i32), Some(20)]),
+    ]);
+    let fixture = ParquetFixture::create()?.with_file("data/t.parquet", vec![block])?;
+    let operator = fixture.operator();
+
+    // The columns are named by their indices in the file.
+    let table = build_table(&operator, &["data/t.parquet"])?;
+    let schema = table.schema();
+    let names = schema
+        .fields()
+        .iter()
+        .map(|field| field.name().as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["col_0", "col_1", "id"]);
+    let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+    let expected = vec![
+        "+-------+-------+----+",
+        "| col_0 | col_1 | id |",
+        "+-------+-------+----+",
+        "| 1     | a     | 10 |",
+        "| 2     | b     | 20 |",
+        "+-------+-------+----+",
+    ];
+    common_datablocks::assert_blocks_sorted_eq(expected, blocks.as_slice());
+
+    // The projections of the synthetic names read the columns at their indices.
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+    let push_downs = PushDownInfo {
+        projection: Some(Projection::Columns(vec![1])),
+        ..Default::default()
+    };
+    let source_plan = table.read_plan(ctx.clone(), Some(push_downs)).await?;
+    ctx.try_set_partitions(source_plan.parts.clone())?;
+    let stream = table.read_data_block_stream(ctx, &source_plan).await?;
+    let blocks = stream.try_collect::<Vec<_>>().await?;
+    let expected = vec![
+        "+-------+",
+        "| col_1 |",
+        "+-------+",
+        "| a     |",
+        "| b     |",
+        "+-------+",
+    ];
+    common_datablocks::assert_blocks_sorted_eq(expected, blocks.as_slice());
+
+    Ok(())
+}
+
+/// A file with the columns `id` and `name` of `id_name_block`, and a bloom filter of
+/// `num_bytes` bytes on `id` in its row group.
+fn id_bloom_filter_file(ids: Vec<i32>, num_bytes: usize) -> Result<Vec<u8>> {
+    let names = ids.iter().map(|_| "a").collect();
+    let block = id_name_block(ids.clone(), Some(names));
+    let schema = block.schema().clone();
+    let mut buf = vec![];
+    let (_, mut meta) = serialize_data_blocks_with_compression(
+        vec![block],
+        &schema,
+        &mut buf,
+        CompressionOptions::Uncompressed,
+    )?;
+
+    // Write the filter before the footer, and rewrite the footer with its offset.
+    strip_footer(&mut buf);
+    let offset = buf.len() as i64;
+    let header = BloomFilterHeader::new(
+        num_bytes as i32,
+        BloomFilterAlgorithm::BLOCK(SplitBlockAlgorithm::new()),
+        BloomFilterHash::XXHASH(XxHash::new()),
+        BloomFilterCompression::UNCOMPRESSED(Uncompressed::new()),
+    );
+    header
+        .write_to_out_protocol(&mut TCompactOutputProtocol::new(&mut buf))
+        .map_err(|e| ErrorCode::Internal(e.to_string()))?;
+    let mut bitset = vec![0; num_bytes];
+    for id in ids {
+        bloom_filter::insert(&mut bitset, bloom_filter::hash_native(id));
+    }
+    buf.extend_from_slice(&bitset);
+    meta.row_groups[0].columns[0]
+        .meta_data
+        .as_mut()
+        .unwrap()
+        .bloom_filter_offset = Some(offset);
+    append_footer(&mut buf, &meta)?;
+    Ok(buf)
+}
+
+#[tokio::test]
+async fn test_read_parquet_features_bloom_filters() -> Result<()> {
+    let tmp_dir = TempDir::new().unwrap();
+    let path = tmp_dir.path().join("a.parquet");
+    std::fs::write(&path, id_bloom_filter_file(vec![1, 2, 3], 1024)?)?;
+    let root = tmp_dir.path().to_str().unwrap();
+
+    // The parameters are read from the header of the filter.
+    let table_args = vec![DataValue::String(path.to_str().unwrap().as_bytes().to_vec())];
+    let config = StorageConfig {
+        parquet_trusted_root: root.to_string(),
+        ..Default::default()
+    };
+    let table = ParquetFeaturesTable::create_with_config(
+        "system",
+        "read_parquet_features",
+        1,
+        Some(table_args),
+        &config,
+    )?;
+    let table = table
+        .as_any()
+        .downcast_ref::<ParquetFeaturesTable>()
+        .unwrap();
+    let params = BloomFilterParams {
+        bits: 8192,
+        hashes: 8,
+    };
+    assert_eq!(table.bloom_filters(), &[
+        ColumnBloomFilter {
+            path: "id".to_string(),
+            present: true,
+            params: Some(params),
+        },
+        ColumnBloomFilter {
+            path: "name".to_string(),
+            present: false,
+            params: None,
+        },
+    ]);
+    let bloom_filters = table
+        .features()
+        .iter()
+        .filter(|feature| feature.kind == FeatureKind::BloomFilter)
+        .map(|feature| (feature.name.as_str(), feature.note.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(bloom_filters, vec![
+        ("bits=8192, hashes=8", "used by id"),
+        ("none", "used by name"),
+    ]);
+
+    // Only the presence is in the footer.
+    let meta = read_metadata(&mut Cursor::new(std::fs::read(&path)?))?;
+    let presence = column_bloom_filters(&meta)
+        .iter()
+        .map(|bloom_filter| bloom_filter.present)
+        .collect::<Vec<_>>();
+    assert_eq!(presence, vec![true, false]);
+    assert!(file_features(&meta).contains(&ParquetFeature {
+        kind: FeatureKind::BloomFilter,
+        name: "present".to_string(),
+        supported: true,
+        note: "used by id".to_string(),
+    }));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_row_group_filter() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+    // The row group `i` has `i + 1` rows, so the even-indexed ones have an odd number.
+    let row_groups = (0..4)
+        .map(|i| id_name_block((i * 10..i * 10 + i + 1).collect(), None))
+        .collect::<Vec<_>>();
+    let fixture = ParquetFixture::create()?.with_file("data/t.parquet", row_groups)?;
+    let operator = fixture.operator();
+    let table = ParquetTableBuilder::create("system", "read_parquet", 1, vec![
+        DataValue::String(b"data/t.parquet".to_vec()),
+    ])
+    .with_operator(operator.clone())
+    .with_row_group_filter(|row_group| row_group.num_rows() % 2 == 1)
+    .build()?;
+
+    let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+    let expected = vec![
+        "+----+", //
+        "| id |", "+----+", "| 0  |", "| 20 |", "| 21 |", "| 22 |", "+----+",
+    ];
+    common_datablocks::assert_blocks_sorted_eq(expected, blocks.as_slice());
+
+    // The selection applies on top of the pruning by the filters pushed down.
+    let (rows, ids) = matched_ids(ctx.clone(), table.clone(), 21).await?;
+    assert_eq!(rows, 3);
+    assert_eq!(ids, vec![DataValue::Int64(21)]);
+    let (rows, ids) = matched_ids(ctx.clone(), table, 10).await?;
+    assert_eq!(rows, 0);
+    assert!(ids.is_empty());
+
+    // The table of the SQL path reads all of them.
+    let table = build_table(&operator, &["data/t.parquet"])?;
+    let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+    let rows = blocks.iter().map(|block| block.num_rows()).sum::<usize>();
+    assert_eq!(rows, 10);
+
+    Ok(())
+}
+
+/// A parquet file of one column `name`, written from `array` with the pages encoded by
+/// `encoding`.
+fn single_column_file(array: Box<dyn Array>, encoding: Encoding) -> Result<Vec<u8>> {
+    let field = ArrowField::new("name", array.data_type().clone(), false);
+    let arrow_schema = ArrowSchema::from(vec![field]);
+    let options = WriteOptions {
+        write_statistics: false,
+        compression: CompressionOptions::Uncompressed,
+        version: Version::V1,
+    };
+    let row_groups = RowGroupIterator::try_new(
+        vec![Ok(Chunk::new(vec![array]))].into_iter(),
+        &arrow_schema,
+        options,
+        vec![vec![encoding]],
+    )?;
+    let file_options = FileWriteOptions {
+        write_statistics: false,
+        version: Version::V1,
+    };
+    let mut buf = vec![];
+    write_parquet_file(&mut buf, row_groups, arrow_schema, file_options)?;
+    Ok(buf)
+}
+
+/// The footer of the parquet file `file` of one column chunk, and the range of the chunk.
+fn footer_and_chunk(file: &[u8]) -> Result<(ThriftFileMetaData, Range<usize>)> {
+    let meta_len = u32::from_le_bytes(file[file.len() - 8..file.len() - 4].try_into().unwrap());
+    let footer = &file[file.len() - 8 - meta_len as usize..file.len() - 8];
+    let mut prot = TCompactInputProtocol::new(footer, footer.len() * 2 + 1024);
+    let meta = ThriftFileMetaData::read_from_in_protocol(&mut prot)
+        .map_err(|e| ErrorCode::ParquetFileInvalid(e.to_string()))?;
+    let column = meta.row_groups[0].columns[0].meta_data.as_ref().unwrap();
+    let start = column.dictionary_page_offset.unwrap_or(column.data_page_offset) as usize;
+    let end = start + column.total_compressed_size as usize;
+    Ok((meta, start..end))
+}
+
+/// A parquet file of a STRING column `name` in one row group, whose column chunk falls
+/// back from dictionary encoded pages to PLAIN ones, like the writers do once the
+/// dictionary grows too large. The values are `dictionary_values` then `plain_values`.
+fn dictionary_fallback_file(dictionary_values: &[&str], plain_values: &[&str]) -> Result<Vec<u8>> {
+    let keys = (0..dictionary_values.len() as i32).collect::<Vec<_>>();
+    let dictionary = DictionaryArray::<i32>::try_from_keys(
+        PrimitiveArray::from_vec(keys),
+        Utf8Array::<i32>::from_slice(dictionary_values).boxed(),
+    )?;
+    let dictionary_file = single_column_file(dictionary.boxed(), Encoding::RleDictionary)?;
+    let plain = Utf8Array::<i32>::from_slice(plain_values);
+    let plain_file = single_column_file(plain.boxed(), Encoding::Plain)?;
+
+    // Append the PLAIN data page to the chunk of the dictionary encoded one.
+    let (mut meta, dictionary_chunk) = footer_and_chunk(&dictionary_file)?;
+    let (plain_meta, plain_chunk) = footer_and_chunk(&plain_file)?;
+    let mut buf = dictionary_file[..dictionary_chunk.end].to_vec();
+    buf.extend_from_slice(&plain_file[plain_chunk]);
+
+    let plain_row_group = &plain_meta.row_groups[0];
+    let plain_column = plain_row_group.columns[0].meta_data.as_ref().unwrap();
+    let row_group = &mut meta.row_groups[0];
+    row_group.num_rows += plain_row_group.num_rows;
+    row_group.total_byte_size += plain_row_group.total_byte_size;
+    row_group.total_compressed_size = None;
+    let chunk = &mut row_group.columns[0];
+    // The page indexes written after the chunks are dropped.
+    chunk.column_index_offset = None;
+    chunk.column_index_length = None;
+    chunk.offset_index_offset = None;
+    chunk.offset_index_length = None;
+    let column = chunk.meta_data.as_mut().unwrap();
+    column.num_values += plain_column.num_values;
+    column.total_compressed_size += plain_column.total_compressed_size;
+    column.total_uncompressed_size += plain_column.total_uncompressed_size;
+    for encoding in plain_column.encodings.iter() {
+        if !column.encodings.contains(encoding) {
+            column.encodings.push(*encoding);
+        }
+    }
+    column.encoding_stats = None;
+    meta.num_rows += plain_meta.num_rows;
+
+    append_footer(&mut buf, &meta)?;
+    Ok(buf)
+}
+
+#[tokio::test]
+async fn test_read_parquet_dictionary_fallback() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+    let operator = memory_operator()?;
+    let file = dictionary_fallback_file(&["a", "b", "c"], &["d", "a", "e"])?;
+    let meta = read_metadata(&mut Cursor::new(file.clone()))?;
+    let encodings = meta.row_groups[0].columns()[0].column_encoding();
+    assert!(encodings.contains(&Encoding::RleDictionary));
+    assert!(encodings.contains(&Encoding::Plain));
+    operator.object("data/t.parquet").write(file).await?;
+
+    // Each data page is decoded by its own encoding, the PLAIN one without the dictionary.
+    let expected = vec![
+        "+------+", //
+        "| name |", "+------+", "| a    |", "| a    |", "| b    |", "| c    |", "| d    |",
+        "| e    |", "+------+",
+    ];
+    let table = build_table(&operator, &["data/t.parquet"])?;
+    let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+    common_datablocks::assert_blocks_sorted_eq(expected.clone(), blocks.as_slice());
+
+    let table = build_table(&operator, &["data/t.parquet", "verify_checksums=true"])?;
+    let source_plan = table
+        .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+        .await?;
+    ctx.try_set_partitions(source_plan.parts.clone())?;
+    let stream = table.read_data_block_stream(ctx, &source_plan).await?;
+    let blocks = stream.try_collect::<Vec<_>>().await?;
+    common_datablocks::assert_blocks_sorted_eq(expected, blocks.as_slice());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_arrow_chunks() -> Result<()> {
+    let table = ParquetFixture::create()?
+        .with_file("data/a.parquet", vec![
+            id_name_block(vec![1, 2], Some(vec!["a", "b"])),
+            id_name_block(vec![3], Some(vec!["c"])),
+        ])?
+        .with_file("data/b.parquet", vec![id_name_block(vec![4], Some(vec!["d"]))])?
+        .build()?;
+
+    // The chunks have the arrow schema of the table schema.
+    let arrow_schema = table.arrow_schema();
+    assert_eq!(arrow_schema, table.schema().to_arrow());
+    let chunks = table.read_chunks()?.collect::<Result<Vec<_>>>()?;
+    for chunk in chunks.iter() {
+        let data_types = chunk
+            .arrays()
+            .iter()
+            .map(|array| array.data_type())
+            .collect::<Vec<_>>();
+        let expected = arrow_schema
+            .fields
+            .iter()
+            .map(|field| field.data_type())
+            .collect::<Vec<_>>();
+        assert_eq!(data_types, expected);
+    }
+
+    // The values are the ones of the blocks.
+    let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+    assert_eq!(chunks.len(), blocks.len());
+    for (chunk, block) in chunks.into_iter().zip(blocks) {
+        assert_eq!(chunk, Chunk::try_from(block)?);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_empty_file() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+    let operator = memory_operator()?;
+    let block = id_name_block(vec![1, 2], Some(vec!["a", "b"]));
+    let schema = block.schema().clone();
+
+    // A file without row groups, only a schema.
+    let mut buf = vec![];
+    serialize_data_blocks_with_compression(
+        vec![],
+        &schema,
+        &mut buf,
+        CompressionOptions::Uncompressed,
+    )?;
+    assert!(read_metadata(&mut Cursor::new(&buf))?.row_groups.is_empty());
+    operator.object("data/empty.parquet").write(buf).await?;
+
+    // The columns are the ones of the schema, without any row.
+    let table = build_table(&operator, &["data/empty.parquet"])?;
+    let names = table
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| field.name().as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["id", "name"]);
+    let data_types = table
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| remove_nullable(field.data_type()).data_type_id())
+        .collect::<Vec<_>>();
+    assert_eq!(data_types, vec![TypeID::Int32, TypeID::String]);
+    assert_eq!(table.read_blocks()?.count(), 0);
+
+    let source_plan = table
+        .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+        .await?;
+    ctx.try_set_partitions(source_plan.parts.clone())?;
+    let stream = table.read_data_block_stream(ctx.clone(), &source_plan).await?;
+    let blocks = stream.try_collect::<Vec<_>>().await?;
+    assert_eq!(blocks.iter().map(|block| block.num_rows()).sum::<usize>(), 0);
+
+    // Along with a file of the same schema, only the rows of that file are read.
+    let mut buf = vec![];
+    serialize_data_blocks_with_compression(
+        vec![block],
+        &schema,
+        &mut buf,
+        CompressionOptions::Uncompressed,
+    )?;
+    operator.object("data/full.parquet").write(buf).await?;
+    let table = build_table(&operator, &["data/*.parquet"])?;
+    let rows = table
+        .read_blocks()?
+        .map(|block| Ok(block?.num_rows()))
+        .sum::<Result<usize>>()?;
+    assert_eq!(rows, 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_parquet_schemas_compatible() -> Result<()> {
+    let operator = memory_operator()?;
+    let files = [
+        ("data/same/a.parquet", nullable_int_columns_file(&[("id", vec![Some(1)])])?),
+        ("data/same/b.parquet", nullable_int_columns_file(&[("id", vec![Some(2)])])?),
+        ("data/merged/a.parquet", nullable_int_columns_file(&[("id", vec![Some(1)])])?),
+        (
+            "data/merged/b.parquet",
+            nullable_int_columns_file(&[("id", vec![Some(2)]), ("v", vec![None])])?,
+        ),
+        ("data/other/a.parquet", nullable_int_columns_file(&[("id", vec![Some(1)])])?),
+        ("data/other/b.parquet", int_column_file("id")?.0),
+    ];
+    for (location, buf) in files {
+        operator.object(location).write(buf).await?;
+    }
+    let incompatibility = |args: &[&str]| {
+        let table_args = args
+            .iter()
+            .map(|arg| DataValue::String(arg.as_bytes().to_vec()))
+            .collect::<Vec<_>>();
+        schema_incompatibility(&table_args, Some(operator.clone()))
+    };
+
+    // The files of the same schema are compatible.
+    assert_eq!(incompatibility(&["data/same/*.parquet"])?, None);
+
+    // The ones with different columns only if they are merged by name.
+    let found = incompatibility(&["data/merged/*.parquet"])?.unwrap();
+    assert!(found.contains("data/merged/b.parquet"), "{}", found);
+    assert_eq!(
+        incompatibility(&["data/merged/*.parquet", "merge_by=name"])?,
+        None
+    );
+
+    // The first incompatibility is reported, here the nullability of `id`.
+    let found = incompatibility(&["data/other/*.parquet", "merge_by=name"])?.unwrap();
+    assert_eq!(
+        found,
+        "The schema of parquet file 'data/other/b.parquet' is different from the schema of 'data/other/a.parquet'"
+    );
+
+    // The files which can't be read are errors, not incompatibilities.
+    let err = incompatibility(&["data/missing.parquet"]).err().unwrap();
+    assert_eq!(err.code(), ErrorCode::STORAGE_NOT_FOUND);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_large_footer() -> Result<()> {
+    // Enough columns of long names for the footer to be larger than the first read.
+    let names = (0..2000)
+        .map(|i| format!("a_rather_long_column_name_to_grow_the_footer_{:04}", i))
+        .collect::<Vec<_>>();
+    let fields = names
+        .iter()
+        .map(|name| DataField::new(name, i32::to_data_type()))
+        .collect();
+    let schema = DataSchemaRefExt::create(fields);
+    let columns = (0..names.len())
+        .map(|i| Series::from_data(vec![i as i32]))
+        .collect();
+    let block = DataBlock::create(schema.clone(), columns);
+    let mut buf = vec![];
+    serialize_data_blocks(vec![block], &schema, &mut buf)?;
+    let trailer = &buf[buf.len() - 8..buf.len() - 4];
+    let footer_size = u32::from_le_bytes(trailer.try_into().unwrap());
+    assert!(footer_size > 64 * 1024, "footer of {} bytes", footer_size);
+
+    let operator = memory_operator()?;
+    operator.object("data/wide.parquet").write(buf).await?;
+    let table = build_table(&operator, &["data/wide.parquet"])?;
+    assert_eq!(table.schema().num_fields(), names.len());
+    assert_eq!(table.schema().field(1999).name(), &names[1999]);
+
+    let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(blocks[0].num_rows(), 1);
+    assert_eq!(blocks[0].column(1999).get(0), DataValue::Int64(1999));
+
+    Ok(())
+}
+
+/// An external index of the statistics of the files, by location.
+struct IndexStatistics(HashMap<String, FileStatistics>);
+
+impl ParquetStatisticsProvider for IndexStatistics {
+    fn file_statistics(&self, location: &str) -> Result<Option<FileStatistics>> {
+        Ok(self.0.get(location).cloned())
+    }
+}
+
+/// The statistics of a file of 10 rows whose ids are between `min` and `max`.
+fn id_range_statistics(min: i64, max: i64) -> FileStatistics {
+    let id = FileColumnStatistics {
+        min: DataValue::Int64(min),
+        max: DataValue::Int64(max),
+        null_count: 0,
+    };
+    FileStatistics {
+        num_rows: 10,
+        columns: HashMap::from([("id".to_string(), id)]),
+    }
+}
+
+#[tokio::test]
+async fn test_read_parquet_statistics_provider() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+    let operator = memory_operator()?;
+    for (name, start) in [("a", 0), ("b", 10), ("c", 20)] {
+        operator
+            .object(&format!("data/{}.parquet", name))
+            .write(int_row_groups_file(&[(start..start + 10).collect()])?)
+            .await?;
+    }
+    // Not a parquet file, its footer can't be read.
+    operator.object("data/d.parquet").write(vec![0; 64]).await?;
+
+    // The index is wrong about b, which shows that the pruning is driven by it, and has
+    // no statistics of c.
+    let index = IndexStatistics(HashMap::from([
+        ("data/a.parquet".to_string(), id_range_statistics(0, 9)),
+        ("data/b.parquet".to_string(), id_range_statistics(100, 109)),
+        ("data/d.parquet".to_string(), id_range_statistics(1000, 1009)),
+    ]));
+    let table = ParquetTableBuilder::create("system", "read_parquet", 1, vec![
+        DataValue::String(b"data/*.parquet".to_vec()),
+    ])
+    .with_operator(operator.clone())
+    .with_statistics_provider(index)
+    .build()?;
+
+    // The files are pruned by the index, before their footers are read.
+    let (rows, ids) = matched_ids(ctx.clone(), table.clone(), 5).await?;
+    assert_eq!(rows, 10);
+    assert_eq!(ids, vec![DataValue::Int64(5)]);
+    let (rows, ids) = matched_ids(ctx.clone(), table.clone(), 15).await?;
+    assert_eq!(rows, 0);
+    assert!(ids.is_empty());
+    // The files the index has no statistics of are pruned by their footers.
+    let (rows, ids) = matched_ids(ctx.clone(), table, 25).await?;
+    assert_eq!(rows, 10);
+    assert_eq!(ids, vec![DataValue::Int64(25)]);
+
+    // Without the index, the footers of all the files are read.
+    let table = build_table(&operator, &["data/*.parquet"])?;
+    assert!(table.read_plan(ctx, Some(id_equals(15))).await.is_err());
+
+    Ok(())
+}
+
+fn id_compared(op: &str, id: i64) -> Expression {
+    Expression::Function {
+        name: op.to_string(),
+        args: vec![
+            Expression::IndexedVariable {
+                name: "id".to_string(),
+                data_type: i32::to_data_type(),
+            },
+            Expression::Constant {
+                value: DataValue::Int64(id),
+                data_type: i64::to_data_type(),
+            },
+        ],
+        return_type: bool::to_data_type(),
+    }
+}
+
+#[tokio::test]
+async fn test_read_parquet_estimated_rows() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+
+    // 1000 ids sorted in 10 row groups, and 500 ids spread over 0..1000 in 5 row groups.
+    let sorted = (0..10)
+        .map(|i| (i * 100..i * 100 + 100).collect::<Vec<i32>>())
+        .collect::<Vec<_>>();
+    let spread = (0..5)
+        .map(|i| (i * 100..i * 100 + 100).map(|j| j * 37 % 1000).collect::<Vec<i32>>())
+        .collect::<Vec<_>>();
+    let operator = memory_operator()?;
+    operator
+        .object("data/sorted.parquet")
+        .write(int_row_groups_file(&sorted)?)
+        .await?;
+    operator
+        .object("data/spread.parquet")
+        .write(int_row_groups_file(&spread)?)
+        .await?;
+    let table = build_table(&operator, &["data/*.parquet"])?;
+    let ids = sorted.iter().chain(spread.iter()).flatten().copied().collect::<Vec<_>>();
+
+    // Without filters, the estimate is the exact count.
+    assert_eq!(table.estimated_rows(ctx.clone(), &None)?, 1500);
+    let push_down = PushDownInfo {
+        projection: Some(Projection::Columns(vec![0])),
+        ..Default::default()
+    };
+    assert_eq!(table.estimated_rows(ctx.clone(), &Some(push_down))?, 1500);
+
+    let cases: Vec<(Vec<Expression>, Box<dyn Fn(i32) -> bool>)> = vec![
+        (vec![id_compared("<", 250)], Box::new(|id| id < 250)),
+        (vec![id_compared(">=", 900)], Box::new(|id| id >= 900)),
+        (vec![id_compared("=", 555)], Box::new(|id| id == 555)),
+        (vec![id_compared(">", 2000)], Box::new(|_| false)),
+        (
+            vec![id_compared(">=", 200), id_compared("<", 400)],
+            Box::new(|id| (200..400).contains(&id)),
+        ),
+    ];
+    for (filters, matches) in cases {
+        let actual = ids.iter().filter(|id| matches(**id)).count();
+        let push_down = PushDownInfo {
+            filters: filters.clone(),
+            ..Default::default()
+        };
+        let estimated = table.estimated_rows(ctx.clone(), &Some(push_down))?;
+        // Within 5% of all the rows.
+        assert!(
+            estimated.abs_diff(actual) <= 75,
+            "estimated {} rows for {} matching {:?}",
+            estimated,
+            actual,
+            filters
+        );
+    }
+
+    // The limit bounds the estimate.
+    let push_down = PushDownInfo {
+        filters: vec![id_compared("<", 250)],
+        limit: Some(10),
+        ..Default::default()
+    };
+    assert_eq!(table.estimated_rows(ctx.clone(), &Some(push_down))?, 10);
+
+    Ok(())
+}
@@ -0,0 +1,883 @@
+//  Copyright 2021 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::io::Cursor;
+use std::sync::Arc;
+
+use common_arrow::parquet::compression::Compression;
+use common_arrow::parquet::compression::CompressionOptions;
+use common_arrow::parquet::write::write_metadata_sidecar;
+use common_base::base::tokio;
+use common_catalog::plan::PushDownInfo;
+use common_catalog::table::Table;
+use common_datablocks::serialize_data_blocks;
+use common_datablocks::serialize_data_blocks_with_compression;
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_meta_types::UserStageInfo;
+use common_sql::executor::table_read_plan::ToReadDataSourcePlan;
+use common_storage::StorageConfig;
+use common_storage::StorageFsConfig;
+use common_storage::StorageParams;
+use common_storages_parquet::resolve_files;
+use common_storages_parquet::testing::NonBlockingLayer;
+use common_storages_parquet::Credentials;
+use common_storages_parquet::DifferenceKind;
+use common_storages_parquet::ParquetSchemaDiffTable;
+use common_storages_parquet::ParquetTable;
+use common_storages_parquet::ParquetTableBuilder;
+use common_users::UserApiProvider;
+use databend_query::sessions::TableContext;
+use databend_query::stream::ReadDataBlockStream;
+use futures::TryStreamExt;
+use tempfile::TempDir;
+
+use super::table_test_fixture::append_footer;
+use super::table_test_fixture::build_table;
+use super::table_test_fixture::id_name_block;
+use super::table_test_fixture::int_column_file;
+use super::table_test_fixture::int_row_groups_file;
+use super::table_test_fixture::matched_ids;
+use super::table_test_fixture::memory_operator;
+use super::table_test_fixture::strip_footer;
+
+#[tokio::test]
+async fn test_read_parquet_with_operator() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+
+    let schema = DataSchemaRefExt::create(vec![
+        DataField::new("id", i32::to_data_type()),
+        DataField::new("name", Vu8::to_data_type()),
+    ]);
+    let block = DataBlock::create(schema.clone(), vec![
+        Series::from_data(vec![1i32, 2, 3]),
+        Series::from_data(vec!["a", "b", "c"]),
+    ]);
+    let mut buf = vec![];
+    serialize_data_blocks(vec![block], &schema, &mut buf)?;
+
+    // The file only exists in the injected operator.
+    let operator = memory_operator()?;
+    operator.object("data/t.parquet").write(buf).await?;
+
+    let table_args = vec![DataValue::String("data/t.parquet".as_bytes().to_vec())];
+    let table: Arc<dyn Table> =
+        ParquetTableBuilder::create("system", "read_parquet", 1, table_args)
+            .with_operator(operator)
+            .build()?;
+
+    let source_plan = table
+        .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+        .await?;
+    ctx.try_set_partitions(source_plan.parts.clone())?;
+
+    let stream = table.read_data_block_stream(ctx, &source_plan).await?;
+    let result = stream.try_collect::<Vec<_>>().await?;
+
+    let expected = vec![
+        "+----+------+",
+        "| id | name |",
+        "+----+------+",
+        "| 1  | a    |",
+        "| 2  | b    |",
+        "| 3  | c    |",
+        "+----+------+",
+    ];
+    common_datablocks::assert_blocks_sorted_eq(expected, result.as_slice());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_error_codes() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+    let operator = memory_operator()?;
+
+    // File not found.
+    let err = build_table(&operator, &["data/missing.parquet"])
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::STORAGE_NOT_FOUND);
+
+    // Not a parquet file.
+    operator
+        .object("data/text.parquet")
+        .write("this is not a parquet file")
+        .await?;
+    let err = build_table(&operator, &["data/text.parquet"])
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::PARQUET_FILE_INVALID);
+
+    // Encrypted footer.
+    let mut encrypted = b"PARE".to_vec();
+    encrypted.extend_from_slice(&[0; 16]);
+    encrypted.extend_from_slice(&16u32.to_le_bytes());
+    encrypted.extend_from_slice(b"PARE");
+    operator
+        .object("data/encrypted.parquet")
+        .write(encrypted)
+        .await?;
+    let err = build_table(&operator, &["data/encrypted.parquet"])
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::PARQUET_DECRYPTION_FAILED);
+
+    // Unsupported compression codec.
+    let (mut buf, mut meta) = int_column_file("a")?;
+    // Rewrite the footer, claiming the column chunks are compressed by LZO.
+    strip_footer(&mut buf);
+    for column in meta
+        .row_groups
+        .iter_mut()
+        .flat_map(|rg| rg.columns.iter_mut())
+    {
+        column.meta_data.as_mut().unwrap().codec = Compression::Lzo.into();
+    }
+    append_footer(&mut buf, &meta)?;
+    operator.object("data/lzo.parquet").write(buf).await?;
+    let err = build_table(&operator, &["data/lzo.parquet"]).err().unwrap();
+    assert_eq!(err.code(), ErrorCode::PARQUET_UNSUPPORTED);
+
+    // Schema mismatch, found while reading the files other than the first one.
+    operator
+        .object("data/a.parquet")
+        .write(int_column_file("a")?.0)
+        .await?;
+    operator
+        .object("data/b.parquet")
+        .write(int_column_file("b")?.0)
+        .await?;
+    let table: Arc<dyn Table> = build_table(&operator, &["data/a.parquet", "data/b.parquet"])?;
+    let err = table
+        .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+        .await
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::PARQUET_SCHEMA_MISMATCH);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_from_stage() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+
+    // An external stage rooted at a temporary directory.
+    let tmp_dir = TempDir::new().unwrap();
+    let storage = StorageParams::Fs(StorageFsConfig {
+        root: tmp_dir.path().to_str().unwrap().to_string(),
+    });
+    let stage = UserStageInfo::new_external_stage(storage, "/").with_stage_name("mystage");
+    UserApiProvider::instance()
+        .add_stage(&ctx.get_tenant(), stage, false)
+        .await?;
+
+    let data_dir = tmp_dir.path().join("data");
+    std::fs::create_dir_all(&data_dir)?;
+    std::fs::write(data_dir.join("a.parquet"), int_column_file("a")?.0)?;
+    std::fs::write(data_dir.join("b.parquet"), int_column_file("a")?.0)?;
+    std::fs::write(data_dir.join("c.txt"), "not matched")?;
+
+    let table_args = vec![DataValue::String(b"@mystage/data/*.parquet".to_vec())];
+    let table = ParquetTable::create("system", "read_parquet", 1, Some(table_args))?.as_table();
+
+    let source_plan = table
+        .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+        .await?;
+    ctx.try_set_partitions(source_plan.parts.clone())?;
+
+    let stream = table.read_data_block_stream(ctx, &source_plan).await?;
+    let result = stream.try_collect::<Vec<_>>().await?;
+
+    let expected = vec![
+        "+---+", //
+        "| a |", "+---+", "| 1 |", "| 1 |", "| 2 |", "| 2 |", "| 3 |", "| 3 |", "+---+",
+    ];
+    common_datablocks::assert_blocks_sorted_eq(expected, result.as_slice());
+
+    // Unknown stage.
+    let table_args = vec![DataValue::String(b"@unknown/data/*.parquet".to_vec())];
+    let err = ParquetTable::create("system", "read_parquet", 1, Some(table_args))
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::UNKNOWN_STAGE);
+
+    // The paths can't go up out of the stage.
+    for pattern in ["@mystage/../data/a.parquet", "@mystage/data/../../*.parquet"] {
+        let table_args = vec![DataValue::String(pattern.as_bytes().to_vec())];
+        let err = ParquetTable::create("system", "read_parquet", 1, Some(table_args))
+            .err()
+            .unwrap();
+        assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS, "{}", pattern);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_stage_without_blocking_api() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+
+    // An in-memory operator standing in for the one of a stage on s3, which has no
+    // blocking API.
+    let operator = memory_operator()?;
+    for path in ["data/a.parquet", "data/b.parquet"] {
+        operator.object(path).write(int_column_file("a")?.0).await?;
+    }
+    let operator = operator.layer(NonBlockingLayer);
+    assert!(!operator.metadata().can_blocking());
+
+    let table_args = vec![DataValue::String(b"@mystage/data/*.parquet".to_vec())];
+    let table = ParquetTableBuilder::create("system", "read_parquet", 1, table_args)
+        .with_stage_operator("mystage", operator)
+        .build()?;
+    let source_plan = table
+        .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+        .await?;
+    ctx.try_set_partitions(source_plan.parts.clone())?;
+    let stream = table.read_data_block_stream(ctx, &source_plan).await?;
+    let result = stream.try_collect::<Vec<_>>().await?;
+    let expected = vec![
+        "+---+", //
+        "| a |", "+---+", "| 1 |", "| 1 |", "| 2 |", "| 2 |", "| 3 |", "| 3 |", "+---+",
+    ];
+    common_datablocks::assert_blocks_sorted_eq(expected, result.as_slice());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_trusted_root() -> Result<()> {
+    let tmp_dir = TempDir::new().unwrap();
+    for dir in ["data", "data/sub", "data2", "other"] {
+        std::fs::create_dir_all(tmp_dir.path().join(dir))?;
+        std::fs::write(tmp_dir.path().join(dir).join("a.parquet"), int_column_file("a")?.0)?;
+    }
+    let root = tmp_dir.path().to_str().unwrap();
+    let conf = crate::tests::ConfigBuilder::create()
+        .parquet_trusted_root(format!("{}/data", root))
+        .build();
+    let (_guard, ctx) = crate::tests::create_query_context_with_config(conf, None).await?;
+    let create = |pattern: String| {
+        let table_args = vec![DataValue::String(pattern.into_bytes())];
+        ctx.get_catalog("default")?
+            .get_table_function("read_parquet", Some(table_args), ctx.as_ref())
+    };
+
+    // Without a trusted root, local files need `allow_insecure`.
+    let table_args = vec![DataValue::String(format!("{}/data/a.parquet", root).into_bytes())];
+    let err = ParquetTable::create("system", "read_parquet", 1, Some(table_args))
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::STORAGE_INSECURE);
+
+    // The sessions can narrow the trusted root of the config, but not widen it.
+    let set_session_root = |session_root: String| {
+        ctx.get_settings()
+            .set_settings("parquet_trusted_root".to_string(), session_root, false)
+    };
+    set_session_root(format!("{}/data/sub", root))?;
+    create(format!("{}/data/sub/a.parquet", root))?;
+    let err = create(format!("{}/data/a.parquet", root)).err().unwrap();
+    assert_eq!(err.code(), ErrorCode::STORAGE_INSECURE);
+    for session_root in ["/".to_string(), format!("{}/data/../other", root)] {
+        set_session_root(session_root.clone())?;
+        let err = create(format!("{}/other/a.parquet", root)).err().unwrap();
+        assert_eq!(err.code(), ErrorCode::STORAGE_INSECURE, "{}", session_root);
+        assert!(err.message().contains("storage.parquet_trusted_root"));
+    }
+    set_session_root("".to_string())?;
+
+    // Allowed in the trusted root.
+    for pattern in [
+        format!("{}/data/a.parquet", root),
+        format!("{}/data/*.parquet", root),
+    ] {
+        let table = create(pattern)?.as_table();
+        let source_plan = table
+            .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+            .await?;
+        ctx.try_set_partitions(source_plan.parts.clone())?;
+        let stream = table.read_data_block_stream(ctx.clone(), &source_plan).await?;
+        let result = stream.try_collect::<Vec<_>>().await?;
+        let expected = vec![
+            "+---+", //
+            "| a |", "+---+", "| 1 |", "| 2 |", "| 3 |", "+---+",
+        ];
+        common_datablocks::assert_blocks_sorted_eq(expected, result.as_slice());
+    }
+
+    // Denied outside of it, including the siblings sharing its prefix and the paths
+    // escaping it.
+    for pattern in [
+        format!("{}/other/a.parquet", root),
+        format!("{}/data2/a.parquet", root),
+        format!("{}/data/../other/a.parquet", root),
+        format!("{}/*/a.parquet", root),
+    ] {
+        let err = create(pattern.clone()).err().unwrap();
+        assert_eq!(err.code(), ErrorCode::STORAGE_INSECURE, "{}", pattern);
+        assert!(err.message().contains("outside the trusted root"), "{}", pattern);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_sidecar_files() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+
+    let operator = memory_operator()?;
+    let (a, mut summary) = int_column_file("id")?;
+    let (mut b, b_meta) = int_column_file("id")?;
+    operator.object("data/a.parquet").write(a).await?;
+    // The footer of `b.parquet` is broken, it can only be read from the summary.
+    let len = b.len();
+    b[len - 1] = b'X';
+    operator.object("data/b.parquet").write(b).await?;
+    operator.object("data/_SUCCESS").write(vec![]).await?;
+
+    // The `_metadata` summary holds the row groups of both files.
+    for column in summary
+        .row_groups
+        .iter_mut()
+        .flat_map(|rg| rg.columns.iter_mut())
+    {
+        column.file_path = Some("a.parquet".to_string());
+    }
+    let mut b_row_groups = b_meta.row_groups;
+    for column in b_row_groups.iter_mut().flat_map(|rg| rg.columns.iter_mut()) {
+        column.file_path = Some("b.parquet".to_string());
+    }
+    summary.num_rows += b_meta.num_rows;
+    summary.row_groups.extend(b_row_groups);
+    let mut buf = vec![];
+    write_metadata_sidecar(&mut buf, &summary)?;
+    operator.object("data/_metadata").write(buf).await?;
+
+    // `_SUCCESS` and `_metadata` are skipped by default, but the footer of `b.parquet` is read.
+    let table = build_table(&operator, &["data/*"])?;
+    let err = table
+        .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+        .await
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::PARQUET_FILE_INVALID);
+
+    let table = build_table(&operator, &["data/*", "use_metadata_file=true"])?;
+    let source_plan = table
+        .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+        .await?;
+    ctx.try_set_partitions(source_plan.parts.clone())?;
+
+    let stream = table.read_data_block_stream(ctx, &source_plan).await?;
+    let result = stream.try_collect::<Vec<_>>().await?;
+    let num_rows = result.iter().map(|b| b.num_rows()).sum::<usize>();
+    assert_eq!(num_rows, 6);
+
+    // Nothing is skipped if the list is empty, `_SUCCESS` is not a parquet file.
+    let err = build_table(&operator, &["data/_*", "sidecar_files="])
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::PARQUET_FILE_INVALID);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_resolve_files() -> Result<()> {
+    let operator = memory_operator()?;
+    let (buf, _) = int_column_file("id")?;
+    for location in ["data/a.parquet", "data/b.parquet", "data/x/c.parquet"] {
+        operator.object(location).write(buf.clone()).await?;
+    }
+    operator
+        .object("data/small.parquet")
+        .write(vec![0; 8])
+        .await?;
+    operator.object("data/_SUCCESS").write(vec![]).await?;
+
+    let args = |args: &[&str]| {
+        args.iter()
+            .map(|arg| DataValue::String(arg.as_bytes().to_vec()))
+            .collect::<Vec<_>>()
+    };
+
+    // Wildcards don't match across directories, plain paths are kept as they are.
+    let (_, files) = resolve_files(&args(&["data/*.parquet"]), Some(operator.clone()), &[])?;
+    assert_eq!(files, vec![
+        "data/a.parquet",
+        "data/b.parquet",
+        "data/small.parquet"
+    ]);
+    let (_, files) = resolve_files(
+        &args(&["data/x/c.parquet", "data/a.parquet"]),
+        Some(operator.clone()),
+        &[],
+    )?;
+    assert_eq!(files, vec!["data/x/c.parquet", "data/a.parquet"]);
+
+    // The sidecar files and the small files are skipped.
+    let (_, files) = resolve_files(
+        &args(&["data/*", "min_file_size=16"]),
+        Some(operator.clone()),
+        &[],
+    )?;
+    assert_eq!(files, vec!["data/a.parquet", "data/b.parquet"]);
+
+    let err = resolve_files(&args(&["data/*.csv"]), Some(operator), &[])
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_multiple_buckets() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+
+    // Two in-memory operators standing in for buckets of different storages.
+    let s3 = memory_operator()?;
+    let gcs = memory_operator()?;
+    for (operator, ids) in [(&s3, vec![1i32, 2]), (&gcs, vec![3i32])] {
+        let buf = int_row_groups_file(&[ids])?;
+        operator.object("data/t.parquet").write(buf).await?;
+    }
+
+    let build = |args: &[&str]| {
+        let table_args = args
+            .iter()
+            .map(|arg| DataValue::String(arg.as_bytes().to_vec()))
+            .collect();
+        ParquetTableBuilder::create("system", "read_parquet", 1, table_args)
+            .with_bucket_operator("s3://a", s3.clone())
+            .with_bucket_operator("gcs://b", gcs.clone())
+            .build()
+    };
+
+    // The files of both buckets are read, each through its own operator.
+    let table = build(&["s3://a/data/*.parquet", "gcs://b/data/t.parquet"])?;
+    let source_plan = table
+        .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+        .await?;
+    assert_eq!(source_plan.parts.len(), 2);
+    ctx.try_set_partitions(source_plan.parts.clone())?;
+    let stream = table
+        .read_data_block_stream(ctx.clone(), &source_plan)
+        .await?;
+    let result = stream.try_collect::<Vec<_>>().await?;
+    let expected = vec![
+        "+----+", //
+        "| id |",
+        "+----+",
+        "| 1  |",
+        "| 2  |",
+        "| 3  |",
+        "+----+",
+    ];
+    common_datablocks::assert_blocks_sorted_eq(expected, result.as_slice());
+
+    // The schemas of the buckets can't be merged.
+    let (buf, _) = int_column_file("name")?;
+    gcs.object("data/name.parquet").write(buf).await?;
+    let err = build(&["s3://a/data/t.parquet", "gcs://b/data/name.parquet"])
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::PARQUET_SCHEMA_MISMATCH);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_without_blocking_api() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+
+    // In-memory operators standing in for the ones of s3 and gcs, which have no blocking
+    // API. The files are listed and their footers read through the async one.
+    let operator = memory_operator()?;
+    let bucket = memory_operator()?;
+    for (operator, path, ids) in [
+        (&operator, "data/a.parquet", vec![1i32, 2]),
+        (&operator, "data/sub/b.parquet", vec![3]),
+        (&bucket, "data/c.parquet", vec![4]),
+    ] {
+        let buf = int_row_groups_file(&[ids])?;
+        operator.object(path).write(buf).await?;
+    }
+    let operator = operator.layer(NonBlockingLayer);
+    let bucket = bucket.layer(NonBlockingLayer);
+    assert!(!operator.metadata().can_blocking());
+    assert!(operator.object("data/a.parquet").blocking_metadata().is_err());
+
+    let table_args = ["data/*.parquet", "data/*/b.parquet", "s3://a/data/*.parquet"]
+        .iter()
+        .map(|arg| DataValue::String(arg.as_bytes().to_vec()))
+        .collect();
+    let table = ParquetTableBuilder::create("system", "read_parquet", 1, table_args)
+        .with_operator(operator)
+        .with_bucket_operator("s3://a", bucket)
+        .build()?;
+    let source_plan = table
+        .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+        .await?;
+    assert_eq!(source_plan.parts.len(), 3);
+    ctx.try_set_partitions(source_plan.parts.clone())?;
+    let stream = table
+        .read_data_block_stream(ctx.clone(), &source_plan)
+        .await?;
+    let result = stream.try_collect::<Vec<_>>().await?;
+    let expected = vec![
+        "+----+", //
+        "| id |",
+        "+----+",
+        "| 1  |",
+        "| 2  |",
+        "| 3  |",
+        "| 4  |",
+        "+----+",
+    ];
+    common_datablocks::assert_blocks_sorted_eq(expected, result.as_slice());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_prefix_credentials() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+
+    // In-memory operators standing in for the credentials of two prefixes of a bucket, and
+    // of the bucket itself, each seeing different files at the same paths.
+    let team_a = memory_operator()?;
+    let team_b = memory_operator()?;
+    let bucket = memory_operator()?;
+    for (operator, ids) in [(&team_a, vec![1i32, 2]), (&team_b, vec![3]), (&bucket, vec![9])] {
+        for path in ["a/t.parquet", "b/t.parquet", "c/t.parquet"] {
+            let buf = int_row_groups_file(&[ids.clone()])?;
+            operator.object(path).write(buf).await?;
+        }
+    }
+
+    let build = |args: &[&str]| {
+        let table_args = args
+            .iter()
+            .map(|arg| DataValue::String(arg.as_bytes().to_vec()))
+            .collect();
+        ParquetTableBuilder::create("system", "read_parquet", 1, table_args)
+            .with_bucket_operator("s3://data", bucket.clone())
+            .with_bucket_operator("s3://data/a/", team_a.clone())
+            .with_bucket_operator("s3://data/b/", team_b.clone())
+            .build()
+    };
+
+    // Each file is listed and read through the operator of its prefix, the others through
+    // the one of the bucket.
+    let table = build(&["s3://data/a/*.parquet", "s3://data/b/t.parquet", "s3://data/c/*"])?;
+    let source_plan = table
+        .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+        .await?;
+    assert_eq!(source_plan.parts.len(), 3);
+    ctx.try_set_partitions(source_plan.parts.clone())?;
+    let stream = table
+        .read_data_block_stream(ctx.clone(), &source_plan)
+        .await?;
+    let result = stream.try_collect::<Vec<_>>().await?;
+    let expected = vec![
+        "+----+", //
+        "| id |",
+        "+----+",
+        "| 1  |",
+        "| 2  |",
+        "| 3  |",
+        "| 9  |",
+        "+----+",
+    ];
+    common_datablocks::assert_blocks_sorted_eq(expected, result.as_slice());
+
+    // The credentials are not taken from the table args, which are kept in the plans.
+    let err = build(&[
+        "s3://data/a/t.parquet",
+        "credentials=s3://data/a/:secret_access_key=TopSecretValue",
+    ])
+    .err()
+    .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+    assert!(!err.message().contains("TopSecretValue"));
+
+    // The secrets of the config are left out of its errors and debug output.
+    for value in [
+        "data/a/:secret_access_key=TopSecretValue",
+        "s3://data/a/:TopSecretValue",
+    ] {
+        let err = Credentials::parse(value).err().unwrap();
+        assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+        assert!(!err.message().contains("TopSecretValue"));
+    }
+    let value = "s3://data/a/:access_key_id=a,secret_access_key=TopSecretValue";
+    let credentials = Credentials::parse(value)?;
+    assert!(!format!("{:?}", credentials).contains("TopSecretValue"));
+    let config = StorageConfig {
+        parquet_credentials: value.to_string(),
+        ..Default::default()
+    };
+    assert!(!format!("{:?}", config).contains("TopSecretValue"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_prefix_without_blocking_api() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+
+    // In-memory operators standing in for the ones built for the credentials of the
+    // prefixes on s3, which have no blocking API. The sizes, the modification times and
+    // the summaries of the files are read through the async one.
+    let team_a = memory_operator()?;
+    let bucket = memory_operator()?;
+    for (operator, path, ids) in [
+        (&team_a, "a/t.parquet", vec![1i32, 2]),
+        (&team_a, "a/u.parquet", vec![3]),
+        (&bucket, "c/t.parquet", vec![9]),
+    ] {
+        let buf = int_row_groups_file(&[ids])?;
+        operator.object(path).write(buf).await?;
+    }
+    team_a.object("a/empty.parquet").write(vec![]).await?;
+    let team_a = team_a.layer(NonBlockingLayer);
+    assert!(!team_a.metadata().can_blocking());
+
+    let table_args = [
+        "s3://data/a/*.parquet",
+        "s3://data/c/t.parquet",
+        "min_file_size=1",
+        "file_order=mtime",
+        "use_metadata_file=true",
+    ]
+    .iter()
+    .map(|arg| DataValue::String(arg.as_bytes().to_vec()))
+    .collect();
+    let table = ParquetTableBuilder::create("system", "read_parquet", 1, table_args)
+        .with_bucket_operator("s3://data", bucket)
+        .with_bucket_operator("s3://data/a/", team_a)
+        .build()?;
+    let source_plan = table
+        .read_plan(ctx.clone(), Some(PushDownInfo::default()))
+        .await?;
+    assert_eq!(source_plan.parts.len(), 3);
+    ctx.try_set_partitions(source_plan.parts.clone())?;
+    let stream = table
+        .read_data_block_stream(ctx.clone(), &source_plan)
+        .await?;
+    let result = stream.try_collect::<Vec<_>>().await?;
+    let expected = vec![
+        "+----+", //
+        "| id |",
+        "+----+",
+        "| 1  |",
+        "| 2  |",
+        "| 3  |",
+        "| 9  |",
+        "+----+",
+    ];
+    common_datablocks::assert_blocks_sorted_eq(expected, result.as_slice());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_parquet_schema_diff_config() -> Result<()> {
+    let tmp_dir = TempDir::new().unwrap();
+    for (dir, name) in [("data", "a"), ("data", "b"), ("other", "a")] {
+        std::fs::create_dir_all(tmp_dir.path().join(dir))?;
+        let path = tmp_dir.path().join(dir).join(format!("{}.parquet", name));
+        std::fs::write(path, int_column_file(name)?.0)?;
+    }
+    let root = tmp_dir.path().to_str().unwrap();
+    let conf = crate::tests::ConfigBuilder::create()
+        .parquet_trusted_root(format!("{}/data", root))
+        .build();
+    let (_guard, ctx) = crate::tests::create_query_context_with_config(conf, None).await?;
+    let args = |patterns: &[String]| {
+        patterns
+            .iter()
+            .map(|pattern| DataValue::String(pattern.as_bytes().to_vec()))
+            .collect::<Vec<_>>()
+    };
+
+    // Both files are read trusting the root of the config.
+    let files = [
+        format!("{}/data/a.parquet", root),
+        format!("{}/data/b.parquet", root),
+    ];
+    let table = ctx
+        .get_catalog("default")?
+        .get_table_function("parquet_schema_diff", Some(args(&files)), ctx.as_ref())?;
+    let table = table
+        .as_any()
+        .downcast_ref::<ParquetSchemaDiffTable>()
+        .unwrap();
+    let kinds = table
+        .differences()
+        .iter()
+        .map(|difference| (difference.column.as_str(), difference.kind))
+        .collect::<Vec<_>>();
+    assert_eq!(kinds, vec![
+        ("a", DifferenceKind::OnlyInA),
+        ("b", DifferenceKind::OnlyInB),
+    ]);
+
+    // Not without it, nor outside of it.
+    let err = ParquetSchemaDiffTable::create("system", "parquet_schema_diff", 1, Some(args(&files)))
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::STORAGE_INSECURE);
+    let files = [
+        format!("{}/data/a.parquet", root),
+        format!("{}/other/a.parquet", root),
+    ];
+    let err = ctx
+        .get_catalog("default")?
+        .get_table_function("parquet_schema_diff", Some(args(&files)), ctx.as_ref())
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::STORAGE_INSECURE);
+
+    // The files under the prefixes of the credentials of the config are read with them,
+    // their secrets are left out of the errors.
+    let files = [
+        "s3://data/a/t.parquet".to_string(),
+        "s3://data/a/u.parquet".to_string(),
+    ];
+    let config = StorageConfig {
+        parquet_credentials: "data/a/:secret_access_key=TopSecretValue".to_string(),
+        ..Default::default()
+    };
+    let err = ParquetSchemaDiffTable::create_with_config(
+        "system",
+        "parquet_schema_diff",
+        1,
+        Some(args(&files)),
+        &config,
+    )
+    .err()
+    .unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+    assert!(!err.message().contains("TopSecretValue"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_partition_values() -> Result<()> {
+    let operator = memory_operator()?;
+    operator
+        .object("data/us/a.parquet")
+        .write(int_row_groups_file(&[vec![1, 2]])?)
+        .await?;
+    operator
+        .object("data/eu/b.parquet")
+        .write(int_row_groups_file(&[vec![3]])?)
+        .await?;
+
+    let table = build_table(&operator, &[
+        "data/*/*.parquet",
+        "partition_values=data/us/*:region=us,year=2022;data/eu/*:Region=eu,year=2023",
+    ])?;
+    let schema = table.schema();
+    let names = schema
+        .fields()
+        .iter()
+        .map(|field| field.name().as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["id", "region", "year"]);
+
+    let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+    let expected = vec![
+        "+----+--------+------+",
+        "| id | region | year |",
+        "+----+--------+------+",
+        "| 1  | us     | 2022 |",
+        "| 2  | us     | 2022 |",
+        "| 3  | eu     | 2023 |",
+        "+----+--------+------+",
+    ];
+    common_datablocks::assert_blocks_sorted_eq(expected, blocks.as_slice());
+
+    // Every file must have partition values, and the same partition columns.
+    let cases = [
+        "partition_values=data/us/*:region=us",
+        "partition_values=data/us/*:region=us;data/eu/*:year=2023",
+        "partition_values=region=us",
+    ];
+    for option in cases {
+        let err = build_table(&operator, &["data/*/*.parquet", option])
+            .err()
+            .unwrap();
+        assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS, "{}", option);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_parquet_stdin() -> Result<()> {
+    let (_guard, ctx) = crate::tests::create_query_context().await?;
+    let block = id_name_block(vec![1, 2, 3], Some(vec!["a", "b", "c"]));
+    let mut buf = vec![];
+    serialize_data_blocks_with_compression(
+        vec![block.clone()],
+        block.schema(),
+        &mut buf,
+        CompressionOptions::Uncompressed,
+    )?;
+    let stdin_table = |args: &[&str]| {
+        let table_args = args
+            .iter()
+            .map(|arg| DataValue::String(arg.as_bytes().to_vec()))
+            .collect();
+        ParquetTableBuilder::create("system", "read_parquet", 1, table_args)
+            .with_operator(memory_operator()?)
+            .with_stdin(Cursor::new(buf.clone()))
+            .build()
+    };
+
+    // The file piped through the standard input is read like any other.
+    let table = stdin_table(&["-"])?;
+    let blocks = table.read_blocks()?.collect::<Result<Vec<_>>>()?;
+    let expected = vec![
+        "+----+------+",
+        "| id | name |",
+        "+----+------+",
+        "| 1  | a    |",
+        "| 2  | b    |",
+        "| 3  | c    |",
+        "+----+------+",
+    ];
+    common_datablocks::assert_blocks_sorted_eq(expected, blocks.as_slice());
+    let (_, ids) = matched_ids(ctx, table, 2).await?;
+    assert_eq!(ids, vec![DataValue::Int64(2)]);
+
+    // It is buffered up to `max_stdin_size` bytes.
+    let max_stdin_size = format!("max_stdin_size={}", buf.len());
+    assert!(stdin_table(&["-", &max_stdin_size]).is_ok());
+    let max_stdin_size = format!("max_stdin_size={}", buf.len() - 1);
+    let err = stdin_table(&["-", &max_stdin_size]).err().unwrap();
+    assert_eq!(err.code(), ErrorCode::BAD_ARGUMENTS);
+    assert!(err.message().contains("max_stdin_size"), "{}", err.message());
+
+    Ok(())
+}
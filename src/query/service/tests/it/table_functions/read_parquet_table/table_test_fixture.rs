@@ -0,0 +1,233 @@
+//  Copyright 2021 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::sync::Arc;
+
+use common_arrow::arrow::array::Array;
+use common_arrow::arrow::array::PrimitiveArray;
+use common_arrow::arrow::array::StructArray;
+use common_arrow::arrow::chunk::Chunk;
+use common_arrow::arrow::datatypes::DataType as ArrowType;
+use common_arrow::arrow::datatypes::Field as ArrowField;
+use common_arrow::arrow::datatypes::Schema as ArrowSchema;
+use common_arrow::arrow::io::parquet::write::transverse;
+use common_arrow::arrow::io::parquet::write::RowGroupIterator;
+use common_arrow::arrow::io::parquet::write::WriteOptions;
+use common_arrow::parquet::compression::CompressionOptions;
+use common_arrow::parquet::encoding::Encoding;
+use common_arrow::parquet::metadata::ThriftFileMetaData;
+use common_arrow::parquet::write::write_metadata_sidecar;
+use common_arrow::parquet::write::Version;
+use common_arrow::parquet::write::WriteOptions as FileWriteOptions;
+use common_arrow::write_parquet_file;
+use common_catalog::plan::Expression;
+use common_catalog::plan::PushDownInfo;
+use common_datablocks::serialize_data_blocks;
+use common_datablocks::serialize_data_blocks_with_compression;
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::Result;
+use common_sql::executor::table_read_plan::ToReadDataSourcePlan;
+use common_storages_parquet::ParquetTable;
+use common_storages_parquet::ParquetTableBuilder;
+use databend_query::sessions::QueryContext;
+use databend_query::sessions::TableContext;
+use databend_query::stream::ReadDataBlockStream;
+use futures::TryStreamExt;
+use opendal::Operator;
+
+pub fn memory_operator() -> Result<Operator> {
+    Ok(Operator::new(
+        opendal::services::memory::Builder::default().build()?,
+    ))
+}
+
+pub fn build_table(operator: &Operator, locations: &[&str]) -> Result<Arc<ParquetTable>> {
+    let table_args = locations
+        .iter()
+        .map(|location| DataValue::String(location.as_bytes().to_vec()))
+        .collect();
+    ParquetTableBuilder::create("system", "read_parquet", 1, table_args)
+        .with_operator(operator.clone())
+        .build()
+}
+
+/// A parquet file of the chunks `chunks` of `fields`, one row group per chunk, with statistics
+/// and PLAIN pages, and its footer.
+pub fn chunks_file(
+    fields: Vec<ArrowField>,
+    chunks: Vec<Chunk<Box<dyn Array>>>,
+) -> Result<(Vec<u8>, ThriftFileMetaData)> {
+    let encodings = fields
+        .iter()
+        .map(|f| transverse(f.data_type(), |_| Encoding::Plain))
+        .collect();
+    let arrow_schema = ArrowSchema::from(fields);
+    let options = WriteOptions {
+        write_statistics: true,
+        compression: CompressionOptions::Uncompressed,
+        version: Version::V2,
+    };
+    let row_groups =
+        RowGroupIterator::try_new(chunks.into_iter().map(Ok), &arrow_schema, options, encodings)?;
+    let file_options = FileWriteOptions {
+        write_statistics: true,
+        version: Version::V2,
+    };
+    let mut buf = vec![];
+    let (_, meta) = write_parquet_file(&mut buf, row_groups, arrow_schema, file_options)?;
+    Ok((buf, meta))
+}
+
+/// Truncates the footer of the parquet file `buf`.
+pub fn strip_footer(buf: &mut Vec<u8>) {
+    let meta_len = u32::from_le_bytes(buf[buf.len() - 8..buf.len() - 4].try_into().unwrap());
+    buf.truncate(buf.len() - 8 - meta_len as usize);
+}
+
+/// Appends `meta` to `buf` as its footer.
+pub fn append_footer(buf: &mut Vec<u8>, meta: &ThriftFileMetaData) -> Result<()> {
+    let mut footer = vec![];
+    write_metadata_sidecar(&mut footer, meta)?;
+    buf.extend_from_slice(&footer[4..]);
+    Ok(())
+}
+
+/// A parquet file with one int column `name`, and its footer.
+pub fn int_column_file(name: &str) -> Result<(Vec<u8>, ThriftFileMetaData)> {
+    let schema = DataSchemaRefExt::create(vec![DataField::new(name, i32::to_data_type())]);
+    let block = DataBlock::create(schema.clone(), vec![Series::from_data(vec![1i32, 2, 3])]);
+    let mut buf = vec![];
+    let (_, meta) = serialize_data_blocks_with_compression(
+        vec![block],
+        &schema,
+        &mut buf,
+        CompressionOptions::Uncompressed,
+    )?;
+    Ok((buf, meta))
+}
+
+/// A parquet file with statistics and one row group per slice of `row_groups`.
+pub fn int_row_groups_file(row_groups: &[Vec<i32>]) -> Result<Vec<u8>> {
+    let schema = DataSchemaRefExt::create(vec![DataField::new("id", i32::to_data_type())]);
+    let columns = row_groups
+        .iter()
+        .map(|ids| Series::from_data(ids.clone()))
+        .collect();
+    row_groups_file(schema, columns)
+}
+
+/// A parquet file of the column of `schema`, with statistics and one row group per
+/// column of `row_groups`.
+pub fn row_groups_file(schema: DataSchemaRef, row_groups: Vec<ColumnRef>) -> Result<Vec<u8>> {
+    let chunks = row_groups
+        .into_iter()
+        .map(|column| Chunk::try_from(DataBlock::create(schema.clone(), vec![column])))
+        .collect::<Result<Vec<_>>>()?;
+    let (buf, _) = chunks_file(schema.to_arrow().fields, chunks)?;
+    Ok(buf)
+}
+
+pub fn id_equals(id: i64) -> PushDownInfo {
+    PushDownInfo {
+        filters: vec![Expression::Function {
+            name: "=".to_string(),
+            args: vec![
+                Expression::IndexedVariable {
+                    name: "id".to_string(),
+                    data_type: i32::to_data_type(),
+                },
+                Expression::Constant {
+                    value: DataValue::Int64(id),
+                    data_type: i64::to_data_type(),
+                },
+            ],
+            return_type: bool::to_data_type(),
+        }],
+        ..Default::default()
+    }
+}
+
+/// The read rows and the ids equal to `id` in the blocks read by `id = <id>` from `table`.
+pub async fn matched_ids(
+    ctx: Arc<QueryContext>,
+    table: Arc<ParquetTable>,
+    id: i64,
+) -> Result<(usize, Vec<DataValue>)> {
+    let source_plan = table.read_plan(ctx.clone(), Some(id_equals(id))).await?;
+    ctx.try_set_partitions(source_plan.parts.clone())?;
+    let stream = table.read_data_block_stream(ctx, &source_plan).await?;
+    let blocks = stream.try_collect::<Vec<_>>().await?;
+    let ids = blocks
+        .iter()
+        .flat_map(|block| {
+            let column = block.column(0);
+            (0..column.len()).map(|i| column.get(i)).collect::<Vec<_>>()
+        })
+        .filter(|value| value == &DataValue::Int64(id))
+        .collect();
+    Ok((source_plan.statistics.read_rows, ids))
+}
+
+/// A parquet file of one row with the columns of `fields`, all holding `1`.
+pub fn int_fields_file(fields: Vec<ArrowField>) -> Result<Vec<u8>> {
+    fn ones(data_type: &ArrowType) -> Box<dyn Array> {
+        match data_type {
+            ArrowType::Struct(fields) => StructArray::new(
+                data_type.clone(),
+                fields.iter().map(|f| ones(f.data_type())).collect(),
+                None,
+            )
+            .boxed(),
+            ArrowType::Int64 => PrimitiveArray::<i64>::from_vec(vec![1]).boxed(),
+            _ => PrimitiveArray::<i32>::from_vec(vec![1]).boxed(),
+        }
+    }
+
+    let chunk = Chunk::new(fields.iter().map(|f| ones(f.data_type())).collect());
+    let (buf, _) = chunks_file(fields, vec![chunk])?;
+    Ok(buf)
+}
+
+/// A parquet file with the nullable int columns `columns`.
+pub fn nullable_int_columns_file(columns: &[(&str, Vec<Option<i32>>)]) -> Result<Vec<u8>> {
+    let fields = columns
+        .iter()
+        .map(|(name, _)| DataField::new_nullable(name, i32::to_data_type()))
+        .collect();
+    let schema = DataSchemaRefExt::create(fields);
+    let block = DataBlock::create(
+        schema.clone(),
+        columns
+            .iter()
+            .map(|(_, values)| Series::from_data(values.clone()))
+            .collect(),
+    );
+    let mut buf = vec![];
+    serialize_data_blocks(vec![block], &schema, &mut buf)?;
+    Ok(buf)
+}
+
+/// A block of the column `id`, and of the column `name` if `names` is some.
+pub fn id_name_block(ids: Vec<i32>, names: Option<Vec<&str>>) -> DataBlock {
+    let mut fields = vec![DataField::new_nullable("id", i32::to_data_type())];
+    let mut columns = vec![Series::from_data(ids.into_iter().map(Some).collect::<Vec<_>>())];
+    if let Some(names) = names {
+        fields.push(DataField::new_nullable("name", Vu8::to_data_type()));
+        columns.push(Series::from_data(
+            names.into_iter().map(Some).collect::<Vec<_>>(),
+        ));
+    }
+    DataBlock::create(DataSchemaRefExt::create(fields), columns)
+}
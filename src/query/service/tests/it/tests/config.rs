@@ -128,6 +128,16 @@ impl ConfigBuilder {
         self
     }
 
+    pub fn parquet_trusted_root(mut self, value: impl Into<String>) -> ConfigBuilder {
+        self.conf.storage.parquet_trusted_root = value.into();
+        self
+    }
+
+    pub fn parquet_credentials(mut self, value: impl Into<String>) -> ConfigBuilder {
+        self.conf.storage.parquet_credentials = value.into();
+        self
+    }
+
     pub fn build(self) -> Config {
         self.conf
     }
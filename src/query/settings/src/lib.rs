@@ -473,6 +473,16 @@ impl Settings {
                 desc: "How many hours will the COPY file metadata expired in the metasrv, default value: 24*7=7days",
                 possible_values: None,
             },
+            SettingValue {
+                default_value: UserSettingValue::String("".to_owned()),
+                user_setting: UserSetting::create(
+                    "parquet_trusted_root",
+                    UserSettingValue::String("".to_owned()),
+                ),
+                level: ScopeLevel::Session,
+                desc: "The directory under storage.parquet_trusted_root read_parquet trusts, default value is \"\": all of it.",
+                possible_values: None,
+            },
         ];
 
         let settings: Arc<DashMap<String, SettingValue>> = Arc::new(DashMap::default());
@@ -767,6 +777,12 @@ impl Settings {
         self.try_get_u64(key)
     }
 
+    pub fn get_parquet_trusted_root(&self) -> Result<String> {
+        let key = "parquet_trusted_root";
+        self.check_and_get_setting_value(key)
+            .and_then(|v| v.user_setting.value.as_string())
+    }
+
     pub fn has_setting(&self, key: &str) -> bool {
         self.settings.get(key).is_some()
     }
@@ -247,6 +247,7 @@ impl<'a> Binder {
                     .get_table_function(
                         &normalize_identifier(name, &self.name_resolution_ctx).name,
                         table_args,
+                        self.ctx.as_ref(),
                     )?;
                 let table = table_meta.as_table();
                 let table_alias_name = if let Some(table_alias) = alias {
@@ -1879,6 +1879,8 @@ impl<'a> TypeChecker<'a> {
             };
 
             let accessor = accessors.pop().unwrap();
+            // Names of identifier keys are also matched after normalization, like columns.
+            let mut normalized_name = None;
             let accessor_lit = match accessor {
                 MapAccessor::Bracket {
                     key:
@@ -1888,6 +1890,8 @@ impl<'a> TypeChecker<'a> {
                         },
                 } => lit,
                 MapAccessor::Period { key } | MapAccessor::Colon { key } => {
+                    normalized_name =
+                        Some(normalize_identifier(&key, self.name_resolution_ctx).name);
                     Literal::String(key.name.clone())
                 }
                 MapAccessor::PeriodNumber { key } => Literal::Integer(key),
@@ -1913,20 +1917,25 @@ impl<'a> TypeChecker<'a> {
                     names.push(inner_name.clone());
                     data_types.push(inner_type.clone());
                 }
-                Literal::String(name) => match inner_names.iter().position(|k| k == &name) {
-                    Some(idx) => {
-                        let inner_name = inner_names.get(idx).unwrap();
-                        let inner_type = inner_types.get(idx).unwrap();
-                        names.push(inner_name.clone());
-                        data_types.push(inner_type.clone());
-                    }
-                    None => {
-                        return Err(ErrorCode::SemanticError(format!(
-                            "tuple name `{}` does not exist, available names are: {:?}",
-                            name, &inner_names
-                        )));
+                Literal::String(name) => {
+                    match inner_names.iter().position(|k| k == &name).or_else(|| {
+                        let normalized_name = normalized_name.as_ref()?;
+                        inner_names.iter().position(|k| k == normalized_name)
+                    }) {
+                        Some(idx) => {
+                            let inner_name = inner_names.get(idx).unwrap();
+                            let inner_type = inner_types.get(idx).unwrap();
+                            names.push(inner_name.clone());
+                            data_types.push(inner_type.clone());
+                        }
+                        None => {
+                            return Err(ErrorCode::SemanticError(format!(
+                                "tuple name `{}` does not exist, available names are: {:?}",
+                                name, &inner_names
+                            )));
+                        }
                     }
-                },
+                }
                 _ => unreachable!(),
             }
         }
@@ -21,6 +21,7 @@ use common_catalog::catalog::StorageDescription;
 use common_catalog::database::Database;
 use common_catalog::table::Table;
 use common_catalog::table_args::TableArgs;
+use common_catalog::table_context::TableContext;
 use common_catalog::table_function::TableFunction;
 use common_exception::ErrorCode;
 use common_exception::Result;
@@ -406,6 +407,7 @@ impl Catalog for HiveCatalog {
         &self,
         _func_name: &str,
         _tbl_args: TableArgs,
+        _ctx: &dyn TableContext,
     ) -> Result<Arc<dyn TableFunction>> {
         unimplemented!()
     }
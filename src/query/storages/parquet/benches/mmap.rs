@@ -0,0 +1,110 @@
+//  Copyright 2022 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+#[macro_use]
+extern crate criterion;
+
+use std::path::Path;
+
+use common_arrow::arrow::array::PrimitiveArray;
+use common_arrow::arrow::array::Utf8Array;
+use common_arrow::arrow::chunk::Chunk;
+use common_arrow::arrow::datatypes::DataType as ArrowType;
+use common_arrow::arrow::datatypes::Field as ArrowField;
+use common_arrow::arrow::datatypes::Schema as ArrowSchema;
+use common_arrow::arrow::io::parquet::write::RowGroupIterator;
+use common_arrow::arrow::io::parquet::write::WriteOptions;
+use common_arrow::parquet::compression::CompressionOptions;
+use common_arrow::parquet::encoding::Encoding;
+use common_arrow::parquet::write::Version;
+use common_arrow::parquet::write::WriteOptions as FileWriteOptions;
+use common_arrow::write_parquet_file;
+use common_datavalues::DataValue;
+use common_storages_parquet::ParquetTableBuilder;
+use criterion::Criterion;
+use tempfile::TempDir;
+
+const ROW_GROUPS: usize = 16;
+const ROWS_PER_ROW_GROUP: usize = 256 * 1024;
+
+/// Benchmark scanning a large local file by reading its column chunks one by one, and by
+/// mapping it in memory with the option `mmap`.
+fn bench_mmap(c: &mut Criterion) {
+    let tmp_dir = TempDir::new().unwrap();
+    let path = tmp_dir.path().join("large.parquet");
+    write_large_file(&path);
+    let location = path.to_str().unwrap().to_string();
+
+    let mut group = c.benchmark_group("parquet_local_read");
+    group.sample_size(10);
+    for (name, mmap) in [("buffered", "mmap=false"), ("mmap", "mmap=true")] {
+        group.bench_function(name, |b| b.iter(|| scan(&location, mmap)));
+    }
+    group.finish();
+}
+
+/// The number of rows read from the file at `location`.
+fn scan(location: &str, mmap: &str) -> usize {
+    let table_args = vec![
+        DataValue::String(location.as_bytes().to_vec()),
+        DataValue::String(mmap.as_bytes().to_vec()),
+    ];
+    let table = ParquetTableBuilder::create("system", "read_parquet", 1, table_args)
+        .build()
+        .unwrap();
+    table
+        .read_blocks()
+        .unwrap()
+        .map(|block| block.unwrap().num_rows())
+        .sum()
+}
+
+/// Write a file of an INT64 column and a STRING column, uncompressed.
+fn write_large_file(path: &Path) {
+    let arrow_schema = ArrowSchema::from(vec![
+        ArrowField::new("id", ArrowType::Int64, false),
+        ArrowField::new("name", ArrowType::Utf8, false),
+    ]);
+    let chunks = (0..ROW_GROUPS).map(|row_group| {
+        let first = (row_group * ROWS_PER_ROW_GROUP) as i64;
+        let ids = (first..first + ROWS_PER_ROW_GROUP as i64).collect::<Vec<_>>();
+        let names = ids.iter().map(|id| format!("name_{}", id));
+        Ok(Chunk::new(vec![
+            PrimitiveArray::<i64>::from_vec(ids).boxed(),
+            Utf8Array::<i32>::from_iter_values(names).boxed(),
+        ]))
+    });
+    let options = WriteOptions {
+        write_statistics: true,
+        compression: CompressionOptions::Uncompressed,
+        version: Version::V2,
+    };
+    let row_groups = RowGroupIterator::try_new(
+        chunks,
+        &arrow_schema,
+        options,
+        vec![vec![Encoding::Plain], vec![Encoding::Plain]],
+    )
+    .unwrap();
+    let file_options = FileWriteOptions {
+        write_statistics: true,
+        version: Version::V2,
+    };
+    let mut buf = vec![];
+    write_parquet_file(&mut buf, row_groups, arrow_schema, file_options).unwrap();
+    std::fs::write(path, buf).unwrap();
+}
+
+criterion_group!(benches, bench_mmap);
+criterion_main!(benches);
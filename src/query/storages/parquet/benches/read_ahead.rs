@@ -0,0 +1,78 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[macro_use]
+extern crate criterion;
+
+use std::time::Duration;
+use std::time::Instant;
+
+use common_base::base::tokio;
+use common_storages_parquet::ReadAheadWindow;
+use criterion::BenchmarkId;
+use criterion::Criterion;
+
+const ROW_GROUPS: usize = 32;
+
+/// Benchmark reading row groups ahead by a fixed or an adaptive window, with the reads
+/// simulated by sleeping for the latency of the storage and the decoding by blocking for
+/// a fixed time per row group, like `ParquetTableSource` does.
+fn bench_read_ahead(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap();
+    let decode = Duration::from_millis(1);
+
+    let mut group = c.benchmark_group("parquet_read_ahead");
+    group.sample_size(10);
+    for latency_ms in [1, 10, 50] {
+        let latency = Duration::from_millis(latency_ms);
+        let windows = [
+            ("fixed_1", ReadAheadWindow::create(1, 1)),
+            ("fixed_4", ReadAheadWindow::create(4, 4)),
+            ("adaptive_1_to_16", ReadAheadWindow::create(1, 16)),
+        ];
+        for (name, window) in windows {
+            group.bench_with_input(
+                BenchmarkId::new(name, format!("{}ms", latency_ms)),
+                &window,
+                |b, window| b.iter(|| runtime.block_on(scan(*window, latency, decode))),
+            );
+        }
+    }
+    group.finish();
+}
+
+async fn scan(mut window: ReadAheadWindow, latency: Duration, decode: Duration) {
+    let mut remaining = ROW_GROUPS;
+    let mut decode_time = Duration::ZERO;
+    while remaining > 0 {
+        let size = window.size().min(remaining);
+        let start = Instant::now();
+        let reads = (0..size).map(|_| tokio::time::sleep(latency));
+        futures::future::join_all(reads).await;
+        window.observe(start.elapsed(), decode_time);
+
+        let start = Instant::now();
+        for _ in 0..size {
+            std::thread::sleep(decode);
+        }
+        decode_time = start.elapsed();
+        remaining -= size;
+    }
+}
+
+criterion_group!(benches, bench_read_ahead);
+criterion_main!(benches);
@@ -0,0 +1,83 @@
+//  Copyright 2022 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::collections::VecDeque;
+
+use common_datablocks::DataBlock;
+use common_exception::Result;
+
+/// The blocks of a [`crate::ParquetTableSource`] re-chunked into blocks of `batch_size`
+/// rows, whatever the row groups and the parts they are decoded from.
+///
+/// The rows are kept in order, only the last block of the source may have fewer rows.
+pub struct BlockBatcher {
+    batch_size: usize,
+    blocks: VecDeque<DataBlock>,
+    num_rows: usize,
+}
+
+impl BlockBatcher {
+    pub fn create(batch_size: usize) -> BlockBatcher {
+        BlockBatcher {
+            batch_size: batch_size.max(1),
+            blocks: VecDeque::new(),
+            num_rows: 0,
+        }
+    }
+
+    pub fn push(&mut self, block: DataBlock) {
+        if block.num_rows() > 0 {
+            self.num_rows += block.num_rows();
+            self.blocks.push_back(block);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.num_rows == 0
+    }
+
+    /// The next block of `batch_size` rows, or of the rows left if `flush` is set and
+    /// there are fewer.
+    pub fn next_batch(&mut self, flush: bool) -> Result<Option<DataBlock>> {
+        if self.num_rows == 0 || (self.num_rows < self.batch_size && !flush) {
+            return Ok(None);
+        }
+
+        let num_rows = self.batch_size.min(self.num_rows);
+        let mut batch = vec![];
+        let mut missing = num_rows;
+        while missing > 0 {
+            let block = match self.blocks.pop_front() {
+                Some(block) => block,
+                None => break,
+            };
+            if block.num_rows() <= missing {
+                missing -= block.num_rows();
+                batch.push(block);
+            } else {
+                batch.push(block.slice(0, missing));
+                let rest = block.slice(missing, block.num_rows() - missing);
+                self.blocks.push_front(rest);
+                missing = 0;
+            }
+        }
+        self.num_rows -= num_rows - missing;
+
+        if batch.len() == 1 {
+            Ok(batch.pop())
+        } else {
+            Ok(Some(DataBlock::concat_blocks(&batch)?))
+        }
+    }
+}
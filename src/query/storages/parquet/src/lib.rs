@@ -14,14 +14,62 @@
 
 #![deny(unused_crate_dependencies)]
 
+mod block_batch;
 mod parquet_column;
 mod parquet_part;
 mod parquet_reader;
 mod parquet_source;
+mod read_ahead;
+mod scan_progress;
 mod table_function;
+mod warnings;
+#[cfg(feature = "testing")]
+pub mod testing;
 
+pub use parquet_column::is_local_timestamp;
+pub use parquet_column::DecimalEncoding;
+pub use parquet_column::DecimalStorage;
 pub use parquet_column::ParquetColumnMeta;
+pub use parquet_column::TimestampUnit;
 pub use parquet_part::ParquetPartInfo;
+pub use parquet_reader::DecimalCast;
+pub use parquet_reader::DecimalRounding;
+pub use parquet_reader::MemoryBudget;
 pub use parquet_reader::ParquetReader;
+pub use parquet_source::ParquetSourceOptions;
 pub use parquet_source::ParquetTableSource;
+pub use read_ahead::ReadAheadWindow;
+pub use scan_progress::ParquetScanProgress;
+pub use scan_progress::ParquetScanProgressValues;
+pub use table_function::column_bloom_filters;
+pub use table_function::file_features;
+pub use table_function::narrow_trusted_root;
+pub use table_function::resolve_files;
+pub use table_function::schema_diff;
+pub use table_function::schema_incompatibility;
+pub use table_function::BloomFilterParams;
+pub use table_function::ColumnBloomFilter;
+pub use table_function::ColumnDifference;
+pub use table_function::CompletedPart;
+pub use table_function::Credentials;
+pub use table_function::DifferenceKind;
+pub use table_function::FeatureKind;
+pub use table_function::FileColumnStatistics;
+pub use table_function::FileOperators;
+pub use table_function::FileStatistics;
+pub use table_function::OpenFile;
+pub use table_function::ParquetBlocks;
+pub use table_function::ParquetChunks;
+pub use table_function::ParquetFeature;
+pub use table_function::ParquetFeaturesTable;
+pub use table_function::ParquetScanCursor;
+pub use table_function::ParquetSchemaDiffTable;
+pub use table_function::ParquetSchemasCompatibleTable;
+pub use table_function::ParquetStatisticsProvider;
 pub use table_function::ParquetTable;
+pub use table_function::ParquetTableBuilder;
+pub use table_function::RequestPermit;
+pub use table_function::ROW_GROUP_INDEX_COLUMN;
+pub use warnings::ParquetWarning;
+pub use warnings::ParquetWarnings;
+pub use warnings::WarningKind;
@@ -13,6 +13,11 @@
 // limitations under the License.
 
 use common_arrow::parquet::compression::Compression as ParquetCompression;
+use common_arrow::parquet::schema::types::PhysicalType;
+use common_arrow::parquet::schema::types::PrimitiveConvertedType;
+use common_arrow::parquet::schema::types::PrimitiveLogicalType;
+use common_arrow::parquet::schema::types::PrimitiveType;
+use common_arrow::parquet::schema::types::TimeUnit as ParquetTimeUnit;
 
 #[derive(Debug, Eq, PartialEq, Hash, Clone, Copy, serde::Deserialize, serde::Serialize)]
 pub enum Compression {
@@ -56,27 +61,145 @@ impl From<ParquetCompression> for Compression {
     }
 }
 
-#[derive(serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+/// The unit of the values of a TIMESTAMP column chunk.
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Copy, serde::Deserialize, serde::Serialize)]
+pub enum TimestampUnit {
+    Milliseconds,
+    Microseconds,
+    Nanoseconds,
+}
+
+impl TimestampUnit {
+    /// The unit of the TIMESTAMP column of `primitive_type`, from its logical type, or its
+    /// converted type for files written before the logical types.
+    pub fn of(primitive_type: &PrimitiveType) -> Option<TimestampUnit> {
+        match (&primitive_type.logical_type, &primitive_type.converted_type) {
+            (Some(PrimitiveLogicalType::Timestamp { unit, .. }), _) => Some((*unit).into()),
+            (_, Some(PrimitiveConvertedType::TimestampMillis)) => Some(TimestampUnit::Milliseconds),
+            (_, Some(PrimitiveConvertedType::TimestampMicros)) => Some(TimestampUnit::Microseconds),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TimestampUnit::Milliseconds => "milliseconds",
+            TimestampUnit::Microseconds => "microseconds",
+            TimestampUnit::Nanoseconds => "nanoseconds",
+        }
+    }
+}
+
+/// Whether the TIMESTAMP column of `primitive_type` holds local times, which are not
+/// adjusted to UTC. The columns of the converted types are adjusted to UTC.
+pub fn is_local_timestamp(primitive_type: &PrimitiveType) -> bool {
+    matches!(
+        primitive_type.logical_type,
+        Some(PrimitiveLogicalType::Timestamp {
+            is_adjusted_to_utc: false,
+            ..
+        })
+    )
+}
+
+/// How the unscaled values of a DECIMAL column chunk are stored.
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Copy, serde::Deserialize, serde::Serialize)]
+pub enum DecimalStorage {
+    Int32,
+    Int64,
+    /// Two's complement integers of a fixed number of bytes.
+    FixedLenByteArray(usize),
+}
+
+/// The storage and the scale of the values of a DECIMAL column chunk.
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Copy, serde::Deserialize, serde::Serialize)]
+pub struct DecimalEncoding {
+    pub storage: DecimalStorage,
+    pub scale: usize,
+}
+
+impl DecimalEncoding {
+    /// The encoding of the DECIMAL column of `primitive_type`, from its logical type, or
+    /// its converted type for files written before the logical types. `None` for the other
+    /// columns and the DECIMAL columns of variable length byte arrays.
+    pub fn of(primitive_type: &PrimitiveType) -> Option<DecimalEncoding> {
+        let scale = match (&primitive_type.logical_type, &primitive_type.converted_type) {
+            (Some(PrimitiveLogicalType::Decimal(_, scale)), _) => *scale,
+            (None, Some(PrimitiveConvertedType::Decimal(_, scale))) => *scale,
+            _ => return None,
+        };
+        let storage = match primitive_type.physical_type {
+            PhysicalType::Int32 => DecimalStorage::Int32,
+            PhysicalType::Int64 => DecimalStorage::Int64,
+            PhysicalType::FixedLenByteArray(size) => DecimalStorage::FixedLenByteArray(size),
+            _ => return None,
+        };
+        Some(DecimalEncoding { storage, scale })
+    }
+}
+
+impl From<TimestampUnit> for ParquetTimeUnit {
+    fn from(value: TimestampUnit) -> ParquetTimeUnit {
+        match value {
+            TimestampUnit::Milliseconds => ParquetTimeUnit::Milliseconds,
+            TimestampUnit::Microseconds => ParquetTimeUnit::Microseconds,
+            TimestampUnit::Nanoseconds => ParquetTimeUnit::Nanoseconds,
+        }
+    }
+}
+
+impl From<ParquetTimeUnit> for TimestampUnit {
+    fn from(value: ParquetTimeUnit) -> Self {
+        match value {
+            ParquetTimeUnit::Milliseconds => TimestampUnit::Milliseconds,
+            ParquetTimeUnit::Microseconds => TimestampUnit::Microseconds,
+            ParquetTimeUnit::Nanoseconds => TimestampUnit::Nanoseconds,
+        }
+    }
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
 pub struct ParquetColumnMeta {
     pub offset: u64,
     pub length: u64,
+    /// The size of this column chunk after decompression, which bounds the sizes of the
+    /// blocks its values are decoded into.
+    pub uncompressed_size: u64,
     pub num_values: u64,
 
+    /// The codec of this column chunk, columns of the same file may use different codecs.
     pub compression: Compression,
+    /// The unit of the values of this column chunk if it is a TIMESTAMP column, they are
+    /// scaled to the unit of the table schema while decoding.
+    pub timestamp_unit: Option<TimestampUnit>,
+    /// Whether the TIMESTAMP values of this column chunk are local times, not adjusted to
+    /// UTC. They are taken as times of the timezone of the reader while decoding.
+    pub local_timestamp: bool,
+    /// The encoding of the values of this column chunk if it is a DECIMAL column, they
+    /// are decoded to the FLOAT64 values of the table schema.
+    pub decimal: Option<DecimalEncoding>,
 }
 
 impl ParquetColumnMeta {
     pub fn create(
         offset: u64,
         length: u64,
+        uncompressed_size: u64,
         num_values: u64,
         compression: Compression,
+        timestamp_unit: Option<TimestampUnit>,
+        local_timestamp: bool,
+        decimal: Option<DecimalEncoding>,
     ) -> ParquetColumnMeta {
         ParquetColumnMeta {
             offset,
             length,
+            uncompressed_size,
             num_values,
             compression,
+            timestamp_unit,
+            local_timestamp,
+            decimal,
         }
     }
 }
@@ -26,14 +26,22 @@ use common_exception::Result;
 
 use crate::ParquetColumnMeta;
 
-#[derive(serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+#[derive(Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
 pub struct ParquetPartInfo {
     pub location: String,
+    /// The index of the row group in the file.
+    pub row_group: usize,
     /// FusePartInfo itself is not versioned
     /// the `format_version` is the version of the block which the `location` points to
     pub format_version: u64,
     pub nums_rows: usize,
     pub columns_meta: HashMap<usize, ParquetColumnMeta>,
+    /// The raw values of the hive partition columns, parsed from the path.
+    pub partition_values: Vec<String>,
+    /// The parts of small files coalesced into this one, read one after the other by the
+    /// same source, see [`ParquetPartInfo::coalesce`]. This part has their location and
+    /// row group, and their total number of rows, but no columns of its own.
+    pub coalesced: Vec<ParquetPartInfo>,
 }
 
 #[typetag::serde(name = "parquet")]
@@ -59,18 +67,64 @@ impl PartInfo for ParquetPartInfo {
 impl ParquetPartInfo {
     pub fn create(
         location: String,
+        row_group: usize,
         format_version: u64,
         rows_count: u64,
         columns_meta: HashMap<usize, ParquetColumnMeta>,
+        partition_values: Vec<String>,
     ) -> Arc<Box<dyn PartInfo>> {
         Arc::new(Box::new(ParquetPartInfo {
             location,
+            row_group,
             format_version,
             columns_meta,
             nums_rows: rows_count as usize,
+            partition_values,
+            coalesced: vec![],
         }))
     }
 
+    /// A part coalescing `parts`, of small files. A single part is kept as is.
+    pub fn coalesce(parts: Vec<PartInfoPtr>) -> Result<PartInfoPtr> {
+        if parts.len() == 1 {
+            return Ok(parts[0].clone());
+        }
+        let coalesced = parts
+            .iter()
+            .map(|part| ParquetPartInfo::from_part(part).cloned())
+            .collect::<Result<Vec<_>>>()?;
+        let first = &coalesced[0];
+        Ok(Arc::new(Box::new(ParquetPartInfo {
+            location: first.location.clone(),
+            row_group: first.row_group,
+            format_version: first.format_version,
+            nums_rows: coalesced.iter().map(|part| part.nums_rows).sum(),
+            columns_meta: HashMap::new(),
+            partition_values: vec![],
+            coalesced,
+        })))
+    }
+
+    /// The parts coalesced into `part`, or `part` itself if it is not a coalesced part.
+    pub fn expand(part: PartInfoPtr) -> Vec<PartInfoPtr> {
+        match ParquetPartInfo::from_part(&part) {
+            Ok(info) if !info.coalesced.is_empty() => info
+                .coalesced
+                .iter()
+                .map(|info| Arc::new(Box::new(info.clone()) as Box<dyn PartInfo>))
+                .collect(),
+            _ => vec![part],
+        }
+    }
+
+    /// The size of the column chunks of this part, as in the footer of its file.
+    pub fn size(&self) -> usize {
+        self.columns_meta
+            .values()
+            .map(|column_meta| column_meta.length as usize)
+            .sum()
+    }
+
     pub fn from_part(info: &PartInfoPtr) -> Result<&ParquetPartInfo> {
         match info.as_any().downcast_ref::<ParquetPartInfo>() {
             Some(part_ref) => Ok(part_ref),
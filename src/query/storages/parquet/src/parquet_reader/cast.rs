@@ -0,0 +1,87 @@
+//  Copyright 2022 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_functions::scalars::cast_with_type;
+use common_functions::scalars::FunctionContext;
+use common_functions::scalars::DEFAULT_CAST_OPTIONS;
+
+/// Cast the columns of `block` at the positions of `cast_columns` to the types of their fields.
+pub fn cast_columns(block: DataBlock, cast_columns: &[(usize, DataField)]) -> Result<DataBlock> {
+    if cast_columns.is_empty() {
+        return Ok(block);
+    }
+
+    let mut fields = block.schema().fields().clone();
+    let mut columns = block.columns().to_vec();
+    for (i, field) in cast_columns {
+        columns[*i] = safe_cast(&columns[*i], fields[*i].data_type(), field)?;
+        fields[*i] = field.clone();
+    }
+    Ok(DataBlock::create(DataSchemaRefExt::create(fields), columns))
+}
+
+/// Cast `column` of `from_type` to the type of `field`.
+///
+/// Numbers are cast back to check that the values are kept, so that overflowing integers or
+/// fractions cast to integers are reported instead of being wrapped or truncated.
+fn safe_cast(column: &ColumnRef, from_type: &DataTypeImpl, field: &DataField) -> Result<ColumnRef> {
+    let func_ctx = FunctionContext::default();
+    let target_type = field.data_type();
+
+    // Only the nullability differs for the columns made non-nullable by their statistics.
+    if from_type.is_nullable() && &remove_nullable(from_type) == target_type {
+        if let (_, Some(validity)) = column.validity() {
+            if validity.unset_bits() > 0 {
+                return Err(ErrorCode::BadDataValueType(format!(
+                    "Column '{}' is non-nullable by the statistics of the files, but holds NULL",
+                    field.name()
+                )));
+            }
+        }
+        return Ok(Series::remove_nullable(column));
+    }
+    let cast = cast_with_type(
+        column,
+        from_type,
+        target_type,
+        &DEFAULT_CAST_OPTIONS,
+        &func_ctx,
+    )?;
+
+    let is_numeric =
+        |data_type: &DataTypeImpl| remove_nullable(data_type).data_type_id().is_numeric();
+    if is_numeric(from_type) && is_numeric(target_type) {
+        let cast_back = cast_with_type(
+            &cast,
+            target_type,
+            from_type,
+            &DEFAULT_CAST_OPTIONS,
+            &func_ctx,
+        )?;
+        if let Some(row) = (0..column.len()).find(|row| column.get(*row) != cast_back.get(*row)) {
+            return Err(ErrorCode::BadDataValueType(format!(
+                "Value {} of column '{}' can't be cast from {} to {} without losing precision",
+                column.get(row),
+                field.name(),
+                from_type.name(),
+                target_type.name()
+            )));
+        }
+    }
+    Ok(cast)
+}
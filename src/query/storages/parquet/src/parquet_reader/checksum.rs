@@ -0,0 +1,72 @@
+//  Copyright 2022 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::io::Cursor;
+
+use common_arrow::parquet::page::ParquetPageHeader;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use parquet_format_safe::thrift::protocol::TCompactInputProtocol;
+
+/// Verify the CRC checksums of the pages in the column chunk `chunk`.
+///
+/// The checksum of a page is the CRC32 of its (compressed) data following the page header.
+/// It is optional, pages without one are not checked.
+pub fn verify_page_checksums(
+    location: &str,
+    row_group: usize,
+    column: &str,
+    chunk: &[u8],
+) -> Result<()> {
+    let page_error = |page: usize, e: String| {
+        ErrorCode::ParquetFileInvalid(format!(
+            "Read page {} of column '{}' in row group {} of parquet file '{}' error: {}",
+            page, column, row_group, location, e
+        ))
+    };
+
+    let mut reader = Cursor::new(chunk);
+    let mut page = 0;
+    while (reader.position() as usize) < chunk.len() {
+        let mut prot = TCompactInputProtocol::new(&mut reader, chunk.len());
+        let header = ParquetPageHeader::read_from_in_protocol(&mut prot)
+            .map_err(|e| page_error(page, e.to_string()))?;
+
+        let start = reader.position() as usize;
+        let end = start + header.compressed_page_size.max(0) as usize;
+        if end > chunk.len() {
+            return Err(page_error(
+                page,
+                format!(
+                    "page size {} exceeds the column chunk",
+                    header.compressed_page_size
+                ),
+            ));
+        }
+
+        if let Some(expected) = header.crc {
+            let actual = crc32fast::hash(&chunk[start..end]);
+            if actual != expected as u32 {
+                return Err(ErrorCode::ParquetChecksumMismatch(format!(
+                    "Checksum mismatch of page {} of column '{}' in row group {} of parquet file '{}': expected {:#010x}, got {:#010x}",
+                    page, column, row_group, location, expected as u32, actual
+                )));
+            }
+        }
+
+        reader.set_position(end as u64);
+        page += 1;
+    }
+    Ok(())
+}
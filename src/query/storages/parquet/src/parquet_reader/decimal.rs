@@ -0,0 +1,283 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parquet DECIMAL columns.
+//!
+//! A DECIMAL value is an unscaled integer times ten to the minus scale, stored as an INT32,
+//! an INT64 or a two's complement fixed length byte array. The table has no decimal type,
+//! the values are read as the closest FLOAT64 ones: the unscaled integers are decoded as
+//! they are, and scaled after.
+//!
+//! The fixed length byte arrays are big-endian by the format. Some writers store them
+//! little-endian, which reads as unrelated values, the option `decimal_byte_order` tells
+//! the byte order of such files.
+//!
+//! The option `cast` can narrow the scale of a DECIMAL column, like `cast=price:decimal(18, 2)`
+//! for the files of `DECIMAL(20, 4)` prices: the unscaled integers are rounded to the scale
+//! before the conversion to FLOAT64, by the rounding of the option `decimal_rounding`.
+
+use common_arrow::arrow::array::Array;
+use common_arrow::arrow::array::FixedSizeBinaryArray;
+use common_arrow::arrow::array::PrimitiveArray;
+use common_arrow::arrow::datatypes::DataType as ArrowType;
+use common_arrow::parquet::schema::types::PhysicalType;
+use common_exception::ErrorCode;
+use common_exception::Result;
+
+use crate::DecimalEncoding;
+use crate::DecimalStorage;
+
+/// The roundings of the DECIMAL values narrowed to a smaller scale by the option `cast`,
+/// set by the option `decimal_rounding`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecimalRounding {
+    /// Round the halves away from zero, set by `decimal_rounding=half_up`. `1.25` becomes
+    /// `1.3` and `-1.25` becomes `-1.3` at scale 1.
+    HalfUp,
+    /// Round the halves to the even digit, set by `decimal_rounding=half_even`. `1.25`
+    /// becomes `1.2` and `1.35` becomes `1.4` at scale 1.
+    HalfEven,
+    /// Drop the digits, set by `decimal_rounding=truncate`. `1.29` becomes `1.2` and `-1.29`
+    /// becomes `-1.2` at scale 1.
+    Truncate,
+}
+
+/// The DECIMAL type a DECIMAL column is cast to by the option `cast`, like
+/// `DECIMAL(18, 2)`. The column is still read as FLOAT64 values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DecimalCast {
+    pub precision: usize,
+    pub scale: usize,
+    /// The rounding of the values of a larger scale, which are rejected if `None`.
+    pub rounding: Option<DecimalRounding>,
+}
+
+/// A DECIMAL column chunk, scaled to FLOAT64 values by [`scale_decimals`].
+pub struct DecimalScale {
+    pub encoding: DecimalEncoding,
+    /// Whether the fixed length byte arrays are little-endian instead of big-endian.
+    pub little_endian: bool,
+    /// The DECIMAL type the values are cast to, if the column is cast.
+    pub cast: Option<DecimalCast>,
+    pub location: String,
+    pub row_group: usize,
+    pub column: String,
+}
+
+impl DecimalScale {
+    /// The physical type of the column chunk.
+    pub fn physical_type(&self) -> PhysicalType {
+        match self.encoding.storage {
+            DecimalStorage::Int32 => PhysicalType::Int32,
+            DecimalStorage::Int64 => PhysicalType::Int64,
+            DecimalStorage::FixedLenByteArray(size) => PhysicalType::FixedLenByteArray(size),
+        }
+    }
+
+    /// The arrow type the unscaled values of the column chunk are decoded as.
+    pub fn decoded_type(&self) -> ArrowType {
+        match self.encoding.storage {
+            DecimalStorage::Int32 => ArrowType::Int32,
+            DecimalStorage::Int64 => ArrowType::Int64,
+            DecimalStorage::FixedLenByteArray(size) => ArrowType::FixedSizeBinary(size),
+        }
+    }
+}
+
+/// Scale the unscaled values of `array`, decoded as [`DecimalScale::decoded_type`], to
+/// FLOAT64 values, once rounded to the scale of the cast if any. The nulls are kept.
+pub fn scale_decimals(array: Box<dyn Array>, scale: &DecimalScale) -> Result<Box<dyn Array>> {
+    let values = match scale.cast {
+        Some(ref cast) => cast_decimals(array.as_ref(), scale, cast)?,
+        None => {
+            let divisor = 10f64.powi(scale.encoding.scale as i32);
+            unscaled_values(array.as_ref(), scale)?
+                .into_iter()
+                .map(|value| value / divisor)
+                .collect()
+        }
+    };
+    Ok(PrimitiveArray::<f64>::new(
+        ArrowType::Float64,
+        values.into(),
+        array.validity().cloned(),
+    )
+    .boxed())
+}
+
+fn unscaled_values(array: &dyn Array, scale: &DecimalScale) -> Result<Vec<f64>> {
+    Ok(match scale.encoding.storage {
+        DecimalStorage::Int32 => downcast::<PrimitiveArray<i32>>(array)?
+            .values()
+            .iter()
+            .map(|value| *value as f64)
+            .collect(),
+        DecimalStorage::Int64 => downcast::<PrimitiveArray<i64>>(array)?
+            .values()
+            .iter()
+            .map(|value| *value as f64)
+            .collect(),
+        DecimalStorage::FixedLenByteArray(size) => downcast::<FixedSizeBinaryArray>(array)?
+            .values()
+            .chunks_exact(size)
+            .map(|bytes| unscaled_value(bytes, scale.little_endian))
+            .collect(),
+    })
+}
+
+/// Round the unscaled values of `array` to the scale of `cast` and scale them to FLOAT64
+/// values. The values with more digits than the precision of `cast` once rounded are
+/// rejected, the values of the nulls are not checked.
+fn cast_decimals(
+    array: &dyn Array,
+    scale: &DecimalScale,
+    cast: &DecimalCast,
+) -> Result<Vec<f64>> {
+    let integers: Vec<i128> = match scale.encoding.storage {
+        DecimalStorage::Int32 => downcast::<PrimitiveArray<i32>>(array)?
+            .values()
+            .iter()
+            .map(|value| *value as i128)
+            .collect(),
+        DecimalStorage::Int64 => downcast::<PrimitiveArray<i64>>(array)?
+            .values()
+            .iter()
+            .map(|value| *value as i128)
+            .collect(),
+        DecimalStorage::FixedLenByteArray(size) if size <= 16 => {
+            downcast::<FixedSizeBinaryArray>(array)?
+                .values()
+                .chunks_exact(size)
+                .map(|bytes| unscaled_integer(bytes, scale.little_endian))
+                .collect()
+        }
+        DecimalStorage::FixedLenByteArray(size) => {
+            return Err(ErrorCode::ParquetUnsupported(format!(
+                "DECIMAL values of {} bytes of column '{}' of parquet file '{}' can't be cast, up to 16 bytes are supported",
+                size, scale.column, scale.location
+            )));
+        }
+    };
+
+    let digits = scale.encoding.scale.saturating_sub(cast.scale);
+    let rounding = match cast.rounding {
+        // Nothing to round.
+        _ if digits == 0 => DecimalRounding::Truncate,
+        Some(rounding) => rounding,
+        None => {
+            return Err(ErrorCode::BadArguments(format!(
+                "Column '{}' of parquet file '{}' has DECIMAL values of scale {}, narrowing them to DECIMAL({}, {}) by read_parquet option 'cast' needs the option 'decimal_rounding'",
+                scale.column, scale.location, scale.encoding.scale, cast.precision, cast.scale
+            )));
+        }
+    };
+    // No value of up to 16 bytes reaches the limits beyond 38 digits.
+    let limit = 10u128.checked_pow(cast.precision as u32);
+    let divisor = 10f64.powi((scale.encoding.scale - digits) as i32);
+    let validity = array.validity();
+    integers
+        .into_iter()
+        .enumerate()
+        .map(|(row, value)| {
+            let rounded = round_unscaled(value, digits, rounding);
+            let is_valid = validity.map_or(true, |validity| validity.get_bit(row));
+            if matches!(limit, Some(limit) if rounded.unsigned_abs() >= limit) && is_valid {
+                return Err(ErrorCode::BadDataValueType(format!(
+                    "DECIMAL value {} of column '{}' in row group {} of parquet file '{}' can't be cast to DECIMAL({}, {}) without losing precision",
+                    value as f64 / 10f64.powi(scale.encoding.scale as i32),
+                    scale.column,
+                    scale.row_group,
+                    scale.location,
+                    cast.precision,
+                    cast.scale
+                )));
+            }
+            Ok(rounded as f64 / divisor)
+        })
+        .collect()
+}
+
+/// Drop the last `digits` digits of the unscaled integer `value` by `rounding`.
+fn round_unscaled(value: i128, digits: usize, rounding: DecimalRounding) -> i128 {
+    if digits == 0 {
+        return value;
+    }
+    // The divisors of more than 38 digits overflow, they are more than twice the integers
+    // of up to 16 bytes, which round to 0.
+    let divisor = match 10i128.checked_pow(digits as u32) {
+        Some(divisor) => divisor,
+        None => return 0,
+    };
+    let (quotient, remainder) = (value / divisor, (value % divisor).abs());
+    // `remainder` against the half of `divisor`, without overflowing `2 * remainder`.
+    let away = match rounding {
+        DecimalRounding::Truncate => false,
+        DecimalRounding::HalfUp => remainder >= divisor - remainder,
+        DecimalRounding::HalfEven => {
+            remainder > divisor - remainder
+                || (remainder == divisor - remainder && quotient % 2 != 0)
+        }
+    };
+    if away {
+        quotient + value.signum()
+    } else {
+        quotient
+    }
+}
+
+fn downcast<T: 'static>(array: &dyn Array) -> Result<&T> {
+    array.as_any().downcast_ref::<T>().ok_or_else(|| {
+        ErrorCode::Internal(format!(
+            "DECIMAL values should be decoded as their storage, but got {:?}",
+            array.data_type()
+        ))
+    })
+}
+
+/// The two's complement integer of `bytes`, big-endian unless `little_endian`.
+///
+/// The integers of up to 16 bytes are exact before the conversion to FLOAT64, the longer
+/// ones are accumulated as FLOAT64 values.
+fn unscaled_value(bytes: &[u8], little_endian: bool) -> f64 {
+    if bytes.len() <= 16 {
+        return unscaled_integer(bytes, little_endian) as f64;
+    }
+    let byte = |i: usize| {
+        if little_endian {
+            bytes[bytes.len() - 1 - i]
+        } else {
+            bytes[i]
+        }
+    };
+    let first = byte(0) as i8 as f64;
+    (1..bytes.len()).fold(first, |value, i| value * 256.0 + byte(i) as f64)
+}
+
+/// The two's complement integer of up to 16 `bytes`, big-endian unless `little_endian`.
+fn unscaled_integer(bytes: &[u8], little_endian: bool) -> i128 {
+    let byte = |i: usize| {
+        if little_endian {
+            bytes[bytes.len() - 1 - i]
+        } else {
+            bytes[i]
+        }
+    };
+    let negative = !bytes.is_empty() && byte(0) & 0x80 != 0;
+    let mut be_bytes = [if negative { 0xff } else { 0 }; 16];
+    let start = 16 - bytes.len();
+    for (i, be_byte) in be_bytes[start..].iter_mut().enumerate() {
+        *be_byte = byte(i);
+    }
+    i128::from_be_bytes(be_bytes)
+}
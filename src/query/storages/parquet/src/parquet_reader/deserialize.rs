@@ -14,67 +14,241 @@
 
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::ops::Range;
 use std::sync::Arc;
 
+use chrono_tz::Tz;
+use common_arrow::arrow::array::new_null_array;
+use common_arrow::arrow::array::Array;
+use common_arrow::arrow::chunk::Chunk;
+use common_arrow::arrow::datatypes::DataType as ArrowType;
 use common_arrow::arrow::datatypes::Field;
+use common_arrow::arrow::error::Error as ArrowError;
 use common_arrow::arrow::io::parquet::read::column_iter_to_arrays;
 use common_arrow::arrow::io::parquet::read::ArrayIter;
 use common_arrow::arrow::io::parquet::read::RowGroupDeserializer;
 use common_arrow::parquet::metadata::ColumnDescriptor;
 use common_arrow::parquet::read::BasicDecompressor;
+use common_arrow::parquet::read::PageFilter;
 use common_arrow::parquet::read::PageMetaData;
 use common_arrow::parquet::read::PageReader;
+use common_arrow::parquet::schema::types::PhysicalType;
+use common_arrow::parquet::schema::types::PrimitiveLogicalType;
 use common_catalog::plan::PartInfoPtr;
 use common_datablocks::DataBlock;
+use common_datavalues::DataField;
+use common_datavalues::DataSchemaRef;
+use common_datavalues::DataType;
+use common_datavalues::DataValue;
+use common_datavalues::IntoColumn;
 use common_exception::ErrorCode;
 use common_exception::Result;
 use common_storage::ColumnLeaf;
 
+use super::cast::cast_columns;
+use super::checksum::verify_page_checksums;
+use super::decimal::scale_decimals;
+use super::decimal::DecimalScale;
+use super::interval::interval_column;
+use super::timestamp::localize_timestamps;
+use super::timestamp::scale_timestamps;
+use super::timestamp::TimestampScale;
+use super::transcode::TranscodedPages;
+use crate::table_function::partition_value;
+use crate::table_function::ROW_GROUP_INDEX_COLUMN;
 use crate::ParquetColumnMeta;
 use crate::ParquetPartInfo;
 use crate::ParquetReader;
+use crate::TimestampUnit;
 
 impl ParquetReader {
-    fn to_array_iter(
+    /// Decode the column chunks `chunks` of the leaves of `field` into arrays of
+    /// `chunk_size` rows, skipping the data pages rejected by `pages_filter`.
+    ///
+    /// Each data page is decoded by the encoding of its header, not the ones of the chunk:
+    /// the writers fall back from dictionary encoded pages to PLAIN ones within a chunk
+    /// once the dictionary grows too large.
+    ///
+    /// The TIMESTAMP values of the other units than microseconds are decoded as they are
+    /// and scaled by `scale`, see [`ParquetReader::timestamp_scale`]. The same goes for the
+    /// DECIMAL values and `decimal`, see [`ParquetReader::decimal_scale`].
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn to_array_iter(
         metas: Vec<&ParquetColumnMeta>,
         chunks: Vec<Vec<u8>>,
         rows: usize,
+        chunk_size: usize,
         column_descriptors: Vec<&ColumnDescriptor>,
         field: Field,
+        pages_filter: PageFilter,
+        timezone: Tz,
+        scale: Option<TimestampScale>,
+        decimal: Option<DecimalScale>,
     ) -> Result<ArrayIter<'static>> {
+        // The descriptors are derived from the table schema, the units of the TIMESTAMP
+        // values and the storages of the DECIMAL ones are the ones of the column chunks.
+        let descriptors = metas
+            .iter()
+            .zip(column_descriptors.iter())
+            .map(|(meta, column_descriptor)| {
+                let mut descriptor = column_descriptor.descriptor.clone();
+                if let Some(unit) = meta.timestamp_unit {
+                    descriptor.primitive_type.logical_type =
+                        Some(PrimitiveLogicalType::Timestamp {
+                            unit: unit.into(),
+                            is_adjusted_to_utc: false,
+                        });
+                }
+                if let Some(ref decimal) = decimal {
+                    descriptor.primitive_type.physical_type = decimal.physical_type();
+                    descriptor.primitive_type.logical_type = None;
+                    descriptor.primitive_type.converted_type = None;
+                }
+                descriptor
+            })
+            .collect::<Vec<_>>();
+
         let columns = metas
             .iter()
-            .zip(chunks.into_iter().zip(column_descriptors.iter()))
-            .map(|(meta, (chunk, column_descriptor))| {
+            .zip(chunks.into_iter().zip(descriptors.iter()))
+            .map(|(meta, (chunk, descriptor))| {
                 let page_meta_data = PageMetaData {
                     column_start: meta.offset,
                     num_values: meta.num_values as i64,
                     compression: meta.compression.into(),
-                    descriptor: column_descriptor.descriptor.clone(),
+                    descriptor: descriptor.clone(),
                 };
                 let pages = PageReader::new_with_page_meta(
                     std::io::Cursor::new(chunk),
                     page_meta_data,
-                    Arc::new(|_, _| true),
+                    pages_filter.clone(),
                     vec![],
                     usize::MAX,
                 );
-                Ok(BasicDecompressor::new(pages, vec![]))
+                Ok(TranscodedPages::new(BasicDecompressor::new(pages, vec![])))
             })
             .collect::<Result<Vec<_>>>()?;
 
-        let types = column_descriptors
+        let types = descriptors
             .iter()
-            .map(|column_descriptor| &column_descriptor.descriptor.primitive_type)
+            .map(|descriptor| &descriptor.primitive_type)
             .collect::<Vec<_>>();
 
-        Ok(column_iter_to_arrays(
-            columns,
-            types,
-            field,
-            Some(rows),
+        // Only the local times of top level TIMESTAMP columns are taken in the timezone.
+        let localize = metas.len() == 1
+            && metas[0].local_timestamp
+            && matches!(field.data_type, ArrowType::Timestamp(_, _))
+            && timezone != Tz::UTC;
+        let data_type = field.data_type.clone();
+        let field = match (&scale, &decimal) {
+            (Some(scale), _) => Field {
+                data_type: scale.decoded_type(&field.data_type),
+                ..field
+            },
+            (None, Some(decimal)) => Field {
+                data_type: decimal.decoded_type(),
+                ..field
+            },
+            (None, None) => field,
+        };
+        let arrays = column_iter_to_arrays(columns, types, field, Some(chunk_size), rows)
+            .map_err(decode_error)?;
+        if !localize && scale.is_none() && decimal.is_none() {
+            return Ok(arrays);
+        }
+        let mut decoded = 0;
+        Ok(Box::new(arrays.map(move |array| {
+            array.and_then(|mut array| {
+                if let Some(ref scale) = scale {
+                    let first_value = decoded;
+                    decoded += array.len();
+                    array = scale_timestamps(array, scale, first_value, &data_type)
+                        .map_err(|e| ArrowError::ExternalFormat(e.message()))?;
+                }
+                if localize {
+                    array = localize_timestamps(array, timezone)
+                        .map_err(|e| ArrowError::ExternalFormat(e.message()))?;
+                }
+                if let Some(ref decimal) = decimal {
+                    array = scale_decimals(array, decimal)
+                        .map_err(|e| ArrowError::ExternalFormat(e.message()))?;
+                }
+                Ok(array)
+            })
+        })))
+    }
+
+    /// The scaling of the TIMESTAMP values of the column chunk of `metas` in `part` to
+    /// the microseconds of `field`, for the top level TIMESTAMP columns of the other units.
+    /// `rows` are the ranges of the rows of the row group which are decoded.
+    pub(super) fn timestamp_scale(
+        &self,
+        part: &ParquetPartInfo,
+        metas: &[&ParquetColumnMeta],
+        field: &Field,
+        rows: Vec<Range<usize>>,
+    ) -> Option<TimestampScale> {
+        let unit = match metas {
+            [meta] => meta.timestamp_unit?,
+            _ => return None,
+        };
+        if unit == TimestampUnit::Microseconds
+            || !matches!(field.data_type, ArrowType::Timestamp(_, _))
+        {
+            return None;
+        }
+        Some(TimestampScale {
+            unit,
+            saturate: self.saturate_timestamps,
+            location: part.location.clone(),
+            row_group: part.row_group,
+            column: field.name.clone(),
             rows,
-        )?)
+        })
+    }
+
+    /// The scaling of the unscaled DECIMAL values of the column chunk of `metas` to the
+    /// FLOAT64 values of `field`, for the top level DECIMAL columns, rounded to the scale
+    /// of the DECIMAL type the column is cast to if any.
+    ///
+    /// The columns cast to DECIMAL types must be DECIMAL columns in the file of `part`.
+    pub(super) fn decimal_scale(
+        &self,
+        part: &ParquetPartInfo,
+        metas: &[&ParquetColumnMeta],
+        field: &Field,
+    ) -> Result<Option<DecimalScale>> {
+        let cast = self
+            .decimal_casts
+            .iter()
+            .find(|(name, _)| name == &field.name)
+            .map(|(_, cast)| *cast);
+        let encoding = match metas {
+            [meta] => meta.decimal,
+            _ => None,
+        };
+        let encoding = match (encoding, cast) {
+            (Some(encoding), _) => encoding,
+            (None, Some(cast)) => {
+                return Err(ErrorCode::BadArguments(format!(
+                    "Column '{}' of parquet file '{}' is not DECIMAL, it can't be cast to DECIMAL({}, {})",
+                    field.name, part.location, cast.precision, cast.scale
+                )));
+            }
+            (None, None) => return Ok(None),
+        };
+        if field.data_type != ArrowType::Float64 {
+            return Ok(None);
+        }
+        Ok(Some(DecimalScale {
+            encoding,
+            little_endian: self.little_endian_decimals,
+            cast,
+            location: part.location.clone(),
+            row_group: part.row_group,
+            column: field.name.clone(),
+        }))
     }
 
     pub fn deserialize(
@@ -82,16 +256,171 @@ impl ParquetReader {
         part: PartInfoPtr,
         chunks: Vec<(usize, Vec<u8>)>,
     ) -> Result<DataBlock> {
+        let partition_values = self.partition_values(&part)?;
+        let mut deserializer = self.row_group_deserializer(part, chunks, None)?;
+        let block = self.try_next_block(&mut deserializer)?;
+        fill_partitions(block, &partition_values, self.count_only)
+    }
+
+    /// Deserialize the row group of `part` into arrow arrays of the types of
+    /// [`common_datavalues::DataSchema::to_arrow`] of the schema of the reader.
+    ///
+    /// The arrays are the ones decoded from the pages, unless the row group has columns
+    /// converted after the decoding (INTERVAL, cast or partition columns), which are then
+    /// converted from a block.
+    pub fn deserialize_chunk(
+        &self,
+        part: PartInfoPtr,
+        chunks: Vec<(usize, Vec<u8>)>,
+    ) -> Result<Chunk<Box<dyn Array>>> {
+        if !self.interval_columns.is_empty()
+            || !self.cast_columns.is_empty()
+            || !self.partition_columns.is_empty()
+        {
+            return Chunk::try_from(self.deserialize(part, chunks)?);
+        }
+        let mut deserializer = self.row_group_deserializer(part, chunks, None)?;
+        match deserializer.next() {
+            None => Err(ErrorCode::Internal(
+                "deserializer from row group: fail to get a chunk",
+            )),
+            Some(Err(cause)) => Err(decode_error(cause)),
+            Some(Ok(chunk)) => Ok(chunk),
+        }
+    }
+
+    /// The number of rows of the blocks to deserialize the row group of `part` into, `None`
+    /// if it is deserialized as a whole.
+    ///
+    /// The row group is split if `max_block_rows` is set, or if its projected string columns
+    /// are larger than `max_string_block_bytes`, so that they are decoded into bounded
+    /// buffers instead of one per column. It is also split to be decoded within the memory
+    /// budget if any, or rejected if it doesn't fit, see [`MemoryBudget`].
+    ///
+    /// The columns of `remain_reader` are decoded in the same blocks with prewhere, they are
+    /// taken into account along with the columns of this reader.
+    pub fn block_rows(
+        &self,
+        part: &PartInfoPtr,
+        max_block_rows: Option<usize>,
+        remain_reader: Option<&ParquetReader>,
+    ) -> Result<Option<usize>> {
+        let part = ParquetPartInfo::from_part(part)?;
+        let mut indices = self.leaf_indices()?;
+        if let Some(remain_reader) = remain_reader {
+            indices.extend(remain_reader.leaf_indices()?);
+        }
+        let string_bytes = indices
+            .iter()
+            .filter(|index| {
+                let descriptor = &self.parquet_schema_descriptor.columns()[**index].descriptor;
+                descriptor.primitive_type.physical_type == PhysicalType::ByteArray
+            })
+            .filter_map(|index| part.columns_meta.get(index))
+            .map(|meta| meta.uncompressed_size)
+            .sum::<u64>();
+
+        let max_string_block_bytes = self.max_string_block_bytes as u64;
+        let string_block_rows = if string_bytes > max_string_block_bytes {
+            let rows = part.nums_rows as u64 * max_string_block_bytes / string_bytes;
+            Some((rows as usize).max(1))
+        } else {
+            None
+        };
+        let budget_block_rows = match &self.memory_budget {
+            Some(memory_budget) => memory_budget.block_rows(part, &indices)?,
+            None => None,
+        };
+        Ok([max_block_rows, string_block_rows, budget_block_rows]
+            .into_iter()
+            .flatten()
+            .min())
+    }
+
+    /// The indices of the leaves of the projected columns.
+    fn leaf_indices(&self) -> Result<HashSet<usize>> {
+        let columns = self.projection.project_column_leaves(&self.column_leaves)?;
+        Ok(columns
+            .iter()
+            .flat_map(|column| column.leaf_ids.iter().copied())
+            .collect())
+    }
+
+    /// Deserialize the row group into blocks of at most `max_block_rows` rows.
+    ///
+    /// The pages are decoded as the blocks are pulled, so only one block is in memory
+    /// at a time.
+    pub fn deserialize_blocks(
+        &self,
+        part: PartInfoPtr,
+        chunks: Vec<(usize, Vec<u8>)>,
+        max_block_rows: usize,
+    ) -> Result<RowGroupBlocks> {
+        let partition_values = self.partition_values(&part)?;
+        let deserializer =
+            self.row_group_deserializer(part.clone(), chunks, Some(max_block_rows))?;
+        Ok(RowGroupBlocks {
+            part,
+            deserializer,
+            schema: self.file_schema.clone(),
+            interval_columns: self.interval_columns.clone(),
+            cast_columns: self.cast_columns.clone(),
+            partition_values,
+            count_only: self.count_only,
+            rows_read: 0,
+        })
+    }
+
+    /// The values of the projected partition columns of `part`, and of `_row_group_index`,
+    /// which follows them.
+    pub(super) fn partition_values(&self, part: &PartInfoPtr) -> Result<Vec<(DataField, DataValue)>> {
+        let part = ParquetPartInfo::from_part(part)?;
+        self.partition_columns
+            .iter()
+            .map(|(position, field)| {
+                if field.name() == ROW_GROUP_INDEX_COLUMN
+                    && *position == part.partition_values.len()
+                {
+                    return Ok((field.clone(), DataValue::UInt64(part.row_group as u64)));
+                }
+                let value = part.partition_values.get(*position).ok_or_else(|| {
+                    ErrorCode::Internal(format!(
+                        "No value of partition column '{}' in part '{}'",
+                        field.name(),
+                        part.location
+                    ))
+                })?;
+                Ok((field.clone(), partition_value(value, field)?))
+            })
+            .collect()
+    }
+
+    fn row_group_deserializer(
+        &self,
+        part: PartInfoPtr,
+        chunks: Vec<(usize, Vec<u8>)>,
+        max_block_rows: Option<usize>,
+    ) -> Result<RowGroupDeserializer> {
         let part = ParquetPartInfo::from_part(&part)?;
         let mut chunk_map: HashMap<usize, Vec<u8>> = chunks.into_iter().collect();
+        self.verify_chunks(part, &chunk_map)?;
         let mut columns_array_iter = Vec::with_capacity(self.projection.len());
 
         let num_rows = part.nums_rows;
+        let chunk_size = max_block_rows.unwrap_or(num_rows);
         let columns = self.projection.project_column_leaves(&self.column_leaves)?;
         let mut cnt_map = Self::build_projection_count_map(&columns);
         for column in &columns {
             let field = column.field.clone();
             let indices = &column.leaf_ids;
+            // The columns missing in the file of the part, merged by name, are all NULL.
+            if indices
+                .iter()
+                .any(|index| !part.columns_meta.contains_key(index))
+            {
+                columns_array_iter.push(null_array_iter(field, num_rows, chunk_size));
+                continue;
+            }
             let mut column_metas = Vec::with_capacity(indices.len());
             let mut column_chunks = Vec::with_capacity(indices.len());
             let mut column_descriptors = Vec::with_capacity(indices.len());
@@ -109,18 +438,48 @@ impl ParquetReader {
                 column_chunks.push(column_chunk);
                 column_descriptors.push(column_descriptor);
             }
+            let scale = self.timestamp_scale(part, &column_metas, &field, vec![0..num_rows]);
+            let decimal = self.decimal_scale(part, &column_metas, &field)?;
             columns_array_iter.push(Self::to_array_iter(
                 column_metas,
                 column_chunks,
                 num_rows,
+                chunk_size,
                 column_descriptors,
                 field,
+                Arc::new(|_, _| true),
+                self.timezone,
+                scale,
+                decimal,
             )?);
         }
 
-        let mut deserializer = RowGroupDeserializer::new(columns_array_iter, num_rows, None);
+        Ok(RowGroupDeserializer::new(
+            columns_array_iter,
+            num_rows,
+            None,
+        ))
+    }
 
-        self.try_next_block(&mut deserializer)
+    /// Verify the checksums of the column chunks `chunks` of `part`, if enabled.
+    pub(super) fn verify_chunks(
+        &self,
+        part: &ParquetPartInfo,
+        chunks: &HashMap<usize, Vec<u8>>,
+    ) -> Result<()> {
+        if !self.verify_checksums {
+            return Ok(());
+        }
+        for (index, chunk) in chunks.iter() {
+            let column_descriptor = &self.parquet_schema_descriptor.columns()[*index];
+            verify_page_checksums(
+                &part.location,
+                part.row_group,
+                &column_descriptor.path_in_schema.join("."),
+                chunk,
+            )?;
+        }
+        Ok(())
     }
 
     fn try_next_block(&self, deserializer: &mut RowGroupDeserializer) -> Result<DataBlock> {
@@ -128,8 +487,11 @@ impl ParquetReader {
             None => Err(ErrorCode::Internal(
                 "deserializer from row group: fail to get a chunk",
             )),
-            Some(Err(cause)) => Err(ErrorCode::from(cause)),
-            Some(Ok(chunk)) => DataBlock::from_chunk(&self.projected_schema, &chunk),
+            Some(Err(cause)) => Err(decode_error(cause)),
+            Some(Ok(chunk)) => {
+                let block = chunk_to_block(&self.file_schema, &chunk, &self.interval_columns)?;
+                cast_columns(block, &self.cast_columns)
+            }
         }
     }
 
@@ -149,3 +511,151 @@ impl ParquetReader {
         cnt_map
     }
 }
+
+/// A bound of the memory the row group of a part is decoded in, set by the options
+/// `memory_budget` and `on_memory_budget` of `read_parquet`.
+///
+/// The memory is estimated from the projected column chunks of the part: the compressed
+/// chunks are held while the row group is decoded, and the values decoded from them grow
+/// with the rows of the blocks, up to the uncompressed sizes of the chunks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MemoryBudget {
+    pub bytes: usize,
+    /// Reject the row groups exceeding the budget instead of decoding them in smaller
+    /// blocks.
+    pub error: bool,
+}
+
+impl MemoryBudget {
+    /// The number of rows of the blocks to decode the row group of `part` into within the
+    /// budget, from the column chunks of the leaves `indices`. `None` if it fits as a whole.
+    fn block_rows(
+        &self,
+        part: &ParquetPartInfo,
+        indices: &HashSet<usize>,
+    ) -> Result<Option<usize>> {
+        let (chunk_bytes, decoded_bytes) = indices
+            .iter()
+            .filter_map(|index| part.columns_meta.get(index))
+            .fold((0, 0), |(chunk_bytes, decoded_bytes), meta| {
+                (chunk_bytes + meta.length, decoded_bytes + meta.uncompressed_size)
+            });
+        let bytes = self.bytes as u64;
+        if chunk_bytes + decoded_bytes <= bytes {
+            return Ok(None);
+        }
+        if self.error || chunk_bytes >= bytes {
+            return Err(ErrorCode::BadArguments(format!(
+                "Row group {} of parquet file '{}' exceeds memory budget: its column chunks take {} bytes, {} bytes once decoded, more than memory_budget ({} bytes)",
+                part.row_group, part.location, chunk_bytes, decoded_bytes, self.bytes
+            )));
+        }
+        let rows = part.nums_rows as u64 * (bytes - chunk_bytes) / decoded_bytes;
+        Ok(Some((rows as usize).max(1)))
+    }
+}
+
+/// The arrays of `rows` NULL values of `field`, split into arrays of `chunk_size` rows.
+fn null_array_iter(field: Field, rows: usize, chunk_size: usize) -> ArrayIter<'static> {
+    let chunk_size = chunk_size.max(1);
+    Box::new((0..rows).step_by(chunk_size).map(move |offset| {
+        Ok(new_null_array(
+            field.data_type.clone(),
+            chunk_size.min(rows - offset),
+        ))
+    }))
+}
+
+/// The blocks of a row group, see [`ParquetReader::deserialize_blocks`].
+pub struct RowGroupBlocks {
+    part: PartInfoPtr,
+    deserializer: RowGroupDeserializer,
+    schema: DataSchemaRef,
+    interval_columns: Vec<usize>,
+    cast_columns: Vec<(usize, DataField)>,
+    partition_values: Vec<(DataField, DataValue)>,
+    count_only: bool,
+    // The rows of the blocks decoded so far.
+    rows_read: usize,
+}
+
+impl RowGroupBlocks {
+    /// The part of the row group.
+    pub fn part(&self) -> &PartInfoPtr {
+        &self.part
+    }
+
+    /// The number of rows of the blocks decoded so far.
+    pub fn rows_read(&self) -> usize {
+        self.rows_read
+    }
+
+    /// Decode the next block, `None` if all the rows of the row group are read.
+    pub fn next_block(&mut self) -> Result<Option<DataBlock>> {
+        match self.deserializer.next() {
+            None => Ok(None),
+            Some(Err(cause)) => Err(decode_error(cause)),
+            Some(Ok(chunk)) => {
+                let block = chunk_to_block(&self.schema, &chunk, &self.interval_columns)?;
+                let block = cast_columns(block, &self.cast_columns)?;
+                let block = fill_partitions(block, &self.partition_values, self.count_only)?;
+                self.rows_read += block.num_rows();
+                Ok(Some(block))
+            }
+        }
+    }
+}
+
+pub(super) fn chunk_to_block(
+    schema: &DataSchemaRef,
+    chunk: &Chunk<Box<dyn Array>>,
+    interval_columns: &[usize],
+) -> Result<DataBlock> {
+    if interval_columns.is_empty() {
+        return DataBlock::from_chunk(schema, chunk);
+    }
+
+    let columns = chunk
+        .columns()
+        .iter()
+        .zip(schema.fields().iter())
+        .enumerate()
+        .map(|(i, (array, field))| {
+            if interval_columns.contains(&i) {
+                interval_column(array.as_ref(), field.data_type())
+            } else if field.is_nullable() {
+                Ok(array.into_nullable_column())
+            } else {
+                Ok(array.into_column())
+            }
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(DataBlock::create(schema.clone(), columns))
+}
+
+/// Append the constant partition columns to `block`.
+///
+/// If `count_only`, the column of `block` was only read for the number of rows and is dropped.
+pub(super) fn fill_partitions(
+    mut block: DataBlock,
+    partition_values: &[(DataField, DataValue)],
+    count_only: bool,
+) -> Result<DataBlock> {
+    let num_rows = block.num_rows();
+    for (field, value) in partition_values {
+        let column = field.data_type().create_constant_column(value, num_rows)?;
+        block = block.add_column(column, field.clone())?;
+    }
+    if count_only {
+        block = block.remove_column_index(0)?;
+    }
+    Ok(block)
+}
+
+/// Arrow reports the encodings it can't decode as not yet implemented.
+pub(super) fn decode_error(cause: ArrowError) -> ErrorCode {
+    match cause {
+        ArrowError::NotYetImplemented(v) => ErrorCode::ParquetUnsupported(format!("arrow: {v}")),
+        cause => ErrorCode::from(cause),
+    }
+}
@@ -0,0 +1,102 @@
+//  Copyright 2022 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+//! Parquet INTERVAL columns.
+//!
+//! An INTERVAL value is a 12 bytes fixed length byte array holding three little-endian
+//! unsigned integers: the number of months, days and milliseconds. Arrow infers them as
+//! `Interval(DayTime)` which drops the months, so they are exposed as a struct of the
+//! three components instead, and decoded as fixed size binaries.
+
+use common_arrow::arrow::array::Array;
+use common_arrow::arrow::array::FixedSizeBinaryArray;
+use common_arrow::arrow::datatypes::DataType as ArrowType;
+use common_arrow::arrow::datatypes::Field as ArrowField;
+use common_arrow::arrow::datatypes::IntervalUnit;
+use common_datavalues::prelude::*;
+use common_exception::ErrorCode;
+use common_exception::Result;
+
+const INTERVAL_SIZE: usize = 12;
+const INTERVAL_COMPONENTS: [&str; 3] = ["months", "days", "milliseconds"];
+
+/// Whether `data_type` is the type arrow infers for parquet INTERVAL columns.
+pub fn is_parquet_interval(data_type: &ArrowType) -> bool {
+    matches!(data_type, ArrowType::Interval(IntervalUnit::DayTime))
+}
+
+/// The type of INTERVAL columns in the table schema.
+///
+/// The struct itself can't be nullable, the nulls are kept by the components.
+pub fn interval_struct_type(nullable: bool) -> ArrowType {
+    ArrowType::Struct(
+        INTERVAL_COMPONENTS
+            .iter()
+            .map(|name| ArrowField::new(*name, ArrowType::UInt32, nullable))
+            .collect(),
+    )
+}
+
+/// The field INTERVAL column `field` of the table schema is decoded as.
+pub fn interval_physical_field(field: &DataField) -> ArrowField {
+    ArrowField::new(
+        field.name(),
+        ArrowType::FixedSizeBinary(INTERVAL_SIZE),
+        has_nullable_components(field.data_type()),
+    )
+}
+
+/// Split the decoded INTERVAL values in `array` into a struct column of `data_type`.
+pub fn interval_column(array: &dyn Array, data_type: &DataTypeImpl) -> Result<ColumnRef> {
+    let array = array
+        .as_any()
+        .downcast_ref::<FixedSizeBinaryArray>()
+        .ok_or_else(|| {
+            ErrorCode::Internal(format!(
+                "INTERVAL values should be decoded as fixed size binary, but got {:?}",
+                array.data_type()
+            ))
+        })?;
+
+    let mut components = vec![Vec::with_capacity(array.len()); INTERVAL_COMPONENTS.len()];
+    for value in array.values().chunks_exact(INTERVAL_SIZE) {
+        for (i, component) in components.iter_mut().enumerate() {
+            component.push(u32::from_le_bytes(
+                value[i * 4..i * 4 + 4].try_into().unwrap(),
+            ));
+        }
+    }
+
+    let nullable = has_nullable_components(data_type);
+    let values = components
+        .into_iter()
+        .map(|component| {
+            let column = Series::from_data(component);
+            if nullable {
+                NullableColumn::wrap_inner(column, array.validity().cloned())
+            } else {
+                column
+            }
+        })
+        .collect();
+
+    Ok(StructColumn::from_data(values, data_type.clone()).arc())
+}
+
+fn has_nullable_components(data_type: &DataTypeImpl) -> bool {
+    match data_type {
+        DataTypeImpl::Struct(inner) => inner.types().iter().any(|ty| ty.is_nullable()),
+        _ => false,
+    }
+}
@@ -14,49 +14,223 @@
 
 use std::sync::Arc;
 
+use chrono_tz::Tz;
+use common_arrow::arrow::datatypes::DataType as ArrowType;
+use common_arrow::arrow::datatypes::Field as ArrowField;
+use common_arrow::arrow::datatypes::Schema as ArrowSchema;
 use common_arrow::arrow::io::parquet::write::to_parquet_schema;
 use common_arrow::parquet::metadata::SchemaDescriptor;
 use common_catalog::plan::Projection;
+use common_datavalues::DataField;
+use common_datavalues::DataSchema;
 use common_datavalues::DataSchemaRef;
+use common_datavalues::DataTypeImpl;
 use common_exception::Result;
 use common_storage::ColumnLeaves;
-use opendal::Operator;
 
+use crate::FileOperators;
+
+mod cast;
+mod checksum;
+mod decimal;
 mod deserialize;
+mod interval;
 mod read;
+mod selection;
+mod timestamp;
+mod transcode;
+
+pub use decimal::DecimalCast;
+pub use decimal::DecimalRounding;
+pub use deserialize::MemoryBudget;
+pub use deserialize::RowGroupBlocks;
+pub(crate) use interval::interval_struct_type;
+pub(crate) use interval::is_parquet_interval;
 
 #[derive(Clone)]
 pub struct ParquetReader {
-    operator: Operator,
+    operators: FileOperators,
+    /// The projection of the columns stored in the files.
     projection: Projection,
+    /// The schema of the output blocks, with the partition columns.
     projected_schema: DataSchemaRef,
+    /// The schema of the columns read from the files.
+    file_schema: DataSchemaRef,
+    /// The hive partition columns of `projected_schema`, with their positions in the
+    /// partition values of the parts.
+    partition_columns: Vec<(usize, DataField)>,
+    /// Whether a column is read only to count the rows, as only partition columns are
+    /// projected. It is dropped from the output blocks.
+    count_only: bool,
     column_leaves: ColumnLeaves,
     parquet_schema_descriptor: SchemaDescriptor,
+    /// The positions of the INTERVAL columns in `file_schema`.
+    interval_columns: Vec<usize>,
+    /// The positions of the columns of `file_schema` cast by the option `cast`, with their
+    /// fields in the table schema.
+    cast_columns: Vec<(usize, DataField)>,
+    verify_checksums: bool,
+    /// The size of the string column chunks of a row group above which it is decoded
+    /// block by block, see [`ParquetReader::block_rows`].
+    max_string_block_bytes: usize,
+    /// The bound of the memory a row group is decoded in, see [`ParquetReader::block_rows`].
+    memory_budget: Option<MemoryBudget>,
+    /// Whether the TIMESTAMP values out of the range of the table timestamps are clamped
+    /// to its bounds instead of failing, see [`ParquetReader::timestamp_scale`].
+    saturate_timestamps: bool,
+    /// Whether the DECIMAL values of fixed length byte arrays are little-endian instead of
+    /// big-endian, see [`ParquetReader::decimal_scale`].
+    little_endian_decimals: bool,
+    /// The DECIMAL columns cast by the option `cast` by their names, see
+    /// [`ParquetReader::decimal_scale`].
+    decimal_casts: Vec<(String, DecimalCast)>,
+    /// The timezone of the TIMESTAMP values not adjusted to UTC.
+    timezone: Tz,
 }
 
 impl ParquetReader {
+    /// Create a reader of `projection` of the table `schema`.
+    ///
+    /// `partition_fields` are the indices of the hive partition fields of `schema`, which
+    /// are not stored in the files but filled from the partition values of the parts.
+    /// `casts` are the indices of the fields whose types in the files are different from
+    /// the ones of `schema`, with their types in the files.
+    /// `max_string_block_bytes` bounds the sizes of the decoded string columns of a block.
+    /// `memory_budget` bounds the memory the row group of a part is decoded in.
+    /// `saturate_timestamps` clamps the TIMESTAMP values out of range instead of failing.
+    /// `little_endian_decimals` reads the DECIMAL fixed length byte arrays as little-endian.
+    /// `decimal_casts` are the indices of the DECIMAL fields of `schema` cast to other
+    /// DECIMAL types, whose values are rounded to their scales.
+    /// `timezone` is the timezone the TIMESTAMP values not adjusted to UTC are taken in.
+    #[allow(clippy::too_many_arguments)]
     pub fn create(
-        operator: Operator,
+        operators: FileOperators,
         schema: DataSchemaRef,
         projection: Projection,
+        interval_fields: &[usize],
+        partition_fields: &[usize],
+        casts: &[(usize, DataTypeImpl)],
+        verify_checksums: bool,
+        max_string_block_bytes: usize,
+        memory_budget: Option<MemoryBudget>,
+        saturate_timestamps: bool,
+        little_endian_decimals: bool,
+        decimal_casts: &[(usize, DecimalCast)],
+        timezone: Tz,
     ) -> Result<Arc<ParquetReader>> {
-        let projected_schema = match projection {
-            Projection::Columns(ref indices) => DataSchemaRef::new(schema.project(indices)),
-            Projection::InnerColumns(ref path_indices) => {
-                DataSchemaRef::new(schema.inner_project(path_indices))
+        let projected_schema = DataSchemaRef::new(projection.project_schema(&schema));
+
+        // Partition fields come after the fields stored in the files, which are decoded as
+        // their types in the files.
+        let file_fields = schema
+            .fields()
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !partition_fields.contains(index))
+            .map(
+                |(index, field)| match casts.iter().find(|(i, _)| *i == index) {
+                    Some((_, data_type)) => DataField::new(field.name(), data_type.clone()),
+                    None => field.clone(),
+                },
+            )
+            .collect::<Vec<_>>();
+        let table_file_schema = DataSchema::new(file_fields);
+
+        let is_partition = |index: &usize| partition_fields.contains(index);
+        let partition_column = |index: &usize| {
+            let position = partition_fields.iter().position(|i| i == index).unwrap();
+            (position, schema.field(*index).clone())
+        };
+        let (mut projection, partition_columns) = match projection {
+            Projection::Columns(indices) => {
+                let partition_columns = indices
+                    .iter()
+                    .filter(|index| is_partition(*index))
+                    .map(partition_column)
+                    .collect::<Vec<_>>();
+                let indices = indices.into_iter().filter(|i| !is_partition(i)).collect();
+                (Projection::Columns(indices), partition_columns)
+            }
+            Projection::InnerColumns(path_indices) => {
+                let partition_columns = path_indices
+                    .values()
+                    .filter(|path| is_partition(&path[0]))
+                    .map(|path| partition_column(&path[0]))
+                    .collect::<Vec<_>>();
+                let path_indices = path_indices
+                    .into_iter()
+                    .filter(|(_, path)| !is_partition(&path[0]))
+                    .collect();
+                (Projection::InnerColumns(path_indices), partition_columns)
             }
         };
 
-        let arrow_schema = schema.to_arrow();
+        // The files are still read for the number of rows.
+        let count_only = projection.is_empty() && !partition_columns.is_empty();
+        if count_only {
+            projection = Projection::Columns(vec![0]);
+        }
+        let file_schema = DataSchemaRef::new(projection.project_schema(&table_file_schema));
+
+        let interval_columns = match projection {
+            Projection::Columns(ref indices) => indices
+                .iter()
+                .enumerate()
+                .filter(|(_, index)| interval_fields.contains(index))
+                .map(|(i, _)| i)
+                .collect(),
+            Projection::InnerColumns(ref path_indices) => path_indices
+                .values()
+                .enumerate()
+                .filter(|(_, path)| path.len() == 1 && interval_fields.contains(&path[0]))
+                .map(|(i, _)| i)
+                .collect(),
+        };
+
+        let is_cast = |index: &usize| casts.iter().any(|(i, _)| i == index);
+        let cast_columns = match projection {
+            _ if count_only => vec![],
+            Projection::Columns(ref indices) => indices
+                .iter()
+                .enumerate()
+                .filter(|(_, index)| is_cast(index))
+                .map(|(i, index)| (i, schema.field(*index).clone()))
+                .collect(),
+            Projection::InnerColumns(ref path_indices) => path_indices
+                .values()
+                .enumerate()
+                .filter(|(_, path)| path.len() == 1 && is_cast(&path[0]))
+                .map(|(i, path)| (i, schema.field(path[0]).clone()))
+                .collect(),
+        };
+
+        let decimal_casts = decimal_casts
+            .iter()
+            .map(|(index, cast)| (schema.field(*index).name().clone(), *cast))
+            .collect();
+
+        let arrow_schema = physical_arrow_schema(&table_file_schema, interval_fields);
         let parquet_schema_descriptor = to_parquet_schema(&arrow_schema)?;
         let column_leaves = ColumnLeaves::new_from_schema(&arrow_schema);
 
         Ok(Arc::new(ParquetReader {
-            operator,
+            operators,
             projection,
             projected_schema,
+            file_schema,
+            partition_columns,
+            count_only,
             parquet_schema_descriptor,
             column_leaves,
+            interval_columns,
+            cast_columns,
+            verify_checksums,
+            max_string_block_bytes,
+            memory_budget,
+            saturate_timestamps,
+            little_endian_decimals,
+            decimal_casts,
+            timezone,
         }))
     }
 
@@ -65,6 +239,51 @@ impl ParquetReader {
     }
 
     pub fn support_blocking_api(&self) -> bool {
-        self.operator.metadata().can_blocking()
+        self.operators.can_blocking()
+    }
+}
+
+/// The arrow schema the columns of `file_schema` are decoded as.
+///
+/// INTERVAL columns are decoded as fixed size binaries, and split after. Strings are
+/// decoded with 64-bit offsets, see [`large_offsets`].
+pub(crate) fn physical_arrow_schema(
+    file_schema: &DataSchema,
+    interval_fields: &[usize],
+) -> ArrowSchema {
+    let mut arrow_schema = file_schema.to_arrow();
+    for field in arrow_schema.fields.iter_mut() {
+        field.data_type = large_offsets(&field.data_type);
+    }
+    for index in interval_fields {
+        arrow_schema.fields[*index] = interval::interval_physical_field(file_schema.field(*index));
+    }
+    arrow_schema
+}
+
+/// `data_type` with the 64-bit offset variants of the string and binary types, nested
+/// ones included.
+///
+/// The offsets of a decoded string array are the cumulative sizes of its values, a column
+/// chunk with more than 2GB of strings would overflow 32-bit ones. The 64-bit variants are
+/// converted to the same `DataSchema` types.
+fn large_offsets(data_type: &ArrowType) -> ArrowType {
+    let large_field = |field: &ArrowField| {
+        let mut field = field.clone();
+        field.data_type = large_offsets(&field.data_type);
+        field
+    };
+    match data_type {
+        ArrowType::Utf8 => ArrowType::LargeUtf8,
+        ArrowType::Binary => ArrowType::LargeBinary,
+        ArrowType::List(field) => ArrowType::List(Box::new(large_field(field))),
+        ArrowType::LargeList(field) => ArrowType::LargeList(Box::new(large_field(field))),
+        ArrowType::Struct(fields) => ArrowType::Struct(fields.iter().map(large_field).collect()),
+        ArrowType::Extension(name, inner, metadata) => ArrowType::Extension(
+            name.clone(),
+            Box::new(large_offsets(inner)),
+            metadata.clone(),
+        ),
+        _ => data_type.clone(),
     }
 }
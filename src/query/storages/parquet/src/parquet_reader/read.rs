@@ -25,18 +25,20 @@ use crate::ParquetReader;
 impl ParquetReader {
     pub async fn read_columns_data(&self, part: PartInfoPtr) -> Result<Vec<(usize, Vec<u8>)>> {
         let part = ParquetPartInfo::from_part(&part)?;
-        let columns = self.projection.project_column_leaves(&self.column_leaves)?;
-        let indices = Self::build_projection_indices(&columns);
-        let mut join_handlers = Vec::with_capacity(indices.len());
+        let columns = self.column_ranges(part)?;
+        let _file = self.operators.open_file(&part.location).await?;
+        if let Some(chunks) = self.read_mapped_columns(part, &columns)? {
+            return Ok(chunks);
+        }
 
-        for index in indices {
-            let column_meta = &part.columns_meta[&index];
-            join_handlers.push(Self::read_column(
-                self.operator.object(&part.location),
-                index,
-                column_meta.offset,
-                column_meta.length,
-            ));
+        let mut join_handlers = Vec::with_capacity(columns.len());
+        for (index, offset, length) in columns {
+            let object = self.operators.object(&part.location)?;
+            let operators = &self.operators;
+            join_handlers.push(async move {
+                let _permit = operators.permit().await?;
+                Self::read_column(object, index, offset, length).await
+            });
         }
 
         futures::future::try_join_all(join_handlers).await
@@ -44,27 +46,65 @@ impl ParquetReader {
 
     pub fn sync_read_columns_data(&self, part: PartInfoPtr) -> Result<Vec<(usize, Vec<u8>)>> {
         let part = ParquetPartInfo::from_part(&part)?;
+        let columns = self.column_ranges(part)?;
+        let _file = self.operators.blocking_open_file(&part.location)?;
+        if let Some(chunks) = self.read_mapped_columns(part, &columns)? {
+            return Ok(chunks);
+        }
 
-        let columns = self.projection.project_column_leaves(&self.column_leaves)?;
-        let indices = Self::build_projection_indices(&columns);
-        let mut results = Vec::with_capacity(indices.len());
-
-        for index in indices {
-            let column_meta = &part.columns_meta[&index];
-
-            let op = self.operator.clone();
-
-            let location = part.location.clone();
-            let offset = column_meta.offset;
-            let length = column_meta.length;
-
-            let result = Self::sync_read_column(op.object(&location), index, offset, length);
+        let mut results = Vec::with_capacity(columns.len());
+        for (index, offset, length) in columns {
+            let _permit = self.operators.blocking_permit()?;
+            let result = Self::sync_read_column(
+                self.operators.object(&part.location)?,
+                index,
+                offset,
+                length,
+            );
             results.push(result?);
         }
 
         Ok(results)
     }
 
+    /// The leaf ids of the projected columns of `part`, with the offsets and the lengths of
+    /// their chunks. The columns missing in the file of the part are not read.
+    fn column_ranges(&self, part: &ParquetPartInfo) -> Result<Vec<(usize, u64, u64)>> {
+        let columns = self.projection.project_column_leaves(&self.column_leaves)?;
+        let indices = Self::build_projection_indices(&columns);
+        Ok(indices
+            .into_iter()
+            .filter_map(|index| {
+                let column_meta = part.columns_meta.get(&index)?;
+                Some((index, column_meta.offset, column_meta.length))
+            })
+            .collect())
+    }
+
+    /// Copy the chunks of `columns` from the file of `part` mapped in memory.
+    ///
+    /// Returns `None` if the file is not mapped, or if it is shorter than the chunks, then
+    /// they are read through the operator.
+    fn read_mapped_columns(
+        &self,
+        part: &ParquetPartInfo,
+        columns: &[(usize, u64, u64)],
+    ) -> Result<Option<Vec<(usize, Vec<u8>)>>> {
+        let file = match self.operators.mapped_file(&part.location)? {
+            Some(file) => file,
+            None => return Ok(None),
+        };
+        let mut chunks = Vec::with_capacity(columns.len());
+        for (index, offset, length) in columns {
+            let (start, end) = (*offset as usize, (*offset + *length) as usize);
+            match file.get(start..end) {
+                Some(chunk) => chunks.push((*index, chunk.to_vec())),
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(chunks))
+    }
+
     pub async fn read_column(
         o: Object,
         index: usize,
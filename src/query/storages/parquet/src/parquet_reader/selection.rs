@@ -0,0 +1,207 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::ops::Range;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use common_arrow::arrow::array::new_empty_array;
+use common_arrow::arrow::array::new_null_array;
+use common_arrow::arrow::array::Array;
+use common_arrow::arrow::array::BooleanArray;
+use common_arrow::arrow::bitmap::Bitmap;
+use common_arrow::arrow::chunk::Chunk;
+use common_arrow::arrow::compute::filter::filter;
+use common_arrow::arrow::datatypes::DataType as ArrowType;
+use common_arrow::parquet::page::ParquetPageHeader;
+use common_arrow::parquet::read::PageFilter;
+use common_catalog::plan::PartInfoPtr;
+use common_datablocks::DataBlock;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_storage::ColumnLeaf;
+use parquet_format_safe::thrift::protocol::TCompactInputProtocol;
+
+use super::cast::cast_columns;
+use super::deserialize::chunk_to_block;
+use super::deserialize::decode_error;
+use super::deserialize::fill_partitions;
+use crate::ParquetPartInfo;
+use crate::ParquetReader;
+
+impl ParquetReader {
+    /// Deserialize the rows of the row group of `part` selected by `selection`, which has
+    /// a bit per row of the row group.
+    ///
+    /// This is for late materialization, the rows are selected by the filters on the
+    /// columns read first and only they are decoded from the column chunks `chunks` of the
+    /// other columns. The data pages of the non-nested columns without any selected row
+    /// are skipped without being decompressed, the rows of the other pages are decoded and
+    /// filtered.
+    pub fn deserialize_selected(
+        &self,
+        part: PartInfoPtr,
+        chunks: Vec<(usize, Vec<u8>)>,
+        selection: &Bitmap,
+    ) -> Result<DataBlock> {
+        let partition_values = self.partition_values(&part)?;
+        let part = ParquetPartInfo::from_part(&part)?;
+        if selection.len() != part.nums_rows {
+            return Err(ErrorCode::BadArguments(format!(
+                "The row selection has {} rows, but row group {} of parquet file '{}' has {}",
+                selection.len(),
+                part.row_group,
+                part.location,
+                part.nums_rows
+            )));
+        }
+        let chunk_map: HashMap<usize, Vec<u8>> = chunks.into_iter().collect();
+        self.verify_chunks(part, &chunk_map)?;
+
+        let columns = self.projection.project_column_leaves(&self.column_leaves)?;
+        let arrays = columns
+            .iter()
+            .map(|column| self.selected_array(part, &chunk_map, column, selection))
+            .collect::<Result<Vec<_>>>()?;
+        let chunk = Chunk::try_new(arrays)?;
+        let block = chunk_to_block(&self.file_schema, &chunk, &self.interval_columns)?;
+        let block = cast_columns(block, &self.cast_columns)?;
+        fill_partitions(block, &partition_values, self.count_only)
+    }
+
+    /// The rows of `column` selected by `selection`.
+    fn selected_array(
+        &self,
+        part: &ParquetPartInfo,
+        chunk_map: &HashMap<usize, Vec<u8>>,
+        column: &ColumnLeaf,
+        selection: &Bitmap,
+    ) -> Result<Box<dyn Array>> {
+        let field = column.field.clone();
+        let indices = &column.leaf_ids;
+        // The columns missing in the file of the part, merged by name, are all NULL.
+        if indices
+            .iter()
+            .any(|index| !part.columns_meta.contains_key(index))
+        {
+            let num_selected = selection.iter().filter(|selected| *selected).count();
+            return Ok(new_null_array(field.data_type, num_selected));
+        }
+
+        // The rows of the pages of nested columns are only known once decoded, all of them
+        // are read.
+        let page_rows = if indices.len() == 1 && !is_nested(&field.data_type) {
+            data_page_rows(&chunk_map[&indices[0]]).map_err(|e| {
+                ErrorCode::ParquetFileInvalid(format!(
+                    "Read page headers of column '{}' in row group {} of parquet file '{}' error: {}",
+                    field.name, part.row_group, part.location, e
+                ))
+            })?
+        } else {
+            vec![part.nums_rows]
+        };
+        let mut kept_pages = Vec::with_capacity(page_rows.len());
+        let mut kept_ranges: Vec<Range<usize>> = vec![];
+        let mut first_row = 0;
+        for rows in page_rows {
+            let range = first_row..(first_row + rows).min(selection.len());
+            let kept = range.clone().any(|row| selection.get_bit(row));
+            if kept {
+                kept_ranges.push(range);
+            }
+            kept_pages.push(kept);
+            first_row += rows;
+        }
+
+        let rows = kept_ranges.iter().map(|range| range.len()).sum::<usize>();
+        if rows == 0 {
+            return Ok(new_empty_array(field.data_type));
+        }
+        let next_page = AtomicUsize::new(0);
+        let pages_filter: PageFilter = Arc::new(move |_, _| {
+            let page = next_page.fetch_add(1, Ordering::Relaxed);
+            kept_pages.get(page).copied().unwrap_or(true)
+        });
+        let metas = indices
+            .iter()
+            .map(|index| &part.columns_meta[index])
+            .collect::<Vec<_>>();
+        let scale = self.timestamp_scale(part, &metas, &field, kept_ranges.clone());
+        let decimal = self.decimal_scale(part, &metas, &field)?;
+        let mut arrays = Self::to_array_iter(
+            metas,
+            indices.iter().map(|index| chunk_map[index].clone()).collect(),
+            rows,
+            rows,
+            indices
+                .iter()
+                .map(|index| &self.parquet_schema_descriptor.columns()[*index])
+                .collect(),
+            field,
+            pages_filter,
+            self.timezone,
+            scale,
+            decimal,
+        )?;
+        let array = match arrays.next() {
+            Some(array) => array.map_err(decode_error)?,
+            None => {
+                return Err(ErrorCode::Internal(
+                    "deserializer from row group: fail to get a chunk",
+                ));
+            }
+        };
+
+        let mask = kept_ranges
+            .into_iter()
+            .flatten()
+            .map(|row| selection.get_bit(row))
+            .collect::<Vec<_>>();
+        filter(array.as_ref(), &BooleanArray::from_slice(mask)).map_err(decode_error)
+    }
+}
+
+fn is_nested(data_type: &ArrowType) -> bool {
+    matches!(
+        data_type,
+        ArrowType::List(_)
+            | ArrowType::LargeList(_)
+            | ArrowType::FixedSizeList(_, _)
+            | ArrowType::Struct(_)
+            | ArrowType::Map(_, _)
+    )
+}
+
+/// The numbers of rows of the data pages of the column chunk `chunk` of a non-nested
+/// column, read from their headers.
+fn data_page_rows(chunk: &[u8]) -> std::result::Result<Vec<usize>, String> {
+    let mut reader = Cursor::new(chunk);
+    let mut page_rows = vec![];
+    while (reader.position() as usize) < chunk.len() {
+        let mut prot = TCompactInputProtocol::new(&mut reader, chunk.len());
+        let header = ParquetPageHeader::read_from_in_protocol(&mut prot)
+            .map_err(|e| e.to_string())?;
+        if let Some(data_header) = &header.data_page_header {
+            page_rows.push(data_header.num_values.max(0) as usize);
+        } else if let Some(data_header) = &header.data_page_header_v2 {
+            page_rows.push(data_header.num_rows.max(0) as usize);
+        }
+        let end = reader.position() + header.compressed_page_size.max(0) as u64;
+        reader.set_position(end);
+    }
+    Ok(page_rows)
+}
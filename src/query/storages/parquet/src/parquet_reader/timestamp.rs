@@ -0,0 +1,189 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parquet TIMESTAMP columns not adjusted to UTC.
+//!
+//! Their values are local times, the wall clock readings of an unknown timezone, while the
+//! timestamps of the table are UTC instants rendered in the session timezone. The local
+//! times are taken as times of the timezone of the reader, so that they are rendered as
+//! they are written when the session has the same timezone.
+//!
+//! The values of the TIMESTAMP column chunks of milliseconds or nanoseconds are scaled to
+//! the microseconds of the table, with the ones out of the range of the table timestamps
+//! handled by a policy instead of wrapping around.
+
+use std::ops::Range;
+
+use chrono::NaiveDateTime;
+use chrono::Offset;
+use chrono::TimeZone;
+use chrono_tz::Tz;
+use common_arrow::arrow::array::Array;
+use common_arrow::arrow::array::PrimitiveArray;
+use common_arrow::arrow::datatypes::DataType as ArrowType;
+use common_arrow::arrow::datatypes::TimeUnit;
+use common_datavalues::TIMESTAMP_MAX;
+use common_datavalues::TIMESTAMP_MIN;
+use common_exception::ErrorCode;
+use common_exception::Result;
+
+use crate::TimestampUnit;
+
+const MICROS_PER_SECOND: i64 = 1_000_000;
+
+/// A TIMESTAMP column chunk of milliseconds or nanoseconds, scaled to microseconds by
+/// [`scale_timestamps`].
+pub struct TimestampScale {
+    /// The unit of the values of the column chunk.
+    pub unit: TimestampUnit,
+    /// Clamp the values out of the range of the table timestamps, from 1000-01-01 to
+    /// 9999-12-31, to its bounds instead of failing.
+    pub saturate: bool,
+    pub location: String,
+    pub row_group: usize,
+    pub column: String,
+    /// The ranges of the rows of the row group the values are decoded from, in order.
+    pub rows: Vec<Range<usize>>,
+}
+
+impl TimestampScale {
+    /// The arrow type the values of the column chunk are decoded as, unscaled, for the
+    /// table type `data_type`.
+    pub fn decoded_type(&self, data_type: &ArrowType) -> ArrowType {
+        let unit = match self.unit {
+            TimestampUnit::Milliseconds => TimeUnit::Millisecond,
+            TimestampUnit::Microseconds => TimeUnit::Microsecond,
+            TimestampUnit::Nanoseconds => TimeUnit::Nanosecond,
+        };
+        match data_type {
+            ArrowType::Timestamp(_, timezone) => ArrowType::Timestamp(unit, timezone.clone()),
+            data_type => data_type.clone(),
+        }
+    }
+
+    /// The row in the row group of the `n`th decoded value.
+    fn row(&self, n: usize) -> usize {
+        let mut n = n;
+        for range in &self.rows {
+            if n < range.len() {
+                return range.start + n;
+            }
+            n -= range.len();
+        }
+        n
+    }
+}
+
+/// Scale the values of `array`, decoded in the unit of `scale`, to the microseconds of
+/// `data_type`. `first_value` is the position of the first value of `array` among the
+/// decoded values of the column chunk.
+///
+/// The milliseconds are checked not to overflow while scaled, the nanoseconds always fit.
+/// The values out of the range of the table timestamps fail, naming their file and row,
+/// unless `scale.saturate` is set.
+pub fn scale_timestamps(
+    array: Box<dyn Array>,
+    scale: &TimestampScale,
+    first_value: usize,
+    data_type: &ArrowType,
+) -> Result<Box<dyn Array>> {
+    let array = array
+        .as_any()
+        .downcast_ref::<PrimitiveArray<i64>>()
+        .ok_or_else(|| {
+            ErrorCode::Internal(format!(
+                "TIMESTAMP values should be decoded as int64, but got {:?}",
+                array.data_type()
+            ))
+        })?;
+
+    let mut values = Vec::with_capacity(array.len());
+    for (i, value) in array.values().iter().enumerate() {
+        let micros = match scale.unit {
+            TimestampUnit::Milliseconds => value.checked_mul(1000),
+            TimestampUnit::Microseconds => Some(*value),
+            TimestampUnit::Nanoseconds => Some(value / 1000),
+        };
+        let micros = match micros {
+            Some(micros) if (TIMESTAMP_MIN..=TIMESTAMP_MAX).contains(&micros) => micros,
+            _ if array.is_null(i) => 0,
+            _ if scale.saturate && *value < 0 => TIMESTAMP_MIN,
+            _ if scale.saturate => TIMESTAMP_MAX,
+            _ => {
+                return Err(ErrorCode::InvalidTimestamp(format!(
+                    "TIMESTAMP value {} ({:?}) of column '{}' at row {} of row group {} of parquet file '{}' is out of range, set read_parquet option 'on_timestamp_overflow=saturate' to clamp it",
+                    value,
+                    scale.unit,
+                    scale.column,
+                    scale.row(first_value + i),
+                    scale.row_group,
+                    scale.location
+                )));
+            }
+        };
+        values.push(micros);
+    }
+    Ok(Box::new(PrimitiveArray::new(
+        data_type.clone(),
+        values.into(),
+        array.validity().cloned(),
+    )))
+}
+
+/// Convert the local times of `array`, decoded as microseconds, to the UTC timestamps of
+/// the same times in `timezone`.
+pub fn localize_timestamps(array: Box<dyn Array>, timezone: Tz) -> Result<Box<dyn Array>> {
+    let array = array
+        .as_any()
+        .downcast_ref::<PrimitiveArray<i64>>()
+        .ok_or_else(|| {
+            ErrorCode::Internal(format!(
+                "TIMESTAMP values should be decoded as int64, but got {:?}",
+                array.data_type()
+            ))
+        })?;
+
+    let values = array
+        .values()
+        .iter()
+        .map(|micros| local_to_utc(*micros, timezone))
+        .collect::<Vec<_>>();
+    Ok(Box::new(PrimitiveArray::new(
+        array.data_type().clone(),
+        values.into(),
+        array.validity().cloned(),
+    )))
+}
+
+/// The UTC timestamp of the local time `micros` in `timezone`.
+///
+/// The offset is taken at the local time first, then at the instant it gives, so that the
+/// local times around the transitions of daylight saving time get the offsets in effect.
+fn local_to_utc(micros: i64, timezone: Tz) -> i64 {
+    let offset_at = |micros: i64| {
+        NaiveDateTime::from_timestamp_opt(
+            micros.div_euclid(MICROS_PER_SECOND),
+            (micros.rem_euclid(MICROS_PER_SECOND) * 1000) as u32,
+        )
+        .map(|datetime| {
+            let offset = timezone.offset_from_utc_datetime(&datetime).fix();
+            offset.local_minus_utc() as i64 * MICROS_PER_SECOND
+        })
+    };
+    match offset_at(micros).and_then(|offset| offset_at(micros - offset)) {
+        Some(offset) => micros - offset,
+        // Out of the range of the calendar, kept as it is.
+        None => micros,
+    }
+}
@@ -0,0 +1,114 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_arrow::parquet::encoding::delta_byte_array;
+use common_arrow::parquet::encoding::Encoding;
+use common_arrow::parquet::error::Error as ParquetError;
+use common_arrow::parquet::page::split_buffer;
+use common_arrow::parquet::page::DataPage;
+use common_arrow::parquet::page::DataPageHeader;
+use common_arrow::parquet::page::Page;
+use common_arrow::parquet::FallibleStreamingIterator;
+
+/// Pages of a column chunk, with the data pages arrow can not decode rewritten into
+/// an encoding it can.
+///
+/// Arrow decodes `DELTA_BINARY_PACKED` and `DELTA_LENGTH_BYTE_ARRAY` natively,
+/// but not `DELTA_BYTE_ARRAY`, so those pages are rewritten to `PLAIN`.
+pub struct TranscodedPages<I> {
+    inner: I,
+    transcoded: Option<Page>,
+}
+
+impl<I> TranscodedPages<I> {
+    pub fn new(inner: I) -> Self {
+        TranscodedPages {
+            inner,
+            transcoded: None,
+        }
+    }
+}
+
+impl<I> FallibleStreamingIterator for TranscodedPages<I>
+where I: FallibleStreamingIterator<Item = Page, Error = ParquetError>
+{
+    type Item = Page;
+    type Error = ParquetError;
+
+    fn advance(&mut self) -> Result<(), ParquetError> {
+        self.transcoded = None;
+        self.inner.advance()?;
+        if let Some(Page::Data(page)) = self.inner.get() {
+            if page.encoding() == Encoding::DeltaByteArray {
+                self.transcoded = Some(Page::Data(delta_byte_array_to_plain(page)?));
+            }
+        }
+        Ok(())
+    }
+
+    fn get(&self) -> Option<&Page> {
+        match &self.transcoded {
+            Some(page) => Some(page),
+            None => self.inner.get(),
+        }
+    }
+}
+
+/// Rebuild the values of a `DELTA_BYTE_ARRAY` page as `PLAIN` byte arrays,
+/// keeping the repetition and definition levels untouched.
+fn delta_byte_array_to_plain(page: &DataPage) -> Result<DataPage, ParquetError> {
+    let (_, _, values) = split_buffer(page)?;
+    let levels_len = page.buffer().len() - values.len();
+
+    let mut decoder = delta_byte_array::Decoder::try_new(values)?;
+    let prefix_lengths = decoder.by_ref().collect::<Result<Vec<_>, _>>()?;
+    let mut decoder = decoder.into_lengths()?;
+    let suffix_lengths = decoder.by_ref().collect::<Result<Vec<_>, _>>()?;
+    let suffixes = decoder.values();
+
+    let mut buffer = Vec::with_capacity(levels_len + suffixes.len() + 4 * suffix_lengths.len());
+    buffer.extend_from_slice(&page.buffer()[..levels_len]);
+
+    let mut value = Vec::new();
+    let mut offset = 0;
+    for (prefix_length, suffix_length) in prefix_lengths.into_iter().zip(suffix_lengths) {
+        let prefix_length = prefix_length as usize;
+        let suffix_length = suffix_length as usize;
+        if prefix_length > value.len() || offset + suffix_length > suffixes.len() {
+            return Err(ParquetError::OutOfSpec(
+                "DELTA_BYTE_ARRAY prefix or suffix length exceeds the page values".to_string(),
+            ));
+        }
+
+        value.truncate(prefix_length);
+        value.extend_from_slice(&suffixes[offset..offset + suffix_length]);
+        offset += suffix_length;
+
+        buffer.extend_from_slice(&(value.len() as u32).to_le_bytes());
+        buffer.extend_from_slice(&value);
+    }
+
+    let header = match page.header().clone() {
+        DataPageHeader::V1(mut header) => {
+            header.encoding = Encoding::Plain.into();
+            DataPageHeader::V1(header)
+        }
+        DataPageHeader::V2(mut header) => {
+            header.encoding = Encoding::Plain.into();
+            DataPageHeader::V2(header)
+        }
+    };
+
+    Ok(DataPage::new(header, buffer, page.descriptor.clone(), None))
+}
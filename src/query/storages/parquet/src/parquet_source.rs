@@ -13,7 +13,10 @@
 //  limitations under the License.
 
 use std::any::Any;
+use std::collections::VecDeque;
 use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
 
 use common_base::base::Progress;
 use common_base::base::ProgressValues;
@@ -30,8 +33,16 @@ use common_pipeline_core::processors::processor::ProcessorPtr;
 use common_pipeline_core::processors::Processor;
 use common_sql::evaluator::EvalNode;
 
+use crate::block_batch::BlockBatcher;
 use crate::parquet_reader::ParquetReader;
+use crate::parquet_reader::RowGroupBlocks;
 use crate::parquet_source::State::Generated;
+use crate::ParquetPartInfo;
+use crate::ParquetScanProgress;
+use crate::ParquetWarning;
+use crate::ParquetWarnings;
+use crate::ReadAheadWindow;
+use crate::WarningKind;
 
 type DataChunks = Vec<(usize, Vec<u8>)>;
 
@@ -40,15 +51,81 @@ pub struct PrewhereData {
     filter: ColumnRef,
 }
 
+/// The blocks of a row group deserialized block by block, see `ParquetReader::block_rows`.
+pub struct PartBlocks {
+    // The blocks of the output columns, or of the prewhere columns with prewhere.
+    blocks: RowGroupBlocks,
+    // The blocks of the remain columns with prewhere, in the same rows as `blocks`.
+    remain_blocks: Option<RowGroupBlocks>,
+}
+
+impl PartBlocks {
+    fn part(&self) -> &PartInfoPtr {
+        self.blocks.part()
+    }
+
+    /// The number of rows of the blocks decoded so far, with all their columns.
+    fn rows_read(&self) -> usize {
+        match &self.remain_blocks {
+            Some(remain_blocks) => remain_blocks.rows_read(),
+            None => self.blocks.rows_read(),
+        }
+    }
+
+    /// Decode the next block, with the remain columns after the prewhere columns if any.
+    fn next_block(&mut self) -> Result<Option<DataBlock>> {
+        let mut block = match self.blocks.next_block()? {
+            None => return Ok(None),
+            Some(block) => block,
+        };
+        if let Some(remain_blocks) = self.remain_blocks.as_mut() {
+            let remain_block = match remain_blocks.next_block()? {
+                Some(remain_block) if remain_block.num_rows() == block.num_rows() => remain_block,
+                _ => {
+                    return Err(ErrorCode::Internal(
+                        "It's a bug. The remain blocks don't match the prewhere blocks",
+                    ));
+                }
+            };
+            for (col, field) in remain_block
+                .columns()
+                .iter()
+                .zip(remain_block.schema().fields())
+            {
+                block = block.add_column(col.clone(), field.clone())?;
+            }
+        }
+        Ok(Some(block))
+    }
+}
+
 pub enum State {
     ReadDataPrewhere(Option<PartInfoPtr>),
     ReadDataRemain(PartInfoPtr, PrewhereData),
+    // The remain columns to read of a row group deserialized block by block with prewhere,
+    // with the data of its prewhere columns and the number of rows of the blocks.
+    ReadDataRemainBlocks(PartInfoPtr, DataChunks, usize),
     PrewhereFilter(PartInfoPtr, DataChunks),
     Deserialize(PartInfoPtr, DataChunks, Option<PrewhereData>),
+    // The row group is deserialized block by block, see `ParquetReader::block_rows`.
+    DeserializeBlocks(PartBlocks),
+    GeneratedBlock(PartBlocks, DataBlock),
     Generated(Option<PartInfoPtr>, DataBlock),
     Finish,
 }
 
+/// The options of a [`ParquetTableSource`] from the options of `read_parquet`, and the
+/// state shared by the sources of the scan.
+#[derive(Clone)]
+pub struct ParquetSourceOptions {
+    pub max_block_rows: Option<usize>,
+    pub skip_corrupt_row_groups: bool,
+    pub batch_size: Option<usize>,
+    pub read_ahead: Option<ReadAheadWindow>,
+    pub file_progress: Arc<ParquetScanProgress>,
+    pub warnings: Arc<ParquetWarnings>,
+}
+
 pub struct ParquetTableSource {
     state: State,
     ctx: Arc<dyn TableContext>,
@@ -61,6 +138,22 @@ pub struct ParquetTableSource {
     remain_reader: Arc<Option<ParquetReader>>,
 
     support_blocking: bool,
+    max_block_rows: Option<usize>,
+    skip_corrupt_row_groups: bool,
+    // The blocks re-chunked into the batches of the option `batch_size`, if set.
+    batcher: Option<BlockBatcher>,
+
+    // The parts read ahead, with the data of their prewhere columns, see `ReadAheadWindow`.
+    read_ahead: Option<ReadAheadWindow>,
+    prefetched: VecDeque<(PartInfoPtr, DataChunks)>,
+    // The time spent decoding since the last read ahead.
+    decode_time: Duration,
+    // The remaining parts of the last coalesced part taken, see `ParquetPartInfo::coalesce`.
+    coalesced: VecDeque<PartInfoPtr>,
+    // The files and bytes of the scan completed, shared by the sources of the scan.
+    file_progress: Arc<ParquetScanProgress>,
+    // The warnings of the table, shared by the sources of the scan.
+    warnings: Arc<ParquetWarnings>,
 }
 
 impl ParquetTableSource {
@@ -71,6 +164,7 @@ impl ParquetTableSource {
         prewhere_reader: Arc<ParquetReader>,
         prewhere_filter: Arc<Option<EvalNode>>,
         remain_reader: Arc<Option<ParquetReader>>,
+        options: ParquetSourceOptions,
     ) -> Result<ProcessorPtr> {
         let scan_progress = ctx.get_scan_progress();
         let support_blocking = prewhere_reader.support_blocking_api();
@@ -84,11 +178,73 @@ impl ParquetTableSource {
             prewhere_filter,
             remain_reader,
             support_blocking,
+            max_block_rows: options.max_block_rows,
+            skip_corrupt_row_groups: options.skip_corrupt_row_groups,
+            batcher: options.batch_size.map(BlockBatcher::create),
+            read_ahead: options.read_ahead,
+            prefetched: VecDeque::new(),
+            decode_time: Duration::ZERO,
+            coalesced: VecDeque::new(),
+            file_progress: options.file_progress,
+            warnings: options.warnings,
         })))
     }
 
+    /// The next part to read, the parts read ahead first.
+    fn next_part(&mut self) -> Option<PartInfoPtr> {
+        match self.prefetched.front() {
+            Some((part, _)) => Some(part.clone()),
+            None => self.take_part(),
+        }
+    }
+
+    /// Take the next part to read, the coalesced parts are taken one by one.
+    fn take_part(&mut self) -> Option<PartInfoPtr> {
+        if self.coalesced.is_empty() {
+            let part = self.ctx.try_get_part()?;
+            self.coalesced.extend(ParquetPartInfo::expand(part));
+        }
+        self.coalesced.pop_front()
+    }
+
+    /// Record that all the rows of `part` were read, or skipped.
+    fn complete_part(&self, part: &PartInfoPtr) -> Result<()> {
+        self.file_progress.complete(ParquetPartInfo::from_part(part)?);
+        Ok(())
+    }
+
+    /// Read the prewhere columns of `part` and of the next parts of `window` concurrently.
+    ///
+    /// The data of the next parts is kept until they are turned to, and the window is
+    /// resized by the latency of the reads.
+    async fn read_ahead(
+        &mut self,
+        part: PartInfoPtr,
+        mut window: ReadAheadWindow,
+    ) -> Result<DataChunks> {
+        let mut parts = vec![part];
+        while parts.len() < window.size() {
+            match self.take_part() {
+                None => break,
+                Some(part) => parts.push(part),
+            }
+        }
+
+        let start = Instant::now();
+        let reads = parts
+            .iter()
+            .map(|part| self.prewhere_reader.read_columns_data(part.clone()));
+        let mut chunks = futures::future::try_join_all(reads).await?;
+        window.observe(start.elapsed(), std::mem::take(&mut self.decode_time));
+        self.read_ahead = Some(window);
+
+        let first = chunks.remove(0);
+        self.prefetched.extend(parts.into_iter().skip(1).zip(chunks));
+        Ok(first)
+    }
+
     fn generate_one_block(&mut self, block: DataBlock) -> Result<()> {
-        let new_part = self.ctx.try_get_part();
+        let new_part = self.next_part();
         // resort and prune columns
         let block = block.resort(self.output_reader.schema())?;
         self.state = State::Generated(new_part, block);
@@ -97,80 +253,184 @@ impl ParquetTableSource {
 
     fn generate_one_empty_block(&mut self) -> Result<()> {
         let schema = self.output_reader.schema();
-        let new_part = self.ctx.try_get_part();
+        let new_part = self.next_part();
         self.state = Generated(new_part, DataBlock::empty_with_schema(schema));
         Ok(())
     }
-}
-
-#[async_trait::async_trait]
-impl Processor for ParquetTableSource {
-    fn name(&self) -> String {
-        "ParquetSource".to_string()
-    }
-
-    fn as_any(&mut self) -> &mut dyn Any {
-        self
-    }
 
-    fn event(&mut self) -> Result<Event> {
-        if matches!(self.state, State::ReadDataPrewhere(None)) {
-            self.state = match self.ctx.try_get_part() {
-                None => State::Finish,
-                Some(part) => State::ReadDataPrewhere(Some(part)),
+    /// Push `block` to the output, or batch it if `batch_size` is set, the batches are
+    /// pushed once they are full.
+    fn push_block(&mut self, block: DataBlock) -> Result<Event> {
+        match self.batcher.as_mut() {
+            Some(batcher) => {
+                batcher.push(block);
+                self.event()
+            }
+            None => {
+                self.output.push_data(Ok(block));
+                Ok(Event::NeedConsume)
             }
         }
+    }
 
-        if matches!(self.state, State::Finish) {
-            self.output.finish();
-            return Ok(Event::Finished);
-        }
-
-        if self.output.is_finished() {
-            return Ok(Event::Finished);
-        }
-
-        if !self.output.can_push() {
-            return Ok(Event::NeedConsume);
-        }
+    /// Skip the row group of `part` which fails to decode, and turn to the next part.
+    ///
+    /// The row group may fail after some of its blocks are pushed if it is decoded block
+    /// by block, those `rows_read` rows are kept. The rest of the rows of the row group are
+    /// still counted as scanned.
+    fn skip_row_group(
+        &mut self,
+        part: &PartInfoPtr,
+        rows_read: usize,
+        cause: ErrorCode,
+    ) -> Result<()> {
+        self.complete_part(part)?;
+        let part = ParquetPartInfo::from_part(part)?;
+        let kind = if cause.code() == ErrorCode::PARQUET_CHECKSUM_MISMATCH {
+            WarningKind::ChecksumMismatch
+        } else {
+            WarningKind::SkippedRowGroup
+        };
+        let message = if rows_read == 0 {
+            format!(
+                "Skip row group {} of parquet file '{}' ({} rows): {}",
+                part.row_group, part.location, part.nums_rows, cause
+            )
+        } else {
+            format!(
+                "Skip the last {} rows of row group {} of parquet file '{}' ({} rows, the first {} are read): {}",
+                part.nums_rows - rows_read,
+                part.row_group,
+                part.location,
+                part.nums_rows,
+                rows_read,
+                cause
+            )
+        };
+        let warning = ParquetWarning::create(kind, message)
+            .with_location(&part.location)
+            .with_row_group(part.row_group);
+        self.warnings.add(warning);
+        let progress_values = ProgressValues {
+            rows: part.nums_rows - rows_read,
+            bytes: 0,
+        };
+        self.scan_progress.incr(&progress_values);
+        self.generate_one_empty_block()
+    }
 
-        if matches!(self.state, State::Generated(_, _)) {
-            if let Generated(part, data_block) = std::mem::replace(&mut self.state, State::Finish) {
-                self.state = match part {
-                    None => State::Finish,
-                    Some(part) => State::ReadDataPrewhere(Some(part)),
-                };
+    /// Deserialize the row group of `part` block by block with prewhere, in blocks of
+    /// `max_block_rows` rows, once the data of its remain columns is read if any.
+    fn prewhere_blocks(
+        &self,
+        part: PartInfoPtr,
+        chunks: DataChunks,
+        remain_chunks: Option<DataChunks>,
+        max_block_rows: usize,
+    ) -> Result<State> {
+        let remain_blocks = match (self.remain_reader.as_ref(), remain_chunks) {
+            (Some(remain_reader), Some(remain_chunks)) => Some(
+                remain_reader.deserialize_blocks(part.clone(), remain_chunks, max_block_rows)?,
+            ),
+            _ => None,
+        };
+        let blocks = self
+            .prewhere_reader
+            .deserialize_blocks(part, chunks, max_block_rows)?;
+        Ok(State::DeserializeBlocks(PartBlocks {
+            blocks,
+            remain_blocks,
+        }))
+    }
 
-                self.output.push_data(Ok(data_block));
-                return Ok(Event::NeedConsume);
+    /// The part being decoded by `state`, if any, with the number of its rows already read.
+    fn decoding_part(state: &State) -> Option<(PartInfoPtr, usize)> {
+        match state {
+            State::PrewhereFilter(part, _) | State::Deserialize(part, _, _) => {
+                Some((part.clone(), 0))
             }
+            State::DeserializeBlocks(blocks) => Some((blocks.part().clone(), blocks.rows_read())),
+            _ => None,
         }
+    }
 
-        match self.state {
-            State::Finish => Ok(Event::Finished),
-            State::ReadDataPrewhere(_) => {
-                if self.support_blocking {
-                    Ok(Event::Sync)
-                } else {
-                    Ok(Event::Async)
+    fn do_process(&mut self) -> Result<()> {
+        let state = match std::mem::replace(&mut self.state, State::Finish) {
+            State::Deserialize(part, chunks, None) => {
+                match self
+                    .output_reader
+                    .block_rows(&part, self.max_block_rows, None)?
+                {
+                    Some(max_block_rows) => {
+                        let blocks = self
+                            .output_reader
+                            .deserialize_blocks(part, chunks, max_block_rows)?;
+                        self.state = State::DeserializeBlocks(PartBlocks {
+                            blocks,
+                            remain_blocks: None,
+                        });
+                        return Ok(());
+                    }
+                    None => State::Deserialize(part, chunks, None),
                 }
             }
-            State::ReadDataRemain(_, _) => {
-                if self.support_blocking {
-                    Ok(Event::Sync)
-                } else {
-                    Ok(Event::Async)
+            // With prewhere, the prewhere and the remain columns are deserialized in the
+            // same blocks, and each block is filtered.
+            State::PrewhereFilter(part, chunks) => {
+                let remain_reader = (*self.remain_reader).as_ref();
+                match self
+                    .prewhere_reader
+                    .block_rows(&part, self.max_block_rows, remain_reader)?
+                {
+                    Some(max_block_rows) if remain_reader.is_some() => {
+                        self.state = State::ReadDataRemainBlocks(part, chunks, max_block_rows);
+                        return Ok(());
+                    }
+                    Some(max_block_rows) => {
+                        self.state = self.prewhere_blocks(part, chunks, None, max_block_rows)?;
+                        return Ok(());
+                    }
+                    None => State::PrewhereFilter(part, chunks),
                 }
             }
-            State::PrewhereFilter(_, _) => Ok(Event::Sync),
-            State::Deserialize(_, _, _) => Ok(Event::Sync),
-            State::Generated(_, _) => Err(ErrorCode::Internal("It's a bug.")),
-        }
-    }
+            state => state,
+        };
 
-    fn process(&mut self) -> Result<()> {
-        match std::mem::replace(&mut self.state, State::Finish) {
+        match state {
+            State::DeserializeBlocks(mut blocks) => {
+                match blocks.next_block()? {
+                    Some(block) => {
+                        let progress_values = ProgressValues {
+                            rows: block.num_rows(),
+                            bytes: block.memory_size(),
+                        };
+                        self.scan_progress.incr(&progress_values);
+                        // The prewhere columns come first in the block, where the filter
+                        // expects them.
+                        let block = match self.prewhere_filter.as_ref() {
+                            Some(filter) => {
+                                let res = filter.eval(&FunctionContext::default(), &block)?.vector;
+                                let filter = DataBlock::cast_to_nonull_boolean(&res)?;
+                                DataBlock::filter_block(block, &filter)?
+                            }
+                            None => block,
+                        };
+                        let block = block.resort(self.output_reader.schema())?;
+                        self.state = State::GeneratedBlock(blocks, block);
+                    }
+                    None => {
+                        // all blocks of the row group are generated, turn to read next part
+                        self.complete_part(blocks.part())?;
+                        self.state = match self.next_part() {
+                            None => State::Finish,
+                            Some(part) => State::ReadDataPrewhere(Some(part)),
+                        };
+                    }
+                }
+                Ok(())
+            }
             State::Deserialize(part, chunks, prewhere_data) => {
+                let completed = part.clone();
                 let data_block = if let Some(PrewhereData {
                     data_block: mut prewhere_blocks,
                     filter,
@@ -210,6 +470,7 @@ impl Processor for ParquetTableSource {
                     block
                 };
 
+                self.complete_part(&completed)?;
                 self.generate_one_block(data_block)?;
                 Ok(())
             }
@@ -231,6 +492,7 @@ impl Processor for ParquetTableSource {
                             bytes: data_block.memory_size(),
                         };
                         self.scan_progress.incr(&progress_values);
+                        self.complete_part(&part)?;
                         self.generate_one_empty_block()?;
                         return Ok(());
                     }
@@ -242,6 +504,7 @@ impl Processor for ParquetTableSource {
                         };
                         self.scan_progress.incr(&progress_values);
                         let block = DataBlock::filter_block(data_block, &filter)?;
+                        self.complete_part(&part)?;
                         self.generate_one_block(block)?;
                     } else {
                         self.state =
@@ -256,7 +519,10 @@ impl Processor for ParquetTableSource {
             }
 
             State::ReadDataPrewhere(Some(part)) => {
-                let chunks = self.prewhere_reader.sync_read_columns_data(part.clone())?;
+                let chunks = match self.prefetched.pop_front() {
+                    Some((_, chunks)) => chunks,
+                    None => self.prewhere_reader.sync_read_columns_data(part.clone())?,
+                };
 
                 if self.prewhere_filter.is_some() {
                     self.state = State::PrewhereFilter(part, chunks);
@@ -275,14 +541,126 @@ impl Processor for ParquetTableSource {
                     Err(ErrorCode::Internal("It's a bug. No remain reader"))
                 }
             }
+            State::ReadDataRemainBlocks(part, chunks, max_block_rows) => {
+                if let Some(remain_reader) = self.remain_reader.as_ref() {
+                    let remain_chunks = remain_reader.sync_read_columns_data(part.clone())?;
+                    self.state =
+                        self.prewhere_blocks(part, chunks, Some(remain_chunks), max_block_rows)?;
+                    Ok(())
+                } else {
+                    Err(ErrorCode::Internal("It's a bug. No remain reader"))
+                }
+            }
             _ => Err(ErrorCode::Internal("It's a bug.")),
         }
     }
+}
+
+#[async_trait::async_trait]
+impl Processor for ParquetTableSource {
+    fn name(&self) -> String {
+        "ParquetSource".to_string()
+    }
+
+    fn as_any(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn event(&mut self) -> Result<Event> {
+        if matches!(self.state, State::ReadDataPrewhere(None)) {
+            self.state = match self.next_part() {
+                None => State::Finish,
+                Some(part) => State::ReadDataPrewhere(Some(part)),
+            }
+        }
+
+        // The rows left in the batches are pushed before finishing.
+        let finished = matches!(self.state, State::Finish);
+        if finished && self.batcher.as_ref().map_or(true, BlockBatcher::is_empty) {
+            self.output.finish();
+            return Ok(Event::Finished);
+        }
+
+        if self.output.is_finished() {
+            return Ok(Event::Finished);
+        }
+
+        if !self.output.can_push() {
+            return Ok(Event::NeedConsume);
+        }
+
+        if let Some(batcher) = self.batcher.as_mut() {
+            if let Some(block) = batcher.next_batch(finished)? {
+                self.output.push_data(Ok(block));
+                return Ok(Event::NeedConsume);
+            }
+        }
+
+        if matches!(self.state, State::GeneratedBlock(_, _)) {
+            if let State::GeneratedBlock(blocks, data_block) =
+                std::mem::replace(&mut self.state, State::Finish)
+            {
+                self.state = State::DeserializeBlocks(blocks);
+                return self.push_block(data_block);
+            }
+        }
+
+        if matches!(self.state, State::Generated(_, _)) {
+            if let Generated(part, data_block) = std::mem::replace(&mut self.state, State::Finish) {
+                self.state = match part {
+                    None => State::Finish,
+                    Some(part) => State::ReadDataPrewhere(Some(part)),
+                };
+
+                return self.push_block(data_block);
+            }
+        }
+
+        match self.state {
+            State::Finish => Ok(Event::Finished),
+            State::ReadDataPrewhere(_) => {
+                if self.support_blocking || !self.prefetched.is_empty() {
+                    Ok(Event::Sync)
+                } else {
+                    Ok(Event::Async)
+                }
+            }
+            State::ReadDataRemain(_, _) | State::ReadDataRemainBlocks(_, _, _) => {
+                if self.support_blocking {
+                    Ok(Event::Sync)
+                } else {
+                    Ok(Event::Async)
+                }
+            }
+            State::PrewhereFilter(_, _) => Ok(Event::Sync),
+            State::Deserialize(_, _, _) => Ok(Event::Sync),
+            State::DeserializeBlocks(_) => Ok(Event::Sync),
+            State::Generated(_, _) | State::GeneratedBlock(_, _) => {
+                Err(ErrorCode::Internal("It's a bug."))
+            }
+        }
+    }
+
+    fn process(&mut self) -> Result<()> {
+        let part = Self::decoding_part(&self.state);
+        let start = Instant::now();
+        let res = self.do_process();
+        self.decode_time += start.elapsed();
+        match (res, part) {
+            (Err(cause), Some((part, rows_read))) if self.skip_corrupt_row_groups => {
+                self.skip_row_group(&part, rows_read, cause)
+            }
+            (res, _) => res,
+        }
+    }
 
     async fn async_process(&mut self) -> Result<()> {
         match std::mem::replace(&mut self.state, State::Finish) {
             State::ReadDataPrewhere(Some(part)) => {
-                let chunks = self.prewhere_reader.read_columns_data(part.clone()).await?;
+                let chunks = match self.read_ahead {
+                    Some(window) => self.read_ahead(part.clone(), window).await?,
+                    None => self.prewhere_reader.read_columns_data(part.clone()).await?,
+                };
 
                 if self.prewhere_filter.is_some() {
                     self.state = State::PrewhereFilter(part, chunks);
@@ -301,6 +679,16 @@ impl Processor for ParquetTableSource {
                     Err(ErrorCode::Internal("It's a bug. No remain reader"))
                 }
             }
+            State::ReadDataRemainBlocks(part, chunks, max_block_rows) => {
+                if let Some(remain_reader) = self.remain_reader.as_ref() {
+                    let remain_chunks = remain_reader.read_columns_data(part.clone()).await?;
+                    self.state =
+                        self.prewhere_blocks(part, chunks, Some(remain_chunks), max_block_rows)?;
+                    Ok(())
+                } else {
+                    Err(ErrorCode::Internal("It's a bug. No remain reader"))
+                }
+            }
             _ => Err(ErrorCode::Internal("It's a bug.")),
         }
     }
@@ -0,0 +1,62 @@
+//  Copyright 2022 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::time::Duration;
+
+/// The number of row groups a [`crate::ParquetTableSource`] reads ahead, tuned by the
+/// latency of the reads.
+///
+/// The row groups of a window are read concurrently, then decoded one by one. If the
+/// window is read slower than the previous one is decoded, the source waits for the
+/// storage and the window doubles, up to `max`. If it is decoded in more than twice the
+/// time it is read, the decoding is the bottleneck and the window shrinks by one row
+/// group, down to `min`. A window with `min == max` has a fixed size.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReadAheadWindow {
+    min: usize,
+    max: usize,
+    size: usize,
+}
+
+impl ReadAheadWindow {
+    /// A window of `min` row groups, growing up to `max`. Both are at least 1.
+    pub fn create(min: usize, max: usize) -> ReadAheadWindow {
+        let min = min.max(1);
+        let max = max.max(min);
+        ReadAheadWindow {
+            min,
+            max,
+            size: min,
+        }
+    }
+
+    /// The number of row groups to read at once.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn is_fixed(&self) -> bool {
+        self.min == self.max
+    }
+
+    /// Resize the window once the row groups of the last one were read in `read_latency`,
+    /// and the ones of the window before were decoded in `decode_time`.
+    pub fn observe(&mut self, read_latency: Duration, decode_time: Duration) {
+        if read_latency > decode_time {
+            self.size = (self.size * 2).min(self.max);
+        } else if decode_time > read_latency * 2 {
+            self.size = (self.size - 1).max(self.min);
+        }
+    }
+}
@@ -0,0 +1,141 @@
+//  Copyright 2022 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::collections::HashMap;
+
+use common_catalog::plan::PartInfoPtr;
+use common_exception::Result;
+use parking_lot::Mutex;
+use tracing::info;
+
+use crate::ParquetPartInfo;
+
+/// The progress of a scan of a [`crate::ParquetTable`] by its files, shared by the
+/// [`crate::ParquetTableSource`]s of the scan.
+///
+/// The totals are known before the scan, from the footers the parts were planned with:
+/// the bytes of a part are the compressed sizes of its column chunks. A file is completed
+/// once all its parts are, the parts pruned by the planning are not counted.
+///
+/// The scan is complete once all its parts are, its values are then final and exact: the
+/// rows, parts and bytes of the parts read, which add up to the totals planned.
+pub struct ParquetScanProgress {
+    state: Mutex<ScanState>,
+}
+
+struct ScanState {
+    values: ParquetScanProgressValues,
+    /// The number of parts not completed yet of each file.
+    remaining_parts: HashMap<String, usize>,
+}
+
+/// A snapshot of a [`ParquetScanProgress`], the completed values never decrease.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ParquetScanProgressValues {
+    pub files_completed: usize,
+    pub files_total: usize,
+    pub bytes_read: usize,
+    pub bytes_total: usize,
+    /// The rows of the parts completed, read or skipped.
+    pub rows_read: usize,
+    pub rows_total: usize,
+    /// The parts of the files completed, a row group each or a whole file.
+    pub parts_completed: usize,
+    pub parts_total: usize,
+    /// The files pruned by the planning of the scan, whose row groups are not counted.
+    pub files_pruned: usize,
+    /// The row groups of the files scanned pruned by the planning of the scan.
+    pub row_groups_pruned: usize,
+}
+
+impl ParquetScanProgress {
+    /// The progress of a scan of `parts`, the coalesced parts count as the ones they hold.
+    pub fn create(parts: &[PartInfoPtr]) -> Result<ParquetScanProgress> {
+        let mut values = ParquetScanProgressValues::default();
+        let mut remaining_parts = HashMap::new();
+        for part in parts.iter().cloned().flat_map(ParquetPartInfo::expand) {
+            let part = ParquetPartInfo::from_part(&part)?;
+            values.bytes_total += part.size();
+            values.rows_total += part.nums_rows;
+            values.parts_total += 1;
+            *remaining_parts.entry(part.location.clone()).or_insert(0) += 1;
+        }
+        values.files_total = remaining_parts.len();
+        Ok(ParquetScanProgress {
+            state: Mutex::new(ScanState {
+                values,
+                remaining_parts,
+            }),
+        })
+    }
+
+    /// Record the files and the row groups pruned by the planning of the scan.
+    pub fn with_pruned(self, files_pruned: usize, row_groups_pruned: usize) -> Self {
+        {
+            let mut state = self.state.lock();
+            state.values.files_pruned = files_pruned;
+            state.values.row_groups_pruned = row_groups_pruned;
+        }
+        self
+    }
+
+    /// Record that all the rows of `part` were read, or skipped.
+    pub fn complete(&self, part: &ParquetPartInfo) {
+        let mut state = self.state.lock();
+        let file_completed = match state.remaining_parts.get_mut(&part.location) {
+            // Not a part of the scan, or completed already.
+            None => return,
+            Some(remaining) => {
+                *remaining -= 1;
+                *remaining == 0
+            }
+        };
+        state.values.bytes_read += part.size();
+        state.values.rows_read += part.nums_rows;
+        state.values.parts_completed += 1;
+        if file_completed {
+            state.remaining_parts.remove(&part.location);
+            state.values.files_completed += 1;
+            let values = state.values;
+            info!(
+                "read_parquet completed file '{}': {}/{} files, {}/{} bytes",
+                part.location,
+                values.files_completed,
+                values.files_total,
+                values.bytes_read,
+                values.bytes_total
+            );
+            if state.remaining_parts.is_empty() {
+                info!(
+                    "read_parquet completed the scan: {} files, {} parts, {} rows, {} bytes read, {} files and {} row groups pruned",
+                    values.files_completed,
+                    values.parts_completed,
+                    values.rows_read,
+                    values.bytes_read,
+                    values.files_pruned,
+                    values.row_groups_pruned
+                );
+            }
+        }
+    }
+
+    pub fn get_values(&self) -> ParquetScanProgressValues {
+        self.state.lock().values
+    }
+
+    /// Whether all the parts of the scan are completed, its values are then final.
+    pub fn is_complete(&self) -> bool {
+        self.state.lock().remaining_parts.is_empty()
+    }
+}
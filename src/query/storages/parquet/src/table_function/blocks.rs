@@ -0,0 +1,232 @@
+//  Copyright 2022 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::sync::Arc;
+use std::vec::IntoIter;
+
+use common_arrow::arrow::array::Array;
+use common_arrow::arrow::chunk::Chunk;
+use common_arrow::arrow::datatypes::Schema as ArrowSchema;
+use common_catalog::plan::PartInfoPtr;
+use common_catalog::plan::Projection;
+use common_datablocks::DataBlock;
+use common_datavalues::DataValue;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_storage::ColumnLeaves;
+
+use super::cursor::ParquetScanCursor;
+use super::ParquetTable;
+use crate::parquet_reader::physical_arrow_schema;
+use crate::ParquetPartInfo;
+use crate::ParquetReader;
+
+/// The number of rows decoded at a time by [`ParquetTable::read_cell`].
+const CELL_BLOCK_ROWS: usize = 1024;
+
+impl ParquetTable {
+    /// Read all the columns of the files as blocks, one per file, in the order of the files.
+    ///
+    /// This is for embedders reading small files (like configs) without a query context or
+    /// pipeline. The files are read one by one through the blocking API of the operator,
+    /// so it is not meant for large scans.
+    pub fn read_blocks(&self) -> Result<ParquetBlocks> {
+        self.resume_blocks(ParquetScanCursor::default())
+    }
+
+    /// Read all the columns of the files as arrow chunks, like [`ParquetTable::read_blocks`]
+    /// without the conversion of the decoded arrays to blocks.
+    ///
+    /// This is for embedders feeding arrow based tools, the chunks have the schema
+    /// [`ParquetTable::arrow_schema`].
+    pub fn read_chunks(&self) -> Result<ParquetChunks> {
+        let mut file_metas = self.read_file_metas(&self.file_locations)?;
+        self.select_row_groups(&mut file_metas);
+        self.check_missing_columns(&file_metas, None)?;
+        let (_, partitions) = self.all_columns_partitions(&file_metas, usize::MAX);
+        let num_fields = self.table_info.schema().num_fields();
+        let reader = self.create_reader(Projection::Columns((0..num_fields).collect()))?;
+        Ok(ParquetChunks {
+            reader,
+            parts: partitions.partitions.into_iter(),
+        })
+    }
+
+    /// The arrow schema of the table, the one of the chunks of
+    /// [`ParquetTable::read_chunks`].
+    pub fn arrow_schema(&self) -> ArrowSchema {
+        self.table_info.schema().to_arrow()
+    }
+
+    /// Like [`ParquetTable::read_blocks`], skipping the parts completed by `cursor`.
+    ///
+    /// The cursor is the one saved from [`ParquetBlocks::cursor`] of an interrupted read,
+    /// the blocks of the parts it completed are not read again.
+    pub fn resume_blocks(&self, cursor: ParquetScanCursor) -> Result<ParquetBlocks> {
+        let mut file_metas = self.read_file_metas(&self.file_locations)?;
+        self.select_row_groups(&mut file_metas);
+        self.check_missing_columns(&file_metas, None)?;
+        let (_, partitions) = self.all_columns_partitions(&file_metas, usize::MAX);
+        let mut parts = Vec::with_capacity(partitions.partitions.len());
+        for part in partitions.partitions {
+            if !cursor.is_completed(ParquetPartInfo::from_part(&part)?) {
+                parts.push(part);
+            }
+        }
+
+        let num_fields = self.table_info.schema().num_fields();
+        let reader = self.create_reader(Projection::Columns((0..num_fields).collect()))?;
+        Ok(ParquetBlocks {
+            reader,
+            parts: parts.into_iter(),
+            cursor,
+        })
+    }
+
+    /// Read the value of `column` at row `row_index` of row group `row_group` of the file
+    /// at `location`.
+    ///
+    /// This is for troubleshooting a single value. Only the column chunk of the value is
+    /// read, and the decoding stops at the block holding the row.
+    pub fn read_cell(
+        &self,
+        location: &str,
+        row_group: usize,
+        row_index: usize,
+        column: &str,
+    ) -> Result<DataValue> {
+        let index = self.table_info.schema().index_of(column).map_err(|_| {
+            ErrorCode::BadArguments(format!("Unknown column '{}' of read_parquet", column))
+        })?;
+
+        let file_metas = self.read_file_metas(&[location.to_string()])?;
+        let meta = &file_metas[0];
+        let num_row_groups = meta.file_meta.row_groups.len();
+        if row_group >= num_row_groups {
+            return Err(ErrorCode::BadArguments(format!(
+                "Row group {} is out of range, parquet file '{}' has {} row groups",
+                row_group, location, num_row_groups
+            )));
+        }
+        let num_rows = meta.file_meta.row_groups[row_group].num_rows();
+        if row_index >= num_rows {
+            return Err(ErrorCode::BadArguments(format!(
+                "Row {} is out of range, row group {} of parquet file '{}' has {} rows",
+                row_index, row_group, location, num_rows
+            )));
+        }
+
+        let projection = Projection::Columns(vec![index]);
+        let arrow_schema = physical_arrow_schema(&self.file_schema(), &self.interval_fields);
+        let column_leaves = ColumnLeaves::new_from_schema(&arrow_schema);
+        let part = Self::projection_part(
+            meta,
+            row_group,
+            num_rows,
+            &column_leaves,
+            &self.file_projection(&projection),
+        );
+
+        let reader = self.create_reader(projection)?;
+        let chunks = reader.sync_read_columns_data(part.clone())?;
+        let mut blocks = reader.deserialize_blocks(part, chunks, CELL_BLOCK_ROWS)?;
+        let mut first_row = 0;
+        while let Some(block) = blocks.next_block()? {
+            if row_index < first_row + block.num_rows() {
+                return Ok(block.column(0).get(row_index - first_row));
+            }
+            first_row += block.num_rows();
+        }
+        Err(ErrorCode::ParquetFileInvalid(format!(
+            "Row group {} of parquet file '{}' has less rows than its metadata",
+            row_group, location
+        )))
+    }
+
+    /// Read the column chunk of the leaf column `leaf_id` of `part` as it is in the file,
+    /// compressed and encoded, without decoding it.
+    ///
+    /// This is for tooling copying column chunks to other files as they are. The chunk is
+    /// located by the offset and the length of the column in the metadata of the part.
+    pub fn read_column_chunk(&self, part: &PartInfoPtr, leaf_id: usize) -> Result<Vec<u8>> {
+        let part = ParquetPartInfo::from_part(part)?;
+        let column_meta = part.columns_meta.get(&leaf_id).ok_or_else(|| {
+            ErrorCode::BadArguments(format!(
+                "Leaf column {} is not read by the part of row group {} of parquet file '{}'",
+                leaf_id, part.row_group, part.location
+            ))
+        })?;
+        let _file = self.operators.blocking_open_file(&part.location)?;
+        let _permit = self.operators.blocking_permit()?;
+        let (_, chunk) = ParquetReader::sync_read_column(
+            self.operators.object(&part.location)?,
+            leaf_id,
+            column_meta.offset,
+            column_meta.length,
+        )?;
+        Ok(chunk)
+    }
+}
+
+/// The blocks of a [`ParquetTable`], see [`ParquetTable::read_blocks`].
+pub struct ParquetBlocks {
+    reader: Arc<ParquetReader>,
+    parts: IntoIter<PartInfoPtr>,
+    cursor: ParquetScanCursor,
+}
+
+impl ParquetBlocks {
+    /// The progress of the read, with the parts of the blocks returned so far completed.
+    ///
+    /// Save it to resume the read by [`ParquetTable::resume_blocks`].
+    pub fn cursor(&self) -> &ParquetScanCursor {
+        &self.cursor
+    }
+}
+
+impl Iterator for ParquetBlocks {
+    type Item = Result<DataBlock>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let part = self.parts.next()?;
+        let block = self
+            .reader
+            .sync_read_columns_data(part.clone())
+            .and_then(|chunks| self.reader.deserialize(part.clone(), chunks))
+            .and_then(|block| {
+                self.cursor.complete(ParquetPartInfo::from_part(&part)?);
+                Ok(block)
+            });
+        Some(block)
+    }
+}
+
+/// The arrow chunks of a [`ParquetTable`], see [`ParquetTable::read_chunks`].
+pub struct ParquetChunks {
+    reader: Arc<ParquetReader>,
+    parts: IntoIter<PartInfoPtr>,
+}
+
+impl Iterator for ParquetChunks {
+    type Item = Result<Chunk<Box<dyn Array>>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let part = self.parts.next()?;
+        let chunk = self
+            .reader
+            .sync_read_columns_data(part.clone())
+            .and_then(|chunks| self.reader.deserialize_chunk(part, chunks));
+        Some(chunk)
+    }
+}
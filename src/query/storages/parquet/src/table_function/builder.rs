@@ -0,0 +1,797 @@
+//  Copyright 2022 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::io::Read;
+use std::sync::Arc;
+
+use chrono::DateTime;
+use chrono::NaiveDateTime;
+use chrono::TimeZone;
+use chrono::Utc;
+use common_arrow::arrow::datatypes::Schema as ArrowSchema;
+use common_arrow::parquet::metadata::RowGroupMetaData;
+use common_datavalues::DataField;
+use common_datavalues::DataSchema;
+use common_datavalues::DataType;
+use common_datavalues::DataValue;
+use common_datavalues::ToDataType;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_meta_app::schema::TableIdent;
+use common_meta_app::schema::TableInfo;
+use common_meta_app::schema::TableMeta;
+use futures::TryStreamExt;
+use opendal::ObjectMode;
+use opendal::Operator;
+use parking_lot::RwLock;
+
+use super::external_statistics::ParquetStatisticsProvider;
+use super::hive::HivePartitions;
+use super::hive::ROW_GROUP_INDEX_COLUMN;
+use super::operators::block_on_io;
+use super::operators::parse_uri_pattern;
+use super::operators::Credentials;
+use super::operators::FileOperators;
+use super::operators::STDIN_LOCATION;
+use super::stage::resolve_stage_patterns;
+use super::table::cast_columns;
+use super::table::infer_schema;
+use super::table::merge_schemas;
+use super::table::ColumnNameTransform;
+use super::table::rename_columns;
+use super::table::schema_from_arrow;
+use super::table::select_columns_like;
+use super::table::sort_columns_by_name;
+use super::table::ParquetTable;
+use super::table::RowGroupFilter;
+use super::table_args::parse_table_args;
+use super::table_args::FileOrder;
+use super::table_args::MergeBy;
+use super::table_args::ParquetTableOptions;
+use super::table_args::DEFAULT_MAX_STDIN_SIZE;
+use crate::ParquetWarning;
+use crate::ParquetWarnings;
+use crate::WarningKind;
+
+/// Builder of [`ParquetTable`].
+///
+/// By default the files are read from the local file system, or from the stage if the
+/// paths are qualified by one (`@<stage>/<path>`). Embedders which already have a
+/// configured [`Operator`] (with their own layers, credentials, ...) can pass it by
+/// [`ParquetTableBuilder::with_operator`], the paths are then resolved against it.
+///
+/// The paths can also be URIs like `s3://<bucket>/<path>`, which are resolved against the
+/// operator of their bucket, so that one table reads files of different storages. The
+/// operators are built from the URIs, or passed by
+/// [`ParquetTableBuilder::with_bucket_operator`]. The files under a path prefix of a bucket
+/// can be read with credentials of their own, by an operator passed for the prefix (like
+/// one configured with a secret of the prefix), or by the connection parameters passed by
+/// [`ParquetTableBuilder::with_credentials`].
+///
+/// The stages are looked up for their operators, unless they are passed by
+/// [`ParquetTableBuilder::with_stage_operator`].
+///
+/// The path `-` reads a parquet file from the standard input, or from the reader passed by
+/// [`ParquetTableBuilder::with_stdin`]. It is buffered in memory up to the option
+/// `max_stdin_size`.
+///
+/// The schema is inferred from the footers of the files, unless it is passed by
+/// [`ParquetTableBuilder::with_arrow_schema`]. The files are pruned by the statistics of
+/// their footers, and by the ones of an external index passed by
+/// [`ParquetTableBuilder::with_statistics_provider`] before their footers are read.
+///
+/// `created_on` and `updated_on` of the table default to the epoch, so that the table info
+/// is the same wherever the table is built.
+pub struct ParquetTableBuilder {
+    database_name: String,
+    table_func_name: String,
+    table_id: u64,
+    table_args: Vec<DataValue>,
+    operator: Option<Operator>,
+    bucket_operators: Vec<(String, Operator)>,
+    stage_operators: Vec<(String, Operator)>,
+    credentials: Credentials,
+    name_transform: Option<ColumnNameTransform>,
+    arrow_schema: Option<ArrowSchema>,
+    row_group_filter: Option<RowGroupFilter>,
+    statistics_provider: Option<Arc<dyn ParquetStatisticsProvider>>,
+    stdin: Option<Box<dyn Read + Send>>,
+    created_on: Option<DateTime<Utc>>,
+    updated_on: Option<DateTime<Utc>>,
+}
+
+impl ParquetTableBuilder {
+    pub fn create(
+        database_name: &str,
+        table_func_name: &str,
+        table_id: u64,
+        table_args: Vec<DataValue>,
+    ) -> Self {
+        ParquetTableBuilder {
+            database_name: database_name.to_string(),
+            table_func_name: table_func_name.to_string(),
+            table_id,
+            table_args,
+            operator: None,
+            bucket_operators: vec![],
+            stage_operators: vec![],
+            credentials: Credentials::default(),
+            name_transform: None,
+            arrow_schema: None,
+            row_group_filter: None,
+            statistics_provider: None,
+            stdin: None,
+            created_on: None,
+            updated_on: None,
+        }
+    }
+
+    /// Read the files through `operator` instead of the local file system.
+    pub fn with_operator(mut self, operator: Operator) -> Self {
+        self.operator = Some(operator);
+        self
+    }
+
+    /// Read the files of the URIs under `uri` (like `s3://<bucket>`) through `operator`,
+    /// which is rooted at the bucket.
+    ///
+    /// `uri` can be a path prefix like `s3://<bucket>/<path>/`, then only the files under it
+    /// are read through `operator`, which is still rooted at the bucket. The longest prefix
+    /// of a file wins, see [`FileOperators::add_prefix`].
+    pub fn with_bucket_operator(mut self, uri: &str, operator: Operator) -> Self {
+        self.bucket_operators.push((uri.to_string(), operator));
+        self
+    }
+
+    /// Read the files of the stage `@<stage_name>` through `operator`, which is rooted at
+    /// the stage, instead of the one of the stage looked up by its name.
+    pub fn with_stage_operator(mut self, stage_name: &str, operator: Operator) -> Self {
+        self.stage_operators
+            .push((stage_name.to_string(), operator));
+        self
+    }
+
+    /// Read the files under the path prefixes of `credentials` through operators built with
+    /// their connection parameters, like the ones of the config `storage.parquet_credentials`.
+    /// The operators passed for the same prefixes by
+    /// [`ParquetTableBuilder::with_bucket_operator`] are replaced.
+    pub fn with_credentials(mut self, credentials: Credentials) -> Self {
+        self.credentials = credentials;
+        self
+    }
+
+    /// Transform the names of the columns inferred from the files by `name_transform`,
+    /// instead of the option `name_transform`. The names are lower cased before.
+    pub fn with_name_transform(
+        mut self,
+        name_transform: impl Fn(&str) -> String + Send + Sync + 'static,
+    ) -> Self {
+        self.name_transform = Some(Arc::new(name_transform));
+        self
+    }
+
+    /// Take `arrow_schema` as the schema of the files instead of inferring it from their
+    /// footers, so that no file is read before the table is.
+    ///
+    /// The names of its fields are lower cased and transformed like the ones of the files,
+    /// the footer of each file is checked against it once read, as by `merge_by` if set.
+    pub fn with_arrow_schema(mut self, arrow_schema: ArrowSchema) -> Self {
+        self.arrow_schema = Some(arrow_schema);
+        self
+    }
+
+    /// Only read the row groups for which `row_group_filter` holds, on top of the pruning
+    /// by the filters pushed down. It is called on the row groups of each file scanned,
+    /// when the partitions are read or by [`ParquetTable::read_blocks`], even if `no_prune`
+    /// is set.
+    pub fn with_row_group_filter(
+        mut self,
+        row_group_filter: impl Fn(&RowGroupMetaData) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.row_group_filter = Some(Arc::new(row_group_filter));
+        self
+    }
+
+    /// Prune the files by the statistics of `provider`, like the ones of an index kept
+    /// along with the files, before reading their footers. The files it has no statistics
+    /// of are pruned by their footers only. It is not used if `no_prune` is set.
+    pub fn with_statistics_provider(
+        mut self,
+        provider: impl ParquetStatisticsProvider + 'static,
+    ) -> Self {
+        self.statistics_provider = Some(Arc::new(provider));
+        self
+    }
+
+    /// Read the path `-` from `stdin` instead of the standard input of the process.
+    pub fn with_stdin(mut self, stdin: impl Read + Send + 'static) -> Self {
+        self.stdin = Some(Box::new(stdin));
+        self
+    }
+
+    /// Set `created_on` of the table instead of the epoch.
+    pub fn with_created_on(mut self, created_on: DateTime<Utc>) -> Self {
+        self.created_on = Some(created_on);
+        self
+    }
+
+    /// Set `updated_on` of the table instead of the epoch.
+    pub fn with_updated_on(mut self, updated_on: DateTime<Utc>) -> Self {
+        self.updated_on = Some(updated_on);
+        self
+    }
+
+    pub fn build(self) -> Result<Arc<ParquetTable>> {
+        if self.table_args.is_empty() {
+            return Err(ErrorCode::BadArguments(
+                "read_parquet needs at least one argument",
+            ));
+        }
+
+        let (_, options) = parse_table_args(&self.table_args)?;
+        let warnings = Arc::new(ParquetWarnings::create(options.collect_warnings));
+        let mut stdin = self.stdin.unwrap_or_else(|| Box::new(std::io::stdin()));
+        let (operators, file_locations) = resolve_files_with_stdin(
+            &self.table_args,
+            self.operator,
+            &self.bucket_operators,
+            &self.stage_operators,
+            &self.credentials,
+            &mut stdin,
+            &warnings,
+        )?;
+
+        // Infer schema from the first parquet file of each storage.
+        // Assume all parquet files of a storage have the same schema.
+        // If not, throw error during reading.
+        // The schemas of all the files are merged if `merge_by` is set.
+        let mut first_locations: Vec<&String> = vec![];
+        for location in file_locations.iter() {
+            let bucket = parse_uri_pattern(location).map(|(uri, _)| uri);
+            if options.merge_by.is_some()
+                || first_locations
+                    .iter()
+                    .all(|first| parse_uri_pattern(first).map(|(uri, _)| uri) != bucket)
+            {
+                first_locations.push(location);
+            }
+        }
+        if options.merge_by == Some(MergeBy::Name) && options.columns.is_some() {
+            return Err(ErrorCode::BadArguments(
+                "read_parquet option 'columns' names the columns by position, it can't be used with 'merge_by=name'",
+            ));
+        }
+        let name_transform = column_name_transform(&options, self.name_transform);
+        let schema_provided = self.arrow_schema.is_some();
+        let (mut schema, interval_fields) = match &self.arrow_schema {
+            Some(arrow_schema) => schema_from_arrow(arrow_schema, name_transform.as_ref())?,
+            None => {
+                let schemas = first_locations
+                    .iter()
+                    .map(|location| {
+                        infer_schema(
+                            &operators,
+                            location,
+                            name_transform.as_ref(),
+                            options.suffix_duplicate_columns,
+                            options.ignore_converted_types,
+                        )
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                merge_schemas(&first_locations, schemas, options.merge_by)?
+            }
+        };
+        if let Some(columns) = &options.columns {
+            schema = rename_columns(schema, columns)?;
+        }
+        let interval_fields = if options.sort_columns_by_name {
+            let (sorted_schema, sorted_interval_fields) =
+                sort_columns_by_name(&schema, &interval_fields);
+            schema = sorted_schema;
+            sorted_interval_fields
+        } else {
+            interval_fields
+        };
+        let interval_fields = match &options.columns_like {
+            Some(pattern) => {
+                let (like_schema, like_interval_fields) =
+                    select_columns_like(&schema, &interval_fields, pattern);
+                if like_schema.num_fields() == 0 {
+                    return Err(ErrorCode::BadArguments(format!(
+                        "read_parquet option 'columns_like' matches no column of the parquet files: '{}'",
+                        pattern
+                    )));
+                }
+                schema = like_schema;
+                like_interval_fields
+            }
+            None => interval_fields,
+        };
+        if let Some(sorted_by) = &options.sorted_by {
+            if !schema.has_field(sorted_by) {
+                return Err(ErrorCode::BadArguments(format!(
+                    "read_parquet option 'sorted_by' has unknown column '{}'",
+                    sorted_by
+                )));
+            }
+        }
+        if let Some(cluster_by) = &options.cluster_by {
+            if !schema.has_field(cluster_by) {
+                return Err(ErrorCode::BadArguments(format!(
+                    "read_parquet option 'cluster_by' has unknown column '{}'",
+                    cluster_by
+                )));
+            }
+        }
+        let (casts, decimal_casts) = match &options.cast {
+            Some(casts) => {
+                let (cast_schema, cast_fields, decimal_fields) =
+                    cast_columns(schema, casts, options.decimal_rounding)?;
+                for (index, from_type) in cast_fields.iter() {
+                    let field = cast_schema.field(*index);
+                    let message = format!(
+                        "Column '{}' is read as {} instead of {} by the option 'cast'",
+                        field.name(),
+                        field.data_type().name(),
+                        from_type.name()
+                    );
+                    let warning = ParquetWarning::create(WarningKind::Coercion, message);
+                    warnings.add(warning.with_column(field.name()));
+                }
+                schema = cast_schema;
+                (cast_fields, decimal_fields)
+            }
+            None => (vec![], vec![]),
+        };
+
+        let partitions = match &options.partition_values {
+            Some(mapping) => Some(HivePartitions::try_create_from_mapping(
+                &file_locations,
+                mapping,
+            )?),
+            None if options.hive_partitioning => {
+                Some(HivePartitions::try_create(&file_locations)?)
+            }
+            None => None,
+        };
+        let partitions = match &options.literals {
+            Some(literals) => {
+                let file_column = literals.iter().find(|(name, ..)| schema.has_field(name));
+                if let Some((name, ..)) = file_column {
+                    return Err(ErrorCode::BadArguments(format!(
+                        "Literal column '{}' of read_parquet option 'literals' is also a column of the parquet files",
+                        name
+                    )));
+                }
+                Some(HivePartitions::add_literals(
+                    partitions,
+                    &file_locations,
+                    literals,
+                )?)
+            }
+            None => partitions,
+        };
+        let partitions = if let Some(partitions) = partitions {
+            let mut fields = schema.fields().clone();
+            for field in &partitions.fields {
+                if schema.has_field(field.name()) {
+                    return Err(ErrorCode::BadArguments(format!(
+                        "Partition column '{}' is also a column of the parquet files",
+                        field.name()
+                    )));
+                }
+                fields.push(field.clone());
+            }
+            schema = DataSchema::new(fields);
+            Some(partitions)
+        } else {
+            None
+        };
+        if options.row_group_index {
+            if schema.has_field(ROW_GROUP_INDEX_COLUMN) {
+                return Err(ErrorCode::BadArguments(format!(
+                    "Column '{}' of read_parquet option 'row_group_index' is also a column of the parquet files",
+                    ROW_GROUP_INDEX_COLUMN
+                )));
+            }
+            let mut fields = schema.fields().clone();
+            fields.push(DataField::new(ROW_GROUP_INDEX_COLUMN, u64::to_data_type()));
+            schema = DataSchema::new(fields);
+        }
+
+        let epoch = Utc.from_utc_datetime(&NaiveDateTime::from_timestamp(0, 0));
+        let table_info = TableInfo {
+            ident: TableIdent::new(self.table_id, 0),
+            desc: format!("'{}'.'{}'", self.database_name, self.table_func_name),
+            name: self.table_func_name,
+            meta: TableMeta {
+                schema: Arc::new(schema),
+                engine: "SystemReadParquet".to_string(),
+                // Assuming that created_on is unnecessary for function table,
+                // we could make created_on fixed by default to pass test_shuffle_action_try_into.
+                created_on: self.created_on.unwrap_or(epoch),
+                updated_on: self.updated_on.unwrap_or(epoch),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let mut table = ParquetTable {
+            table_args: self.table_args,
+            file_locations,
+            table_info,
+            operators,
+            options,
+            interval_fields,
+            partitions,
+            casts,
+            decimal_casts,
+            name_transform,
+            schema_provided,
+            row_group_filter: self.row_group_filter,
+            statistics_provider: self.statistics_provider,
+            cluster_key: None,
+            scan_progress: RwLock::new(None),
+            pruned: RwLock::new((0, 0)),
+            warnings,
+        };
+
+        if table.options.observed_nullability {
+            table.tighten_nullability()?;
+        }
+
+        // Read the files in the order of the key they are clustered by, once checked.
+        if let Some(cluster_by) = table.options.cluster_by.clone() {
+            let warn_on_unclustered = table.options.warn_on_unclustered;
+            match table.clustered_files(&cluster_by) {
+                Ok(file_locations) => {
+                    table.file_locations = file_locations;
+                    table.cluster_key = Some(cluster_by);
+                }
+                Err(e) if warn_on_unclustered && e.code() == ErrorCode::BAD_ARGUMENTS => {
+                    let message =
+                        format!("{}, the files are read in the order of their paths", e.message());
+                    let warning = ParquetWarning::create(WarningKind::Unclustered, message);
+                    table.warnings.add(warning.with_column(&cluster_by));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(Arc::new(table))
+    }
+}
+
+/// The transform of the column names inferred from the files: `name_transform` if any,
+/// otherwise the option `name_transform`, followed by the option `identifier_policy`.
+pub(super) fn column_name_transform(
+    options: &ParquetTableOptions,
+    name_transform: Option<ColumnNameTransform>,
+) -> Option<ColumnNameTransform> {
+    let name_transform = name_transform.or_else(|| {
+        options.name_transform.map(|transform| {
+            Arc::new(move |name: &str| transform.apply(name)) as ColumnNameTransform
+        })
+    });
+    match options.identifier_policy {
+        Some(policy) => Some(Arc::new(move |name: &str| match &name_transform {
+            Some(name_transform) => policy.apply(&name_transform(name)),
+            None => policy.apply(name),
+        }) as ColumnNameTransform),
+        None => name_transform,
+    }
+}
+
+/// Resolve the files matched by the paths of the `read_parquet` arguments `table_args`.
+///
+/// The paths are resolved against `operator` if any, otherwise against the stage they are
+/// qualified by, or the local file system. The sidecar files and the files smaller than
+/// `min_file_size` are skipped, and the others are ordered by `file_order` if it is set.
+/// The local files are mapped in memory if `mmap` is set. Returns the operators to read the
+/// files with, and their locations.
+///
+/// URI paths like `s3://<bucket>/<path>` are resolved against the operators of their
+/// buckets, taken from `bucket_operators` or built from the URIs. The files matched by them
+/// keep the URIs of their buckets in their locations. The files under the path prefixes
+/// of `bucket_operators` are read through the operators of their longest prefixes.
+///
+/// The path `-` is the standard input, buffered in memory as the file at
+/// [`STDIN_LOCATION`].
+pub fn resolve_files(
+    table_args: &[DataValue],
+    operator: Option<Operator>,
+    bucket_operators: &[(String, Operator)],
+) -> Result<(FileOperators, Vec<String>)> {
+    let warnings = ParquetWarnings::create(false);
+    let mut stdin = std::io::stdin();
+    let credentials = Credentials::default();
+    resolve_files_with_stdin(
+        table_args,
+        operator,
+        bucket_operators,
+        &[],
+        &credentials,
+        &mut stdin,
+        &warnings,
+    )
+}
+
+/// [`resolve_files`], reading the path `-` from `stdin`, the files skipped are added to
+/// `warnings`. The files under the path prefixes of `credentials` are read through
+/// operators built with their connection parameters, and the ones of the stages of
+/// `stage_operators` through their operators.
+fn resolve_files_with_stdin(
+    table_args: &[DataValue],
+    operator: Option<Operator>,
+    bucket_operators: &[(String, Operator)],
+    stage_operators: &[(String, Operator)],
+    credentials: &Credentials,
+    stdin: &mut dyn Read,
+    warnings: &ParquetWarnings,
+) -> Result<(FileOperators, Vec<String>)> {
+    let (patterns, options) = parse_table_args(table_args)?;
+    let mut paths = patterns
+        .iter()
+        .filter(|pattern| *pattern != STDIN_LOCATION && parse_uri_pattern(pattern).is_none())
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let mut operator = operator;
+    if operator.is_none() {
+        if let Some((stage_operator, stage_paths)) = resolve_stage_patterns(&paths, stage_operators)? {
+            operator = Some(stage_operator);
+            paths = stage_paths;
+        }
+    }
+
+    let is_local = operator.is_none();
+    let operator = match operator {
+        Some(operator) => operator,
+        None => {
+            let mut builder = opendal::services::fs::Builder::default();
+            builder.root("/");
+            Operator::new(builder.build()?)
+        }
+    };
+    let mut operators = FileOperators::create(operator.clone());
+    for (uri, bucket_operator) in bucket_operators {
+        match parse_uri_pattern(uri) {
+            Some((_, path)) if !path.is_empty() => {
+                operators.add_prefix(uri, bucket_operator.clone())?
+            }
+            _ => operators.add_bucket(uri, bucket_operator.clone()),
+        }
+    }
+    for (prefix, connection) in credentials.iter() {
+        operators.add_credentials(prefix, connection)?;
+    }
+    if is_local && options.mmap {
+        operators.map_local_files();
+    }
+    if let Some(max_concurrent_requests) = options.max_concurrent_requests {
+        operators.limit_requests(max_concurrent_requests);
+    }
+    if let Some(max_open_files) = options.max_open_files {
+        operators.limit_open_files(max_open_files);
+    }
+
+    // Follow the order of the patterns, whatever they are resolved against.
+    let sort_listed = options.file_order != Some(FileOrder::AsListed);
+    let mut paths = paths.into_iter();
+    let mut file_locations = Vec::with_capacity(patterns.len());
+    let mut reads_stdin = false;
+    for pattern in patterns.iter() {
+        if pattern == STDIN_LOCATION {
+            file_locations.push(STDIN_LOCATION.to_string());
+            reads_stdin = true;
+            continue;
+        }
+        match parse_uri_pattern(pattern) {
+            Some((uri, path)) => {
+                let bucket_operator = operators.bucket_of_path(&uri, path)?;
+                let files = list_matched_files(&bucket_operator, path, sort_listed)?;
+                file_locations.extend(files.into_iter().map(|file| format!("{}{}", uri, file)));
+            }
+            None => {
+                let path = paths.next().unwrap();
+                if is_local {
+                    file_locations.extend(glob_local_files(&path)?);
+                } else {
+                    file_locations.extend(list_matched_files(&operator, &path, sort_listed)?);
+                }
+            }
+        }
+    }
+
+    if reads_stdin {
+        let max_stdin_size = options.max_stdin_size.unwrap_or(DEFAULT_MAX_STDIN_SIZE);
+        operators.set_stdin(read_stdin(stdin, max_stdin_size)?)?;
+    }
+
+    file_locations.retain(|location| !options.is_sidecar_file(location));
+
+    if let Some(min_file_size) = options.min_file_size {
+        file_locations = skip_small_files(&operators, file_locations, min_file_size, warnings)?;
+    }
+
+    if let Some(file_order) = options.file_order {
+        file_locations = order_files(&operators, file_locations, file_order)?;
+    }
+
+    if file_locations.is_empty() {
+        return Err(ErrorCode::BadArguments(
+            "No matched files found for read_parquet",
+        ));
+    }
+
+    Ok((operators, file_locations))
+}
+
+/// Buffer the parquet file read from `stdin`, of at most `max_stdin_size` bytes.
+fn read_stdin(stdin: &mut dyn Read, max_stdin_size: usize) -> Result<Vec<u8>> {
+    let mut content = vec![];
+    stdin
+        .take(max_stdin_size as u64 + 1)
+        .read_to_end(&mut content)
+        .map_err(|e| {
+            ErrorCode::StorageOther(format!("failed to read the standard input: {}", e))
+        })?;
+    if content.len() > max_stdin_size {
+        return Err(ErrorCode::BadArguments(format!(
+            "The standard input read by read_parquet is larger than {} bytes, set the option 'max_stdin_size' to read more",
+            max_stdin_size
+        )));
+    }
+    Ok(content)
+}
+
+fn glob_local_files(pattern: &str) -> Result<Vec<String>> {
+    // Keep plain paths as they are, so that a missing file is reported as not found.
+    if !pattern.contains(is_wildcard) {
+        return Ok(vec![pattern.to_string()]);
+    }
+
+    let paths =
+        glob::glob(pattern).map_err(|e| ErrorCode::Internal(format!("glob error: {}", e)))?;
+    paths
+        .map(|entry| match entry {
+            Ok(path) => Ok(path.to_string_lossy().to_string()),
+            Err(e) => Err(ErrorCode::Internal(format!("glob error: {}", e))),
+        })
+        .collect()
+}
+
+/// Resolve `pattern` against `operator`.
+///
+/// A pattern without wildcards is taken as a file path as is, otherwise the directories
+/// under its longest literal prefix are listed and the files matching it are returned,
+/// sorted by name if `sort` is set.
+fn list_matched_files(operator: &Operator, pattern: &str, sort: bool) -> Result<Vec<String>> {
+    // Paths of the listed objects are relative to the root of the operator.
+    let pattern = pattern.trim_start_matches('/');
+    let wildcard = match pattern.find(is_wildcard) {
+        Some(wildcard) => wildcard,
+        None => return Ok(vec![pattern.to_string()]),
+    };
+
+    let matcher = glob::Pattern::new(pattern)
+        .map_err(|e| ErrorCode::Internal(format!("glob error: {}", e)))?;
+    let options = glob::MatchOptions {
+        require_literal_separator: true,
+        ..Default::default()
+    };
+
+    let root = match pattern[..wildcard].rfind('/') {
+        Some(pos) => &pattern[..=pos],
+        None => "/",
+    };
+
+    let mut files = vec![];
+    let mut dirs = vec![root.to_string()];
+    while let Some(dir) = dirs.pop() {
+        for (path, mode) in list_dir(operator, &dir)? {
+            match mode {
+                ObjectMode::DIR => dirs.push(path),
+                ObjectMode::FILE if matcher.matches_with(&path, options) => files.push(path),
+                _ => {}
+            }
+        }
+    }
+    if sort {
+        files.sort();
+    }
+    Ok(files)
+}
+
+/// The paths of the objects in the directory `dir` of `operator`, with their modes.
+///
+/// The operators without the blocking API, like the ones of s3 and gcs, are listed through
+/// their async API on the IO runtime.
+fn list_dir(operator: &Operator, dir: &str) -> Result<Vec<(String, ObjectMode)>> {
+    let object = operator.object(dir);
+    if operator.metadata().can_blocking() {
+        let mut entries = vec![];
+        for object in object.blocking_list()? {
+            let object = object?;
+            let mode = object.blocking_mode()?;
+            entries.push((object.path().to_string(), mode));
+        }
+        return Ok(entries);
+    }
+
+    block_on_io(async move {
+        let mut entries = vec![];
+        let mut objects = object.list().await.map_err(ErrorCode::from)?;
+        while let Some(object) = objects.try_next().await.map_err(ErrorCode::from)? {
+            let mode = object.mode().await.map_err(ErrorCode::from)?;
+            entries.push((object.path().to_string(), mode));
+        }
+        Ok(entries)
+    })
+}
+
+pub(super) fn is_wildcard(c: char) -> bool {
+    matches!(c, '*' | '?' | '[')
+}
+
+/// Drop the files smaller than `min_file_size` bytes, files exactly at the threshold are kept.
+fn skip_small_files(
+    operators: &FileOperators,
+    file_locations: Vec<String>,
+    min_file_size: u64,
+    warnings: &ParquetWarnings,
+) -> Result<Vec<String>> {
+    let mut kept = Vec::with_capacity(file_locations.len());
+    for location in file_locations {
+        let _permit = operators.blocking_permit()?;
+        let meta = operators.stat(&location)?;
+        if meta.content_length() >= min_file_size {
+            kept.push(location);
+        } else {
+            let message = format!(
+                "Skip parquet file '{}' of {} bytes, smaller than 'min_file_size' ({})",
+                location,
+                meta.content_length(),
+                min_file_size
+            );
+            let warning = ParquetWarning::create(WarningKind::SkippedFile, message);
+            warnings.add(warning.with_location(&location));
+        }
+    }
+    Ok(kept)
+}
+
+/// Order the files `file_locations` by `file_order`.
+fn order_files(
+    operators: &FileOperators,
+    mut file_locations: Vec<String>,
+    file_order: FileOrder,
+) -> Result<Vec<String>> {
+    match file_order {
+        FileOrder::Name => file_locations.sort(),
+        FileOrder::NameDesc => file_locations.sort_by(|a, b| b.cmp(a)),
+        FileOrder::Mtime => {
+            let mut files = Vec::with_capacity(file_locations.len());
+            for location in file_locations {
+                let _permit = operators.blocking_permit()?;
+                let meta = operators.stat(&location)?;
+                let modified = meta.last_modified().map(|t| t.unix_timestamp_nanos());
+                files.push((modified, location));
+            }
+            files.sort();
+            file_locations = files.into_iter().map(|(_, location)| location).collect();
+        }
+        FileOrder::AsListed => {}
+    }
+    Ok(file_locations)
+}
@@ -0,0 +1,284 @@
+//  Copyright 2022 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::sync::Arc;
+
+use common_arrow::arrow::datatypes::Field as ArrowField;
+use common_arrow::arrow::datatypes::Schema as ArrowSchema;
+use common_catalog::plan::Expression;
+use common_catalog::plan::PushDownInfo;
+use common_datavalues::prelude::*;
+use common_exception::Result;
+use common_storage::ColumnLeaves;
+
+use super::row_groups::column_value;
+use super::row_groups::is_comparable;
+use super::row_groups::row_group_ranges;
+use super::table::ParquetFileMeta;
+use super::ParquetTable;
+use super::TableContext;
+use crate::parquet_reader::physical_arrow_schema;
+
+/// The fraction of the rows taken to match the filters the statistics can't estimate.
+const DEFAULT_SELECTIVITY: f64 = 0.25;
+
+impl ParquetTable {
+    /// An estimate of the number of rows read by `push_down`, for the optimizer to order
+    /// the joins and plan the memory with, where the total row count of the files is exact.
+    ///
+    /// It is only an estimate, neither a lower nor an upper bound. The files and the row
+    /// groups pruned by `push_down` are left out like by [`Table::read_partitions`], and the
+    /// rows of the others are scaled by the selectivities of its filters, found from the
+    /// null counts and the min and max values of the row groups as if the values were
+    /// uniformly spread between them. The filters are taken as independent, the ones the
+    /// statistics can't estimate keep [`DEFAULT_SELECTIVITY`] of the rows. The projection
+    /// reads all the rows of the files, and the limit bounds the estimate.
+    ///
+    /// [`Table::read_partitions`]: common_catalog::table::Table::read_partitions
+    pub fn estimated_rows(
+        &self,
+        ctx: Arc<dyn TableContext>,
+        push_down: &Option<PushDownInfo>,
+    ) -> Result<usize> {
+        let file_metas = self.pruned_file_metas(ctx, push_down)?;
+
+        let file_schema = self.file_schema();
+        let arrow_schema = physical_arrow_schema(&file_schema, &self.interval_fields);
+        let column_leaves = ColumnLeaves::new_from_schema(&arrow_schema);
+        let filters = match push_down {
+            Some(extras) => extras.filters.as_slice(),
+            None => &[],
+        };
+        let predicates = filters
+            .iter()
+            .map(|filter| {
+                ColumnPredicate::try_create(filter, &file_schema, &arrow_schema, &column_leaves)
+            })
+            .collect::<Vec<_>>();
+
+        let mut rows = 0.0;
+        for meta in &file_metas {
+            let row_groups = match &meta.row_groups {
+                Some(row_groups) => row_groups.clone(),
+                None => (0..meta.file_meta.row_groups.len()).collect(),
+            };
+            let ranges = predicates
+                .iter()
+                .map(|predicate| match predicate {
+                    Some(predicate) => predicate.ranges(meta),
+                    None => Ok(None),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            for row_group in row_groups {
+                let selectivity = predicates
+                    .iter()
+                    .zip(ranges.iter())
+                    .map(|(predicate, ranges)| match predicate {
+                        Some(predicate) => predicate.selectivity(meta, row_group, ranges),
+                        None => DEFAULT_SELECTIVITY,
+                    })
+                    .product::<f64>();
+                rows += meta.file_meta.row_groups[row_group].num_rows() as f64 * selectivity;
+            }
+        }
+
+        let limit = push_down
+            .as_ref()
+            .filter(|p| p.order_by.is_empty())
+            .and_then(|p| p.limit)
+            .unwrap_or(usize::MAX);
+        Ok((rows.round() as usize).min(limit))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Eq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    IsNull,
+    IsNotNull,
+}
+
+/// A predicate comparing a non-nested column of the files with a constant, or checking
+/// whether it is null, whose selectivity in a row group is estimated from its statistics.
+struct ColumnPredicate {
+    field: ArrowField,
+    leaf_id: usize,
+    comparison: Comparison,
+    /// The constant with the signedness of the column, `Null` for the null checks.
+    value: DataValue,
+    /// Whether the column holds integers, whose values between the min and the max are
+    /// counted instead of measured.
+    is_integer: bool,
+}
+
+/// The min and max values of the column of a predicate in the row groups of a file.
+type Ranges = Option<(Vec<DataValue>, Vec<DataValue>)>;
+
+impl ColumnPredicate {
+    fn try_create(
+        filter: &Expression,
+        schema: &DataSchema,
+        arrow_schema: &ArrowSchema,
+        column_leaves: &ColumnLeaves,
+    ) -> Option<Self> {
+        let (name, comparison, value) = comparison_operands(filter)?;
+        let index = schema.index_of(name).ok()?;
+        let leaf = &column_leaves.column_leaves[index];
+        if leaf.children.is_some() || leaf.leaf_ids.len() != 1 {
+            return None;
+        }
+        let data_type = schema.field(index).data_type();
+        let value = match value {
+            Some(value) if is_comparable(data_type, value) => column_value(data_type, value).0,
+            Some(_) => return None,
+            None => DataValue::Null,
+        };
+        Some(ColumnPredicate {
+            field: arrow_schema.fields[index].clone(),
+            leaf_id: leaf.leaf_ids[0],
+            comparison,
+            value,
+            is_integer: remove_nullable(data_type).data_type_id().is_integer(),
+        })
+    }
+
+    /// The min and max values of the column in the row groups of the file of `meta`,
+    /// `None` for the null checks and the files missing the column.
+    fn ranges(&self, meta: &ParquetFileMeta) -> Result<Ranges> {
+        if matches!(self.comparison, Comparison::IsNull | Comparison::IsNotNull)
+            || meta.file_meta.row_groups.is_empty()
+        {
+            return Ok(None);
+        }
+        match meta.file_leaf_id(self.leaf_id) {
+            Some(leaf_id) => Ok(Some(row_group_ranges(&self.field, leaf_id, &meta.file_meta)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// The estimated fraction of the rows of row group `row_group` of the file of `meta`
+    /// matching the predicate, `ranges` being the ones of the file.
+    fn selectivity(&self, meta: &ParquetFileMeta, row_group: usize, ranges: &Ranges) -> f64 {
+        // The column is all null in the files missing it.
+        let leaf_id = match meta.file_leaf_id(self.leaf_id) {
+            Some(leaf_id) => leaf_id,
+            None if self.comparison == Comparison::IsNull => return 1.0,
+            None => return 0.0,
+        };
+        let row_group_meta = &meta.file_meta.row_groups[row_group];
+        let num_rows = row_group_meta.num_rows();
+        if num_rows == 0 {
+            return 0.0;
+        }
+        let null_fraction = row_group_meta.columns()[leaf_id]
+            .metadata()
+            .statistics
+            .as_ref()
+            .and_then(|statistics| statistics.null_count)
+            .map(|null_count| null_count as f64 / num_rows as f64);
+        match self.comparison {
+            Comparison::IsNull => return null_fraction.unwrap_or(DEFAULT_SELECTIVITY),
+            Comparison::IsNotNull => return null_fraction.map_or(1.0, |f| 1.0 - f),
+            _ => {}
+        }
+
+        let not_null = 1.0 - null_fraction.unwrap_or(0.0);
+        let (min, max) = match ranges {
+            Some((mins, maxs)) if !mins[row_group].is_null() && !maxs[row_group].is_null() => {
+                (&mins[row_group], &maxs[row_group])
+            }
+            _ => return not_null * DEFAULT_SELECTIVITY,
+        };
+        not_null * self.range_selectivity(min, max)
+    }
+
+    /// The estimated fraction of the values between `min` and `max` matching the predicate.
+    fn range_selectivity(&self, min: &DataValue, max: &DataValue) -> f64 {
+        let value = &self.value;
+        // The fractions of the values below the constant and equal to it, the latter is
+        // unknown for the floats and the strings within the range.
+        let (below, equal) = match (min.as_f64(), max.as_f64(), value.as_f64()) {
+            (Ok(min), Ok(max), Ok(value)) if value < min => (0.0, Some(0.0)),
+            (Ok(min), Ok(max), Ok(value)) if value > max => (1.0, Some(0.0)),
+            (Ok(min), Ok(max), Ok(_)) if min == max => (0.0, Some(1.0)),
+            (Ok(min), Ok(max), Ok(value)) if self.is_integer => {
+                let count = max - min + 1.0;
+                let equal = if value.fract() == 0.0 {
+                    1.0 / count
+                } else {
+                    0.0
+                };
+                ((value.ceil() - min) / count, Some(equal))
+            }
+            (Ok(min), Ok(max), Ok(value)) => ((value - min) / (max - min), None),
+            // The strings are only placed out of the range or in its middle.
+            _ if value < min => (0.0, Some(0.0)),
+            _ if value > max => (1.0, Some(0.0)),
+            _ if min == max => (0.0, Some(1.0)),
+            _ => (0.5, None),
+        };
+        let selectivity = match self.comparison {
+            Comparison::Eq => equal.unwrap_or(DEFAULT_SELECTIVITY),
+            Comparison::Lt => below,
+            Comparison::LtEq => below + equal.unwrap_or(0.0),
+            Comparison::Gt => 1.0 - below - equal.unwrap_or(0.0),
+            Comparison::GtEq => 1.0 - below,
+            Comparison::IsNull | Comparison::IsNotNull => 1.0,
+        };
+        selectivity.clamp(0.0, 1.0)
+    }
+}
+
+/// The column name, the comparison and the constant of `<column> <op> <constant>` (or
+/// `<constant> <op> <column>`), `is_not_null(<column>)` or `not(is_not_null(<column>))`.
+fn comparison_operands(filter: &Expression) -> Option<(&str, Comparison, Option<&DataValue>)> {
+    let (name, args) = match filter {
+        Expression::Function { name, args, .. } => (name.as_str(), args),
+        _ => return None,
+    };
+    match (name, args.as_slice()) {
+        ("is_not_null", [Expression::IndexedVariable { name, .. }]) => {
+            Some((name.as_str(), Comparison::IsNotNull, None))
+        }
+        ("not", [arg]) => match comparison_operands(arg)? {
+            (name, Comparison::IsNotNull, None) => Some((name, Comparison::IsNull, None)),
+            _ => None,
+        },
+        (op, [left, right]) => {
+            let (column, value, flipped) = match (left, right) {
+                (Expression::IndexedVariable { name, .. }, Expression::Constant { value, .. }) => {
+                    (name, value, false)
+                }
+                (Expression::Constant { value, .. }, Expression::IndexedVariable { name, .. }) => {
+                    (name, value, true)
+                }
+                _ => return None,
+            };
+            let comparison = match (op, flipped) {
+                ("=", _) => Comparison::Eq,
+                ("<", false) | (">", true) => Comparison::Lt,
+                ("<=", false) | (">=", true) => Comparison::LtEq,
+                (">", false) | ("<", true) => Comparison::Gt,
+                (">=", false) | ("<=", true) => Comparison::GtEq,
+                _ => return None,
+            };
+            Some((column.as_str(), comparison, Some(value)))
+        }
+        _ => None,
+    }
+}
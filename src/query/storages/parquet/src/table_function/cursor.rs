@@ -0,0 +1,92 @@
+//  Copyright 2022 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::collections::BTreeSet;
+
+use common_exception::ErrorCode;
+use common_exception::Result;
+
+use crate::ParquetPartInfo;
+
+const CURSOR_VERSION: u32 = 1;
+
+/// The progress of a scan of a [`crate::ParquetTable`], to resume it after an interruption.
+///
+/// The cursor records the parts completed, by the locations of their files and the
+/// indices of their row groups, and a resumed scan skips them. The files must not change
+/// in between. It is saved as JSON:
+///
+/// ```json
+/// {"version":1,"completed":[{"location":"data/a.parquet","row_group":0}]}
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ParquetScanCursor {
+    version: u32,
+    completed: BTreeSet<CompletedPart>,
+}
+
+/// A part of a [`ParquetScanCursor`], the row group `row_group` of the file at `location`.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+pub struct CompletedPart {
+    pub location: String,
+    pub row_group: usize,
+}
+
+impl Default for ParquetScanCursor {
+    fn default() -> Self {
+        ParquetScanCursor {
+            version: CURSOR_VERSION,
+            completed: BTreeSet::new(),
+        }
+    }
+}
+
+impl ParquetScanCursor {
+    pub fn complete(&mut self, part: &ParquetPartInfo) {
+        self.completed.insert(CompletedPart {
+            location: part.location.clone(),
+            row_group: part.row_group,
+        });
+    }
+
+    pub fn is_completed(&self, part: &ParquetPartInfo) -> bool {
+        self.completed.contains(&CompletedPart {
+            location: part.location.clone(),
+            row_group: part.row_group,
+        })
+    }
+
+    pub fn completed(&self) -> impl Iterator<Item = &CompletedPart> {
+        self.completed.iter()
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self).map_err(|e| {
+            ErrorCode::Internal(format!("Serialize cursor of read_parquet error: {}", e))
+        })
+    }
+
+    pub fn from_json(json: &str) -> Result<ParquetScanCursor> {
+        let cursor: ParquetScanCursor = serde_json::from_str(json).map_err(|e| {
+            ErrorCode::BadArguments(format!("invalid cursor of read_parquet: {}", e))
+        })?;
+        if cursor.version != CURSOR_VERSION {
+            return Err(ErrorCode::BadArguments(format!(
+                "invalid cursor of read_parquet: unsupported version {}, expect {}",
+                cursor.version, CURSOR_VERSION
+            )));
+        }
+        Ok(cursor)
+    }
+}
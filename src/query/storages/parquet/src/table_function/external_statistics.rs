@@ -0,0 +1,113 @@
+//  Copyright 2022 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use common_catalog::plan::Expression;
+use common_datavalues::DataSchema;
+use common_datavalues::DataValue;
+use common_exception::Result;
+use common_storages_index::range_filter::RangeFilter;
+use common_storages_table_meta::meta::ColumnStatistics;
+use common_storages_table_meta::meta::StatisticsOfColumns;
+
+use super::TableContext;
+
+/// The statistics of the files kept by an index outside of them, like a stats file
+/// maintained along with a dataset, see
+/// [`crate::ParquetTableBuilder::with_statistics_provider`].
+///
+/// The files whose statistics don't match the filters of a scan are pruned before any
+/// footer is read. The other files are pruned by the statistics of their footers as usual.
+pub trait ParquetStatisticsProvider: Send + Sync {
+    /// The statistics of the file at `location`, as resolved by the table, or `None` if
+    /// the index has none.
+    fn file_statistics(&self, location: &str) -> Result<Option<FileStatistics>>;
+}
+
+/// The statistics of a file given by a [`ParquetStatisticsProvider`].
+#[derive(Clone, Debug, Default)]
+pub struct FileStatistics {
+    pub num_rows: u64,
+    /// The statistics of the columns by their names in the table. The columns left out
+    /// are not used for the pruning of the file.
+    pub columns: HashMap<String, FileColumnStatistics>,
+}
+
+/// The statistics of a column of a file given by a [`ParquetStatisticsProvider`].
+///
+/// The values are the ones of the statistics of the footers: the integers are `Int64`
+/// values, `UInt64` ones if unsigned, and the floats `Float64` values. `Null` if all the
+/// values of the column are null.
+#[derive(Clone, Debug)]
+pub struct FileColumnStatistics {
+    pub min: DataValue,
+    pub max: DataValue,
+    pub null_count: u64,
+}
+
+/// Drop the files of `locations` whose statistics given by `provider` don't match
+/// `filters`, the files without statistics are kept.
+///
+/// `schema` is the table schema of the file columns. The filters are checked against the
+/// columns the statistics are given for, the files with the same columns share a filter.
+pub(super) fn prune_files(
+    ctx: Arc<dyn TableContext>,
+    provider: &dyn ParquetStatisticsProvider,
+    filters: &[Expression],
+    schema: &DataSchema,
+    locations: Vec<String>,
+) -> Result<Vec<String>> {
+    let mut range_filters: HashMap<Vec<usize>, RangeFilter> = HashMap::new();
+    let mut kept = Vec::with_capacity(locations.len());
+    for location in locations {
+        let statistics = match provider.file_statistics(&location)? {
+            Some(statistics) => statistics,
+            None => {
+                kept.push(location);
+                continue;
+            }
+        };
+        let indices = (0..schema.num_fields())
+            .filter(|index| statistics.columns.contains_key(schema.field(*index).name()))
+            .collect::<Vec<_>>();
+        let range_filter = match range_filters.entry(indices.clone()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let fields = indices.iter().map(|index| schema.field(*index).clone());
+                let schema = Arc::new(DataSchema::new(fields.collect()));
+                entry.insert(RangeFilter::try_create(ctx.clone(), filters, schema)?)
+            }
+        };
+
+        // The columns are identified by their positions in the schema of the filter.
+        let mut stats = StatisticsOfColumns::with_capacity(indices.len());
+        for (id, index) in indices.iter().enumerate() {
+            let column = &statistics.columns[schema.field(*index).name()];
+            stats.insert(id as u32, ColumnStatistics {
+                min: column.min.clone(),
+                max: column.max.clone(),
+                null_count: column.null_count,
+                in_memory_size: 0,
+                distinct_of_values: None,
+            });
+        }
+        if range_filter.eval(&stats, statistics.num_rows)? {
+            kept.push(location);
+        }
+    }
+    Ok(kept)
+}
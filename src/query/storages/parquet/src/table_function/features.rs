@@ -0,0 +1,589 @@
+//  Copyright 2022 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::any::Any;
+use std::sync::Arc;
+
+use common_arrow::parquet::compression::Compression;
+use common_arrow::parquet::encoding::Encoding;
+use common_arrow::parquet::metadata::FileMetaData;
+use common_arrow::parquet::schema::types::GroupLogicalType;
+use common_arrow::parquet::schema::types::ParquetType;
+use common_arrow::parquet::schema::types::PhysicalType;
+use common_arrow::parquet::schema::types::PrimitiveConvertedType;
+use common_arrow::parquet::schema::types::PrimitiveLogicalType;
+use common_arrow::parquet::schema::types::PrimitiveType;
+use common_arrow::parquet::thrift_format::PageEncodingStats;
+use common_arrow::parquet::thrift_format::PageType;
+use common_catalog::plan::DataSourcePlan;
+use common_catalog::plan::PartStatistics;
+use common_catalog::plan::Partitions;
+use common_catalog::plan::PushDownInfo;
+use common_catalog::table::Table;
+use common_catalog::table_args::TableArgs;
+use common_catalog::table_function::TableFunction;
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_meta_app::schema::TableIdent;
+use common_meta_app::schema::TableInfo;
+use common_meta_app::schema::TableMeta;
+use common_pipeline_core::Pipeline;
+use common_pipeline_sources::processors::sources::SyncSource;
+use common_pipeline_sources::processors::sources::SyncSourcer;
+use common_storage::StorageConfig;
+use parquet_format_safe::thrift::protocol::TCompactInputProtocol;
+use parquet_format_safe::BloomFilterAlgorithm;
+use parquet_format_safe::BloomFilterHeader;
+
+use super::builder::resolve_files;
+use super::table::check_allow_insecure;
+use super::table::read_parquet_meta;
+use super::FileOperators;
+use super::TableContext;
+
+/// The bytes read at the offset of a bloom filter for its header, which is a few bytes.
+const BLOOM_FILTER_HEADER_READ_SIZE: u64 = 64;
+
+/// The kind of a [`ParquetFeature`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeatureKind {
+    Encoding,
+    Compression,
+    LogicalType,
+    Encryption,
+    PageFormat,
+    BloomFilter,
+}
+
+impl FeatureKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FeatureKind::Encoding => "encoding",
+            FeatureKind::Compression => "compression",
+            FeatureKind::LogicalType => "logical_type",
+            FeatureKind::Encryption => "encryption",
+            FeatureKind::PageFormat => "page_format",
+            FeatureKind::BloomFilter => "bloom_filter",
+        }
+    }
+}
+
+/// A feature of the format a parquet file uses, see [`file_features`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParquetFeature {
+    pub kind: FeatureKind,
+    /// The name of the feature, like `Snappy` for a compression or `DECIMAL` for a logical
+    /// type.
+    pub name: String,
+    /// Whether `read_parquet` reads the columns using it.
+    pub supported: bool,
+    /// Why the feature is not supported if it is not, followed by the columns using it.
+    /// A feature is not supported if any of the columns using it is not.
+    pub note: String,
+}
+
+/// The parameters of a split block bloom filter, the only algorithm of the format.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BloomFilterParams {
+    /// The size of the bitset.
+    pub bits: u64,
+    /// The number of bits set by each value.
+    pub hashes: u32,
+}
+
+/// Whether a leaf column of a parquet file has bloom filters, see [`column_bloom_filters`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColumnBloomFilter {
+    pub path: String,
+    /// Whether the column has a bloom filter in any row group.
+    pub present: bool,
+    /// The parameters of the first bloom filter of the column, if they were read.
+    pub params: Option<BloomFilterParams>,
+}
+
+/// Whether `read_parquet` decodes the column chunks compressed by `compression`.
+pub(super) fn is_supported_compression(compression: Compression) -> bool {
+    compression != Compression::Lzo
+}
+
+/// Whether `read_parquet` decodes the pages encoded by `encoding`.
+pub(super) fn is_supported_encoding(encoding: Encoding) -> bool {
+    encoding != Encoding::BitPacked
+}
+
+/// The encodings, compressions, logical types, bloom filters and encryption the parquet
+/// file of `meta` uses, in the order they are found in the file.
+///
+/// The formats of the data pages (`DATA_PAGE` or `DATA_PAGE_V2`) are reported if the
+/// writer recorded the page encoding statistics in the footer, both are supported. The
+/// reader finds the format of each page from its header, whatever the other files use.
+///
+/// The bloom filters of the columns are reported as `present`, their parameters are not
+/// in the footer.
+pub fn file_features(meta: &FileMetaData) -> Vec<ParquetFeature> {
+    file_features_with_bloom_filters(meta, &column_bloom_filters(meta))
+}
+
+/// Like [`file_features`], with the bloom filters `bloom_filters` of the columns.
+fn file_features_with_bloom_filters(
+    meta: &FileMetaData,
+    bloom_filters: &[ColumnBloomFilter],
+) -> Vec<ParquetFeature> {
+    let mut features = Features::default();
+    for column in meta.row_groups.iter().flat_map(|rg| rg.columns()) {
+        let path = column.descriptor().path_in_schema.join(".");
+        let compression = column.compression();
+        let reason = (!is_supported_compression(compression))
+            .then(|| format!("{:?} compressed column chunks are not supported", compression));
+        features.add(FeatureKind::Compression, format!("{:?}", compression), reason, &path);
+        for encoding in column.column_encoding() {
+            let reason = (!is_supported_encoding(*encoding))
+                .then(|| format!("{:?} encoded pages are not supported", encoding));
+            features.add(FeatureKind::Encoding, format!("{:?}", encoding), reason, &path);
+        }
+        for page_format in data_page_formats(column.metadata().encoding_stats.as_deref()) {
+            features.add(FeatureKind::PageFormat, page_format.to_string(), None, &path);
+        }
+    }
+
+    for field in meta.schema().fields() {
+        logical_types(field, "", &mut features);
+    }
+
+    for bloom_filter in bloom_filters {
+        let name = match (bloom_filter.present, bloom_filter.params) {
+            (false, _) => "none".to_string(),
+            (true, None) => "present".to_string(),
+            (true, Some(params)) => format!("bits={}, hashes={}", params.bits, params.hashes),
+        };
+        features.add(FeatureKind::BloomFilter, name, None, &bloom_filter.path);
+    }
+
+    let encrypted = meta
+        .row_groups
+        .iter()
+        .flat_map(|rg| rg.columns())
+        .filter(|column| column.column_chunk().crypto_metadata.is_some())
+        .map(|column| column.descriptor().path_in_schema.join("."))
+        .collect::<Vec<_>>();
+    if encrypted.is_empty() {
+        features.add(FeatureKind::Encryption, "none".to_string(), None, "");
+    } else {
+        let reason = Some("encrypted columns can't be decrypted".to_string());
+        for path in encrypted {
+            features.add(FeatureKind::Encryption, "columns".to_string(), reason.clone(), &path);
+        }
+    }
+
+    features.finish()
+}
+
+/// Whether the leaf columns of the parquet file of `meta` have bloom filters, from the
+/// offsets of the filters in the footer, in the order of the columns.
+pub fn column_bloom_filters(meta: &FileMetaData) -> Vec<ColumnBloomFilter> {
+    meta.schema()
+        .columns()
+        .iter()
+        .enumerate()
+        .map(|(leaf_id, descriptor)| ColumnBloomFilter {
+            path: descriptor.path_in_schema.join("."),
+            present: meta.row_groups.iter().any(|rg| {
+                rg.columns()[leaf_id]
+                    .metadata()
+                    .bloom_filter_offset
+                    .is_some()
+            }),
+            params: None,
+        })
+        .collect()
+}
+
+/// Read the parameters of the first bloom filter of each column of `bloom_filters` having
+/// one, from the headers of the filters in the file at `location`.
+///
+/// The parameters of the filters which can't be read are left unknown.
+fn read_bloom_filter_params(
+    operators: &FileOperators,
+    location: &str,
+    meta: &FileMetaData,
+    bloom_filters: &mut [ColumnBloomFilter],
+) -> Result<()> {
+    let _permit = operators.blocking_permit()?;
+    let file_size = operators.stat(location)?.content_length();
+    for (leaf_id, bloom_filter) in bloom_filters.iter_mut().enumerate() {
+        let offset = meta
+            .row_groups
+            .iter()
+            .find_map(|rg| rg.columns()[leaf_id].metadata().bloom_filter_offset);
+        let offset = match offset {
+            Some(offset) if offset >= 0 && (offset as u64) < file_size => offset as u64,
+            _ => continue,
+        };
+        let end = (offset + BLOOM_FILTER_HEADER_READ_SIZE).min(file_size);
+        let data = operators.range_read(location, offset..end)?;
+        let mut prot = TCompactInputProtocol::new(data.as_slice(), data.len() * 2 + 1024);
+        if let Ok(header) = BloomFilterHeader::read_from_in_protocol(&mut prot) {
+            bloom_filter.params = match header.algorithm {
+                // Each value sets a bit in each of the 8 words of a block.
+                BloomFilterAlgorithm::BLOCK(_) if header.num_bytes >= 0 => {
+                    Some(BloomFilterParams {
+                        bits: header.num_bytes as u64 * 8,
+                        hashes: 8,
+                    })
+                }
+                _ => None,
+            };
+        }
+    }
+    Ok(())
+}
+
+/// The formats of the data pages counted by the page encoding statistics `encoding_stats`
+/// of a column chunk, if any.
+fn data_page_formats(encoding_stats: Option<&[PageEncodingStats]>) -> Vec<&'static str> {
+    let mut formats = vec![];
+    for stats in encoding_stats.unwrap_or_default() {
+        let format = match stats.page_type {
+            PageType::DATA_PAGE => "DATA_PAGE",
+            PageType::DATA_PAGE_V2 => "DATA_PAGE_V2",
+            _ => continue,
+        };
+        if !formats.contains(&format) {
+            formats.push(format);
+        }
+    }
+    formats
+}
+
+/// The features found so far, with the reasons why they are not supported and the
+/// columns using them.
+#[derive(Default)]
+struct Features(Vec<(FeatureKind, String, Option<String>, Vec<String>)>);
+
+impl Features {
+    /// Add the feature `name` of `kind` used by the column `path`, unsupported if there is a
+    /// `reason`.
+    fn add(&mut self, kind: FeatureKind, name: String, reason: Option<String>, path: &str) {
+        let index = match self.0.iter().position(|f| f.0 == kind && f.1 == name) {
+            Some(index) => index,
+            None => {
+                self.0.push((kind, name, None, vec![]));
+                self.0.len() - 1
+            }
+        };
+        let (_, _, feature_reason, paths) = &mut self.0[index];
+        if feature_reason.is_none() {
+            *feature_reason = reason;
+        }
+        if !path.is_empty() && !paths.iter().any(|p| p == path) {
+            paths.push(path.to_string());
+        }
+    }
+
+    fn finish(self) -> Vec<ParquetFeature> {
+        self.0
+            .into_iter()
+            .map(|(kind, name, reason, paths)| {
+                let mut note = reason.clone().unwrap_or_default();
+                if !paths.is_empty() {
+                    if !note.is_empty() {
+                        note.push_str("; ");
+                    }
+                    note.push_str(&format!("used by {}", paths.join(", ")));
+                }
+                ParquetFeature {
+                    kind,
+                    name,
+                    supported: reason.is_none(),
+                    note,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Add the logical types of `field`, under the path `prefix`, and of its nested fields.
+fn logical_types(field: &ParquetType, prefix: &str, features: &mut Features) {
+    let path = format!("{}{}", prefix, field.name());
+    match field {
+        ParquetType::PrimitiveType(primitive) => {
+            if let Some((name, reason)) = primitive_logical_type(primitive) {
+                let reason = match reason {
+                    None if name == "INTERVAL" && !prefix.is_empty() => {
+                        Some("nested INTERVAL values are not supported".to_string())
+                    }
+                    reason => reason.map(str::to_string),
+                };
+                features.add(FeatureKind::LogicalType, name.to_string(), reason, &path);
+            }
+        }
+        ParquetType::GroupType {
+            logical_type,
+            fields,
+            ..
+        } => {
+            match logical_type {
+                Some(GroupLogicalType::List) => {
+                    features.add(FeatureKind::LogicalType, "LIST".to_string(), None, &path)
+                }
+                Some(GroupLogicalType::Map) => {
+                    let reason = Some("MAP columns are not supported".to_string());
+                    features.add(FeatureKind::LogicalType, "MAP".to_string(), reason, &path)
+                }
+                None => {}
+            }
+            let prefix = format!("{}.", path);
+            for field in fields {
+                logical_types(field, &prefix, features);
+            }
+        }
+    }
+}
+
+/// The name of the logical type of `primitive`, taken from its converted type if it has
+/// none, with the reason why it is not supported if it is not.
+fn primitive_logical_type(
+    primitive: &PrimitiveType,
+) -> Option<(&'static str, Option<&'static str>)> {
+    // The DECIMAL values are read as FLOAT64 ones, but not the ones of variable length.
+    let decimal = match primitive.physical_type {
+        PhysicalType::ByteArray => (
+            "DECIMAL",
+            Some("decoding DECIMAL values of BYTE_ARRAY columns is not supported"),
+        ),
+        _ => ("DECIMAL", None),
+    };
+    let time = ("TIME", Some("decoding TIME values is not supported"));
+    match (&primitive.logical_type, &primitive.converted_type) {
+        (Some(logical_type), _) => Some(match logical_type {
+            PrimitiveLogicalType::String => ("STRING", None),
+            PrimitiveLogicalType::Enum => ("ENUM", None),
+            PrimitiveLogicalType::Decimal(_, _) => decimal,
+            PrimitiveLogicalType::Date => ("DATE", None),
+            PrimitiveLogicalType::Time { .. } => time,
+            PrimitiveLogicalType::Timestamp { .. } => ("TIMESTAMP", None),
+            PrimitiveLogicalType::Integer(_) => ("INTEGER", None),
+            PrimitiveLogicalType::Unknown => ("UNKNOWN", None),
+            PrimitiveLogicalType::Json => ("JSON", None),
+            PrimitiveLogicalType::Bson => ("BSON", None),
+            PrimitiveLogicalType::Uuid => ("UUID", Some("decoding UUID values is not supported")),
+        }),
+        (None, Some(converted_type)) => Some(match converted_type {
+            PrimitiveConvertedType::Utf8 => ("STRING", None),
+            PrimitiveConvertedType::Enum => ("ENUM", None),
+            PrimitiveConvertedType::Decimal(_, _) => decimal,
+            PrimitiveConvertedType::Date => ("DATE", None),
+            PrimitiveConvertedType::TimeMillis | PrimitiveConvertedType::TimeMicros => time,
+            PrimitiveConvertedType::TimestampMillis | PrimitiveConvertedType::TimestampMicros => {
+                ("TIMESTAMP", None)
+            }
+            PrimitiveConvertedType::Json => ("JSON", None),
+            PrimitiveConvertedType::Bson => ("BSON", None),
+            PrimitiveConvertedType::Interval => ("INTERVAL", None),
+            _ => ("INTEGER", None),
+        }),
+        (None, None) => None,
+    }
+}
+
+/// The table function `read_parquet_features`, the features of the format a parquet file
+/// uses, and whether `read_parquet` supports them.
+///
+/// ```sql
+/// select * from read_parquet_features('/data/a.parquet');
+/// ```
+///
+/// Only the footer of the file and the headers of its bloom filters are read. The footers of
+/// encrypted files can't be read, they are reported as such.
+pub struct ParquetFeaturesTable {
+    table_info: TableInfo,
+    table_args: Vec<DataValue>,
+    features: Vec<ParquetFeature>,
+    bloom_filters: Vec<ColumnBloomFilter>,
+}
+
+impl ParquetFeaturesTable {
+    pub fn create(
+        database_name: &str,
+        table_func_name: &str,
+        table_id: u64,
+        table_args: TableArgs,
+    ) -> Result<Arc<dyn TableFunction>> {
+        let config = StorageConfig::default();
+        Self::create_with_config(database_name, table_func_name, table_id, table_args, &config)
+    }
+
+    /// Create the table trusting the local files under the directory
+    /// `parquet_trusted_root` of `config`, see [`super::ParquetTable::create_with_config`].
+    pub fn create_with_config(
+        database_name: &str,
+        table_func_name: &str,
+        table_id: u64,
+        table_args: TableArgs,
+        config: &StorageConfig,
+    ) -> Result<Arc<dyn TableFunction>> {
+        let table_args = table_args.unwrap_or_default();
+        check_allow_insecure(&table_args, "read_parquet_features", &config.parquet_trusted_root)?;
+        let (operators, locations) = resolve_files(&table_args, None, &[])?;
+        if locations.len() != 1 {
+            return Err(ErrorCode::BadArguments(format!(
+                "read_parquet_features expects 1 file, but got {}",
+                locations.len()
+            )));
+        }
+
+        let (features, bloom_filters) = match read_parquet_meta(&operators, &locations[0]) {
+            Ok(meta) => {
+                let mut bloom_filters = column_bloom_filters(&meta);
+                read_bloom_filter_params(&operators, &locations[0], &meta, &mut bloom_filters)?;
+                let features = file_features_with_bloom_filters(&meta, &bloom_filters);
+                (features, bloom_filters)
+            }
+            Err(e) if e.code() == ErrorCode::PARQUET_DECRYPTION_FAILED => {
+                let feature = ParquetFeature {
+                    kind: FeatureKind::Encryption,
+                    name: "footer".to_string(),
+                    supported: false,
+                    note: "the footer is encrypted, the rest of the file can't be read"
+                        .to_string(),
+                };
+                (vec![feature], vec![])
+            }
+            Err(e) => return Err(e),
+        };
+
+        let table_info = TableInfo {
+            ident: TableIdent::new(table_id, 0),
+            desc: format!("'{}'.'{}'", database_name, table_func_name),
+            name: table_func_name.to_string(),
+            meta: TableMeta {
+                schema: Self::schema(),
+                engine: "SystemParquetFeatures".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        Ok(Arc::new(ParquetFeaturesTable {
+            table_info,
+            table_args,
+            features,
+            bloom_filters,
+        }))
+    }
+
+    pub fn schema() -> DataSchemaRef {
+        DataSchemaRefExt::create(vec![
+            DataField::new("kind", Vu8::to_data_type()),
+            DataField::new("feature", Vu8::to_data_type()),
+            DataField::new("supported", bool::to_data_type()),
+            DataField::new("note", Vu8::to_data_type()),
+        ])
+    }
+
+    pub fn features(&self) -> &[ParquetFeature] {
+        &self.features
+    }
+
+    /// Whether the leaf columns of the file have bloom filters, with their parameters.
+    pub fn bloom_filters(&self) -> &[ColumnBloomFilter] {
+        &self.bloom_filters
+    }
+
+    fn to_block(&self) -> DataBlock {
+        let len = self.features.len();
+        let mut kinds: Vec<Vec<u8>> = Vec::with_capacity(len);
+        let mut names: Vec<Vec<u8>> = Vec::with_capacity(len);
+        let mut supported: Vec<bool> = Vec::with_capacity(len);
+        let mut notes: Vec<Vec<u8>> = Vec::with_capacity(len);
+        for feature in self.features.iter() {
+            kinds.push(feature.kind.as_str().as_bytes().to_vec());
+            names.push(feature.name.clone().into_bytes());
+            supported.push(feature.supported);
+            notes.push(feature.note.clone().into_bytes());
+        }
+
+        DataBlock::create(Self::schema(), vec![
+            Series::from_data(kinds),
+            Series::from_data(names),
+            Series::from_data(supported),
+            Series::from_data(notes),
+        ])
+    }
+}
+
+#[async_trait::async_trait]
+impl Table for ParquetFeaturesTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn get_table_info(&self) -> &TableInfo {
+        &self.table_info
+    }
+
+    fn table_args(&self) -> Option<Vec<DataValue>> {
+        Some(self.table_args.clone())
+    }
+
+    async fn read_partitions(
+        &self,
+        _ctx: Arc<dyn TableContext>,
+        _push_downs: Option<PushDownInfo>,
+    ) -> Result<(PartStatistics, Partitions)> {
+        Ok((PartStatistics::default(), Partitions::default()))
+    }
+
+    fn read_data(
+        &self,
+        ctx: Arc<dyn TableContext>,
+        _plan: &DataSourcePlan,
+        pipeline: &mut Pipeline,
+    ) -> Result<()> {
+        let block = self.to_block();
+        pipeline.add_source(
+            |output| {
+                SyncSourcer::create(ctx.clone(), output, FeaturesSource {
+                    block: Some(block.clone()),
+                })
+            },
+            1,
+        )
+    }
+}
+
+impl TableFunction for ParquetFeaturesTable {
+    fn function_name(&self) -> &str {
+        self.name()
+    }
+
+    fn as_table<'a>(self: Arc<Self>) -> Arc<dyn Table + 'a>
+    where Self: 'a {
+        self
+    }
+}
+
+struct FeaturesSource {
+    block: Option<DataBlock>,
+}
+
+impl SyncSource for FeaturesSource {
+    const NAME: &'static str = "read_parquet_features";
+
+    fn generate(&mut self) -> Result<Option<DataBlock>> {
+        Ok(self.block.take())
+    }
+}
@@ -0,0 +1,310 @@
+//  Copyright 2022 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use common_catalog::plan::Expression;
+use common_datavalues::prelude::*;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_functions::scalars::cast_with_type;
+use common_functions::scalars::FunctionContext;
+use common_functions::scalars::DEFAULT_CAST_OPTIONS;
+use common_storages_index::range_filter::RangeFilter;
+use common_storages_table_meta::meta::ColumnStatistics;
+use common_storages_table_meta::meta::StatisticsOfColumns;
+
+use super::TableContext;
+
+/// The directory name of the partitions whose value is null.
+pub const HIVE_DEFAULT_PARTITION: &str = "__HIVE_DEFAULT_PARTITION__";
+
+/// The virtual column of the option `row_group_index`, the index of the row group of the
+/// rows in their file.
+pub const ROW_GROUP_INDEX_COLUMN: &str = "_row_group_index";
+
+/// The partition columns of files laid out in hive style, like `/data/year=2023/month=1/a.parquet`.
+///
+/// The partition values are parsed from the `<column>=<value>` directories of the paths, or
+/// taken from an explicit mapping of the files to their values, see
+/// [`HivePartitions::try_create_from_mapping`].
+pub struct HivePartitions {
+    /// The partition columns, in the order of the directories.
+    pub fields: Vec<DataField>,
+    /// The raw partition values of the files, by location.
+    values: HashMap<String, Vec<String>>,
+}
+
+impl HivePartitions {
+    /// Parse the partitions of `locations`, all of them must have the same partition columns.
+    pub fn try_create(locations: &[String]) -> Result<Self> {
+        let first = parse_hive_partitions(&locations[0]);
+        if first.is_empty() {
+            return Err(ErrorCode::BadArguments(format!(
+                "hive_partitioning is enabled, but no partition directories are found in path '{}'",
+                locations[0]
+            )));
+        }
+        let names = first.into_iter().map(|(name, _)| name).collect::<Vec<_>>();
+
+        let mut values = HashMap::with_capacity(locations.len());
+        for location in locations {
+            let partitions = parse_hive_partitions(location);
+            if partitions.len() != names.len()
+                || partitions
+                    .iter()
+                    .zip(names.iter())
+                    .any(|((k, _), n)| k != n)
+            {
+                return Err(ErrorCode::BadArguments(format!(
+                    "The partition columns of path '{}' are different from the ones of '{}'",
+                    location, locations[0]
+                )));
+            }
+            let partition_values = partitions.into_iter().map(|(_, v)| v).collect();
+            values.insert(location.clone(), partition_values);
+        }
+
+        Ok(Self::create(&names, values))
+    }
+
+    /// Take the partitions of `locations` from `mapping`, the `<column>=<value>` pairs of the
+    /// files matching glob patterns, as set by the option `partition_values`.
+    ///
+    /// Every file takes the values of the first pattern it matches, and must match one. All
+    /// the patterns must have the same partition columns.
+    pub fn try_create_from_mapping(
+        locations: &[String],
+        mapping: &[(String, Vec<(String, String)>)],
+    ) -> Result<Self> {
+        let names = mapping[0]
+            .1
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+        let mut patterns = Vec::with_capacity(mapping.len());
+        for (pattern, partitions) in mapping {
+            if partitions.len() != names.len()
+                || partitions
+                    .iter()
+                    .zip(names.iter())
+                    .any(|((k, _), n)| k != n)
+            {
+                return Err(ErrorCode::BadArguments(format!(
+                    "The partition columns of pattern '{}' are different from the ones of '{}'",
+                    pattern, mapping[0].0
+                )));
+            }
+            let matcher = glob::Pattern::new(pattern).map_err(|e| {
+                ErrorCode::BadArguments(format!(
+                    "invalid pattern '{}' for read_parquet option 'partition_values': {}",
+                    pattern, e
+                ))
+            })?;
+            patterns.push((matcher, partitions));
+        }
+
+        let mut values = HashMap::with_capacity(locations.len());
+        for location in locations {
+            let partitions = patterns
+                .iter()
+                .find(|(matcher, _)| matcher.matches(location))
+                .map(|(_, partitions)| partitions)
+                .ok_or_else(|| {
+                    ErrorCode::BadArguments(format!(
+                        "No partition values found for '{}' in read_parquet option 'partition_values'",
+                        location
+                    ))
+                })?;
+            let partition_values = partitions.iter().map(|(_, v)| v.clone()).collect();
+            values.insert(location.clone(), partition_values);
+        }
+
+        Ok(Self::create(&names, values))
+    }
+
+    /// Add the constant columns `literals` of the option `literals` to `partitions`, or to
+    /// no partitions. They take the same values for all the `locations`.
+    pub fn add_literals(
+        partitions: Option<Self>,
+        locations: &[String],
+        literals: &[(String, String, Option<String>)],
+    ) -> Result<Self> {
+        let mut partitions = partitions.unwrap_or_else(|| HivePartitions {
+            fields: vec![],
+            values: locations
+                .iter()
+                .map(|location| (location.clone(), vec![]))
+                .collect(),
+        });
+        for (name, value, type_name) in literals {
+            if partitions.fields.iter().any(|field| field.name() == name) {
+                return Err(ErrorCode::BadArguments(format!(
+                    "read_parquet option 'literals' has duplicated column '{}'",
+                    name
+                )));
+            }
+            let data_type = match type_name {
+                Some(type_name) => TypeFactory::instance().get(type_name).map_err(|e| {
+                    ErrorCode::BadArguments(format!(
+                        "invalid type '{}' of column '{}' in read_parquet option 'literals': {}",
+                        type_name,
+                        name,
+                        e.message()
+                    ))
+                })?,
+                None if value.parse::<i64>().is_ok() => i64::to_data_type(),
+                None => Vu8::to_data_type(),
+            };
+            let field = DataField::new(name, data_type);
+            // Fail on the values of the wrong type before any file is read.
+            partition_value(value, &field)?;
+            partitions.fields.push(field);
+            for values in partitions.values.values_mut() {
+                values.push(value.clone());
+            }
+        }
+        Ok(partitions)
+    }
+
+    fn create(names: &[String], values: HashMap<String, Vec<String>>) -> Self {
+        // Partition columns are integers if all the values are, strings otherwise.
+        let fields = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let is_integer = values.values().all(|values: &Vec<String>| {
+                    values[i] == HIVE_DEFAULT_PARTITION || values[i].parse::<i64>().is_ok()
+                });
+                let data_type = if is_integer {
+                    i64::to_data_type()
+                } else {
+                    Vu8::to_data_type()
+                };
+                DataField::new_nullable(name, data_type)
+            })
+            .collect();
+
+        HivePartitions { fields, values }
+    }
+
+    /// The raw partition values of the file at `location`.
+    pub fn values(&self, location: &str) -> Result<&Vec<String>> {
+        self.values.get(location).ok_or_else(|| {
+            ErrorCode::Internal(format!("No partition values found for '{}'", location))
+        })
+    }
+
+    /// Drop the files of `locations` whose partitions don't match `filters`.
+    ///
+    /// Only the paths are checked, so the dropped files are never opened.
+    pub fn prune(
+        &self,
+        ctx: Arc<dyn TableContext>,
+        filters: &[Expression],
+        locations: &[String],
+    ) -> Result<Vec<String>> {
+        let schema = Arc::new(DataSchema::new(self.fields.clone()));
+        let range_filter = RangeFilter::try_create(ctx, filters, schema)?;
+
+        let mut kept = Vec::with_capacity(locations.len());
+        for location in locations {
+            let stats = self.column_stats(location)?;
+            if range_filter.eval(&stats, 1)? {
+                kept.push(location.clone());
+            }
+        }
+        Ok(kept)
+    }
+
+    fn column_stats(&self, location: &str) -> Result<StatisticsOfColumns> {
+        let mut stats = HashMap::with_capacity(self.fields.len());
+        for (index, (field, value)) in self
+            .fields
+            .iter()
+            .zip(self.values(location)?.iter())
+            .enumerate()
+        {
+            let value = partition_value(value, field)?;
+            let null_count = if value.is_null() { 1 } else { 0 };
+            stats.insert(index as u32, ColumnStatistics {
+                min: value.clone(),
+                max: value,
+                null_count,
+                in_memory_size: 0,
+                distinct_of_values: None,
+            });
+        }
+        Ok(stats)
+    }
+}
+
+/// Convert the raw partition value `value` of the partition column `field`.
+///
+/// The values of the columns neither BIGINT nor STRING, which can be given to the option
+/// `literals`, are cast from their strings.
+pub fn partition_value(value: &str, field: &DataField) -> Result<DataValue> {
+    if value == HIVE_DEFAULT_PARTITION {
+        return Ok(DataValue::Null);
+    }
+
+    let data_type = remove_nullable(field.data_type());
+    match data_type.data_type_id() {
+        TypeID::Int64 => value.parse::<i64>().map(DataValue::Int64).map_err(|e| {
+            ErrorCode::BadArguments(format!(
+                "invalid value '{}' of partition column '{}': {}",
+                value,
+                field.name(),
+                e
+            ))
+        }),
+        TypeID::String => Ok(DataValue::String(value.as_bytes().to_vec())),
+        _ => {
+            let string_type = Vu8::to_data_type();
+            let string = DataValue::String(value.as_bytes().to_vec());
+            let column = string_type.create_constant_column(&string, 1)?;
+            let cast = cast_with_type(
+                &column,
+                &string_type,
+                &data_type,
+                &DEFAULT_CAST_OPTIONS,
+                &FunctionContext::default(),
+            )
+            .map_err(|e| {
+                ErrorCode::BadArguments(format!(
+                    "invalid value '{}' of column '{}' of type {}: {}",
+                    value,
+                    field.name(),
+                    data_type.name(),
+                    e.message()
+                ))
+            })?;
+            Ok(cast.get(0))
+        }
+    }
+}
+
+/// Parse the `<column>=<value>` directories of `location`, the column names are lower cased.
+fn parse_hive_partitions(location: &str) -> Vec<(String, String)> {
+    let dirs = match location.rsplit_once('/') {
+        Some((dirs, _)) => dirs,
+        None => return vec![],
+    };
+    dirs.split('/')
+        .filter_map(|dir| dir.split_once('='))
+        .filter(|(name, _)| !name.is_empty())
+        .map(|(name, value)| (name.to_lowercase(), value.to_string()))
+        .collect()
+}
@@ -12,9 +12,53 @@
 //  See the License for the specific language governing permissions and
 //  limitations under the License.
 
+mod blocks;
+mod builder;
+mod cardinality;
+mod cursor;
+mod external_statistics;
+mod features;
+mod hive;
+mod operators;
 mod part;
 mod read;
+mod row_groups;
+mod schema_diff;
+mod schemas_compatible;
+mod sorted;
+mod stage;
+mod statistics;
 mod table;
+mod table_args;
 
+pub use blocks::ParquetBlocks;
+pub use blocks::ParquetChunks;
+pub use builder::resolve_files;
+pub use builder::ParquetTableBuilder;
+pub use cursor::CompletedPart;
+pub use cursor::ParquetScanCursor;
+pub use external_statistics::FileColumnStatistics;
+pub use external_statistics::FileStatistics;
+pub use external_statistics::ParquetStatisticsProvider;
+pub use features::column_bloom_filters;
+pub use features::file_features;
+pub use features::BloomFilterParams;
+pub use features::ColumnBloomFilter;
+pub use features::FeatureKind;
+pub use features::ParquetFeature;
+pub use features::ParquetFeaturesTable;
 use common_catalog::table_context::TableContext;
+pub(crate) use hive::partition_value;
+pub use hive::ROW_GROUP_INDEX_COLUMN;
+pub use operators::Credentials;
+pub use operators::FileOperators;
+pub use operators::OpenFile;
+pub use operators::RequestPermit;
+pub use schema_diff::schema_diff;
+pub use schema_diff::ColumnDifference;
+pub use schema_diff::DifferenceKind;
+pub use schema_diff::ParquetSchemaDiffTable;
+pub use schemas_compatible::schema_incompatibility;
+pub use schemas_compatible::ParquetSchemasCompatibleTable;
+pub use table::narrow_trusted_root;
 pub use table::ParquetTable;
@@ -0,0 +1,670 @@
+//  Copyright 2022 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fs::File;
+use std::future::Future;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use common_base::base::mask_string;
+use common_base::base::GlobalIORuntime;
+use common_base::base::TrySpawn;
+use common_base::base::tokio::sync::OwnedSemaphorePermit;
+use common_base::base::tokio::sync::Semaphore;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_storage::init_operator;
+use common_storage::parse_uri_location;
+use common_storage::UriLocation;
+use memmap2::Mmap;
+use opendal::Object;
+use opendal::ObjectMetadata;
+use opendal::Operator;
+use parking_lot::Mutex;
+use parking_lot::RwLock;
+
+use super::builder::is_wildcard;
+
+/// The operators to read the files of a [`crate::ParquetTable`] through.
+///
+/// The files of URI patterns like `s3://<bucket>/<path>` keep their URIs as locations, and
+/// are read through the operator of their bucket. The other files are read through the
+/// default operator.
+///
+/// The files under a path prefix like `s3://<bucket>/<path>/` can be read through an
+/// operator of their own, with the credentials of the prefix, see
+/// [`FileOperators::add_prefix`]. The longest prefix of a location wins over the others and
+/// over its bucket.
+///
+/// The operators are shared by the clones (like the ones of the readers of a table), and
+/// released by [`FileOperators::close`], reading through any of the clones fails after.
+///
+/// The local files read through the default operator can be mapped in memory, see
+/// [`FileOperators::map_local_files`].
+///
+/// The file at [`STDIN_LOCATION`] is the standard input buffered in memory, see
+/// [`FileOperators::set_stdin`].
+///
+/// The requests to the storages can be limited to a number in flight at once, shared by
+/// all the operators and their clones, see [`FileOperators::limit_requests`]. The files
+/// open at once for reading can be limited too, see [`FileOperators::limit_open_files`].
+#[derive(Clone)]
+pub struct FileOperators {
+    /// `None` once closed.
+    inner: Arc<RwLock<Option<Operators>>>,
+}
+
+struct Operators {
+    default: Operator,
+    /// The operators rooted at the buckets, by their URIs (like `s3://<bucket>/`).
+    buckets: BTreeMap<String, Operator>,
+    /// The operators rooted at the buckets of the path prefixes (like
+    /// `s3://<bucket>/<path>/`), by their prefixes, the longest first.
+    prefixes: Vec<(String, Operator)>,
+    /// The local files mapped in memory by their locations, `None` for the ones which
+    /// failed to be mapped. `None` if the files are not mapped.
+    mapped_files: Option<HashMap<String, Option<Arc<Mmap>>>>,
+    /// The memory operator holding the standard input, if it is read.
+    stdin: Option<Operator>,
+    /// The limit of the requests in flight, if any.
+    request_limit: Option<Arc<RequestLimit>>,
+    /// The files open for reading, if they are limited.
+    open_files: Option<Arc<OpenFiles>>,
+}
+
+/// The requests in flight through the operators, bounded by the permits of `semaphore`.
+struct RequestLimit {
+    semaphore: Arc<Semaphore>,
+    in_flight: AtomicUsize,
+    /// The most requests in flight at once so far.
+    peak: AtomicUsize,
+}
+
+/// The files open for reading, at most the permits of `semaphore`.
+struct OpenFiles {
+    semaphore: Arc<Semaphore>,
+    /// The readers of each open file, with the permit it holds.
+    files: Mutex<HashMap<String, (usize, OwnedSemaphorePermit)>>,
+    /// The most files open at once so far.
+    peak: AtomicUsize,
+}
+
+impl OpenFiles {
+    /// Add a reader to the file at `location` if it is open, returns whether it is.
+    fn reopen(&self, location: &str) -> bool {
+        match self.files.lock().get_mut(location) {
+            Some((readers, _)) => {
+                *readers += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Open the file at `location` under `permit`. The permit is released if the file was
+    /// opened by another reader meanwhile, which it is added to.
+    fn open(&self, location: &str, permit: OwnedSemaphorePermit) {
+        let mut files = self.files.lock();
+        match files.get_mut(location) {
+            Some((readers, _)) => *readers += 1,
+            None => {
+                files.insert(location.to_string(), (1, permit));
+                self.peak.fetch_max(files.len(), Ordering::SeqCst);
+            }
+        }
+    }
+
+    /// Remove a reader of the file at `location`, returns whether the file is closed.
+    fn close(&self, location: &str) -> bool {
+        let mut files = self.files.lock();
+        match files.get_mut(location) {
+            Some((readers, _)) if *readers > 1 => {
+                *readers -= 1;
+                false
+            }
+            Some(_) => {
+                files.remove(location);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// A file open for reading, see [`FileOperators::open_file`]. The file is closed once all
+/// its readers are dropped, which releases its slot for another file.
+pub struct OpenFile {
+    /// `None` if the open files are not limited.
+    file: Option<(FileOperators, String)>,
+}
+
+impl Drop for OpenFile {
+    fn drop(&mut self) {
+        if let Some((operators, location)) = &self.file {
+            operators.close_file(location);
+        }
+    }
+}
+
+/// A request allowed to be sent to the storages, counted as in flight until dropped. See
+/// [`FileOperators::permit`].
+pub struct RequestPermit {
+    /// `None` if the requests are not limited.
+    limit: Option<(Arc<RequestLimit>, OwnedSemaphorePermit)>,
+}
+
+impl RequestPermit {
+    fn create(limit: Arc<RequestLimit>, permit: OwnedSemaphorePermit) -> Self {
+        let in_flight = limit.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        limit.peak.fetch_max(in_flight, Ordering::SeqCst);
+        RequestPermit {
+            limit: Some((limit, permit)),
+        }
+    }
+}
+
+impl Drop for RequestPermit {
+    fn drop(&mut self) {
+        if let Some((limit, _)) = &self.limit {
+            limit.in_flight.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+}
+
+/// The location of the file read from the standard input, given by the path `-`.
+pub const STDIN_LOCATION: &str = "-";
+
+impl FileOperators {
+    pub fn create(default: Operator) -> Self {
+        FileOperators {
+            inner: Arc::new(RwLock::new(Some(Operators {
+                default,
+                buckets: BTreeMap::new(),
+                prefixes: vec![],
+                mapped_files: None,
+                stdin: None,
+                request_limit: None,
+                open_files: None,
+            }))),
+        }
+    }
+
+    /// Read the files of the bucket `uri` through `operator`, which is rooted at the bucket.
+    pub fn add_bucket(&mut self, uri: &str, operator: Operator) {
+        if let Some(operators) = self.inner.write().as_mut() {
+            operators.buckets.insert(bucket_uri(uri), operator);
+        }
+    }
+
+    /// Read the files under the path prefix `prefix` (like `s3://<bucket>/<path>/`) through
+    /// `operator`, which is rooted at the bucket of the prefix. It replaces the operator of
+    /// the same prefix, if any.
+    pub fn add_prefix(&mut self, prefix: &str, operator: Operator) -> Result<()> {
+        let prefix = match parse_uri_pattern(prefix) {
+            Some((uri, path)) => format!("{}{}", uri, path),
+            None => return Err(invalid_prefix_error(prefix)),
+        };
+        if let Some(operators) = self.inner.write().as_mut() {
+            operators.prefixes.retain(|(p, _)| *p != prefix);
+            operators.prefixes.push((prefix, operator));
+            operators.prefixes.sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()));
+        }
+        Ok(())
+    }
+
+    /// Read the files under the path prefix `prefix` through an operator built from its URI
+    /// with the connection parameters `connection`, like `access_key_id`. The parameters
+    /// are secrets, they are left out of the errors.
+    ///
+    /// The operators of s3 and gcs have no blocking API, the files are stated and read
+    /// through their async one, see [`FileOperators::stat`].
+    pub fn add_credentials(
+        &mut self,
+        prefix: &str,
+        connection: &BTreeMap<String, String>,
+    ) -> Result<()> {
+        let uri = match parse_uri_pattern(prefix) {
+            Some((uri, _)) => uri,
+            None => return Err(invalid_prefix_error(prefix)),
+        };
+        let operator = build_bucket_operator(&uri, connection.clone())?;
+        self.add_prefix(prefix, operator)
+    }
+
+    /// The operator of the bucket `uri` returned by [`parse_uri_pattern`], built from the
+    /// URI if not added yet.
+    pub(super) fn bucket(&mut self, uri: &str) -> Result<Operator> {
+        let mut inner = self.inner.write();
+        let operators = inner.as_mut().ok_or_else(closed_error)?;
+        if let Some(operator) = operators.buckets.get(uri) {
+            return Ok(operator.clone());
+        }
+
+        let operator = build_bucket_operator(uri, BTreeMap::new())?;
+        operators.buckets.insert(uri.to_string(), operator.clone());
+        Ok(operator)
+    }
+
+    /// The operator to list the files matched by the path `path` of the bucket `uri`, the
+    /// one of the longest prefix of the path before its first wildcard if any, otherwise
+    /// the one of the bucket, see [`FileOperators::bucket`].
+    ///
+    /// The patterns matching files under several prefixes are listed with the operator
+    /// of their bucket, which must be allowed to list them.
+    pub(super) fn bucket_of_path(&mut self, uri: &str, path: &str) -> Result<Operator> {
+        let literal = match path.find(is_wildcard) {
+            Some(wildcard) => &path[..wildcard],
+            None => path,
+        };
+        let location = format!("{}{}", uri, literal);
+        if let Some(operator) = self.prefix_operator(&location)? {
+            return Ok(operator);
+        }
+        self.bucket(uri)
+    }
+
+    /// The operator of the longest prefix of `location`, if any.
+    fn prefix_operator(&self, location: &str) -> Result<Option<Operator>> {
+        let inner = self.inner.read();
+        let operators = inner.as_ref().ok_or_else(closed_error)?;
+        Ok(operators
+            .prefixes
+            .iter()
+            .find(|(prefix, _)| location.starts_with(prefix.as_str()))
+            .map(|(_, operator)| operator.clone()))
+    }
+
+    /// Read the file at [`STDIN_LOCATION`] from `content`, the buffered standard input.
+    pub fn set_stdin(&mut self, content: Vec<u8>) -> Result<()> {
+        let operator = Operator::new(opendal::services::memory::Builder::default().build()?);
+        operator.object(STDIN_LOCATION).blocking_write(content)?;
+        if let Some(operators) = self.inner.write().as_mut() {
+            operators.stdin = Some(operator);
+        }
+        Ok(())
+    }
+
+    /// The object of the file at `location`.
+    pub fn object(&self, location: &str) -> Result<Object> {
+        Ok(self.operator_object(location)?.1)
+    }
+
+    /// The object of the file at `location`, with the operator it is read through.
+    fn operator_object(&self, location: &str) -> Result<(Operator, Object)> {
+        let inner = self.inner.read();
+        let operators = inner.as_ref().ok_or_else(closed_error)?;
+        if let (STDIN_LOCATION, Some(stdin)) = (location, &operators.stdin) {
+            return Ok((stdin.clone(), stdin.object(STDIN_LOCATION)));
+        }
+        for (prefix, operator) in operators.prefixes.iter() {
+            if location.starts_with(prefix.as_str()) {
+                // The operators of the prefixes are rooted at their buckets.
+                let (_, path) = parse_uri_pattern(location).unwrap();
+                return Ok((operator.clone(), operator.object(path)));
+            }
+        }
+        for (uri, operator) in operators.buckets.iter() {
+            if let Some(path) = location.strip_prefix(uri.as_str()) {
+                return Ok((operator.clone(), operator.object(path)));
+            }
+        }
+        let operator = &operators.default;
+        Ok((operator.clone(), operator.object(location)))
+    }
+
+    /// The metadata of the file at `location`, blocking the thread while waiting for it.
+    ///
+    /// Like [`FileOperators::range_read`] and [`FileOperators::is_exist`], this goes through
+    /// the blocking API of the operator of the file if it has one, and through its async
+    /// API on the IO runtime otherwise (like the operators of s3 and gcs), see
+    /// [`block_on_io`].
+    pub fn stat(&self, location: &str) -> Result<ObjectMetadata> {
+        let (operator, object) = self.operator_object(location)?;
+        if operator.metadata().can_blocking() {
+            return Ok(object.blocking_metadata()?);
+        }
+        block_on_io(async move { object.metadata().await.map_err(ErrorCode::from) })
+    }
+
+    /// The bytes `range` of the file at `location`, see [`FileOperators::stat`].
+    pub fn range_read(&self, location: &str, range: Range<u64>) -> Result<Vec<u8>> {
+        let (operator, object) = self.operator_object(location)?;
+        if operator.metadata().can_blocking() {
+            return Ok(object.blocking_range_read(range)?);
+        }
+        block_on_io(async move { object.range_read(range).await.map_err(ErrorCode::from) })
+    }
+
+    /// Whether the file at `location` exists, see [`FileOperators::stat`].
+    pub fn is_exist(&self, location: &str) -> Result<bool> {
+        let (operator, object) = self.operator_object(location)?;
+        if operator.metadata().can_blocking() {
+            return Ok(object.blocking_is_exist()?);
+        }
+        block_on_io(async move { object.is_exist().await.map_err(ErrorCode::from) })
+    }
+
+    /// Map the files read through the default operator in memory, which must be the one of
+    /// the local file system rooted at `/`.
+    pub fn map_local_files(&mut self) {
+        if let Some(operators) = self.inner.write().as_mut() {
+            operators.mapped_files.get_or_insert_with(HashMap::new);
+        }
+    }
+
+    /// The content of the local file at `location` mapped in memory, `None` if the files
+    /// are not mapped or it fails to be mapped, then it is read through its operator.
+    ///
+    /// The file must not be modified while it is mapped.
+    pub fn mapped_file(&self, location: &str) -> Result<Option<Arc<Mmap>>> {
+        let mut inner = self.inner.write();
+        let operators = inner.as_mut().ok_or_else(closed_error)?;
+        let mapped_files = match &mut operators.mapped_files {
+            Some(mapped_files) => mapped_files,
+            None => return Ok(None),
+        };
+        if location == STDIN_LOCATION
+            || operators.buckets.keys().any(|uri| location.starts_with(uri.as_str()))
+            || operators.prefixes.iter().any(|(prefix, _)| location.starts_with(prefix.as_str()))
+        {
+            return Ok(None);
+        }
+        let mapped = mapped_files.entry(location.to_string()).or_insert_with(|| {
+            let file = File::open(Path::new("/").join(location)).ok()?;
+            // Safety: the files are not modified while they are read by the query.
+            let mmap = unsafe { Mmap::map(&file) }.ok()?;
+            Some(Arc::new(mmap))
+        });
+        Ok(mapped.clone())
+    }
+
+    /// Allow at most `max_requests` requests to the storages in flight at once, through any
+    /// of the operators. Each request waits for a permit, see [`FileOperators::permit`].
+    pub fn limit_requests(&mut self, max_requests: usize) {
+        if let Some(operators) = self.inner.write().as_mut() {
+            operators.request_limit = Some(Arc::new(RequestLimit {
+                semaphore: Arc::new(Semaphore::new(max_requests)),
+                in_flight: AtomicUsize::new(0),
+                peak: AtomicUsize::new(0),
+            }));
+        }
+    }
+
+    /// Allow at most `max_open_files` files open at once for reading, through any of the
+    /// operators. Each read of a file waits for it to be open, see
+    /// [`FileOperators::open_file`].
+    pub fn limit_open_files(&mut self, max_open_files: usize) {
+        if let Some(operators) = self.inner.write().as_mut() {
+            operators.open_files = Some(Arc::new(OpenFiles {
+                semaphore: Arc::new(Semaphore::new(max_open_files)),
+                files: Mutex::new(HashMap::new()),
+                peak: AtomicUsize::new(0),
+            }));
+        }
+    }
+
+    /// Open the file at `location` for reading, to hold while it is read. The readers of a
+    /// file share its slot, the other files wait for the slots of the closed ones.
+    pub async fn open_file(&self, location: &str) -> Result<OpenFile> {
+        let open_files = match self.open_files()? {
+            Some(open_files) => open_files,
+            None => return Ok(OpenFile { file: None }),
+        };
+        if !open_files.reopen(location) {
+            let permit = open_files.semaphore.clone().acquire_owned().await;
+            open_files.open(location, permit.map_err(semaphore_error)?);
+        }
+        Ok(OpenFile {
+            file: Some((self.clone(), location.to_string())),
+        })
+    }
+
+    /// Like [`FileOperators::open_file`], blocking the thread while waiting for a slot.
+    pub fn blocking_open_file(&self, location: &str) -> Result<OpenFile> {
+        futures::executor::block_on(self.open_file(location))
+    }
+
+    /// The most files open at once so far, `None` if the open files are not limited.
+    pub fn peak_open_files(&self) -> Option<usize> {
+        let inner = self.inner.read();
+        let open_files = inner.as_ref()?.open_files.as_ref()?;
+        Some(open_files.peak.load(Ordering::SeqCst))
+    }
+
+    fn open_files(&self) -> Result<Option<Arc<OpenFiles>>> {
+        let inner = self.inner.read();
+        let operators = inner.as_ref().ok_or_else(closed_error)?;
+        Ok(operators.open_files.clone())
+    }
+
+    /// Remove a reader of the file at `location`, the file is unmapped once closed.
+    fn close_file(&self, location: &str) {
+        if let Some(operators) = self.inner.write().as_mut() {
+            let closed = match &operators.open_files {
+                Some(open_files) => open_files.close(location),
+                None => false,
+            };
+            if let (true, Some(mapped_files)) = (closed, &mut operators.mapped_files) {
+                mapped_files.remove(location);
+            }
+        }
+    }
+
+    /// Wait for the permit of a request to the storages, to hold while it is in flight.
+    pub async fn permit(&self) -> Result<RequestPermit> {
+        match self.request_limit()? {
+            Some(limit) => {
+                let permit = limit.semaphore.clone().acquire_owned().await;
+                Ok(RequestPermit::create(limit, permit.map_err(semaphore_error)?))
+            }
+            None => Ok(RequestPermit { limit: None }),
+        }
+    }
+
+    /// Like [`FileOperators::permit`], blocking the thread while waiting for it.
+    pub fn blocking_permit(&self) -> Result<RequestPermit> {
+        match self.request_limit()? {
+            Some(limit) => {
+                let permit = futures::executor::block_on(limit.semaphore.clone().acquire_owned());
+                Ok(RequestPermit::create(limit, permit.map_err(semaphore_error)?))
+            }
+            None => Ok(RequestPermit { limit: None }),
+        }
+    }
+
+    /// The most requests in flight at once so far, `None` if the requests are not limited.
+    pub fn peak_requests(&self) -> Option<usize> {
+        let inner = self.inner.read();
+        let limit = inner.as_ref()?.request_limit.as_ref()?;
+        Some(limit.peak.load(Ordering::SeqCst))
+    }
+
+    fn request_limit(&self) -> Result<Option<Arc<RequestLimit>>> {
+        let inner = self.inner.read();
+        let operators = inner.as_ref().ok_or_else(closed_error)?;
+        Ok(operators.request_limit.clone())
+    }
+
+    /// Whether all the operators support the blocking API, `false` once closed.
+    pub fn can_blocking(&self) -> bool {
+        match self.inner.read().as_ref() {
+            Some(operators) => {
+                operators.default.metadata().can_blocking()
+                    && operators
+                        .buckets
+                        .values()
+                        .all(|operator| operator.metadata().can_blocking())
+                    && operators
+                        .prefixes
+                        .iter()
+                        .all(|(_, operator)| operator.metadata().can_blocking())
+            }
+            None => false,
+        }
+    }
+
+    /// Release the operators, closing them for all the clones.
+    ///
+    /// The connections of an operator are released once the objects opened by it (like
+    /// the ones being read) are dropped too.
+    pub fn close(&self) {
+        self.inner.write().take();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.inner.read().is_none()
+    }
+}
+
+/// Run `future` on the IO runtime, blocking the thread until it is done.
+///
+/// This drives the async API of the operators without a blocking one from the code which
+/// isn't async, like the creation of the table, the way the stages are looked up.
+pub(super) fn block_on_io<T, F>(future: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: Future<Output = Result<T>> + Send + 'static,
+{
+    let handle = GlobalIORuntime::instance().spawn(future);
+    futures::executor::block_on(handle)
+        .map_err(|e| ErrorCode::Internal(format!("IO task of read_parquet failed: {}", e)))?
+}
+
+fn closed_error() -> ErrorCode {
+    ErrorCode::StorageUnavailable("The operators of read_parquet are closed")
+}
+
+fn semaphore_error<E: std::fmt::Display>(e: E) -> ErrorCode {
+    ErrorCode::Internal(format!("Failed to wait for a request permit of read_parquet: {}", e))
+}
+
+/// The connection parameters of the storages by path prefix, set by the config
+/// `storage.parquet_credentials`, see [`crate::ParquetTableBuilder::with_credentials`].
+///
+/// The parameters are secrets: they are masked in the `Debug` output and left out of the
+/// errors. They are not taken from the arguments of the table functions, which end up in
+/// the plans and in the query log.
+#[derive(Clone, Default)]
+pub struct Credentials(Vec<(String, BTreeMap<String, String>)>);
+
+impl Credentials {
+    /// Parse the entries `<prefix>:<key>=<value>,...` separated by `;` of `value`, like
+    /// `s3://bucket/a/:access_key_id=...,secret_access_key=...`. Empty for none.
+    pub fn parse(value: &str) -> Result<Credentials> {
+        let credentials = value
+            .split(';')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(parse_credentials)
+            .collect::<Option<Vec<_>>>();
+        match credentials {
+            Some(credentials) => Ok(Credentials(credentials)),
+            // The value is left out, it holds secrets.
+            None => Err(ErrorCode::BadArguments(
+                "invalid config 'storage.parquet_credentials': expect '<prefix>:<key>=<value>,...' entries separated by ';', with prefixes like 's3://<bucket>/<path>/'",
+            )),
+        }
+    }
+
+    /// The path prefixes with their connection parameters, in the order of the config.
+    pub fn iter(&self) -> impl Iterator<Item = &(String, BTreeMap<String, String>)> {
+        self.0.iter()
+    }
+}
+
+impl Debug for Credentials {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_map()
+            .entries(self.0.iter().map(|(prefix, connection)| {
+                let masked = connection
+                    .iter()
+                    .map(|(key, value)| (key, mask_string(value, 3)))
+                    .collect::<BTreeMap<_, _>>();
+                (prefix, masked)
+            }))
+            .finish()
+    }
+}
+
+/// Parse an entry `<prefix>:<key>=<value>,...` of [`Credentials`], the prefix ends at the
+/// last `:` before the first `=` and must be a URI. The keys are lower cased.
+fn parse_credentials(entry: &str) -> Option<(String, BTreeMap<String, String>)> {
+    let colon = entry[..entry.find('=')?].rfind(':')?;
+    let prefix = entry[..colon].trim();
+    parse_uri_pattern(prefix)?;
+    let connection = entry[colon + 1..]
+        .split(',')
+        .map(|parameter| {
+            let (key, value) = parameter.split_once('=')?;
+            let key = key.trim().to_lowercase();
+            (!key.is_empty()).then(|| (key, value.trim().to_string()))
+        })
+        .collect::<Option<BTreeMap<_, _>>>()?;
+    Some((prefix.to_string(), connection))
+}
+
+fn invalid_prefix_error(prefix: &str) -> ErrorCode {
+    ErrorCode::BadArguments(format!(
+        "invalid path prefix '{}' for read_parquet: expect a URI like 's3://<bucket>/<path>/'",
+        prefix
+    ))
+}
+
+/// Build the operator rooted at the bucket `uri` returned by [`parse_uri_pattern`], with
+/// the connection parameters `connection`, which are left out of the errors.
+fn build_bucket_operator(uri: &str, connection: BTreeMap<String, String>) -> Result<Operator> {
+    let (scheme, bucket) = uri.trim_end_matches('/').split_once("://").ok_or_else(|| {
+        ErrorCode::BadArguments(format!("invalid location '{}' for read_parquet", uri))
+    })?;
+    let location = UriLocation {
+        protocol: scheme.to_string(),
+        name: bucket.to_string(),
+        path: "/".to_string(),
+        connection,
+    };
+    let (params, _) = parse_uri_location(&location).map_err(|e| {
+        ErrorCode::BadArguments(format!(
+            "invalid location '{}' for read_parquet: {}",
+            uri, e
+        ))
+    })?;
+    init_operator(&params)
+}
+
+/// Split a URI pattern `<scheme>://<bucket>/<path>` into the URI of the bucket and the path.
+///
+/// Returns `None` if `pattern` is not a URI.
+pub(super) fn parse_uri_pattern(pattern: &str) -> Option<(String, &str)> {
+    let (scheme, rest) = pattern.split_once("://")?;
+    if scheme.is_empty() || !scheme.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    let (bucket, path) = rest.split_once('/').unwrap_or((rest, ""));
+    Some((format!("{}://{}/", scheme.to_lowercase(), bucket), path))
+}
+
+/// Normalize the URI of a bucket to `<scheme>://<bucket>/`.
+fn bucket_uri(uri: &str) -> String {
+    match parse_uri_pattern(uri) {
+        Some((uri, _)) => uri,
+        None => format!("{}/", uri.trim_end_matches('/')),
+    }
+}
@@ -13,6 +13,7 @@
 //  limitations under the License.
 
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use common_catalog::plan::PartInfoPtr;
 use common_catalog::plan::PartStatistics;
@@ -20,22 +21,42 @@ use common_catalog::plan::Partitions;
 use common_catalog::plan::PartitionsShuffleKind;
 use common_catalog::plan::Projection;
 use common_catalog::plan::PushDownInfo;
+use common_datavalues::DataSchema;
+use common_exception::ErrorCode;
 use common_exception::Result;
 use common_storage::ColumnLeaves;
 
+use super::external_statistics::prune_files;
+use super::row_groups::NullPredicate;
+use super::row_groups::PointLookup;
+use super::sorted::KeyRange;
+use super::table_args::MergeBy;
+use super::table_args::MissingColumns;
+use super::table_args::PartOrder;
 use super::table::ParquetFileMeta;
 use super::ParquetTable;
+use super::TableContext;
+use crate::is_local_timestamp;
+use crate::parquet_reader::physical_arrow_schema;
+use crate::DecimalEncoding;
 use crate::ParquetColumnMeta;
 use crate::ParquetPartInfo;
+use crate::TimestampUnit;
 
 impl ParquetTable {
     #[inline]
     pub(super) fn do_read_partitions(
         &self,
+        ctx: Arc<dyn TableContext>,
         push_down: Option<PushDownInfo>,
     ) -> Result<(PartStatistics, Partitions)> {
-        let file_metas = self.read_file_metas()?;
-        let arrow_schema = self.table_info.schema().to_arrow();
+        let file_metas = self.pruned_file_metas(ctx, &push_down)?;
+        let projection = push_down.as_ref().and_then(|p| p.projection.as_ref());
+        self.check_missing_columns(&file_metas, projection)?;
+
+        // The partition columns are not stored in the files.
+        let file_schema = self.file_schema();
+        let arrow_schema = physical_arrow_schema(&file_schema, &self.interval_fields);
         let column_leaves = ColumnLeaves::new_from_schema(&arrow_schema);
 
         let limit = push_down
@@ -44,24 +65,275 @@ impl ParquetTable {
             .and_then(|p| p.limit)
             .unwrap_or(usize::MAX);
 
-        let (mut statistics, partitions) = match &push_down {
-            None => self.all_columns_partitions(&file_metas, limit),
-            Some(extras) => match &extras.projection {
+        // The rows matching a single `IS NULL` filter are counted by the null counts.
+        let null_counted = self.null_count_partitions(
+            &file_metas,
+            &push_down,
+            &file_schema,
+            &column_leaves,
+            limit,
+        );
+        let exact_filters = null_counted.is_some() || Self::is_exact(&push_down);
+        let (mut statistics, partitions) = match (null_counted, &push_down) {
+            (Some(null_counted), _) => null_counted,
+            (None, None) => self.all_columns_partitions(&file_metas, limit),
+            (None, Some(extras)) => match &extras.projection {
                 None => self.all_columns_partitions(&file_metas, limit),
                 Some(projection) => {
-                    self.projection_partitions(&file_metas, &column_leaves, projection, limit)
+                    let projection = self.file_projection(projection);
+                    self.projection_partitions(&file_metas, &column_leaves, &projection, limit)
                 }
             },
         };
 
-        statistics.is_exact = statistics.is_exact && Self::is_exact(&push_down);
-        // TODO: support prune parittions.
+        let partitions = match self.options.coalesce_file_size {
+            Some(coalesce_file_size) => coalesce_small_files(partitions, coalesce_file_size)?,
+            None => partitions,
+        };
+        let partitions = match self.options.part_order {
+            Some(part_order) => order_parts(partitions, part_order)?,
+            None => partitions,
+        };
+
+        statistics.is_exact = statistics.is_exact && exact_filters;
         statistics.partitions_scanned = file_metas.len();
-        statistics.partitions_total = file_metas.len();
+        statistics.partitions_total = self.file_locations.len();
+
+        let row_groups_pruned = file_metas
+            .iter()
+            .filter_map(|meta| {
+                let row_groups = meta.row_groups.as_ref()?;
+                Some(meta.file_meta.row_groups.len() - row_groups.len())
+            })
+            .sum();
+        let files_pruned = self.file_locations.len() - file_metas.len();
+        *self.pruned.write() = (files_pruned, row_groups_pruned);
+
+        if let Some(max_scan_bytes) = self.options.max_scan_bytes {
+            if statistics.read_bytes > max_scan_bytes {
+                return Err(ErrorCode::BadArguments(format!(
+                    "read_parquet would scan about {} bytes, more than max_scan_bytes ({} bytes)",
+                    statistics.read_bytes, max_scan_bytes
+                )));
+            }
+        }
 
         Ok((statistics, partitions))
     }
 
+    /// The footers of the files read by `push_down`, with the row groups to read, once the
+    /// files and row groups which can't match its filters are pruned.
+    pub(super) fn pruned_file_metas(
+        &self,
+        ctx: Arc<dyn TableContext>,
+        push_down: &Option<PushDownInfo>,
+    ) -> Result<Vec<ParquetFileMeta>> {
+        // Drop the files of the partitions not matching the filters before reading any footer.
+        let no_prune = self.options.no_prune;
+        let locations = match (&self.partitions, push_down) {
+            (Some(partitions), Some(extras)) if !no_prune && !extras.filters.is_empty() => {
+                partitions.prune(ctx.clone(), &extras.filters, &self.file_locations)?
+            }
+            _ => self.file_locations.clone(),
+        };
+
+        // Then the ones whose statistics in the external index don't match them.
+        let locations = match (&self.statistics_provider, push_down) {
+            (Some(provider), Some(extras)) if !no_prune && !extras.filters.is_empty() => {
+                let table_schema = self.table_info.schema();
+                let table_file_schema =
+                    DataSchema::new(table_schema.fields()[..self.num_file_fields()].to_vec());
+                prune_files(
+                    ctx,
+                    provider.as_ref(),
+                    &extras.filters,
+                    &table_file_schema,
+                    locations,
+                )?
+            }
+            _ => locations,
+        };
+
+        // The partition columns are not stored in the files.
+        let file_schema = self.file_schema();
+        let arrow_schema = physical_arrow_schema(&file_schema, &self.interval_fields);
+        let column_leaves = ColumnLeaves::new_from_schema(&arrow_schema);
+
+        // Only read the footers of the files which may match the predicates on the key the
+        // files are sorted by.
+        let key_range = match (&self.options.sorted_by, push_down) {
+            (Some(sorted_by), Some(extras)) if !no_prune => KeyRange::try_create(
+                sorted_by,
+                &extras.filters,
+                &file_schema,
+                &arrow_schema,
+                &column_leaves,
+            ),
+            _ => None,
+        };
+        let mut file_metas = match key_range {
+            Some(key_range) => {
+                let (locations, read_metas) =
+                    self.sorted_candidate_files(&locations, &key_range)?;
+                self.read_file_metas_with(&locations, read_metas)?
+            }
+            None => self.read_file_metas(&locations)?,
+        };
+
+        // Only read the row groups which may match an equality predicate.
+        let lookup = push_down.as_ref().and_then(|extras| {
+            PointLookup::try_create(&extras.filters, &file_schema, &arrow_schema, &column_leaves)
+        });
+        if no_prune {
+            for meta in file_metas.iter_mut() {
+                meta.row_groups = Some((0..meta.file_meta.row_groups.len()).collect());
+            }
+        } else if let Some(lookup) = lookup {
+            for meta in file_metas.iter_mut() {
+                meta.row_groups = Some(lookup.candidate_row_groups(meta)?);
+            }
+            file_metas.retain(
+                |meta| !matches!(&meta.row_groups, Some(row_groups) if row_groups.is_empty()),
+            );
+        }
+
+        // Skip the row groups whose null counts don't match the null predicates.
+        let null_predicates = match push_down {
+            Some(extras) if !no_prune => {
+                NullPredicate::try_create_all(&extras.filters, &file_schema, &column_leaves)
+            }
+            _ => vec![],
+        };
+        if !null_predicates.is_empty() {
+            for meta in file_metas.iter_mut() {
+                let row_groups = match &meta.row_groups {
+                    Some(row_groups) => row_groups.clone(),
+                    None => (0..meta.file_meta.row_groups.len()).collect(),
+                };
+                let row_groups = row_groups
+                    .into_iter()
+                    .filter(|row_group| {
+                        null_predicates
+                            .iter()
+                            .all(|predicate| predicate.may_match(meta, *row_group))
+                    })
+                    .collect();
+                meta.row_groups = Some(row_groups);
+            }
+            file_metas.retain(
+                |meta| !matches!(&meta.row_groups, Some(row_groups) if row_groups.is_empty()),
+            );
+        }
+
+        self.select_row_groups(&mut file_metas);
+        Ok(file_metas)
+    }
+
+    /// Skip the row groups of `file_metas` not selected by the row group filter of the
+    /// builder, if any, then the ones left out of the sample of the option `fraction`, if
+    /// set, and the files left without row groups.
+    ///
+    /// With the option `row_group_index`, every row group is selected on its own, so that
+    /// each part has a single row group to take the index of.
+    pub(super) fn select_row_groups(&self, file_metas: &mut Vec<ParquetFileMeta>) {
+        if self.options.row_group_index {
+            for meta in file_metas.iter_mut().filter(|meta| meta.row_groups.is_none()) {
+                meta.row_groups = Some((0..meta.file_meta.row_groups.len()).collect());
+            }
+        }
+        if self.row_group_filter.is_none() && self.options.sample_fraction.is_none() {
+            return;
+        }
+        if let Some(row_group_filter) = &self.row_group_filter {
+            for meta in file_metas.iter_mut() {
+                let row_groups = match &meta.row_groups {
+                    Some(row_groups) => row_groups.clone(),
+                    None => (0..meta.file_meta.row_groups.len()).collect(),
+                };
+                let row_groups = row_groups
+                    .into_iter()
+                    .filter(|row_group| row_group_filter(&meta.file_meta.row_groups[*row_group]))
+                    .collect();
+                meta.row_groups = Some(row_groups);
+            }
+        }
+        if let Some(fraction) = self.options.sample_fraction {
+            sample_row_groups(file_metas, fraction);
+        }
+        file_metas.retain(
+            |meta| !matches!(&meta.row_groups, Some(row_groups) if row_groups.is_empty()),
+        );
+    }
+
+    /// The part of `projection` stored in the files.
+    ///
+    /// If only partition columns (or `_row_group_index`) are projected, the first column
+    /// is still read to count the rows, like [`crate::ParquetReader`] does.
+    pub(super) fn file_projection(&self, projection: &Projection) -> Projection {
+        let num_file_fields = self.num_file_fields();
+        let projection = match projection {
+            Projection::Columns(indices) => Projection::Columns(
+                indices
+                    .iter()
+                    .copied()
+                    .filter(|index| *index < num_file_fields)
+                    .collect(),
+            ),
+            Projection::InnerColumns(path_indices) => Projection::InnerColumns(
+                path_indices
+                    .iter()
+                    .filter(|(_, path)| path[0] < num_file_fields)
+                    .map(|(index, path)| (*index, path.clone()))
+                    .collect(),
+            ),
+        };
+        let has_virtual_fields = num_file_fields < self.table_info.schema().num_fields();
+        if projection.is_empty() && has_virtual_fields {
+            Projection::Columns(vec![0])
+        } else {
+            projection
+        }
+    }
+
+    /// Reject the files of `file_metas` missing a column of `projection` (all the columns
+    /// if `None`), if the option `on_missing_columns=error` is set along `merge_by=name`.
+    /// The missing columns are read as NULL otherwise.
+    pub(super) fn check_missing_columns(
+        &self,
+        file_metas: &[ParquetFileMeta],
+        projection: Option<&Projection>,
+    ) -> Result<()> {
+        if self.options.merge_by != Some(MergeBy::Name)
+            || self.options.on_missing_columns != Some(MissingColumns::Error)
+        {
+            return Ok(());
+        }
+
+        let file_schema = self.file_schema();
+        let arrow_schema = physical_arrow_schema(&file_schema, &self.interval_fields);
+        let column_leaves = ColumnLeaves::new_from_schema(&arrow_schema);
+        let projection = projection.map(|projection| self.file_projection(projection));
+        let columns = match &projection {
+            Some(projection) => projection.project_column_leaves(&column_leaves)?,
+            None => column_leaves.column_leaves.iter().collect(),
+        };
+        for meta in file_metas {
+            let missing = columns.iter().find(|column| {
+                column
+                    .leaf_ids
+                    .iter()
+                    .any(|leaf_id| meta.file_leaf_id(*leaf_id).is_none())
+            });
+            if let Some(column) = missing {
+                return Err(ErrorCode::ParquetSchemaMismatch(format!(
+                    "Column '{}' is missing in parquet file '{}', which is rejected by the option 'on_missing_columns=error'",
+                    column.field.name, meta.location
+                )));
+            }
+        }
+        Ok(())
+    }
+
     fn is_exact(push_downs: &Option<PushDownInfo>) -> bool {
         match push_downs {
             None => true,
@@ -69,7 +341,110 @@ impl ParquetTable {
         }
     }
 
-    fn all_columns_partitions(
+    /// The parts of the rows matching the filter of `push_down` when it is only
+    /// `<column> IS NULL`, answered by the null counts of the row groups of `file_metas`
+    /// without scanning the data, like the total row count of the files.
+    ///
+    /// A part is made for each row group holding nulls in the column, with as many rows as
+    /// nulls and no column chunk to read, its columns are all NULL. The filter is still
+    /// applied to them, which keeps all their rows, and the row counts are exact. `None`
+    /// falls back to the parts of the scan, if the projection or the prewhere read
+    /// other columns, if the row groups are read as they are by `no_prune`,
+    /// `verify_checksums` or `skip_corrupt_row_groups`, or if some of the selected row
+    /// groups have no null count for the column.
+    fn null_count_partitions(
+        &self,
+        file_metas: &[ParquetFileMeta],
+        push_down: &Option<PushDownInfo>,
+        file_schema: &DataSchema,
+        column_leaves: &ColumnLeaves,
+        limit: usize,
+    ) -> Option<(PartStatistics, Partitions)> {
+        let options = &self.options;
+        if options.no_prune || options.verify_checksums || options.skip_corrupt_row_groups {
+            return None;
+        }
+        let extras = push_down.as_ref()?;
+        let predicates = NullPredicate::try_create_all(&extras.filters, file_schema, column_leaves);
+        let predicate = match predicates.as_slice() {
+            [predicate] if extras.filters.len() == 1 && predicate.is_null() => predicate,
+            _ => return None,
+        };
+
+        // The column of the filter is the only one read, if any.
+        let reads_other_columns = |projection: &Projection| {
+            let projection = self.file_projection(projection);
+            match projection.project_column_leaves(column_leaves) {
+                Ok(columns) => columns
+                    .iter()
+                    .flat_map(|column| column.leaf_ids.iter())
+                    .any(|leaf_id| *leaf_id != predicate.leaf_id()),
+                Err(_) => true,
+            }
+        };
+        match &extras.projection {
+            Some(projection) if !reads_other_columns(projection) => {}
+            _ => return None,
+        }
+        if let Some(prewhere) = &extras.prewhere {
+            if reads_other_columns(&prewhere.prewhere_columns)
+                || reads_other_columns(&prewhere.remain_columns)
+            {
+                return None;
+            }
+        }
+
+        let null_counts = file_metas
+            .iter()
+            .map(|meta| {
+                let row_groups = match &meta.row_groups {
+                    Some(row_groups) => row_groups.clone(),
+                    None => (0..meta.file_meta.row_groups.len()).collect(),
+                };
+                row_groups
+                    .into_iter()
+                    .map(|row_group| Some((row_group, predicate.null_count(meta, row_group)?)))
+                    .collect::<Option<Vec<_>>>()
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        let mut statistics = PartStatistics::default_exact();
+        let mut partitions = Partitions::default();
+
+        if limit == 0 {
+            return Some((statistics, partitions));
+        }
+
+        let mut remaining = limit;
+
+        'files: for (meta, null_counts) in file_metas.iter().zip(null_counts) {
+            for (row_group, rows) in null_counts.into_iter().filter(|(_, rows)| *rows > 0) {
+                partitions.partitions.push(ParquetPartInfo::create(
+                    meta.location.clone(),
+                    row_group,
+                    0,
+                    rows as u64,
+                    HashMap::new(),
+                    meta.partition_values.clone(),
+                ));
+                statistics.read_rows += rows;
+
+                if remaining > rows {
+                    remaining -= rows;
+                } else {
+                    // the last block we shall take
+                    if remaining != rows {
+                        statistics.is_exact = false;
+                    }
+                    break 'files;
+                }
+            }
+        }
+
+        Some((statistics, partitions))
+    }
+
+    pub(super) fn all_columns_partitions(
         &self,
         file_metas: &[ParquetFileMeta],
         limit: usize,
@@ -83,25 +458,31 @@ impl ParquetTable {
 
         let mut remaining = limit;
 
-        for meta in file_metas {
-            let rows = meta.file_meta.num_rows;
-            partitions.partitions.push(Self::all_columns_part(meta));
-            statistics.read_rows += rows;
-            statistics.read_bytes += meta
-                .file_meta
-                .row_groups
-                .iter()
-                .map(|rg| rg.total_byte_size())
-                .sum::<usize>();
+        'files: for meta in file_metas {
+            for (row_group, rows) in scanned_row_groups(meta) {
+                partitions
+                    .partitions
+                    .push(Self::all_columns_part(meta, row_group, rows));
+                statistics.read_rows += rows;
+                statistics.read_bytes += match &meta.row_groups {
+                    None => meta
+                        .file_meta
+                        .row_groups
+                        .iter()
+                        .map(|rg| rg.total_byte_size())
+                        .sum::<usize>(),
+                    Some(_) => meta.file_meta.row_groups[row_group].total_byte_size(),
+                };
 
-            if remaining > rows {
-                remaining -= rows;
-            } else {
-                // the last block we shall take
-                if remaining != rows {
-                    statistics.is_exact = false;
+                if remaining > rows {
+                    remaining -= rows;
+                } else {
+                    // the last block we shall take
+                    if remaining != rows {
+                        statistics.is_exact = false;
+                    }
+                    break 'files;
                 }
-                break;
             }
         }
 
@@ -124,41 +505,59 @@ impl ParquetTable {
 
         let mut remaining = limit;
 
-        for meta in file_metas {
-            let rows = meta.file_meta.num_rows;
-            partitions
-                .partitions
-                .push(Self::projection_part(meta, column_leaves, projection));
-
-            statistics.read_rows += rows;
-            let columns = projection.project_column_leaves(column_leaves).unwrap();
-            for column in &columns {
-                let indices = &column.leaf_ids;
-                let col_metas = meta.file_meta.row_groups[0].columns();
-                for index in indices {
-                    let col_meta = col_metas[*index].metadata();
-                    statistics.read_bytes += col_meta.total_compressed_size as usize;
+        'files: for meta in file_metas {
+            for (row_group, rows) in scanned_row_groups(meta) {
+                partitions.partitions.push(Self::projection_part(
+                    meta,
+                    row_group,
+                    rows,
+                    column_leaves,
+                    projection,
+                ));
+
+                statistics.read_rows += rows;
+                let columns = projection.project_column_leaves(column_leaves).unwrap();
+                for column in &columns {
+                    let indices = &column.leaf_ids;
+                    let col_metas = meta.file_meta.row_groups[row_group].columns();
+                    for index in indices.iter().filter_map(|index| meta.file_leaf_id(*index)) {
+                        let col_meta = col_metas[index].metadata();
+                        statistics.read_bytes += col_meta.total_compressed_size as usize;
+                    }
                 }
-            }
 
-            if remaining > rows {
-                remaining -= rows;
-            } else {
-                // the last block we shall take
-                if remaining != rows {
-                    statistics.is_exact = false;
+                if remaining > rows {
+                    remaining -= rows;
+                } else {
+                    // the last block we shall take
+                    if remaining != rows {
+                        statistics.is_exact = false;
+                    }
+                    break 'files;
                 }
-                break;
             }
         }
         (statistics, partitions)
     }
 
-    fn all_columns_part(parquet_file_meta: &ParquetFileMeta) -> PartInfoPtr {
-        let columns = parquet_file_meta.file_meta.row_groups[0].columns();
-        let mut columns_meta = HashMap::with_capacity(columns.len());
+    fn all_columns_part(
+        parquet_file_meta: &ParquetFileMeta,
+        row_group: usize,
+        rows: usize,
+    ) -> PartInfoPtr {
+        let columns = parquet_file_meta.file_meta.row_groups[row_group].columns();
+        let num_leaves = match &parquet_file_meta.leaf_ids {
+            Some(leaf_ids) => leaf_ids.len(),
+            None => columns.len(),
+        };
+        let mut columns_meta = HashMap::with_capacity(num_leaves);
 
-        for (idx, column_meta) in columns.iter().enumerate() {
+        // The leaf columns missing in the file are read as NULL.
+        for idx in 0..num_leaves {
+            let column_meta = match parquet_file_meta.file_leaf_id(idx) {
+                Some(file_idx) => &columns[file_idx],
+                None => continue,
+            };
             let metadata = column_meta.metadata();
             let col_start = if let Some(dict_page_offset) = metadata.dictionary_page_offset {
                 dict_page_offset
@@ -170,33 +569,44 @@ impl ParquetTable {
                 ParquetColumnMeta::create(
                     col_start as u64,
                     metadata.total_compressed_size as u64,
+                    metadata.total_uncompressed_size as u64,
                     metadata.num_values as u64,
                     column_meta.compression().into(),
+                    TimestampUnit::of(&column_meta.descriptor().descriptor.primitive_type),
+                    is_local_timestamp(&column_meta.descriptor().descriptor.primitive_type),
+                    DecimalEncoding::of(&column_meta.descriptor().descriptor.primitive_type),
                 ),
             );
         }
 
         ParquetPartInfo::create(
             parquet_file_meta.location.clone(),
+            row_group,
             0,
-            parquet_file_meta.file_meta.num_rows as u64,
+            rows as u64,
             columns_meta,
+            parquet_file_meta.partition_values.clone(),
         )
     }
 
-    fn projection_part(
+    pub(super) fn projection_part(
         parquet_file_meta: &ParquetFileMeta,
+        row_group: usize,
+        rows: usize,
         column_leaves: &ColumnLeaves,
         projection: &Projection,
     ) -> PartInfoPtr {
         let mut columns_meta = HashMap::with_capacity(projection.len());
-        let parquet_column_metas = parquet_file_meta.file_meta.row_groups[0].columns();
+        let parquet_column_metas = parquet_file_meta.file_meta.row_groups[row_group].columns();
 
         let columns = projection.project_column_leaves(column_leaves).unwrap();
         for column in &columns {
             let indices = &column.leaf_ids;
             for index in indices {
-                let parquet_column_meta = &parquet_column_metas[*index];
+                let parquet_column_meta = match parquet_file_meta.file_leaf_id(*index) {
+                    Some(file_index) => &parquet_column_metas[file_index],
+                    None => continue,
+                };
                 let metadata = parquet_column_meta.metadata();
                 let col_start = if let Some(dict_page_offset) = metadata.dictionary_page_offset {
                     dict_page_offset
@@ -209,8 +619,18 @@ impl ParquetTable {
                     ParquetColumnMeta::create(
                         col_start as u64,
                         metadata.total_compressed_size as u64,
+                        metadata.total_uncompressed_size as u64,
                         metadata.num_values as u64,
                         parquet_column_meta.compression().into(),
+                        TimestampUnit::of(
+                            &parquet_column_meta.descriptor().descriptor.primitive_type,
+                        ),
+                        is_local_timestamp(
+                            &parquet_column_meta.descriptor().descriptor.primitive_type,
+                        ),
+                        DecimalEncoding::of(
+                            &parquet_column_meta.descriptor().descriptor.primitive_type,
+                        ),
                     ),
                 );
             }
@@ -218,9 +638,118 @@ impl ParquetTable {
 
         ParquetPartInfo::create(
             parquet_file_meta.location.clone(),
+            row_group,
             0,
-            parquet_file_meta.file_meta.num_rows as u64,
+            rows as u64,
             columns_meta,
+            parquet_file_meta.partition_values.clone(),
         )
     }
 }
+
+/// The row groups of the file of `meta` to read, with their number of rows. A file
+/// without row groups, holding only a schema, has nothing to read.
+fn scanned_row_groups(meta: &ParquetFileMeta) -> Vec<(usize, usize)> {
+    match &meta.row_groups {
+        Some(row_groups) => row_groups
+            .iter()
+            .map(|row_group| (*row_group, meta.file_meta.row_groups[*row_group].num_rows()))
+            .collect(),
+        None if meta.file_meta.row_groups.is_empty() => vec![],
+        None => vec![(0, meta.file_meta.num_rows)],
+    }
+}
+
+/// Keep about `fraction` of the rows of the row groups of `file_metas`, by whole row groups
+/// spread evenly over the files in their order.
+///
+/// A row group is kept if the rows kept before it, with half of its own rows, are at most
+/// `fraction` of the rows of the row groups up to it. The rows kept are then within half a
+/// row group of `fraction` of the rows, all along the files, and the same row groups are
+/// kept by each scan.
+fn sample_row_groups(file_metas: &mut [ParquetFileMeta], fraction: f64) {
+    let mut seen_rows = 0;
+    let mut kept_rows = 0;
+    for meta in file_metas.iter_mut() {
+        let row_groups = match &meta.row_groups {
+            Some(row_groups) => row_groups.clone(),
+            None => (0..meta.file_meta.row_groups.len()).collect(),
+        };
+        let row_groups = row_groups
+            .into_iter()
+            .filter(|row_group| {
+                let rows = meta.file_meta.row_groups[*row_group].num_rows();
+                seen_rows += rows;
+                let keep = (kept_rows + rows / 2) as f64 <= fraction * seen_rows as f64;
+                if keep {
+                    kept_rows += rows;
+                }
+                keep
+            })
+            .collect();
+        meta.row_groups = Some(row_groups);
+    }
+}
+
+/// Coalesce the parts of the files whose scanned column chunks are smaller than
+/// `coalesce_file_size` bytes, following one another, into parts of about this size. The
+/// parts of the other files are kept as they are.
+fn coalesce_small_files(partitions: Partitions, coalesce_file_size: usize) -> Result<Partitions> {
+    let mut parts = Vec::with_capacity(partitions.partitions.len());
+    let mut group = vec![];
+    let mut group_size = 0;
+
+    let mut remaining = partitions.partitions.into_iter().peekable();
+    while let Some(part) = remaining.next() {
+        // The parts of a file follow one another.
+        let location = ParquetPartInfo::from_part(&part)?.location.clone();
+        let mut file_parts = vec![part];
+        while let Some(next) = remaining.peek() {
+            if ParquetPartInfo::from_part(next)?.location != location {
+                break;
+            }
+            file_parts.extend(remaining.next());
+        }
+        let file_size = file_parts
+            .iter()
+            .map(|part| Ok(ParquetPartInfo::from_part(part)?.size()))
+            .sum::<Result<usize>>()?;
+        if file_size >= coalesce_file_size {
+            parts.extend(file_parts);
+            continue;
+        }
+
+        group.extend(file_parts);
+        group_size += file_size;
+        if group_size >= coalesce_file_size {
+            parts.push(ParquetPartInfo::coalesce(std::mem::take(&mut group))?);
+            group_size = 0;
+        }
+    }
+    if !group.is_empty() {
+        parts.push(ParquetPartInfo::coalesce(group)?);
+    }
+    Ok(Partitions::create(partitions.kind, parts))
+}
+
+/// Sort `partitions` by the size of their column chunks, the coalesced parts by the sizes
+/// of the parts they hold. The parts of the same size keep their order.
+fn order_parts(partitions: Partitions, part_order: PartOrder) -> Result<Partitions> {
+    let mut sized_parts = partitions
+        .partitions
+        .into_iter()
+        .map(|part| {
+            let size = ParquetPartInfo::expand(part.clone())
+                .iter()
+                .map(|part| Ok(ParquetPartInfo::from_part(part)?.size()))
+                .sum::<Result<usize>>()?;
+            Ok((size, part))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    match part_order {
+        PartOrder::SizeDesc => sized_parts.sort_by(|(a, _), (b, _)| b.cmp(a)),
+        PartOrder::SizeAsc => sized_parts.sort_by_key(|(size, _)| *size),
+    }
+    let parts = sized_parts.into_iter().map(|(_, part)| part).collect();
+    Ok(Partitions::create(partitions.kind, parts))
+}
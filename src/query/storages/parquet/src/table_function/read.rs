@@ -14,6 +14,7 @@
 
 use std::sync::Arc;
 
+use chrono_tz::Tz;
 use common_catalog::plan::DataSourcePlan;
 use common_catalog::plan::Projection;
 use common_catalog::plan::PushDownInfo;
@@ -24,38 +25,77 @@ use common_pipeline_core::Pipeline;
 use common_sql::evaluator::EvalNode;
 use common_sql::evaluator::Evaluator;
 
+use super::table_args::DEFAULT_MAX_STRING_BLOCK_BYTES;
 use super::ParquetTable;
 use super::TableContext;
+use crate::MemoryBudget;
 use crate::ParquetReader;
+use crate::ParquetScanProgress;
+use crate::ParquetSourceOptions;
 use crate::ParquetTableSource;
 
 impl ParquetTable {
+    /// Create a reader of `projection`, taking the local TIMESTAMP values in the timezone of
+    /// the option `timezone`, or in UTC.
     pub fn create_reader(&self, projection: Projection) -> Result<Arc<ParquetReader>> {
+        self.create_session_reader(projection, Tz::UTC)
+    }
+
+    /// Like [`ParquetTable::create_reader`], with the local TIMESTAMP values taken in
+    /// `session_timezone` if the option `timezone` is not set.
+    fn create_session_reader(
+        &self,
+        projection: Projection,
+        session_timezone: Tz,
+    ) -> Result<Arc<ParquetReader>> {
         let table_schema = self.table_info.schema();
-        ParquetReader::create(self.operator.clone(), table_schema, projection)
+        ParquetReader::create(
+            self.operators.clone(),
+            table_schema,
+            projection,
+            &self.interval_fields,
+            &self.partition_fields(),
+            &self.casts,
+            self.options.verify_checksums,
+            self.options
+                .max_string_block_bytes
+                .unwrap_or(DEFAULT_MAX_STRING_BLOCK_BYTES),
+            self.options.memory_budget.map(|bytes| MemoryBudget {
+                bytes,
+                error: self.options.error_on_memory_budget,
+            }),
+            self.options.saturate_timestamps,
+            self.options.little_endian_decimals,
+            &self.decimal_casts,
+            self.options.timezone.unwrap_or(session_timezone),
+        )
     }
 
     // Build the block reader.
-    fn build_reader(&self, plan: &DataSourcePlan) -> Result<Arc<ParquetReader>> {
+    fn build_reader(&self, plan: &DataSourcePlan, timezone: Tz) -> Result<Arc<ParquetReader>> {
         match PushDownInfo::prewhere_of_push_downs(&plan.push_downs) {
             None => {
                 let projection =
                     PushDownInfo::projection_of_push_downs(&plan.schema(), &plan.push_downs);
-                self.create_reader(projection)
+                self.create_session_reader(projection, timezone)
             }
-            Some(v) => self.create_reader(v.output_columns),
+            Some(v) => self.create_session_reader(v.output_columns, timezone),
         }
     }
 
     // Build the prewhere reader.
-    fn build_prewhere_reader(&self, plan: &DataSourcePlan) -> Result<Arc<ParquetReader>> {
+    fn build_prewhere_reader(
+        &self,
+        plan: &DataSourcePlan,
+        timezone: Tz,
+    ) -> Result<Arc<ParquetReader>> {
         match PushDownInfo::prewhere_of_push_downs(&plan.push_downs) {
             None => {
                 let projection =
                     PushDownInfo::projection_of_push_downs(&plan.schema(), &plan.push_downs);
-                self.create_reader(projection)
+                self.create_session_reader(projection, timezone)
             }
-            Some(v) => self.create_reader(v.prewhere_columns),
+            Some(v) => self.create_session_reader(v.prewhere_columns, timezone),
         }
     }
 
@@ -78,7 +118,11 @@ impl ParquetTable {
     }
 
     // Build the remain reader.
-    fn build_remain_reader(&self, plan: &DataSourcePlan) -> Result<Arc<Option<ParquetReader>>> {
+    fn build_remain_reader(
+        &self,
+        plan: &DataSourcePlan,
+        timezone: Tz,
+    ) -> Result<Arc<Option<ParquetReader>>> {
         Ok(
             match PushDownInfo::prewhere_of_push_downs(&plan.push_downs) {
                 None => Arc::new(None),
@@ -86,7 +130,8 @@ impl ParquetTable {
                     if v.remain_columns.is_empty() {
                         Arc::new(None)
                     } else {
-                        Arc::new(Some((*self.create_reader(v.remain_columns)?).clone()))
+                        let reader = self.create_session_reader(v.remain_columns, timezone)?;
+                        Arc::new(Some((*reader).clone()))
                     }
                 }
             },
@@ -129,11 +174,25 @@ impl ParquetTable {
     ) -> Result<()> {
         let projection = PushDownInfo::projection_of_push_downs(&plan.schema(), &plan.push_downs);
         let max_io_requests = self.adjust_io_request(&ctx, &projection)?;
-        let block_reader = self.build_reader(plan)?;
-        let prewhere_reader = self.build_prewhere_reader(plan)?;
+        let timezone = ctx.try_get_function_context()?.tz;
+        let block_reader = self.build_reader(plan, timezone)?;
+        let prewhere_reader = self.build_prewhere_reader(plan, timezone)?;
         let prewhere_filter =
             self.build_prewhere_filter_executor(ctx.clone(), plan, prewhere_reader.schema())?;
-        let remain_reader = self.build_remain_reader(plan)?;
+        let remain_reader = self.build_remain_reader(plan, timezone)?;
+        let (files_pruned, row_groups_pruned) = *self.pruned.read();
+        let file_progress = ParquetScanProgress::create(&plan.parts.partitions)?
+            .with_pruned(files_pruned, row_groups_pruned);
+        let file_progress = Arc::new(file_progress);
+        *self.scan_progress.write() = Some(file_progress.clone());
+        let source_options = ParquetSourceOptions {
+            max_block_rows: self.options.max_block_rows,
+            skip_corrupt_row_groups: self.options.skip_corrupt_row_groups,
+            batch_size: self.options.batch_size,
+            read_ahead: self.options.read_ahead_window(),
+            file_progress,
+            warnings: self.warnings.clone(),
+        };
 
         // Add source pipe.
         pipeline.add_source(
@@ -145,6 +204,7 @@ impl ParquetTable {
                     prewhere_reader.clone(),
                     prewhere_filter.clone(),
                     remain_reader.clone(),
+                    source_options.clone(),
                 )
             },
             max_io_requests,
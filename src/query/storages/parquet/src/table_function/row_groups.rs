@@ -0,0 +1,294 @@
+//  Copyright 2022 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use common_arrow::arrow::datatypes::DataType as ArrowType;
+use common_arrow::arrow::datatypes::Field as ArrowField;
+use common_arrow::arrow::datatypes::Schema as ArrowSchema;
+use common_arrow::arrow::io::parquet::read::statistics;
+use common_arrow::parquet::metadata::FileMetaData;
+use common_catalog::plan::Expression;
+use common_datavalues::prelude::*;
+use common_exception::Result;
+use common_storage::ColumnLeaves;
+
+use super::table::ParquetFileMeta;
+use crate::DecimalEncoding;
+
+/// An equality predicate `<column> = <constant>` on a primitive column of the files.
+///
+/// Files sorted on the column (like the ones clustered on a key) have row groups with
+/// increasing and non-overlapping min/max values, the row groups which may hold the value
+/// are then found by a binary search instead of checking the statistics of all of them.
+pub struct PointLookup {
+    field: ArrowField,
+    leaf_id: usize,
+    /// The value with the signedness of the column, `None` if no value of the column is
+    /// equal to it.
+    value: Option<DataValue>,
+}
+
+impl PointLookup {
+    /// The first equality predicate of `filters` which can be looked up by statistics.
+    ///
+    /// `schema` is the table schema of the file columns, `arrow_schema` and `column_leaves`
+    /// the ones the files are read with.
+    pub fn try_create(
+        filters: &[Expression],
+        schema: &DataSchema,
+        arrow_schema: &ArrowSchema,
+        column_leaves: &ColumnLeaves,
+    ) -> Option<Self> {
+        filters.iter().find_map(|filter| {
+            let (name, value) = equality_operands(filter)?;
+            let index = schema.index_of(name).ok()?;
+            let leaf = &column_leaves.column_leaves[index];
+            if leaf.children.is_some() || leaf.leaf_ids.len() != 1 {
+                return None;
+            }
+            let data_type = schema.field(index).data_type();
+            if !is_comparable(data_type, value) {
+                return None;
+            }
+            let (value, exact) = column_value(data_type, value);
+            Some(PointLookup {
+                field: arrow_schema.fields[index].clone(),
+                leaf_id: leaf.leaf_ids[0],
+                value: exact.then_some(value),
+            })
+        })
+    }
+
+    /// The indices of the row groups of the file of `meta` which may hold the value.
+    ///
+    /// All of them are candidates if the file misses the column.
+    pub fn candidate_row_groups(&self, meta: &ParquetFileMeta) -> Result<Vec<usize>> {
+        let file_meta = &meta.file_meta;
+        let value = match &self.value {
+            Some(value) if !file_meta.row_groups.is_empty() => value,
+            _ => return Ok(vec![]),
+        };
+        let leaf_id = match meta.file_leaf_id(self.leaf_id) {
+            Some(leaf_id) => leaf_id,
+            None => return Ok((0..file_meta.row_groups.len()).collect()),
+        };
+
+        let (mins, maxs) = row_group_ranges(&self.field, leaf_id, file_meta)?;
+        Ok(search_row_groups(&mins, &maxs, value))
+    }
+}
+
+/// A predicate `<column> IS NOT NULL` or `<column> IS NULL` on a non-nested column of the
+/// files.
+///
+/// The row groups whose null counts show that none of their rows match are skipped, the
+/// ones where the column is all null for `IS NOT NULL`, and the ones where it has no null
+/// for `IS NULL`.
+pub struct NullPredicate {
+    leaf_id: usize,
+    is_null: bool,
+}
+
+impl NullPredicate {
+    /// The null predicates of `filters` which can be checked by statistics.
+    pub fn try_create_all(
+        filters: &[Expression],
+        schema: &DataSchema,
+        column_leaves: &ColumnLeaves,
+    ) -> Vec<Self> {
+        filters
+            .iter()
+            .filter_map(|filter| {
+                let (name, is_null) = null_operand(filter)?;
+                let index = schema.index_of(name).ok()?;
+                let leaf = &column_leaves.column_leaves[index];
+                // The null counts of the lists count their null items, not their rows.
+                let is_list = matches!(
+                    leaf.field.data_type,
+                    ArrowType::List(_) | ArrowType::LargeList(_) | ArrowType::FixedSizeList(_, _)
+                );
+                if leaf.children.is_some() || leaf.leaf_ids.len() != 1 || is_list {
+                    return None;
+                }
+                Some(NullPredicate {
+                    leaf_id: leaf.leaf_ids[0],
+                    is_null,
+                })
+            })
+            .collect()
+    }
+
+    /// Whether rows of row group `row_group` of the file of `meta` may match.
+    ///
+    /// The column is all null in the files missing it, and the row groups without a null
+    /// count may match.
+    pub fn may_match(&self, meta: &ParquetFileMeta, row_group: usize) -> bool {
+        let leaf_id = match meta.file_leaf_id(self.leaf_id) {
+            Some(leaf_id) => leaf_id,
+            None => return self.is_null,
+        };
+        let row_group = &meta.file_meta.row_groups[row_group];
+        let null_count = row_group.columns()[leaf_id]
+            .metadata()
+            .statistics
+            .as_ref()
+            .and_then(|statistics| statistics.null_count);
+        match null_count {
+            None => true,
+            Some(null_count) if self.is_null => null_count > 0,
+            Some(null_count) => (null_count as usize) < row_group.num_rows(),
+        }
+    }
+
+    /// Whether the predicate is `IS NULL`, not `IS NOT NULL`.
+    pub fn is_null(&self) -> bool {
+        self.is_null
+    }
+
+    pub fn leaf_id(&self) -> usize {
+        self.leaf_id
+    }
+
+    /// The number of nulls of the column in row group `row_group` of the file of `meta`,
+    /// all its rows in the files missing the column, `None` without a null count.
+    pub fn null_count(&self, meta: &ParquetFileMeta, row_group: usize) -> Option<usize> {
+        let row_group = &meta.file_meta.row_groups[row_group];
+        let leaf_id = match meta.file_leaf_id(self.leaf_id) {
+            Some(leaf_id) => leaf_id,
+            None => return Some(row_group.num_rows()),
+        };
+        row_group.columns()[leaf_id]
+            .metadata()
+            .statistics
+            .as_ref()
+            .and_then(|statistics| statistics.null_count)
+            .map(|null_count| null_count as usize)
+    }
+}
+
+/// The min and max values of the leaf column `leaf_id` of `field` in the row groups of
+/// `file_meta`, missing statistics are `Null`.
+///
+/// The statistics of DECIMAL columns are the ones of their unscaled values, not of the
+/// FLOAT64 values they are read as, and are taken as missing.
+pub(super) fn row_group_ranges(
+    field: &ArrowField,
+    leaf_id: usize,
+    file_meta: &FileMetaData,
+) -> Result<(Vec<DataValue>, Vec<DataValue>)> {
+    // The statistics are matched by the name of the column in the file, which may
+    // differ from the one in the table (lower cased or renamed by `columns`).
+    let column = &file_meta.row_groups[0].columns()[leaf_id];
+    if DecimalEncoding::of(&column.descriptor().descriptor.primitive_type).is_some() {
+        let missing = vec![DataValue::Null; file_meta.row_groups.len()];
+        return Ok((missing.clone(), missing));
+    }
+    let mut field = field.clone();
+    field.name = column.descriptor().path_in_schema[0].clone();
+
+    let stats = statistics::deserialize(&field, &file_meta.row_groups)?;
+    let mins = stats.min_value.into_nullable_column();
+    let maxs = stats.max_value.into_nullable_column();
+    let mins = (0..mins.len()).map(|i| mins.get(i)).collect::<Vec<_>>();
+    let maxs = (0..maxs.len()).map(|i| maxs.get(i)).collect::<Vec<_>>();
+    Ok((mins, maxs))
+}
+
+/// The row groups whose `[min, max]` range may hold `value`, missing statistics are `Null`.
+///
+/// The search is binary if the ranges are sorted, and linear otherwise.
+fn search_row_groups(mins: &[DataValue], maxs: &[DataValue], value: &DataValue) -> Vec<usize> {
+    let has_all_stats = mins.iter().chain(maxs.iter()).all(|v| !v.is_null());
+    let sorted = has_all_stats && (1..mins.len()).all(|i| maxs[i - 1] <= mins[i]);
+    if sorted {
+        // Only adjacent row groups sharing a bound may hold the same value.
+        let start = maxs.partition_point(|max| max < value);
+        (start..mins.len())
+            .take_while(|i| &mins[*i] <= value)
+            .collect()
+    } else {
+        (0..mins.len())
+            .filter(|i| {
+                (mins[*i].is_null() || &mins[*i] <= value)
+                    && (maxs[*i].is_null() || &maxs[*i] >= value)
+            })
+            .collect()
+    }
+}
+
+/// The column name and the constant of `<column> = <constant>` (or `<constant> = <column>`).
+fn equality_operands(filter: &Expression) -> Option<(&str, &DataValue)> {
+    match filter {
+        Expression::Function { name, args, .. } if name == "=" && args.len() == 2 => {
+            match (&args[0], &args[1]) {
+                (Expression::IndexedVariable { name, .. }, Expression::Constant { value, .. })
+                | (Expression::Constant { value, .. }, Expression::IndexedVariable { name, .. }) => {
+                    Some((name.as_str(), value))
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// The column name of `is_not_null(<column>)`, or `not(is_not_null(<column>))` as `IS NULL`
+/// is planned, with whether it is the latter.
+fn null_operand(filter: &Expression) -> Option<(&str, bool)> {
+    match filter {
+        Expression::Function { name, args, .. } if name == "is_not_null" && args.len() == 1 => {
+            match &args[0] {
+                Expression::IndexedVariable { name, .. } => Some((name.as_str(), false)),
+                _ => None,
+            }
+        }
+        Expression::Function { name, args, .. } if name == "not" && args.len() == 1 => {
+            let (name, is_null) = null_operand(&args[0])?;
+            (!is_null).then_some((name, true))
+        }
+        _ => None,
+    }
+}
+
+/// Whether the statistics of a column of `data_type` can be compared with `value`.
+pub(super) fn is_comparable(data_type: &DataTypeImpl, value: &DataValue) -> bool {
+    let data_type = remove_nullable(data_type);
+    match data_type.data_type_id() {
+        TypeID::String => matches!(value, DataValue::String(_)),
+        id if id.is_numeric() => value.is_numeric(),
+        _ => false,
+    }
+}
+
+/// `value` with the signedness of the integer column of `data_type`, and whether it is in
+/// the range of the column.
+///
+/// The statistics of unsigned columns are `UInt64` values, and the ones of signed columns
+/// `Int64` values. Comparing them with a value of the other signedness would take the
+/// values over `i64::MAX` as negative ones, `value` is converted instead. The values out
+/// of the range of the column are clamped to it, negative ones to 0 for unsigned columns
+/// and the ones over `i64::MAX` to `i64::MAX` for signed columns.
+pub(super) fn column_value(data_type: &DataTypeImpl, value: &DataValue) -> (DataValue, bool) {
+    let id = remove_nullable(data_type).data_type_id();
+    match value {
+        DataValue::Int64(v) if id.is_unsigned_integer() => match u64::try_from(*v) {
+            Ok(v) => (DataValue::UInt64(v), true),
+            Err(_) => (DataValue::UInt64(0), false),
+        },
+        DataValue::UInt64(v) if id.is_signed_integer() => match i64::try_from(*v) {
+            Ok(v) => (DataValue::Int64(v), true),
+            Err(_) => (DataValue::Int64(i64::MAX), false),
+        },
+        _ => (value.clone(), true),
+    }
+}
@@ -0,0 +1,358 @@
+//  Copyright 2022 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::any::Any;
+use std::sync::Arc;
+
+use common_catalog::plan::DataSourcePlan;
+use common_catalog::plan::PartStatistics;
+use common_catalog::plan::Partitions;
+use common_catalog::plan::PushDownInfo;
+use common_catalog::table::Table;
+use common_catalog::table_args::TableArgs;
+use common_catalog::table_function::TableFunction;
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_meta_app::schema::TableIdent;
+use common_meta_app::schema::TableInfo;
+use common_meta_app::schema::TableMeta;
+use common_pipeline_core::Pipeline;
+use common_pipeline_sources::processors::sources::SyncSource;
+use common_pipeline_sources::processors::sources::SyncSourcer;
+use common_storage::StorageConfig;
+
+use super::table_args::parse_table_args;
+use super::ParquetTable;
+use super::TableContext;
+
+/// The kind of a [`ColumnDifference`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DifferenceKind {
+    /// The column is only in the first schema.
+    OnlyInA,
+    /// The column is only in the second schema.
+    OnlyInB,
+    /// The column has different types, apart from their nullability.
+    Type,
+    /// The column is nullable in only one of the schemas.
+    Nullability,
+}
+
+impl DifferenceKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DifferenceKind::OnlyInA => "only_in_a",
+            DifferenceKind::OnlyInB => "only_in_b",
+            DifferenceKind::Type => "type",
+            DifferenceKind::Nullability => "nullability",
+        }
+    }
+}
+
+/// A difference of a column between two schemas, see [`schema_diff`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColumnDifference {
+    /// The path of the column. The fields of structs are joined by `.` to the path of the
+    /// struct, and the elements of arrays are `[]` after the path of the array.
+    pub column: String,
+    pub kind: DifferenceKind,
+    /// The SQL type of the column in the first schema, `None` if it is not there.
+    pub a_type: Option<String>,
+    /// The SQL type of the column in the second schema, `None` if it is not there.
+    pub b_type: Option<String>,
+}
+
+/// The differences of the columns of `a` and `b`, nested fields included.
+///
+/// The columns are matched by name, in the order of `a` followed by the ones only in `b`.
+/// The nested fields of the columns of both are compared, instead of reporting the
+/// changes of their types.
+pub fn schema_diff(a: &DataSchema, b: &DataSchema) -> Vec<ColumnDifference> {
+    let fields = |schema: &DataSchema| {
+        schema
+            .fields()
+            .iter()
+            .map(|field| (field.name().clone(), field.data_type().clone()))
+            .collect::<Vec<_>>()
+    };
+    let mut differences = vec![];
+    diff_fields("", &fields(a), &fields(b), &mut differences);
+    differences
+}
+
+fn diff_fields(
+    prefix: &str,
+    a: &[(String, DataTypeImpl)],
+    b: &[(String, DataTypeImpl)],
+    differences: &mut Vec<ColumnDifference>,
+) {
+    let path = |name: &str| format!("{}{}", prefix, name);
+    for (name, a_type) in a {
+        match b.iter().find(|(b_name, _)| b_name == name) {
+            Some((_, b_type)) => diff_types(&path(name), a_type, b_type, differences),
+            None => differences.push(ColumnDifference {
+                column: path(name),
+                kind: DifferenceKind::OnlyInA,
+                a_type: Some(a_type.sql_name()),
+                b_type: None,
+            }),
+        }
+    }
+    for (name, b_type) in b {
+        if !a.iter().any(|(a_name, _)| a_name == name) {
+            differences.push(ColumnDifference {
+                column: path(name),
+                kind: DifferenceKind::OnlyInB,
+                a_type: None,
+                b_type: Some(b_type.sql_name()),
+            });
+        }
+    }
+}
+
+fn diff_types(
+    path: &str,
+    a_type: &DataTypeImpl,
+    b_type: &DataTypeImpl,
+    differences: &mut Vec<ColumnDifference>,
+) {
+    let difference = |kind| ColumnDifference {
+        column: path.to_string(),
+        kind,
+        a_type: Some(a_type.sql_name()),
+        b_type: Some(b_type.sql_name()),
+    };
+    if a_type.is_nullable() != b_type.is_nullable() {
+        differences.push(difference(DifferenceKind::Nullability));
+    }
+
+    match (remove_nullable(a_type), remove_nullable(b_type)) {
+        (DataTypeImpl::Struct(a_struct), DataTypeImpl::Struct(b_struct)) => diff_fields(
+            &format!("{}.", path),
+            &struct_fields(&a_struct),
+            &struct_fields(&b_struct),
+            differences,
+        ),
+        (DataTypeImpl::Array(a_array), DataTypeImpl::Array(b_array)) => diff_types(
+            &format!("{}[]", path),
+            a_array.inner_type(),
+            b_array.inner_type(),
+            differences,
+        ),
+        (a_inner, b_inner) if a_inner != b_inner => {
+            differences.push(difference(DifferenceKind::Type));
+        }
+        _ => {}
+    }
+}
+
+/// The fields of `struct_type`, named by their positions from 1 if it is a tuple.
+fn struct_fields(struct_type: &StructType) -> Vec<(String, DataTypeImpl)> {
+    let names = match struct_type.names() {
+        Some(names) => names.clone(),
+        None => (1..=struct_type.types().len())
+            .map(|i| i.to_string())
+            .collect(),
+    };
+    names
+        .into_iter()
+        .zip(struct_type.types().iter().cloned())
+        .collect()
+}
+
+/// The table function `parquet_schema_diff`, the differences of the schemas of two
+/// parquet files.
+///
+/// ```sql
+/// select * from parquet_schema_diff('/data/a.parquet', '/data/b.parquet');
+/// ```
+///
+/// The schemas are the ones `read_parquet` infers for each file, the options of
+/// `read_parquet` (like `name_transform`) are applied to both.
+pub struct ParquetSchemaDiffTable {
+    table_info: TableInfo,
+    table_args: Vec<DataValue>,
+    differences: Vec<ColumnDifference>,
+}
+
+impl ParquetSchemaDiffTable {
+    pub fn create(
+        database_name: &str,
+        table_func_name: &str,
+        table_id: u64,
+        table_args: TableArgs,
+    ) -> Result<Arc<dyn TableFunction>> {
+        let config = StorageConfig::default();
+        Self::create_with_config(database_name, table_func_name, table_id, table_args, &config)
+    }
+
+    /// Create the table with the storage config `config`, which both files are read with,
+    /// see [`ParquetTable::create_with_config`].
+    pub fn create_with_config(
+        database_name: &str,
+        table_func_name: &str,
+        table_id: u64,
+        table_args: TableArgs,
+        config: &StorageConfig,
+    ) -> Result<Arc<dyn TableFunction>> {
+        let table_args = table_args.unwrap_or_default();
+        let (mut files, mut options) = (vec![], vec![]);
+        for arg in table_args.iter() {
+            let (patterns, _) = parse_table_args(std::slice::from_ref(arg))?;
+            if patterns.is_empty() {
+                options.push(arg.clone());
+            } else {
+                files.push(arg.clone());
+            }
+        }
+        if files.len() != 2 {
+            return Err(ErrorCode::BadArguments(format!(
+                "parquet_schema_diff expects 2 files to compare, but got {}",
+                files.len()
+            )));
+        }
+
+        let mut schemas = Vec::with_capacity(files.len());
+        for file in files {
+            let mut args = vec![file];
+            args.extend(options.iter().cloned());
+            let table = ParquetTable::create_with_config(
+                database_name,
+                table_func_name,
+                table_id,
+                Some(args),
+                config,
+            )?;
+            schemas.push(table.as_table().schema());
+        }
+        let differences = schema_diff(&schemas[0], &schemas[1]);
+
+        let table_info = TableInfo {
+            ident: TableIdent::new(table_id, 0),
+            desc: format!("'{}'.'{}'", database_name, table_func_name),
+            name: table_func_name.to_string(),
+            meta: TableMeta {
+                schema: Self::schema(),
+                engine: "SystemParquetSchemaDiff".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        Ok(Arc::new(ParquetSchemaDiffTable {
+            table_info,
+            table_args,
+            differences,
+        }))
+    }
+
+    pub fn schema() -> DataSchemaRef {
+        DataSchemaRefExt::create(vec![
+            DataField::new("column", Vu8::to_data_type()),
+            DataField::new("difference", Vu8::to_data_type()),
+            DataField::new_nullable("a_type", Vu8::to_data_type()),
+            DataField::new_nullable("b_type", Vu8::to_data_type()),
+        ])
+    }
+
+    pub fn differences(&self) -> &[ColumnDifference] {
+        &self.differences
+    }
+
+    fn to_block(&self) -> DataBlock {
+        let len = self.differences.len();
+        let mut columns: Vec<Vec<u8>> = Vec::with_capacity(len);
+        let mut kinds: Vec<Vec<u8>> = Vec::with_capacity(len);
+        let mut a_types: Vec<Option<Vec<u8>>> = Vec::with_capacity(len);
+        let mut b_types: Vec<Option<Vec<u8>>> = Vec::with_capacity(len);
+        for difference in self.differences.iter() {
+            columns.push(difference.column.clone().into_bytes());
+            kinds.push(difference.kind.as_str().as_bytes().to_vec());
+            a_types.push(difference.a_type.clone().map(String::into_bytes));
+            b_types.push(difference.b_type.clone().map(String::into_bytes));
+        }
+
+        DataBlock::create(Self::schema(), vec![
+            Series::from_data(columns),
+            Series::from_data(kinds),
+            Series::from_data(a_types),
+            Series::from_data(b_types),
+        ])
+    }
+}
+
+#[async_trait::async_trait]
+impl Table for ParquetSchemaDiffTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn get_table_info(&self) -> &TableInfo {
+        &self.table_info
+    }
+
+    fn table_args(&self) -> Option<Vec<DataValue>> {
+        Some(self.table_args.clone())
+    }
+
+    async fn read_partitions(
+        &self,
+        _ctx: Arc<dyn TableContext>,
+        _push_downs: Option<PushDownInfo>,
+    ) -> Result<(PartStatistics, Partitions)> {
+        Ok((PartStatistics::default(), Partitions::default()))
+    }
+
+    fn read_data(
+        &self,
+        ctx: Arc<dyn TableContext>,
+        _plan: &DataSourcePlan,
+        pipeline: &mut Pipeline,
+    ) -> Result<()> {
+        let block = self.to_block();
+        pipeline.add_source(
+            |output| {
+                SyncSourcer::create(ctx.clone(), output, SchemaDiffSource {
+                    block: Some(block.clone()),
+                })
+            },
+            1,
+        )
+    }
+}
+
+impl TableFunction for ParquetSchemaDiffTable {
+    fn function_name(&self) -> &str {
+        self.name()
+    }
+
+    fn as_table<'a>(self: Arc<Self>) -> Arc<dyn Table + 'a>
+    where Self: 'a {
+        self
+    }
+}
+
+struct SchemaDiffSource {
+    block: Option<DataBlock>,
+}
+
+impl SyncSource for SchemaDiffSource {
+    const NAME: &'static str = "parquet_schema_diff";
+
+    fn generate(&mut self) -> Result<Option<DataBlock>> {
+        Ok(self.block.take())
+    }
+}
@@ -0,0 +1,213 @@
+//  Copyright 2022 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::any::Any;
+use std::sync::Arc;
+
+use common_catalog::plan::DataSourcePlan;
+use common_catalog::plan::PartStatistics;
+use common_catalog::plan::Partitions;
+use common_catalog::plan::PushDownInfo;
+use common_catalog::table::Table;
+use common_catalog::table_args::TableArgs;
+use common_catalog::table_function::TableFunction;
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::Result;
+use common_meta_app::schema::TableIdent;
+use common_meta_app::schema::TableInfo;
+use common_meta_app::schema::TableMeta;
+use common_pipeline_core::Pipeline;
+use common_pipeline_sources::processors::sources::SyncSource;
+use common_pipeline_sources::processors::sources::SyncSourcer;
+use common_storage::StorageConfig;
+use opendal::Operator;
+
+use super::builder::column_name_transform;
+use super::builder::resolve_files;
+use super::table::check_allow_insecure;
+use super::table::check_schemas;
+use super::table::infer_schema;
+use super::table_args::parse_table_args;
+use super::TableContext;
+
+/// The first incompatibility of the schemas of the files matched by the `read_parquet`
+/// arguments `table_args`, `None` if `read_parquet` would read them together.
+///
+/// The paths are resolved against `operator` if any, like by [`resolve_files`]. Only the
+/// footers of the files are read, the schemas of all of them are inferred and checked as
+/// set by the options `merge_by`, `name_transform`, ...
+pub fn schema_incompatibility(
+    table_args: &[DataValue],
+    operator: Option<Operator>,
+) -> Result<Option<String>> {
+    let (_, options) = parse_table_args(table_args)?;
+    let (operators, locations) = resolve_files(table_args, operator, &[])?;
+    let name_transform = column_name_transform(&options, None);
+    let schemas = locations
+        .iter()
+        .map(|location| {
+            infer_schema(
+                &operators,
+                location,
+                name_transform.as_ref(),
+                options.suffix_duplicate_columns,
+                options.ignore_converted_types,
+            )
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let locations = locations.iter().collect::<Vec<_>>();
+    check_schemas(&locations, schemas, options.merge_by)
+}
+
+/// The table function `parquet_schemas_compatible`, whether the files matched by its
+/// arguments have schemas `read_parquet` reads together, with the first incompatibility.
+///
+/// ```sql
+/// select * from parquet_schemas_compatible('/data/*.parquet', 'merge_by=name');
+/// ```
+///
+/// It takes the options of `read_parquet`, see [`schema_incompatibility`].
+pub struct ParquetSchemasCompatibleTable {
+    table_info: TableInfo,
+    table_args: Vec<DataValue>,
+    incompatibility: Option<String>,
+}
+
+impl ParquetSchemasCompatibleTable {
+    pub fn create(
+        database_name: &str,
+        table_func_name: &str,
+        table_id: u64,
+        table_args: TableArgs,
+    ) -> Result<Arc<dyn TableFunction>> {
+        let config = StorageConfig::default();
+        Self::create_with_config(database_name, table_func_name, table_id, table_args, &config)
+    }
+
+    /// Create the table trusting the local files under the directory
+    /// `parquet_trusted_root` of `config`, see [`super::ParquetTable::create_with_config`].
+    pub fn create_with_config(
+        database_name: &str,
+        table_func_name: &str,
+        table_id: u64,
+        table_args: TableArgs,
+        config: &StorageConfig,
+    ) -> Result<Arc<dyn TableFunction>> {
+        let table_args = table_args.unwrap_or_default();
+        let trusted_root = &config.parquet_trusted_root;
+        check_allow_insecure(&table_args, "parquet_schemas_compatible", trusted_root)?;
+        let incompatibility = schema_incompatibility(&table_args, None)?;
+
+        let table_info = TableInfo {
+            ident: TableIdent::new(table_id, 0),
+            desc: format!("'{}'.'{}'", database_name, table_func_name),
+            name: table_func_name.to_string(),
+            meta: TableMeta {
+                schema: Self::schema(),
+                engine: "SystemParquetSchemasCompatible".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        Ok(Arc::new(ParquetSchemasCompatibleTable {
+            table_info,
+            table_args,
+            incompatibility,
+        }))
+    }
+
+    pub fn schema() -> DataSchemaRef {
+        DataSchemaRefExt::create(vec![
+            DataField::new("compatible", bool::to_data_type()),
+            DataField::new_nullable("incompatibility", Vu8::to_data_type()),
+        ])
+    }
+
+    pub fn incompatibility(&self) -> Option<&str> {
+        self.incompatibility.as_deref()
+    }
+
+    fn to_block(&self) -> DataBlock {
+        let incompatibility = self.incompatibility.clone().map(String::into_bytes);
+        DataBlock::create(Self::schema(), vec![
+            Series::from_data(vec![incompatibility.is_none()]),
+            Series::from_data(vec![incompatibility]),
+        ])
+    }
+}
+
+#[async_trait::async_trait]
+impl Table for ParquetSchemasCompatibleTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn get_table_info(&self) -> &TableInfo {
+        &self.table_info
+    }
+
+    fn table_args(&self) -> Option<Vec<DataValue>> {
+        Some(self.table_args.clone())
+    }
+
+    async fn read_partitions(
+        &self,
+        _ctx: Arc<dyn TableContext>,
+        _push_downs: Option<PushDownInfo>,
+    ) -> Result<(PartStatistics, Partitions)> {
+        Ok((PartStatistics::default(), Partitions::default()))
+    }
+
+    fn read_data(
+        &self,
+        ctx: Arc<dyn TableContext>,
+        _plan: &DataSourcePlan,
+        pipeline: &mut Pipeline,
+    ) -> Result<()> {
+        let block = self.to_block();
+        pipeline.add_source(
+            |output| {
+                SyncSourcer::create(ctx.clone(), output, SchemasCompatibleSource {
+                    block: Some(block.clone()),
+                })
+            },
+            1,
+        )
+    }
+}
+
+impl TableFunction for ParquetSchemasCompatibleTable {
+    fn function_name(&self) -> &str {
+        self.name()
+    }
+
+    fn as_table<'a>(self: Arc<Self>) -> Arc<dyn Table + 'a>
+    where Self: 'a {
+        self
+    }
+}
+
+struct SchemasCompatibleSource {
+    block: Option<DataBlock>,
+}
+
+impl SyncSource for SchemasCompatibleSource {
+    const NAME: &'static str = "parquet_schemas_compatible";
+
+    fn generate(&mut self) -> Result<Option<DataBlock>> {
+        Ok(self.block.take())
+    }
+}
@@ -0,0 +1,351 @@
+//  Copyright 2022 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use common_arrow::arrow::datatypes::Field as ArrowField;
+use common_arrow::arrow::datatypes::Schema as ArrowSchema;
+use common_arrow::parquet::metadata::FileMetaData;
+use common_catalog::plan::Expression;
+use common_datavalues::DataSchema;
+use common_datavalues::DataValue;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_storage::ColumnLeaves;
+
+use super::row_groups::column_value;
+use super::row_groups::is_comparable;
+use super::row_groups::row_group_ranges;
+use super::table::file_leaf_id;
+use super::table::read_parquet_meta;
+use super::ParquetTable;
+use crate::parquet_reader::physical_arrow_schema;
+
+/// The range of the key the files are sorted by, as hinted by the option `sorted_by`,
+/// restricted by the predicates comparing the key with constants.
+pub struct KeyRange {
+    /// The option hinting the key, `sorted_by` or `cluster_by`.
+    option: &'static str,
+    name: String,
+    field: ArrowField,
+    leaf_id: usize,
+    /// The inclusive lower bound of the key, `None` if unbounded.
+    min: Option<DataValue>,
+    /// The inclusive upper bound of the key, `None` if unbounded.
+    max: Option<DataValue>,
+}
+
+impl KeyRange {
+    /// The range of the column `name` restricted by `filters`, `None` if it is not.
+    ///
+    /// `schema` is the table schema of the file columns, `arrow_schema` and `column_leaves`
+    /// the ones the files are read with.
+    pub fn try_create(
+        name: &str,
+        filters: &[Expression],
+        schema: &DataSchema,
+        arrow_schema: &ArrowSchema,
+        column_leaves: &ColumnLeaves,
+    ) -> Option<Self> {
+        let mut range = Self::unbounded("sorted_by", name, schema, arrow_schema, column_leaves)?;
+        let data_type = schema.field(schema.index_of(name).ok()?).data_type();
+        let mut min: Option<DataValue> = None;
+        let mut max: Option<DataValue> = None;
+        for (op, value) in filters.iter().filter_map(|f| comparison_operands(f, name)) {
+            if !is_comparable(data_type, value) {
+                continue;
+            }
+            // The bounds clamped to the range of the key still hold all the matching keys.
+            let (value, _) = column_value(data_type, value);
+            // Strict comparisons are taken as inclusive, the candidates are a superset.
+            if matches!(op, "=" | ">" | ">=") && min.as_ref().map_or(true, |min| &value > min) {
+                min = Some(value.clone());
+            }
+            if matches!(op, "=" | "<" | "<=") && max.as_ref().map_or(true, |max| &value < max) {
+                max = Some(value.clone());
+            }
+        }
+        if min.is_none() && max.is_none() {
+            return None;
+        }
+
+        range.min = min;
+        range.max = max;
+        Some(range)
+    }
+
+    /// The whole range of the column `name` hinted by `option`, `None` if it is not a
+    /// column of a primitive type.
+    fn unbounded(
+        option: &'static str,
+        name: &str,
+        schema: &DataSchema,
+        arrow_schema: &ArrowSchema,
+        column_leaves: &ColumnLeaves,
+    ) -> Option<Self> {
+        let index = schema.index_of(name).ok()?;
+        let leaf = &column_leaves.column_leaves[index];
+        if leaf.children.is_some() || leaf.leaf_ids.len() != 1 {
+            return None;
+        }
+
+        Some(KeyRange {
+            option,
+            name: name.to_string(),
+            field: arrow_schema.fields[index].clone(),
+            leaf_id: leaf.leaf_ids[0],
+            min: None,
+            max: None,
+        })
+    }
+
+    /// The min and max keys of the file at `location`, `None` if some of its row groups
+    /// have no statistics of the key or the file misses it.
+    ///
+    /// `leaf_ids` are the leaf columns of the file holding the ones of the table, see
+    /// [`super::table::ParquetFileMeta::leaf_ids`]. The row groups of the file must be sorted
+    /// by the key.
+    fn file_range(
+        &self,
+        location: &str,
+        file_meta: &FileMetaData,
+        leaf_ids: Option<&[Option<usize>]>,
+    ) -> Result<Option<(DataValue, DataValue)>> {
+        let leaf_id = match file_leaf_id(leaf_ids, self.leaf_id) {
+            Some(leaf_id) if !file_meta.row_groups.is_empty() => leaf_id,
+            _ => return Ok(None),
+        };
+        let (mins, maxs) = row_group_ranges(&self.field, leaf_id, file_meta)?;
+        if mins.iter().chain(maxs.iter()).any(|value| value.is_null()) {
+            return Ok(None);
+        }
+        for i in 0..mins.len() {
+            if mins[i] > maxs[i] || (i > 0 && maxs[i - 1] > mins[i]) {
+                return Err(ErrorCode::BadArguments(format!(
+                    "The row groups of parquet file '{}' are not sorted by '{}', as hinted by read_parquet option '{}'",
+                    location, self.name, self.option
+                )));
+            }
+        }
+        Ok(Some((mins[0].clone(), maxs[maxs.len() - 1].clone())))
+    }
+}
+
+impl ParquetTable {
+    /// The files of the table in the order of the key `cluster_by`, checked to be clustered
+    /// by it with the statistics of their footers. The files without row groups come first.
+    ///
+    /// Fails with [`ErrorCode::BadArguments`] if the row groups of a file are not sorted by
+    /// the key, the ranges of two files overlap or a file has no statistics of the key.
+    pub(super) fn clustered_files(&self, cluster_by: &str) -> Result<Vec<String>> {
+        let file_schema = self.file_schema();
+        let arrow_schema = physical_arrow_schema(&file_schema, &self.interval_fields);
+        let column_leaves = ColumnLeaves::new_from_schema(&arrow_schema);
+        let range = KeyRange::unbounded(
+            "cluster_by",
+            cluster_by,
+            &file_schema,
+            &arrow_schema,
+            &column_leaves,
+        )
+        .ok_or_else(|| {
+            ErrorCode::BadArguments(format!(
+                "read_parquet option 'cluster_by' needs a column of a primitive type, '{}' is not",
+                cluster_by
+            ))
+        })?;
+
+        let mut empty_files = vec![];
+        let mut ranges = Vec::with_capacity(self.file_locations.len());
+        for location in self.file_locations.iter() {
+            let file_meta = read_parquet_meta(&self.operators, location)?;
+            let leaf_ids = self.check_file_meta(location, &file_meta)?;
+            if file_meta.row_groups.is_empty() {
+                empty_files.push(location.clone());
+                continue;
+            }
+            match range.file_range(location, &file_meta, leaf_ids.as_deref())? {
+                Some((min, max)) => ranges.push((min, max, location)),
+                None => {
+                    return Err(ErrorCode::BadArguments(format!(
+                        "Parquet file '{}' has no statistics of '{}' to check it is clustered by it, as hinted by read_parquet option 'cluster_by'",
+                        location, cluster_by
+                    )));
+                }
+            }
+        }
+
+        ranges.sort_by(|(a, ..), (b, ..)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        for pair in ranges.windows(2) {
+            let (_, prev_max, prev) = &pair[0];
+            let (next_min, _, next) = &pair[1];
+            if prev_max > next_min {
+                return Err(ErrorCode::BadArguments(format!(
+                    "The ranges of '{}' of parquet files '{}' and '{}' overlap, they are not clustered by it as hinted by read_parquet option 'cluster_by'",
+                    cluster_by, prev, next
+                )));
+            }
+        }
+        empty_files.extend(ranges.into_iter().map(|(_, _, location)| location.clone()));
+        Ok(empty_files)
+    }
+
+    /// The files of `locations` which may hold the keys of `range`, with the footers read
+    /// to find them.
+    ///
+    /// `locations` are sorted by the key, the first and the last candidates are found by
+    /// binary searches, so only the footers of the probed files and of the candidates are
+    /// read. The files are checked to be sorted as far as their footers are read. All the
+    /// files are candidates if a probed file has no statistics of the key.
+    pub(super) fn sorted_candidate_files(
+        &self,
+        locations: &[String],
+        range: &KeyRange,
+    ) -> Result<(Vec<String>, HashMap<String, FileMetaData>)> {
+        let mut files = SortedFiles {
+            table: self,
+            locations,
+            range,
+            file_metas: HashMap::new(),
+            ranges: HashMap::new(),
+        };
+
+        // The first file whose max key is not below the range, and the first file after
+        // it whose min key is above the range.
+        let start = match &range.min {
+            Some(min) => files.partition_point(|_, file_max| file_max < min)?,
+            None => Some(0),
+        };
+        let end = match &range.max {
+            Some(max) => files.partition_point(|file_min, _| file_min <= max)?,
+            None => Some(locations.len()),
+        };
+        let candidates = match (start, end) {
+            (Some(start), Some(end)) => start..end.max(start),
+            _ => 0..locations.len(),
+        };
+
+        for index in candidates.clone() {
+            files.range_of(index)?;
+        }
+        files.check_sorted()?;
+
+        let candidates = locations[candidates].to_vec();
+        Ok((candidates, files.file_metas))
+    }
+}
+
+/// The files sorted by a key, with their footers read on demand.
+struct SortedFiles<'a> {
+    table: &'a ParquetTable,
+    locations: &'a [String],
+    range: &'a KeyRange,
+    /// The footers read, by location.
+    file_metas: HashMap<String, FileMetaData>,
+    /// The ranges of the keys of the files read, by index.
+    ranges: HashMap<usize, Option<(DataValue, DataValue)>>,
+}
+
+impl SortedFiles<'_> {
+    /// The range of the keys of the file at `index`, reading its footer if not read yet.
+    fn range_of(&mut self, index: usize) -> Result<Option<(DataValue, DataValue)>> {
+        if let Some(range) = self.ranges.get(&index) {
+            return Ok(range.clone());
+        }
+        let location = &self.locations[index];
+        let file_meta = read_parquet_meta(&self.table.operators, location)?;
+        let leaf_ids = self.table.check_file_meta(location, &file_meta)?;
+        let range = self
+            .range
+            .file_range(location, &file_meta, leaf_ids.as_deref())?;
+        self.file_metas.insert(location.clone(), file_meta);
+        self.ranges.insert(index, range.clone());
+        Ok(range)
+    }
+
+    /// The index of the first file whose range of keys doesn't match `pred`, like
+    /// `partition_point` of slices, `None` if a probed file has no statistics.
+    fn partition_point(
+        &mut self,
+        pred: impl Fn(&DataValue, &DataValue) -> bool,
+    ) -> Result<Option<usize>> {
+        let (mut low, mut high) = (0, self.locations.len());
+        while low < high {
+            let mid = low + (high - low) / 2;
+            match self.range_of(mid)? {
+                Some((min, max)) if pred(&min, &max) => low = mid + 1,
+                Some(_) => high = mid,
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(low))
+    }
+
+    /// Check that the ranges of the keys of the files read don't overlap out of order.
+    fn check_sorted(&self) -> Result<()> {
+        let mut indices = self
+            .ranges
+            .iter()
+            .filter(|(_, range)| range.is_some())
+            .map(|(index, _)| *index)
+            .collect::<Vec<_>>();
+        indices.sort_unstable();
+        for pair in indices.windows(2) {
+            let (_, prev_max) = self.ranges[&pair[0]].as_ref().unwrap();
+            let (next_min, _) = self.ranges[&pair[1]].as_ref().unwrap();
+            if prev_max > next_min {
+                return Err(ErrorCode::BadArguments(format!(
+                    "Parquet files '{}' and '{}' are not sorted by '{}', as hinted by read_parquet option 'sorted_by'",
+                    self.locations[pair[0]], self.locations[pair[1]], self.range.name
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The comparison operator and the constant of `<column> <op> <constant>`, with the
+/// operator flipped for `<constant> <op> <column>`.
+fn comparison_operands<'a>(
+    filter: &'a Expression,
+    column: &str,
+) -> Option<(&'static str, &'a DataValue)> {
+    match filter {
+        Expression::Function { name, args, .. } if args.len() == 2 => {
+            let (op, flipped) = match name.as_str() {
+                "=" => ("=", "="),
+                "<" => ("<", ">"),
+                "<=" => ("<=", ">="),
+                ">" => (">", "<"),
+                ">=" => (">=", "<="),
+                _ => return None,
+            };
+            match (&args[0], &args[1]) {
+                (Expression::IndexedVariable { name, .. }, Expression::Constant { value, .. })
+                    if name == column =>
+                {
+                    Some((op, value))
+                }
+                (Expression::Constant { value, .. }, Expression::IndexedVariable { name, .. })
+                    if name == column =>
+                {
+                    Some((flipped, value))
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
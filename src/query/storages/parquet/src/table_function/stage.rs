@@ -0,0 +1,123 @@
+//  Copyright 2022 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::path::Component;
+use std::path::Path;
+
+use common_base::base::GlobalIORuntime;
+use common_base::base::TrySpawn;
+use common_config::GlobalConfig;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_meta_types::StageType;
+use common_storage::init_operator;
+use common_storage::DataOperator;
+use common_users::UserApiProvider;
+use opendal::layers::SubdirLayer;
+use opendal::Operator;
+
+/// Split a stage qualified pattern `@<stage>/<path>` into the stage name and the path.
+///
+/// Returns `None` if `pattern` is not qualified by a stage.
+pub(super) fn parse_stage_pattern(pattern: &str) -> Option<(&str, &str)> {
+    let location = pattern.strip_prefix('@')?;
+    Some(location.split_once('/').unwrap_or((location, "")))
+}
+
+/// Whether `path` goes up with `..`, which could escape the root it is resolved against.
+pub(super) fn has_parent_dir(path: &str) -> bool {
+    Path::new(path)
+        .components()
+        .any(|component| component == Component::ParentDir)
+}
+
+/// Check that the path of a stage qualified pattern stays in stage `stage_name`.
+pub(super) fn check_stage_path(stage_name: &str, path: &str) -> Result<()> {
+    if has_parent_dir(path) {
+        return Err(ErrorCode::BadArguments(format!(
+            "read_parquet path '{}' can't go up out of stage '{}'",
+            path, stage_name
+        )));
+    }
+    Ok(())
+}
+
+/// Resolve the stage of stage qualified `patterns`.
+///
+/// Returns the operator of the stage, taken from `stage_operators` by its name or looked
+/// up, and the patterns relative to its root, or `None` if the patterns are plain paths.
+pub(super) fn resolve_stage_patterns(
+    patterns: &[String],
+    stage_operators: &[(String, Operator)],
+) -> Result<Option<(Operator, Vec<String>)>> {
+    let stage_patterns = patterns
+        .iter()
+        .filter_map(|pattern| parse_stage_pattern(pattern))
+        .collect::<Vec<_>>();
+    if stage_patterns.is_empty() {
+        return Ok(None);
+    }
+
+    let stage_name = stage_patterns[0].0;
+    if stage_patterns.len() != patterns.len()
+        || stage_patterns.iter().any(|(name, _)| *name != stage_name)
+    {
+        return Err(ErrorCode::BadArguments(
+            "read_parquet can't read files from different stages or local paths at the same time",
+        ));
+    }
+    for (_, path) in &stage_patterns {
+        check_stage_path(stage_name, path)?;
+    }
+
+    let operator = match stage_operators.iter().find(|(name, _)| name == stage_name) {
+        Some((_, operator)) => operator.clone(),
+        None => stage_operator(stage_name)?,
+    };
+    let paths = stage_patterns
+        .into_iter()
+        .map(|(_, path)| path.to_string())
+        .collect();
+    Ok(Some((operator, paths)))
+}
+
+/// Build the operator of stage `stage_name`, rooted at the stage.
+fn stage_operator(stage_name: &str) -> Result<Operator> {
+    if stage_name == "~" {
+        return Err(ErrorCode::BadArguments(
+            "read_parquet doesn't support the user stage '@~'",
+        ));
+    }
+
+    // Table functions are created without a context, so the stage is looked up on the
+    // IO runtime.
+    let tenant = GlobalConfig::instance().query.tenant_id.clone();
+    let name = stage_name.to_string();
+    let get_handle = GlobalIORuntime::instance().spawn(async move {
+        UserApiProvider::instance().get_stage(&tenant, &name).await
+    });
+    let stage = futures::executor::block_on(get_handle).map_err(|_| {
+        ErrorCode::Internal(format!(
+            "Failed to look up stage '{}' for read_parquet",
+            stage_name
+        ))
+    })??;
+
+    if stage.stage_type == StageType::External {
+        Ok(init_operator(&stage.stage_params.storage)?)
+    } else {
+        let operator = DataOperator::instance().operator();
+        Ok(operator.layer(SubdirLayer::new(&stage.stage_prefix())))
+    }
+}
@@ -0,0 +1,158 @@
+//  Copyright 2022 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::collections::HashMap;
+
+use common_arrow::parquet::metadata::ColumnChunkMetaData;
+use common_catalog::table::ColumnId;
+use common_catalog::table::ColumnStatistics;
+use common_catalog::table::ColumnStatisticsProvider;
+use common_datavalues::DataSchema;
+use common_datavalues::DataValue;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_storage::ColumnLeaves;
+use parquet_format_safe::thrift::protocol::TCompactInputProtocol;
+use parquet_format_safe::ColumnIndex;
+
+use super::FileOperators;
+use super::ParquetTable;
+use crate::parquet_reader::physical_arrow_schema;
+
+/// The null counts of the leaf columns of a [`ParquetTable`].
+///
+/// Only the null counts are known, the min and max values are null and the number of
+/// distinct values is the number of rows.
+#[derive(Default)]
+pub struct ParquetColumnStatisticsProvider {
+    null_counts: HashMap<ColumnId, u64>,
+    num_rows: u64,
+}
+
+impl ColumnStatisticsProvider for ParquetColumnStatisticsProvider {
+    fn column_statistics(&self, column_id: ColumnId) -> Option<ColumnStatistics> {
+        self.null_counts
+            .get(&column_id)
+            .map(|null_count| ColumnStatistics {
+                min: DataValue::Null,
+                max: DataValue::Null,
+                null_count: *null_count,
+                number_of_distinct_values: self.num_rows,
+            })
+    }
+}
+
+impl ParquetTable {
+    /// Sum the null counts of the pages of the columns, from the column indexes of the files.
+    ///
+    /// The column indexes are read from every file, so it is only done if the option
+    /// `page_null_counts` is set. Column chunks without a column index fall back to the
+    /// null count of their statistics, the columns without any are left out.
+    pub(super) fn read_column_statistics(&self) -> Result<ParquetColumnStatisticsProvider> {
+        let file_metas = self.read_file_metas(&self.file_locations)?;
+
+        // Columns are identified by their leaves in the table schema, INTERVAL columns have
+        // more leaves there than in the files and are left out.
+        let table_schema = self.table_info.schema();
+        let table_file_schema =
+            DataSchema::new(table_schema.fields()[..self.num_file_fields()].to_vec());
+        let table_leaves = ColumnLeaves::new_from_schema(&table_file_schema.to_arrow());
+        let file_leaves = ColumnLeaves::new_from_schema(&physical_arrow_schema(
+            &self.file_schema(),
+            &self.interval_fields,
+        ));
+        let leaf_ids = table_leaves
+            .column_leaves
+            .iter()
+            .zip(file_leaves.column_leaves.iter())
+            .filter(|(table_leaf, file_leaf)| table_leaf.leaf_ids.len() == file_leaf.leaf_ids.len())
+            .flat_map(|(table_leaf, file_leaf)| {
+                table_leaf
+                    .leaf_ids
+                    .iter()
+                    .copied()
+                    .zip(file_leaf.leaf_ids.iter().copied())
+            })
+            .collect::<Vec<_>>();
+
+        let mut null_counts: HashMap<ColumnId, Option<u64>> = HashMap::new();
+        let mut num_rows = 0;
+        for meta in file_metas.iter() {
+            // Only the first row group of a file is read, the files without any have no rows.
+            let row_group = match meta.file_meta.row_groups.first() {
+                Some(row_group) => row_group,
+                None => continue,
+            };
+            num_rows += row_group.num_rows() as u64;
+            for (table_leaf_id, file_leaf_id) in leaf_ids.iter() {
+                // The columns missing in the file are all NULL there.
+                let null_count = match meta.file_leaf_id(*file_leaf_id) {
+                    Some(file_leaf_id) => {
+                        let column = &row_group.columns()[file_leaf_id];
+                        column_null_count(&self.operators, &meta.location, column)?
+                    }
+                    None => Some(row_group.num_rows() as u64),
+                };
+                let total = null_counts
+                    .entry(*table_leaf_id as ColumnId)
+                    .or_insert(Some(0));
+                *total = total.zip(null_count).map(|(total, n)| total + n);
+            }
+        }
+
+        Ok(ParquetColumnStatisticsProvider {
+            null_counts: null_counts
+                .into_iter()
+                .filter_map(|(column_id, null_count)| Some((column_id, null_count?)))
+                .collect(),
+            num_rows,
+        })
+    }
+}
+
+/// The null count of the column chunk `column`, summed over its pages if it has a column index.
+fn column_null_count(
+    operators: &FileOperators,
+    location: &str,
+    column: &ColumnChunkMetaData,
+) -> Result<Option<u64>> {
+    let column_chunk = column.column_chunk();
+    if let (Some(offset), Some(length)) = (
+        column_chunk.column_index_offset,
+        column_chunk.column_index_length,
+    ) {
+        let offset = offset as u64;
+        let _permit = operators.blocking_permit()?;
+        let data = operators.range_read(location, offset..offset + length as u64)?;
+        let mut prot = TCompactInputProtocol::new(data.as_slice(), data.len() * 2 + 1024);
+        let index = ColumnIndex::read_from_in_protocol(&mut prot).map_err(|e| {
+            ErrorCode::ParquetFileInvalid(format!(
+                "Read column index of column '{}' in parquet file '{}' error: {}",
+                column.descriptor().path_in_schema.join("."),
+                location,
+                e
+            ))
+        })?;
+        if let Some(page_null_counts) = index.null_counts {
+            return Ok(Some(page_null_counts.iter().map(|n| *n as u64).sum()));
+        }
+    }
+
+    Ok(column
+        .metadata()
+        .statistics
+        .as_ref()
+        .and_then(|statistics| statistics.null_count)
+        .map(|null_count| null_count as u64))
+}
@@ -13,48 +13,169 @@
 //  limitations under the License.
 
 use std::any::Any;
-use std::fs::File;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::path::Path;
 use std::sync::Arc;
 
-use chrono::NaiveDateTime;
-use chrono::TimeZone;
-use chrono::Utc;
+use common_arrow::arrow::datatypes::DataType as ArrowType;
+use common_arrow::arrow::datatypes::Field as ArrowField;
 use common_arrow::arrow::datatypes::Schema as ArrowSchema;
-use common_arrow::arrow::io::parquet;
+use common_arrow::arrow::io::parquet::read::infer_schema as infer_arrow_schema;
 use common_arrow::arrow::io::parquet::read::schema::parquet_to_arrow_schema;
+use common_arrow::parquet;
 use common_arrow::parquet::metadata::FileMetaData;
+use common_arrow::parquet::metadata::RowGroupMetaData;
+use common_arrow::parquet::schema::types::IntegerType;
 use common_arrow::parquet::schema::types::ParquetType;
+use common_arrow::parquet::schema::types::PrimitiveConvertedType;
+use common_arrow::parquet::schema::types::PrimitiveLogicalType;
+use common_arrow::parquet::schema::types::TimeUnit as ParquetTimeUnit;
 use common_catalog::plan::DataSourcePlan;
+use common_catalog::plan::Expression;
 use common_catalog::plan::PartStatistics;
 use common_catalog::plan::Partitions;
 use common_catalog::plan::PushDownInfo;
+use common_catalog::table::ColumnStatisticsProvider;
 use common_catalog::table::Table;
 use common_catalog::table_args::TableArgs;
 use common_catalog::table_function::TableFunction;
 use common_config::GlobalConfig;
+use common_datablocks::DataBlock;
+use common_datavalues::remove_nullable;
+use common_datavalues::DataField;
 use common_datavalues::DataSchema;
+use common_datavalues::DataType;
+use common_datavalues::DataTypeImpl;
 use common_datavalues::DataValue;
+use common_datavalues::NullableType;
+use common_datavalues::TypeFactory;
+use common_datavalues::TypeID;
+use common_datavalues::ARROW_EXTENSION_META;
+use common_datavalues::ARROW_EXTENSION_NAME;
 use common_exception::ErrorCode;
 use common_exception::Result;
-use common_meta_app::schema::TableIdent;
 use common_meta_app::schema::TableInfo;
-use common_meta_app::schema::TableMeta;
 use common_pipeline_core::Pipeline;
-use opendal::Operator;
+use common_storage::ColumnLeaves;
+use common_storage::StorageConfig;
+use parking_lot::RwLock;
 
+use super::external_statistics::ParquetStatisticsProvider;
+use super::features::is_supported_compression;
+use super::features::is_supported_encoding;
+use super::hive::HivePartitions;
+use super::operators::parse_uri_pattern;
+use super::operators::Credentials;
+use super::operators::FileOperators;
+use super::stage::check_stage_path;
+use super::stage::has_parent_dir;
+use super::stage::parse_stage_pattern;
+use super::statistics::ParquetColumnStatisticsProvider;
+use super::table_args::parse_table_args;
+use super::table_args::MergeBy;
+use super::table_args::MissingColumns;
+use super::table_args::ParquetTableOptions;
+use super::ParquetTableBuilder;
 use super::TableContext;
+use crate::parquet_reader::interval_struct_type;
+use crate::parquet_reader::is_parquet_interval;
+use crate::parquet_reader::physical_arrow_schema;
+use crate::DecimalCast;
+use crate::DecimalRounding;
+use crate::ParquetScanProgress;
+use crate::ParquetScanProgressValues;
+use crate::ParquetWarning;
+use crate::ParquetWarnings;
+use crate::TimestampUnit;
+use crate::WarningKind;
+
+const PARQUET_MAGIC: [u8; 4] = *b"PAR1";
+/// The magic of parquet files with an encrypted footer.
+const PARQUET_ENCRYPTED_MAGIC: [u8; 4] = *b"PARE";
+/// The metadata length (4 bytes) followed by the magic.
+const FOOTER_SIZE: u64 = 8;
+const DEFAULT_FOOTER_READ_SIZE: u64 = 64 * 1024;
+/// The summary of the footers of the files in a directory, written by Spark and Hive.
+const METADATA_FILE: &str = "_metadata";
+/// The key of the footer metadata holding the arrow schema written by the arrow writers.
+const ARROW_SCHEMA_KEY: &str = "ARROW:schema";
+
+/// A transform of the column names, applied to the names inferred from the files.
+pub type ColumnNameTransform = Arc<dyn Fn(&str) -> String + Send + Sync>;
+
+/// A selection of the row groups to read, by their metadata.
+pub type RowGroupFilter = Arc<dyn Fn(&RowGroupMetaData) -> bool + Send + Sync>;
 
 pub struct ParquetFileMeta {
     pub location: String,
     pub file_meta: FileMetaData,
+    /// The raw values of the hive partition columns, empty if hive partitioning is disabled.
+    pub partition_values: Vec<String>,
+    /// The row groups to read if narrowed down by a point lookup or all of them are read
+    /// by `no_prune`, otherwise the first row group is read for the whole file.
+    pub row_groups: Option<Vec<usize>>,
+    /// The leaf columns of the file holding the leaf columns of the table, `None` for the
+    /// missing ones. `None` if they are the same, which is the case unless the schemas are
+    /// merged by name.
+    pub leaf_ids: Option<Vec<Option<usize>>>,
+}
+
+impl ParquetFileMeta {
+    /// The leaf column of the file holding the leaf column `leaf_id` of the table, `None`
+    /// if the file misses it.
+    pub fn file_leaf_id(&self, leaf_id: usize) -> Option<usize> {
+        file_leaf_id(self.leaf_ids.as_deref(), leaf_id)
+    }
+}
+
+pub(super) fn file_leaf_id(leaf_ids: Option<&[Option<usize>]>, leaf_id: usize) -> Option<usize> {
+    match leaf_ids {
+        Some(leaf_ids) => leaf_ids[leaf_id],
+        None => Some(leaf_id),
+    }
 }
 
 pub struct ParquetTable {
-    table_args: Vec<DataValue>,
+    pub(super) table_args: Vec<DataValue>,
 
-    file_locations: Vec<String>,
+    pub(super) file_locations: Vec<String>,
     pub(super) table_info: TableInfo,
-    pub(super) operator: Operator,
+    pub(super) operators: FileOperators,
+    pub(super) options: ParquetTableOptions,
+    /// The indices of the fields holding parquet INTERVAL values.
+    pub(super) interval_fields: Vec<usize>,
+    /// The hive partitions of the files, their columns come last in the schema.
+    pub(super) partitions: Option<HivePartitions>,
+    /// The indices of the fields cast by the option `cast` or made non-nullable by
+    /// `nullability=observed`, with their types in the files.
+    pub(super) casts: Vec<(usize, DataTypeImpl)>,
+    /// The indices of the DECIMAL fields cast to other DECIMAL types by the option `cast`,
+    /// their types are unchanged.
+    pub(super) decimal_casts: Vec<(usize, DecimalCast)>,
+    /// The transform of the column names, by the builder or the option `name_transform`.
+    pub(super) name_transform: Option<ColumnNameTransform>,
+    /// Whether the schema was provided to the builder instead of inferred from the files,
+    /// see [`ParquetTableBuilder::with_arrow_schema`].
+    pub(super) schema_provided: bool,
+    /// The selection of the row groups to read, see
+    /// [`ParquetTableBuilder::with_row_group_filter`].
+    pub(super) row_group_filter: Option<RowGroupFilter>,
+    /// The statistics of the files kept by an external index, see
+    /// [`ParquetTableBuilder::with_statistics_provider`].
+    pub(super) statistics_provider: Option<Arc<dyn ParquetStatisticsProvider>>,
+    /// The column the files are clustered by and ordered by, once checked, see the option
+    /// `cluster_by`.
+    pub(super) cluster_key: Option<String>,
+    /// The progress of the last scan of the table by its files, see
+    /// [`ParquetTable::scan_progress`].
+    pub(super) scan_progress: RwLock<Option<Arc<ParquetScanProgress>>>,
+    /// The files and the row groups pruned by the last planning of a scan of the table.
+    pub(super) pruned: RwLock<(usize, usize)>,
+    /// The non-fatal issues met while building and scanning the table, see
+    /// [`ParquetTable::warnings`].
+    pub(super) warnings: Arc<ParquetWarnings>,
 }
 
 impl ParquetTable {
@@ -64,96 +185,340 @@ impl ParquetTable {
         table_id: u64,
         table_args: TableArgs,
     ) -> Result<Arc<dyn TableFunction>> {
-        if !GlobalConfig::instance().storage.allow_insecure {
-            return Err(ErrorCode::StorageInsecure(
-                "Should enable `allow_insecure` to use table function `read_parquet`",
-            ));
-        }
+        let config = StorageConfig::default();
+        Self::create_with_config(database_name, table_func_name, table_id, table_args, &config)
+    }
 
-        if table_args.is_none() || table_args.as_ref().unwrap().is_empty() {
-            return Err(ErrorCode::BadArguments(
-                "read_parquet needs at least one argument",
-            ));
-        }
+    /// Create the table with the storage config `config`: the local files under the
+    /// directory `parquet_trusted_root` are read without `allow_insecure`, and the files
+    /// under the path prefixes of `parquet_credentials` with their connection parameters.
+    pub fn create_with_config(
+        database_name: &str,
+        table_func_name: &str,
+        table_id: u64,
+        table_args: TableArgs,
+        config: &StorageConfig,
+    ) -> Result<Arc<dyn TableFunction>> {
+        let table_args = table_args.unwrap_or_default();
+        check_allow_insecure(&table_args, "read_parquet", &config.parquet_trusted_root)?;
+        let credentials = Credentials::parse(&config.parquet_credentials)?;
 
-        let table_args = table_args.unwrap();
-
-        let mut file_locations = Vec::with_capacity(table_args.len());
-        for arg in table_args.iter() {
-            match arg {
-                DataValue::String(path) => {
-                    let maybe_glob_path = std::str::from_utf8(path).unwrap();
-                    let paths = glob::glob(maybe_glob_path)
-                        .map_err(|e| ErrorCode::Internal(format!("glob error: {}", e)))?;
-                    for entry in paths {
-                        match entry {
-                            Ok(path) => {
-                                file_locations.push(path.to_string_lossy().to_string());
-                            }
-                            Err(e) => {
-                                return Err(ErrorCode::Internal(format!("glob error: {}", e)));
-                            }
-                        }
-                    }
-                }
-                _ => {
-                    return Err(ErrorCode::BadArguments(
-                        "read_parquet only accepts string arguments",
-                    ));
-                }
-            }
-        }
+        let table =
+            ParquetTableBuilder::create(database_name, table_func_name, table_id, table_args)
+                .with_credentials(credentials)
+                .build()?;
+        Ok(table)
+    }
 
-        if file_locations.is_empty() {
+    /// Create the table of the table function `read_parquet_sample`, which reads about
+    /// the fraction of the rows of the files set by its option `fraction`, like
+    ///
+    /// ```sql
+    /// select * from read_parquet_sample('/data/*.parquet', 'fraction=0.01');
+    /// ```
+    ///
+    /// It takes the other options of `read_parquet`. The sampling unit is the row group,
+    /// see [`ParquetTableOptions::sample_fraction`].
+    pub fn create_sample_with_config(
+        database_name: &str,
+        table_func_name: &str,
+        table_id: u64,
+        table_args: TableArgs,
+        config: &StorageConfig,
+    ) -> Result<Arc<dyn TableFunction>> {
+        let table_args = table_args.unwrap_or_default();
+        check_allow_insecure(&table_args, "read_parquet_sample", &config.parquet_trusted_root)?;
+        let credentials = Credentials::parse(&config.parquet_credentials)?;
+        let (_, options) = parse_table_args(&table_args)?;
+        if options.sample_fraction.is_none() {
             return Err(ErrorCode::BadArguments(
-                "No matched files found for read_parquet",
+                "read_parquet_sample needs the option 'fraction', like 'fraction=0.01'",
             ));
         }
 
-        // Infer schema from the first parquet file.
-        // Assume all parquet files have the same schema.
-        // If not, throw error during reading.
-        let schema = infer_schema(&file_locations[0])?;
-
-        let table_info = TableInfo {
-            ident: TableIdent::new(table_id, 0),
-            desc: format!("'{}'.'{}'", database_name, table_func_name),
-            name: table_func_name.to_string(),
-            meta: TableMeta {
-                schema: Arc::new(schema),
-                engine: "SystemReadParquet".to_string(),
-                // Assuming that created_on is unnecessary for function table,
-                // we could make created_on fixed to pass test_shuffle_action_try_into.
-                created_on: Utc.from_utc_datetime(&NaiveDateTime::from_timestamp(0, 0)),
-                updated_on: Utc.from_utc_datetime(&NaiveDateTime::from_timestamp(0, 0)),
-                ..Default::default()
-            },
-            ..Default::default()
-        };
+        let table =
+            ParquetTableBuilder::create(database_name, table_func_name, table_id, table_args)
+                .with_credentials(credentials)
+                .build()?;
+        Ok(table)
+    }
 
-        let mut builder = opendal::services::fs::Builder::default();
-        builder.root("/");
-        let operator = Operator::new(builder.build()?);
+    /// Release the operators the files are read through, once the table is done.
+    ///
+    /// The readers of the table share the operators, reading the table or any of its
+    /// readers fails with `StorageUnavailable` after. Closing a closed table does nothing.
+    pub fn close(&self) {
+        self.operators.close();
+    }
 
-        Ok(Arc::new(ParquetTable {
-            table_args,
-            file_locations,
-            table_info,
-            operator,
-        }))
+    pub fn is_closed(&self) -> bool {
+        self.operators.is_closed()
     }
 
-    pub(super) fn read_file_metas(&self) -> Result<Vec<ParquetFileMeta>> {
-        self.file_locations
+    /// The files completed and the bytes read of the last scan of the table, out of the
+    /// totals planned. `None` before the table is scanned.
+    pub fn scan_progress(&self) -> Option<ParquetScanProgressValues> {
+        self.scan_progress
+            .read()
+            .as_ref()
+            .map(|progress| progress.get_values())
+    }
+
+    /// The final statistics of the last scan of the table, once all its parts are read:
+    /// the exact files, parts, rows and bytes read, with the files and the row groups
+    /// pruned by its planning. `None` before the table is scanned and during the scan.
+    pub fn final_scan_statistics(&self) -> Option<ParquetScanProgressValues> {
+        self.scan_progress
+            .read()
+            .as_ref()
+            .filter(|progress| progress.is_complete())
+            .map(|progress| progress.get_values())
+    }
+
+    /// The non-fatal issues met so far while building and scanning the table, like the
+    /// coerced columns and the skipped files, if the option `warnings=collect` is set.
+    pub fn warnings(&self) -> Vec<ParquetWarning> {
+        self.warnings.get()
+    }
+
+    /// [`ParquetTable::warnings`] as a companion result set of the query, with the schema
+    /// [`ParquetWarnings::schema`].
+    pub fn warnings_block(&self) -> DataBlock {
+        self.warnings.to_block()
+    }
+
+    /// The most requests to the storages in flight at once so far, while the requests are
+    /// limited by the option `max_concurrent_requests`. `None` if they are not limited.
+    pub fn peak_concurrent_requests(&self) -> Option<usize> {
+        self.operators.peak_requests()
+    }
+
+    /// The most files open at once so far, while the open files are limited by the option
+    /// `max_open_files`. `None` if they are not limited.
+    pub fn peak_open_files(&self) -> Option<usize> {
+        self.operators.peak_open_files()
+    }
+
+    /// The number of fields stored in the files, the hive partition fields and the
+    /// `_row_group_index` one follow them.
+    pub(super) fn num_file_fields(&self) -> usize {
+        let num_partition_fields = self.partitions.as_ref().map_or(0, |p| p.fields.len());
+        let num_virtual_fields = self.options.row_group_index as usize;
+        self.table_info.schema().num_fields() - num_partition_fields - num_virtual_fields
+    }
+
+    /// The indices of the fields not stored in the files in the table schema, the hive
+    /// partition fields and the `_row_group_index` one, which are filled per part.
+    pub(super) fn partition_fields(&self) -> Vec<usize> {
+        (self.num_file_fields()..self.table_info.schema().num_fields()).collect()
+    }
+
+    /// The schema of the columns stored in the files, with their types in the files.
+    pub(super) fn file_schema(&self) -> DataSchema {
+        let table_schema = self.table_info.schema();
+        let fields = table_schema.fields()[..self.num_file_fields()]
+            .iter()
+            .enumerate()
+            .map(
+                |(index, field)| match self.casts.iter().find(|(i, _)| *i == index) {
+                    Some((_, data_type)) => DataField::new(field.name(), data_type.clone()),
+                    None => field.clone(),
+                },
+            )
+            .collect();
+        DataSchema::new(fields)
+    }
+
+    /// Make the nullable columns of the files holding no NULL in any of them non-nullable in
+    /// the table schema, for the option `nullability=observed`.
+    ///
+    /// The NULLs are counted by the statistics of the footers. The columns missing from a
+    /// file or without a null count in a row group keep their nullability, like the nested,
+    /// INTERVAL and cast ones. The files are still decoded with the nullable types, which
+    /// are cast to the non-nullable ones.
+    pub(super) fn tighten_nullability(&mut self) -> Result<()> {
+        let file_metas = self.read_file_metas(&self.file_locations)?;
+        let file_schema = self.file_schema();
+        let arrow_schema = physical_arrow_schema(&file_schema, &self.interval_fields);
+        let column_leaves = ColumnLeaves::new_from_schema(&arrow_schema);
+        let mut fields = self.table_info.schema().fields().clone();
+        for (index, field) in file_schema.fields().iter().enumerate() {
+            let leaf = &column_leaves.column_leaves[index];
+            // The null counts of the lists count their null items, not their rows.
+            let is_list = matches!(
+                leaf.field.data_type,
+                ArrowType::List(_) | ArrowType::LargeList(_) | ArrowType::FixedSizeList(_, _)
+            );
+            if !field.is_nullable()
+                || leaf.children.is_some()
+                || leaf.leaf_ids.len() != 1
+                || is_list
+                || self.interval_fields.contains(&index)
+                || self.casts.iter().any(|(i, _)| *i == index)
+            {
+                continue;
+            }
+            let has_nulls = file_metas.iter().any(|meta| {
+                let leaf_id = match meta.file_leaf_id(leaf.leaf_ids[0]) {
+                    Some(leaf_id) => leaf_id,
+                    None => return true,
+                };
+                meta.file_meta.row_groups.iter().any(|row_group| {
+                    let null_count = row_group.columns()[leaf_id]
+                        .metadata()
+                        .statistics
+                        .as_ref()
+                        .and_then(|statistics| statistics.null_count);
+                    null_count != Some(0)
+                })
+            });
+            if !has_nulls {
+                fields[index] = DataField::new(field.name(), remove_nullable(field.data_type()));
+                self.casts.push((index, field.data_type().clone()));
+            }
+        }
+        self.table_info.meta.schema = Arc::new(DataSchema::new(fields));
+        Ok(())
+    }
+
+    pub(super) fn read_file_metas(&self, locations: &[String]) -> Result<Vec<ParquetFileMeta>> {
+        self.read_file_metas_with(locations, HashMap::new())
+    }
+
+    /// Like [`ParquetTable::read_file_metas`], with the footers of some files already read.
+    pub(super) fn read_file_metas_with(
+        &self,
+        locations: &[String],
+        mut read_metas: HashMap<String, FileMetaData>,
+    ) -> Result<Vec<ParquetFileMeta>> {
+        if self.options.use_metadata_file {
+            for (location, file_meta) in read_summary_metas(&self.operators, locations)? {
+                read_metas.entry(location).or_insert(file_meta);
+            }
+        }
+        let file_metas = locations
             .iter()
             .map(|location| {
-                let file_meta = read_parquet_meta(location)?;
+                let file_meta = match read_metas.remove(location) {
+                    Some(file_meta) => file_meta,
+                    None => read_parquet_meta(&self.operators, location)?,
+                };
+                let leaf_ids = self.check_file_meta(location, &file_meta)?;
+                let partition_values = match &self.partitions {
+                    Some(partitions) => partitions.values(location)?.clone(),
+                    None => vec![],
+                };
                 Ok(ParquetFileMeta {
                     location: location.clone(),
                     file_meta,
+                    partition_values,
+                    row_groups: None,
+                    leaf_ids,
                 })
             })
-            .collect::<Result<Vec<_>>>()
+            .collect::<Result<Vec<_>>>()?;
+        self.check_time_units(&file_metas)?;
+        Ok(file_metas)
+    }
+
+    /// Check that the TIMESTAMP columns have the same unit in the files of `file_metas`,
+    /// as set by the option `on_mixed_time_units`. The first file of another unit than the
+    /// first one of a column is reported, or rejected.
+    fn check_time_units(&self, file_metas: &[ParquetFileMeta]) -> Result<()> {
+        let mut first_units: HashMap<String, (&str, TimestampUnit)> = HashMap::new();
+        let mut reported = HashSet::new();
+        for meta in file_metas {
+            for column in meta.file_meta.schema().columns() {
+                let unit = match TimestampUnit::of(&column.descriptor.primitive_type) {
+                    Some(unit) => unit,
+                    None => continue,
+                };
+                let name = column.path_in_schema.join(".");
+                let (first_location, first_unit) = *first_units
+                    .entry(name.clone())
+                    .or_insert((&meta.location, unit));
+                if unit == first_unit || reported.contains(&name) {
+                    continue;
+                }
+                let message = format!(
+                    "TIMESTAMP column '{}' is of {} in parquet file '{}', but of {} in parquet file '{}'",
+                    name,
+                    unit.as_str(),
+                    meta.location,
+                    first_unit.as_str(),
+                    first_location
+                );
+                if self.options.error_on_mixed_time_units {
+                    return Err(ErrorCode::ParquetSchemaMismatch(message));
+                }
+                let warning = ParquetWarning::create(WarningKind::MixedTimeUnits, message)
+                    .with_location(&meta.location)
+                    .with_column(&name);
+                self.warnings.add(warning);
+                reported.insert(name);
+            }
+        }
+        Ok(())
+    }
+
+    /// Check the schema of the file at `location` against the table, and find the leaf
+    /// columns of the file holding the ones of the table, see [`ParquetFileMeta::leaf_ids`].
+    pub(super) fn check_file_meta(
+        &self,
+        location: &str,
+        file_meta: &FileMetaData,
+    ) -> Result<Option<Vec<Option<usize>>>> {
+        check_supported(location, file_meta)?;
+        let (mut schema, interval_fields) = schema_from_meta(
+            location,
+            file_meta,
+            self.options.suffix_duplicate_columns,
+            self.options.ignore_converted_types,
+        )?;
+        if let Some(name_transform) = &self.name_transform {
+            let source = format!("parquet file '{}'", location);
+            schema = transform_names(&source, schema, name_transform)?;
+        }
+        if let Some(columns) = &self.options.columns {
+            schema = rename_columns(schema, columns)?;
+        }
+
+        // The columns not matched by `columns_like` are not read, the other ones are found
+        // by name in the file.
+        let file_schema = (schema, interval_fields);
+        let (schema, merge_by) = match &self.options.columns_like {
+            Some(pattern) => {
+                let merge_by = if self.options.on_missing_columns == Some(MissingColumns::Null) {
+                    Some(MergeBy::Name)
+                } else {
+                    self.options.merge_by
+                };
+                let schema = select_columns_like(&file_schema.0, &file_schema.1, pattern);
+                (schema, merge_by)
+            }
+            None => (file_schema.clone(), self.options.merge_by),
+        };
+        // The columns of the table are sorted by name, the ones of the file are found by
+        // name.
+        let schema = if self.options.sort_columns_by_name {
+            sort_columns_by_name(&schema.0, &schema.1)
+        } else {
+            schema
+        };
+        let merged = (self.file_schema(), self.interval_fields.clone());
+        let reference = if self.schema_provided {
+            "the provided schema".to_string()
+        } else {
+            format!("the schema of '{}'", self.file_locations[0])
+        };
+        check_file_schema(location, &schema, &reference, &merged, merge_by)?;
+        Ok(match merge_by {
+            _ if self.options.columns_like.is_some() || self.options.sort_columns_by_name => {
+                leaf_ids_by_name(&file_schema, &merged)
+            }
+            Some(MergeBy::Name) => leaf_ids_by_name(&schema, &merged),
+            _ => None,
+        })
     }
 }
 
@@ -176,19 +541,43 @@ impl Table for ParquetTable {
     }
 
     fn has_exact_total_row_count(&self) -> bool {
-        true
+        // A sample reads a part of the rows.
+        self.options.sample_fraction.is_none()
+    }
+
+    fn cluster_keys(&self) -> Vec<Expression> {
+        let schema = self.table_info.schema();
+        match &self.cluster_key {
+            Some(name) => match schema.index_of(name) {
+                Ok(index) => vec![Expression::IndexedVariable {
+                    name: name.clone(),
+                    data_type: schema.field(index).data_type().clone(),
+                }],
+                Err(_) => vec![],
+            },
+            None => vec![],
+        }
     }
 
     fn table_args(&self) -> Option<Vec<DataValue>> {
         Some(self.table_args.clone())
     }
 
+    async fn column_statistics_provider(&self) -> Result<Box<dyn ColumnStatisticsProvider>> {
+        let provider = if self.options.page_null_counts {
+            self.read_column_statistics()?
+        } else {
+            ParquetColumnStatisticsProvider::default()
+        };
+        Ok(Box::new(provider))
+    }
+
     async fn read_partitions(
         &self,
-        _ctx: Arc<dyn TableContext>,
+        ctx: Arc<dyn TableContext>,
         push_down: Option<PushDownInfo>,
     ) -> Result<(PartStatistics, Partitions)> {
-        self.do_read_partitions(push_down)
+        self.do_read_partitions(ctx, push_down)
     }
 
     fn read_data(
@@ -212,45 +601,973 @@ impl TableFunction for ParquetTable {
     }
 }
 
-fn read_parquet_meta(location: &str) -> Result<FileMetaData> {
-    let mut file = File::open(location)
-        .map_err(|e| ErrorCode::Internal(format!("Failed to open file '{}': {}", location, e)))?;
-    parquet::read::read_metadata(&mut file).map_err(|e| {
-        ErrorCode::Internal(format!(
+/// Check that the files of the arguments `table_args` of the table function `function`
+/// can be read.
+///
+/// Reading from stages is always allowed, as long as the paths stay in the stage. Other
+/// files need `allow_insecure`, unless they are local files under the directory
+/// `trusted_root` (empty for no trusted root).
+pub(super) fn check_allow_insecure(
+    table_args: &[DataValue],
+    function: &str,
+    trusted_root: &str,
+) -> Result<()> {
+    let (patterns, _) = parse_table_args(table_args)?;
+    for pattern in &patterns {
+        if let Some((stage_name, path)) = parse_stage_pattern(pattern) {
+            check_stage_path(stage_name, path)?;
+        }
+    }
+    let from_stage = !patterns.is_empty()
+        && patterns
+            .iter()
+            .all(|pattern| parse_stage_pattern(pattern).is_some());
+    let under_trusted_root = !patterns.is_empty()
+        && patterns
+            .iter()
+            .all(|pattern| is_under_trusted_root(pattern, trusted_root));
+    if from_stage || under_trusted_root || GlobalConfig::instance().storage.allow_insecure {
+        return Ok(());
+    }
+    if trusted_root.is_empty() {
+        return Err(ErrorCode::StorageInsecure(format!(
+            "Should enable `allow_insecure` to use table function `{}`",
+            function
+        )));
+    }
+    Err(ErrorCode::StorageInsecure(format!(
+        "Should enable `allow_insecure` to use table function `{}` outside the trusted root '{}'",
+        function, trusted_root
+    )))
+}
+
+/// The trusted root of a session setting `session_root`, which narrows the trusted root
+/// `trusted_root` of the config to a directory under it. The sessions can't widen it.
+///
+/// An empty `session_root` keeps `trusted_root`. The others must be absolute, without
+/// `..`, and under `trusted_root`, which must not be empty.
+pub fn narrow_trusted_root(trusted_root: &str, session_root: &str) -> Result<String> {
+    if session_root.is_empty() {
+        return Ok(trusted_root.to_string());
+    }
+    if !is_under_trusted_root(session_root, trusted_root) {
+        return Err(ErrorCode::StorageInsecure(format!(
+            "The setting parquet_trusted_root '{}' must be under the config storage.parquet_trusted_root '{}'",
+            session_root, trusted_root
+        )));
+    }
+    Ok(session_root.to_string())
+}
+
+/// Whether the local file pattern `pattern` stays under the directory `trusted_root`.
+///
+/// Both must be absolute. The paths are compared by their components, so `/data` doesn't
+/// trust `/data2`, and the patterns going up with `..` are never trusted.
+fn is_under_trusted_root(pattern: &str, trusted_root: &str) -> bool {
+    if trusted_root.is_empty()
+        || parse_stage_pattern(pattern).is_some()
+        || parse_uri_pattern(pattern).is_some()
+    {
+        return false;
+    }
+    let path = Path::new(pattern);
+    let root = Path::new(trusted_root);
+    root.is_absolute() && path.is_absolute() && !has_parent_dir(pattern) && path.starts_with(root)
+}
+
+/// Read the footer of the parquet file at `location` through `operators`.
+///
+/// Only the tail of the file is fetched, so this works for any storage backend. A tail of
+/// [`DEFAULT_FOOTER_READ_SIZE`] bytes is read first, its trailer has the exact size of the
+/// metadata, which is completed by a second read if it is larger.
+pub(super) fn read_parquet_meta(operators: &FileOperators, location: &str) -> Result<FileMetaData> {
+    let meta_error = |e: String| {
+        ErrorCode::ParquetFileInvalid(format!(
             "Read parquet file '{}''s meta error: {}",
             location, e
         ))
-    })
-}
+    };
+
+    // The requests of the footer are sent one after the other, under the same permit.
+    let _permit = operators.blocking_permit()?;
+    let file_size = operators.stat(location)?.content_length();
+    if file_size < PARQUET_MAGIC.len() as u64 + FOOTER_SIZE {
+        return Err(meta_error(
+            "A parquet file must contain a header and footer with at least 12 bytes".to_string(),
+        ));
+    }
 
-/// Infer [`DataSchema`] from [`FileMetaData`]
-fn infer_schema(location: &str) -> Result<DataSchema> {
-    let meta = read_parquet_meta(location)?;
-    if meta.row_groups.is_empty() {
-        return Err(ErrorCode::Internal(format!(
-            "No row groups found in parquet file '{}'",
+    // Read the default tail first, the metadata of most files fits into it.
+    let tail_size = DEFAULT_FOOTER_READ_SIZE.min(file_size);
+    let mut buffer = operators.range_read(location, file_size - tail_size..file_size)?;
+    let footer = &buffer[buffer.len() - FOOTER_SIZE as usize..];
+    if footer[4..] == PARQUET_ENCRYPTED_MAGIC {
+        return Err(ErrorCode::ParquetDecryptionFailed(format!(
+            "Parquet file '{}' is encrypted, reading encrypted parquet files is not supported",
             location
         )));
     }
+    if footer[4..] != PARQUET_MAGIC {
+        return Err(meta_error("The file must end with PAR1".to_string()));
+    }
+
+    let metadata_size = u32::from_le_bytes(footer[..4].try_into().unwrap()) as u64;
+    if metadata_size + FOOTER_SIZE + PARQUET_MAGIC.len() as u64 > file_size {
+        return Err(meta_error(format!(
+            "The footer size {} is larger than the file size {}",
+            metadata_size, file_size
+        )));
+    }
+    // The metadata larger than the tail is completed by reading the bytes missing before it.
+    if metadata_size + FOOTER_SIZE > tail_size {
+        let metadata_start = file_size - metadata_size - FOOTER_SIZE;
+        let mut head = operators.range_read(location, metadata_start..file_size - tail_size)?;
+        head.extend_from_slice(&buffer);
+        buffer = head;
+    }
+
+    let metadata_end = buffer.len() - FOOTER_SIZE as usize;
+    let metadata = &buffer[metadata_end - metadata_size as usize..metadata_end];
+    parquet::read::deserialize_metadata(metadata, metadata.len() * 2 + 1024)
+        .map_err(|e| meta_error(e.to_string()))
+}
 
-    let column_metas = meta.row_groups[0].columns();
-    let parquet_fields = column_metas
+/// Read the `_metadata` summaries of the directories of `locations`.
+///
+/// A summary holds the row groups of all the files of its directory, they are split into
+/// the footers of the files they belong to. Directories without a summary are skipped.
+fn read_summary_metas(
+    operators: &FileOperators,
+    locations: &[String],
+) -> Result<HashMap<String, FileMetaData>> {
+    let dirs = locations
         .iter()
-        .map(|col_meta| {
-            // convert name to lower case.
-            let mut pt = col_meta.descriptor().base_type.clone();
-            match &mut pt {
-                ParquetType::PrimitiveType(primitive) => {
-                    primitive.field_info.name = primitive.field_info.name.to_lowercase()
+        .map(|location| match location.rfind('/') {
+            Some(pos) => &location[..=pos],
+            None => "",
+        })
+        .collect::<BTreeSet<_>>();
+
+    let mut file_metas = HashMap::new();
+    for dir in dirs {
+        let summary_location = format!("{}{}", dir, METADATA_FILE);
+        let permit = operators.blocking_permit()?;
+        if !operators.is_exist(&summary_location)? {
+            continue;
+        }
+        drop(permit);
+
+        let summary = read_parquet_meta(operators, &summary_location)?;
+        for row_group in summary.row_groups.iter() {
+            let file_path = row_group
+                .columns()
+                .first()
+                .and_then(|column| column.file_path().as_ref())
+                .ok_or_else(|| {
+                    ErrorCode::ParquetFileInvalid(format!(
+                        "Row groups of the summary '{}' must have the path of their file",
+                        summary_location
+                    ))
+                })?;
+            let file_meta = file_metas
+                .entry(format!("{}{}", dir, file_path))
+                .or_insert_with(|| FileMetaData {
+                    version: summary.version,
+                    num_rows: 0,
+                    created_by: summary.created_by.clone(),
+                    row_groups: vec![],
+                    key_value_metadata: summary.key_value_metadata.clone(),
+                    schema_descr: summary.schema_descr.clone(),
+                    column_orders: summary.column_orders.clone(),
+                });
+            file_meta.num_rows += row_group.num_rows();
+            file_meta.row_groups.push(row_group.clone());
+        }
+    }
+    Ok(file_metas)
+}
+
+/// Infer [`DataSchema`] from [`FileMetaData`], and the indices of its INTERVAL fields.
+///
+/// The names of the columns are transformed by `name_transform` if any. The columns without
+/// a name, or with one of control characters, are named `col_<index>`. The columns named
+/// like a previous one once lower cased are renamed if `suffix_duplicates`, and rejected
+/// otherwise. The schema of a file without row groups is inferred all the same. The columns
+/// annotated only by a converted type are read as their physical types if
+/// `ignore_converted_types`, see [`apply_converted_types`].
+pub(super) fn infer_schema(
+    operators: &FileOperators,
+    location: &str,
+    name_transform: Option<&ColumnNameTransform>,
+    suffix_duplicates: bool,
+    ignore_converted_types: bool,
+) -> Result<(DataSchema, Vec<usize>)> {
+    let meta = read_parquet_meta(operators, location)?;
+    check_supported(location, &meta)?;
+    let (schema, interval_fields) =
+        schema_from_meta(location, &meta, suffix_duplicates, ignore_converted_types)?;
+    match name_transform {
+        Some(name_transform) => Ok((
+            transform_names(
+                &format!("parquet file '{}'", location),
+                schema,
+                name_transform,
+            )?,
+            interval_fields,
+        )),
+        None => Ok((schema, interval_fields)),
+    }
+}
+
+/// Transform the names of the columns of `schema`, inferred from `source` (like the
+/// parquet file `'<location>'`).
+///
+/// Only the names of the columns are transformed, not the ones of their nested fields.
+fn transform_names(
+    source: &str,
+    schema: DataSchema,
+    name_transform: &ColumnNameTransform,
+) -> Result<DataSchema> {
+    let mut fields: Vec<DataField> = Vec::with_capacity(schema.num_fields());
+    for field in schema.fields() {
+        let name = name_transform(field.name());
+        if let Some(other) = schema
+            .fields()
+            .iter()
+            .zip(fields.iter())
+            .find(|(_, transformed)| transformed.name() == &name)
+        {
+            return Err(ErrorCode::BadArguments(format!(
+                "Columns '{}' and '{}' of {} are both named '{}' after the name transform",
+                other.0.name(),
+                field.name(),
+                source,
+                name
+            )));
+        }
+        fields.push(DataField::new(&name, field.data_type().clone()));
+    }
+    Ok(DataSchema::new(fields))
+}
+
+/// Merge the schemas inferred from the files at `locations`, with the indices of their
+/// INTERVAL fields, as set by the option `merge_by`.
+///
+/// By default the files are decoded by the same physical schema, so the schemas are only
+/// compatible if they have the same fields.
+pub(super) fn merge_schemas(
+    locations: &[&String],
+    schemas: Vec<(DataSchema, Vec<usize>)>,
+    merge_by: Option<MergeBy>,
+) -> Result<(DataSchema, Vec<usize>)> {
+    let (first_location, first) = locations
+        .first()
+        .zip(schemas.first())
+        .ok_or_else(|| ErrorCode::Internal("No parquet files to merge the schemas of"))?;
+    let mut merged = first.clone();
+    if merge_by == Some(MergeBy::Name) {
+        // The columns are in the order they first appear in the files.
+        let (mut fields, mut interval_fields) = (merged.0.fields().clone(), merged.1);
+        for (schema, schema_interval_fields) in schemas.iter().skip(1) {
+            for (index, field) in schema.fields().iter().enumerate() {
+                if fields.iter().all(|f| f.name() != field.name()) {
+                    if schema_interval_fields.contains(&index) {
+                        interval_fields.push(fields.len());
+                    }
+                    fields.push(field.clone());
                 }
-                ParquetType::GroupType { field_info, .. } => {
-                    field_info.name = field_info.name.to_lowercase()
+            }
+        }
+        merged = (DataSchema::new(fields), interval_fields);
+    }
+
+    let reference = format!("the schema of '{}'", first_location);
+    for (location, schema) in locations.iter().zip(schemas.iter()) {
+        check_file_schema(location, schema, &reference, &merged, merge_by)?;
+    }
+    Ok(merged)
+}
+
+/// Check that the schemas inferred from the files at `locations` can be merged as set by the
+/// option `merge_by`, like [`merge_schemas`]. Returns the first incompatibility found, `None`
+/// if they are compatible.
+pub(super) fn check_schemas(
+    locations: &[&String],
+    schemas: Vec<(DataSchema, Vec<usize>)>,
+    merge_by: Option<MergeBy>,
+) -> Result<Option<String>> {
+    match merge_schemas(locations, schemas, merge_by) {
+        Ok(_) => Ok(None),
+        Err(e) if e.code() == ErrorCode::PARQUET_SCHEMA_MISMATCH => Ok(Some(e.message())),
+        Err(e) => Err(e),
+    }
+}
+
+/// Check the schema of the file at `location`, with the indices of its INTERVAL fields,
+/// against the `merged` schema of the files, taken from `reference` (like the schema of
+/// the first file).
+fn check_file_schema(
+    location: &str,
+    schema: &(DataSchema, Vec<usize>),
+    reference: &str,
+    merged: &(DataSchema, Vec<usize>),
+    merge_by: Option<MergeBy>,
+) -> Result<()> {
+    let (fields, interval_fields) = (schema.0.fields(), &schema.1);
+    let (merged_fields, merged_interval_fields) = (merged.0.fields(), &merged.1);
+    let compatible = match merge_by {
+        None => fields == merged_fields && interval_fields == merged_interval_fields,
+        Some(MergeBy::Position) => {
+            fields.len() == merged_fields.len()
+                && fields
+                    .iter()
+                    .zip(merged_fields.iter())
+                    .all(|(field, merged_field)| field.data_type() == merged_field.data_type())
+                && interval_fields == merged_interval_fields
+        }
+        Some(MergeBy::Name) => {
+            for merged_field in merged_fields.iter() {
+                if schema.0.has_field(merged_field.name()) || merged_field.is_nullable() {
+                    continue;
                 }
+                return Err(ErrorCode::ParquetSchemaMismatch(format!(
+                    "Column '{}' is missing in parquet file '{}', but it is not nullable in the other files",
+                    merged_field.name(),
+                    location
+                )));
             }
+            fields.iter().enumerate().all(|(index, field)| {
+                merged.0.index_of(field.name()).ok().map_or(false, |merged_index| {
+                    field.data_type() == merged_fields[merged_index].data_type()
+                        && interval_fields.contains(&index)
+                            == merged_interval_fields.contains(&merged_index)
+                })
+            })
+        }
+    };
+    if !compatible {
+        return Err(ErrorCode::ParquetSchemaMismatch(format!(
+            "The schema of parquet file '{}' is different from {}",
+            location, reference
+        )));
+    }
+    Ok(())
+}
+
+/// The leaf columns of the file of `schema` holding the leaf columns of the `merged`
+/// schema, matched by the names of their columns. `None` if they are the same.
+fn leaf_ids_by_name(
+    schema: &(DataSchema, Vec<usize>),
+    merged: &(DataSchema, Vec<usize>),
+) -> Option<Vec<Option<usize>>> {
+    if schema.0.fields() == merged.0.fields() {
+        return None;
+    }
+
+    let leaves = ColumnLeaves::new_from_schema(&physical_arrow_schema(&schema.0, &schema.1));
+    let merged_leaves =
+        ColumnLeaves::new_from_schema(&physical_arrow_schema(&merged.0, &merged.1));
+    let num_leaves = merged_leaves
+        .column_leaves
+        .iter()
+        .map(|leaf| leaf.leaf_ids.len())
+        .sum::<usize>();
+    let mut leaf_ids = vec![None; num_leaves];
+    for (merged_leaf, merged_field) in merged_leaves
+        .column_leaves
+        .iter()
+        .zip(merged.0.fields().iter())
+    {
+        if let Ok(index) = schema.0.index_of(merged_field.name()) {
+            let file_leaf_ids = &leaves.column_leaves[index].leaf_ids;
+            for (merged_id, file_id) in merged_leaf.leaf_ids.iter().zip(file_leaf_ids.iter()) {
+                leaf_ids[*merged_id] = Some(*file_id);
+            }
+        }
+    }
+    Some(leaf_ids)
+}
+
+fn schema_from_meta(
+    location: &str,
+    meta: &FileMetaData,
+    suffix_duplicates: bool,
+    ignore_converted_types: bool,
+) -> Result<(DataSchema, Vec<usize>)> {
+    let parquet_fields = meta
+        .schema()
+        .fields()
+        .iter()
+        .map(|pt| {
+            let mut pt = pt.clone();
+            lowercase_names(&mut pt);
+            apply_converted_types(&mut pt, ignore_converted_types);
             pt
         })
         .collect::<Vec<_>>();
-    let arrow_fields = ArrowSchema::from(parquet_to_arrow_schema(&parquet_fields));
+    let mut arrow_fields = parquet_to_arrow_schema(&parquet_fields);
+    prefer_embedded_types(meta, &mut arrow_fields);
+    synthesize_names(&mut arrow_fields);
+    suffix_duplicate_names(location, meta, &mut arrow_fields, suffix_duplicates)?;
+    let source = format!("parquet file '{}'", location);
+    let interval_fields = convert_intervals(&source, &mut arrow_fields)?;
+    convert_decimals(&source, &mut arrow_fields)?;
 
-    Ok(DataSchema::from(&arrow_fields))
+    let arrow_schema = ArrowSchema::from(arrow_fields);
+    Ok((DataSchema::from(&arrow_schema), interval_fields))
+}
+
+/// Take the types of `arrow_fields`, converted from the parquet schema of `meta`, from the
+/// arrow schema embedded in the file by the arrow writers, if there is one.
+///
+/// The embedded schema keeps the exact types of the columns written, like the types of
+/// Databend kept in the metadata of the fields, which the parquet types don't tell.
+/// Dictionaries are read as their values. The fields whose embedded types have no table
+/// type, and all of them if the embedded schema doesn't match the parquet one, keep the
+/// converted types.
+fn prefer_embedded_types(meta: &FileMetaData, arrow_fields: &mut [ArrowField]) {
+    let has_arrow_schema = meta
+        .key_value_metadata()
+        .as_ref()
+        .map_or(false, |kvs| kvs.iter().any(|kv| kv.key == ARROW_SCHEMA_KEY));
+    if !has_arrow_schema {
+        return;
+    }
+    let mut embedded = match infer_arrow_schema(meta) {
+        Ok(schema) => schema.fields,
+        Err(_) => return,
+    };
+    embedded.iter_mut().for_each(lowercase_arrow_names);
+    embedded.iter_mut().for_each(read_dictionary_values);
+    if embedded.len() != arrow_fields.len()
+        || embedded
+            .iter()
+            .zip(arrow_fields.iter())
+            .any(|(embedded, field)| embedded.name != field.name)
+    {
+        return;
+    }
+
+    for (field, embedded) in arrow_fields.iter_mut().zip(embedded) {
+        if has_table_type(&embedded) {
+            // The nullability of the columns is the one of the parquet schema.
+            let is_nullable = field.is_nullable;
+            *field = embedded;
+            field.is_nullable = is_nullable;
+        }
+    }
+}
+
+/// Replace the dictionaries of `field` by their values.
+fn read_dictionary_values(field: &mut ArrowField) {
+    if let ArrowType::Dictionary(_, values, _) = &field.data_type {
+        field.data_type = values.as_ref().clone();
+    }
+    match &mut field.data_type {
+        ArrowType::Struct(fields) => fields.iter_mut().for_each(read_dictionary_values),
+        ArrowType::List(field)
+        | ArrowType::LargeList(field)
+        | ArrowType::FixedSizeList(field, _) => read_dictionary_values(field),
+        _ => {}
+    }
+}
+
+/// Whether `field` of an embedded arrow schema converts to a table type, see
+/// [`common_datavalues::from_arrow_field`].
+fn has_table_type(field: &ArrowField) -> bool {
+    match field.metadata.get(ARROW_EXTENSION_NAME).map(String::as_str) {
+        Some("Date" | "Timestamp" | "Variant" | "VariantArray" | "VariantObject") => return true,
+        Some("Interval") => return field.metadata.contains_key(ARROW_EXTENSION_META),
+        Some("Tuple") if !matches!(field.data_type, ArrowType::Struct(_)) => return false,
+        _ => {}
+    }
+    match &field.data_type {
+        ArrowType::Null
+        | ArrowType::Boolean
+        | ArrowType::Int8
+        | ArrowType::Int16
+        | ArrowType::Int32
+        | ArrowType::Int64
+        | ArrowType::UInt8
+        | ArrowType::UInt16
+        | ArrowType::UInt32
+        | ArrowType::UInt64
+        | ArrowType::Float32
+        | ArrowType::Float64
+        | ArrowType::Binary
+        | ArrowType::LargeBinary
+        | ArrowType::Utf8
+        | ArrowType::LargeUtf8
+        | ArrowType::Timestamp(_, _)
+        | ArrowType::Date32
+        | ArrowType::Date64 => true,
+        ArrowType::List(field)
+        | ArrowType::LargeList(field)
+        | ArrowType::FixedSizeList(field, _) => has_table_type(field),
+        ArrowType::Struct(fields) => fields.iter().all(has_table_type),
+        ArrowType::Extension(name, _, _) => {
+            matches!(name.as_str(), "Variant" | "VariantArray" | "VariantObject")
+        }
+        data_type => is_parquet_interval(data_type),
+    }
+}
+
+/// The schema of the files given by `arrow_schema`, with the indices of its INTERVAL
+/// fields, like [`infer_schema`] would infer it from a file of that schema.
+///
+/// The names are lower cased and transformed by `name_transform`, the files are checked
+/// against it by their schemas inferred the same way.
+pub(super) fn schema_from_arrow(
+    arrow_schema: &ArrowSchema,
+    name_transform: Option<&ColumnNameTransform>,
+) -> Result<(DataSchema, Vec<usize>)> {
+    let source = "the provided schema";
+    let mut arrow_fields = arrow_schema.fields.clone();
+    arrow_fields.iter_mut().for_each(lowercase_arrow_names);
+    let interval_fields = convert_intervals(source, &mut arrow_fields)?;
+    convert_decimals(source, &mut arrow_fields)?;
+
+    let mut schema = DataSchema::from(&ArrowSchema::from(arrow_fields));
+    if let Some(name_transform) = name_transform {
+        schema = transform_names(source, schema, name_transform)?;
+    }
+    Ok((schema, interval_fields))
+}
+
+/// Convert the INTERVAL fields of `arrow_fields`, from `source` (like the parquet file
+/// `'<location>'`), to the type of the INTERVAL columns of the table, and return their
+/// indices.
+fn convert_intervals(source: &str, arrow_fields: &mut [ArrowField]) -> Result<Vec<usize>> {
+    let mut interval_fields = vec![];
+    for (index, field) in arrow_fields.iter_mut().enumerate() {
+        if is_parquet_interval(&field.data_type) {
+            field.data_type = interval_struct_type(field.is_nullable);
+            interval_fields.push(index);
+        } else if contains_interval(&field.data_type) {
+            return Err(ErrorCode::ParquetUnsupported(format!(
+                "Column '{}' of {} has nested INTERVAL values, which are not supported",
+                field.name, source
+            )));
+        }
+    }
+    Ok(interval_fields)
+}
+
+/// Convert the DECIMAL fields of `arrow_fields`, from `source`, to the FLOAT64 type their
+/// values are read as, see [`crate::DecimalEncoding`].
+fn convert_decimals(source: &str, arrow_fields: &mut [ArrowField]) -> Result<()> {
+    for field in arrow_fields.iter_mut() {
+        if matches!(field.data_type, ArrowType::Decimal(_, _)) {
+            field.data_type = ArrowType::Float64;
+        } else if contains_decimal(&field.data_type) {
+            return Err(ErrorCode::ParquetUnsupported(format!(
+                "Column '{}' of {} has nested DECIMAL values, which are not supported",
+                field.name, source
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// The columns of `schema` whose names match the SQL `LIKE` pattern `pattern`, with the
+/// indices of their INTERVAL fields, given the ones of `schema` by `interval_fields`.
+pub(super) fn select_columns_like(
+    schema: &DataSchema,
+    interval_fields: &[usize],
+    pattern: &str,
+) -> (DataSchema, Vec<usize>) {
+    let mut fields = vec![];
+    let mut like_interval_fields = vec![];
+    for (index, field) in schema.fields().iter().enumerate() {
+        if like(field.name().as_bytes(), pattern.as_bytes()) {
+            if interval_fields.contains(&index) {
+                like_interval_fields.push(fields.len());
+            }
+            fields.push(field.clone());
+        }
+    }
+    (DataSchema::new(fields), like_interval_fields)
+}
+
+/// Whether `name` matches the SQL `LIKE` pattern `pattern`, where `%` matches any bytes,
+/// `_` a single one, and `\` escapes the next byte.
+fn like(name: &[u8], pattern: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some((b'%', rest)) => (0..=name.len()).any(|skip| like(&name[skip..], rest)),
+        Some((b'_', rest)) => !name.is_empty() && like(&name[1..], rest),
+        Some((b'\\', [escaped, rest @ ..])) | Some((escaped, rest)) => {
+            name.first() == Some(escaped) && like(&name[1..], rest)
+        }
+    }
+}
+
+/// `schema` with its columns sorted by name, with the indices of its INTERVAL fields,
+/// given the ones of `schema` by `interval_fields`, as set by `column_order=name`.
+pub(super) fn sort_columns_by_name(
+    schema: &DataSchema,
+    interval_fields: &[usize],
+) -> (DataSchema, Vec<usize>) {
+    let mut indices = (0..schema.num_fields()).collect::<Vec<_>>();
+    indices.sort_by(|a, b| schema.field(*a).name().cmp(schema.field(*b).name()));
+    let fields = indices.iter().map(|index| schema.field(*index).clone());
+    let sorted_interval_fields = indices
+        .iter()
+        .enumerate()
+        .filter(|(_, index)| interval_fields.contains(index))
+        .map(|(sorted_index, _)| sorted_index)
+        .collect();
+    (DataSchema::new(fields.collect()), sorted_interval_fields)
+}
+
+/// Rename the columns of `schema` to `columns` by position.
+pub(super) fn rename_columns(schema: DataSchema, columns: &[String]) -> Result<DataSchema> {
+    if columns.len() != schema.num_fields() {
+        return Err(ErrorCode::BadArguments(format!(
+            "read_parquet option 'columns' has {} names, but the parquet files have {} columns",
+            columns.len(),
+            schema.num_fields()
+        )));
+    }
+
+    let mut fields = Vec::with_capacity(columns.len());
+    for (field, name) in schema.fields().iter().zip(columns.iter()) {
+        if fields.iter().any(|f: &DataField| f.name() == name) {
+            return Err(ErrorCode::BadArguments(format!(
+                "read_parquet option 'columns' has duplicated column name '{}'",
+                name
+            )));
+        }
+        fields.push(DataField::new(name, field.data_type().clone()));
+    }
+    Ok(DataSchema::new(fields))
+}
+
+/// Cast the columns of `schema` to the types of `casts`, which are `(<column>, <type>)` pairs.
+///
+/// Returns the schema with the new types, and the indices of the cast fields with their
+/// types in `schema`. The casts which can't be done safely are rejected here, before
+/// reading any data.
+///
+/// The casts of the FLOAT64 columns to DECIMAL types like `decimal(18, 2)` keep their
+/// types, they are returned apart, with the rounding `decimal_rounding` of their values.
+pub(super) fn cast_columns(
+    schema: DataSchema,
+    casts: &[(String, String)],
+    decimal_rounding: Option<DecimalRounding>,
+) -> Result<(DataSchema, Vec<(usize, DataTypeImpl)>, Vec<(usize, DecimalCast)>)> {
+    let mut fields = schema.fields().clone();
+    let mut cast_fields: Vec<(usize, DataTypeImpl)> = Vec::with_capacity(casts.len());
+    let mut decimal_fields: Vec<(usize, DecimalCast)> = vec![];
+    for (name, type_name) in casts {
+        let index = schema.index_of(name).map_err(|_| {
+            ErrorCode::BadArguments(format!(
+                "read_parquet option 'cast' has unknown column '{}'",
+                name
+            ))
+        })?;
+        if cast_fields.iter().any(|(i, _)| *i == index)
+            || decimal_fields.iter().any(|(i, _)| *i == index)
+        {
+            return Err(ErrorCode::BadArguments(format!(
+                "read_parquet option 'cast' has duplicated column '{}'",
+                name
+            )));
+        }
+
+        let from_type = fields[index].data_type().clone();
+        if let Some(decimal_type) = parse_decimal_type(type_name) {
+            let (precision, scale) = decimal_type.map_err(|e| {
+                ErrorCode::BadArguments(format!(
+                    "invalid type '{}' of column '{}' in read_parquet option 'cast': {}",
+                    type_name, name, e
+                ))
+            })?;
+            if remove_nullable(&from_type).data_type_id() != TypeID::Float64 {
+                return Err(ErrorCode::BadArguments(format!(
+                    "Column '{}' can't be cast from {} to DECIMAL({}, {}), only the DECIMAL columns can",
+                    name,
+                    from_type.name(),
+                    precision,
+                    scale
+                )));
+            }
+            let cast = DecimalCast {
+                precision,
+                scale,
+                rounding: decimal_rounding,
+            };
+            decimal_fields.push((index, cast));
+            continue;
+        }
+
+        let mut target_type = TypeFactory::instance().get(type_name).map_err(|e| {
+            ErrorCode::BadArguments(format!(
+                "invalid type '{}' of column '{}' in read_parquet option 'cast': {}",
+                type_name,
+                name,
+                e.message()
+            ))
+        })?;
+        if from_type.is_nullable() && !target_type.is_nullable() {
+            target_type = NullableType::new_impl(target_type);
+        }
+        if !is_castable(&from_type, &target_type) {
+            return Err(ErrorCode::BadArguments(format!(
+                "Column '{}' can't be cast from {} to {}",
+                name,
+                from_type.name(),
+                target_type.name()
+            )));
+        }
+
+        fields[index] = DataField::new(name, target_type);
+        cast_fields.push((index, from_type));
+    }
+    Ok((DataSchema::new(fields), cast_fields, decimal_fields))
+}
+
+/// Parse the DECIMAL type `decimal(<precision>, <scale>)`, or `decimal(<precision>)` of
+/// scale 0, case insensitive. `None` for the other types.
+fn parse_decimal_type(type_name: &str) -> Option<std::result::Result<(usize, usize), String>> {
+    let lower = type_name.trim().to_lowercase();
+    let args = lower.strip_prefix("decimal")?.trim_start();
+    let args = match args.strip_prefix('(').and_then(|args| args.strip_suffix(')')) {
+        Some(args) => args,
+        None => return Some(Err("expect 'decimal(<precision>, <scale>)'".to_string())),
+    };
+    let mut numbers = args.split(',').map(|number| number.trim().parse::<usize>());
+    let (precision, scale) = match (numbers.next(), numbers.next(), numbers.next()) {
+        (Some(Ok(precision)), None, None) => (precision, 0),
+        (Some(Ok(precision)), Some(Ok(scale)), None) => (precision, scale),
+        _ => return Some(Err("expect 'decimal(<precision>, <scale>)'".to_string())),
+    };
+    if precision == 0 || scale > precision {
+        return Some(Err(format!(
+            "the precision must be greater than 0 and at least the scale, got {} and {}",
+            precision, scale
+        )));
+    }
+    Some(Ok((precision, scale)))
+}
+
+/// Whether the values of `from_type` can be cast to `target_type`.
+///
+/// Nested values can't be cast, the values changed by the cast (like overflowing integers)
+/// are still reported while reading.
+fn is_castable(from_type: &DataTypeImpl, target_type: &DataTypeImpl) -> bool {
+    let from = remove_nullable(from_type).data_type_id();
+    let target = remove_nullable(target_type).data_type_id();
+    match target {
+        TypeID::String => {
+            from.is_numeric()
+                || matches!(
+                    from,
+                    TypeID::Boolean | TypeID::String | TypeID::Date | TypeID::Timestamp
+                )
+        }
+        TypeID::Boolean => from.is_numeric() || matches!(from, TypeID::Boolean | TypeID::String),
+        TypeID::Date | TypeID::Timestamp => {
+            matches!(from, TypeID::Date | TypeID::Timestamp | TypeID::String)
+        }
+        target if target.is_numeric() => {
+            from.is_numeric() || matches!(from, TypeID::Boolean | TypeID::String)
+        }
+        _ => false,
+    }
+}
+
+/// Name the columns of `fields` with empty names, or names of control characters, like
+/// `col_<index>` with their indices in the file.
+///
+/// The names are only used to reference the columns, which are still read by their
+/// positions in the files, so the names tell the columns they are read from.
+fn synthesize_names(fields: &mut [ArrowField]) {
+    for (index, field) in fields.iter_mut().enumerate() {
+        let name = field.name.trim();
+        if name.is_empty() || name.chars().any(char::is_control) {
+            field.name = format!("col_{}", index);
+        }
+    }
+}
+
+/// Rename the columns of `fields` named like a previous one once lower cased, by appending
+/// `_1`, `_2`, ... to their names, or reject them if not `suffix_duplicates`.
+fn suffix_duplicate_names(
+    location: &str,
+    meta: &FileMetaData,
+    fields: &mut [ArrowField],
+    suffix_duplicates: bool,
+) -> Result<()> {
+    for index in 0..fields.len() {
+        let name = fields[index].name.clone();
+        let first = match fields[..index].iter().position(|field| field.name == name) {
+            Some(first) => first,
+            None => continue,
+        };
+        if !suffix_duplicates {
+            let file_fields = meta.schema().fields();
+            return Err(ErrorCode::BadArguments(format!(
+                "Columns '{}' and '{}' of parquet file '{}' are both named '{}' once lower cased, set read_parquet option 'on_duplicate=suffix' to rename them",
+                file_fields[first].name(),
+                file_fields[index].name(),
+                location,
+                name
+            )));
+        }
+
+        let mut suffix = 1;
+        while fields
+            .iter()
+            .any(|field| field.name == format!("{}_{}", name, suffix))
+        {
+            suffix += 1;
+        }
+        fields[index].name = format!("{}_{}", name, suffix);
+    }
+    Ok(())
+}
+
+/// Convert the names of `pt` and its nested fields to lower case.
+///
+/// Unquoted identifiers are lower cased, so that the columns and the fields of their
+/// tuples can be referenced however they are cased in the file.
+fn lowercase_names(pt: &mut ParquetType) {
+    match pt {
+        ParquetType::PrimitiveType(primitive) => {
+            primitive.field_info.name = primitive.field_info.name.to_lowercase()
+        }
+        ParquetType::GroupType {
+            field_info, fields, ..
+        } => {
+            field_info.name = field_info.name.to_lowercase();
+            fields.iter_mut().for_each(lowercase_names);
+        }
+    }
+}
+
+/// Give the primitive columns of `pt` annotated only by a converted type, as written before
+/// the logical types, the logical type it stands for, or drop it if `ignore_converted_types`
+/// so that they are read as their physical types.
+///
+/// The converted types of the columns with a logical type are left as they are, the logical
+/// types take precedence. INTERVAL has no logical type and is kept either way.
+fn apply_converted_types(pt: &mut ParquetType, ignore_converted_types: bool) {
+    match pt {
+        ParquetType::PrimitiveType(primitive) => {
+            let logical_type = match (&primitive.logical_type, &primitive.converted_type) {
+                (None, Some(converted_type)) => converted_logical_type(converted_type),
+                _ => None,
+            };
+            match logical_type {
+                None => {}
+                Some(_) if ignore_converted_types => primitive.converted_type = None,
+                Some(logical_type) => primitive.logical_type = Some(logical_type),
+            }
+        }
+        ParquetType::GroupType { fields, .. } => fields
+            .iter_mut()
+            .for_each(|field| apply_converted_types(field, ignore_converted_types)),
+    }
+}
+
+/// The logical type the converted type `converted_type` stands for, see the compatibility
+/// section of the parquet logical types, `None` for INTERVAL. The times and timestamps are
+/// adjusted to UTC.
+fn converted_logical_type(converted_type: &PrimitiveConvertedType) -> Option<PrimitiveLogicalType> {
+    Some(match converted_type {
+        PrimitiveConvertedType::Utf8 => PrimitiveLogicalType::String,
+        PrimitiveConvertedType::Enum => PrimitiveLogicalType::Enum,
+        PrimitiveConvertedType::Decimal(precision, scale) => {
+            PrimitiveLogicalType::Decimal(*precision, *scale)
+        }
+        PrimitiveConvertedType::Date => PrimitiveLogicalType::Date,
+        PrimitiveConvertedType::TimeMillis => PrimitiveLogicalType::Time {
+            unit: ParquetTimeUnit::Milliseconds,
+            is_adjusted_to_utc: true,
+        },
+        PrimitiveConvertedType::TimeMicros => PrimitiveLogicalType::Time {
+            unit: ParquetTimeUnit::Microseconds,
+            is_adjusted_to_utc: true,
+        },
+        PrimitiveConvertedType::TimestampMillis => PrimitiveLogicalType::Timestamp {
+            unit: ParquetTimeUnit::Milliseconds,
+            is_adjusted_to_utc: true,
+        },
+        PrimitiveConvertedType::TimestampMicros => PrimitiveLogicalType::Timestamp {
+            unit: ParquetTimeUnit::Microseconds,
+            is_adjusted_to_utc: true,
+        },
+        PrimitiveConvertedType::Uint8 => PrimitiveLogicalType::Integer(IntegerType::UInt8),
+        PrimitiveConvertedType::Uint16 => PrimitiveLogicalType::Integer(IntegerType::UInt16),
+        PrimitiveConvertedType::Uint32 => PrimitiveLogicalType::Integer(IntegerType::UInt32),
+        PrimitiveConvertedType::Uint64 => PrimitiveLogicalType::Integer(IntegerType::UInt64),
+        PrimitiveConvertedType::Int8 => PrimitiveLogicalType::Integer(IntegerType::Int8),
+        PrimitiveConvertedType::Int16 => PrimitiveLogicalType::Integer(IntegerType::Int16),
+        PrimitiveConvertedType::Int32 => PrimitiveLogicalType::Integer(IntegerType::Int32),
+        PrimitiveConvertedType::Int64 => PrimitiveLogicalType::Integer(IntegerType::Int64),
+        PrimitiveConvertedType::Json => PrimitiveLogicalType::Json,
+        PrimitiveConvertedType::Bson => PrimitiveLogicalType::Bson,
+        PrimitiveConvertedType::Interval => return None,
+    })
+}
+
+/// Like [`lowercase_names`], for the fields of an arrow schema.
+fn lowercase_arrow_names(field: &mut ArrowField) {
+    field.name = field.name.to_lowercase();
+    match &mut field.data_type {
+        ArrowType::Struct(fields) => fields.iter_mut().for_each(lowercase_arrow_names),
+        ArrowType::List(field)
+        | ArrowType::LargeList(field)
+        | ArrowType::FixedSizeList(field, _)
+        | ArrowType::Map(field, _) => lowercase_arrow_names(field),
+        _ => {}
+    }
+}
+
+fn contains_interval(data_type: &ArrowType) -> bool {
+    match data_type {
+        ArrowType::Struct(fields) => fields.iter().any(|f| contains_interval(&f.data_type)),
+        ArrowType::List(field)
+        | ArrowType::LargeList(field)
+        | ArrowType::FixedSizeList(field, _)
+        | ArrowType::Map(field, _) => contains_interval(&field.data_type),
+        data_type => is_parquet_interval(data_type),
+    }
+}
+
+fn contains_decimal(data_type: &ArrowType) -> bool {
+    match data_type {
+        ArrowType::Struct(fields) => fields.iter().any(|f| contains_decimal(&f.data_type)),
+        ArrowType::List(field)
+        | ArrowType::LargeList(field)
+        | ArrowType::FixedSizeList(field, _)
+        | ArrowType::Map(field, _) => contains_decimal(&field.data_type),
+        data_type => matches!(data_type, ArrowType::Decimal(_, _)),
+    }
+}
+
+/// Check that the column chunks of `meta` are stored in ways we can decode.
+fn check_supported(location: &str, meta: &FileMetaData) -> Result<()> {
+    for column in meta.row_groups.iter().flat_map(|rg| rg.columns()) {
+        let unsupported = if !is_supported_compression(column.compression()) {
+            Some(format!("compression {:?}", column.compression()))
+        } else {
+            column
+                .column_encoding()
+                .iter()
+                .find(|encoding| !is_supported_encoding(**encoding))
+                .map(|encoding| format!("encoding {:?}", encoding))
+        };
+
+        if let Some(unsupported) = unsupported {
+            return Err(ErrorCode::ParquetUnsupported(format!(
+                "Column '{}' of parquet file '{}' uses unsupported {}",
+                column.descriptor().path_in_schema.join("."),
+                location,
+                unsupported
+            )));
+        }
+    }
+    Ok(())
 }
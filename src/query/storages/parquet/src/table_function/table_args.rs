@@ -0,0 +1,793 @@
+//  Copyright 2022 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use chrono_tz::Tz;
+use common_ast::parser::token::TokenKind;
+use common_ast::parser::tokenize_sql;
+use common_datavalues::DataValue;
+use common_exception::ErrorCode;
+use common_exception::Result;
+
+use crate::DecimalRounding;
+use crate::ReadAheadWindow;
+
+/// Options of `read_parquet`.
+///
+/// They are passed as `'<name>=<value>'` string arguments, mixed with the file paths:
+///
+/// ```sql
+/// select * from read_parquet('/data/*.parquet', 'min_file_size=1024');
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ParquetTableOptions {
+    /// Files smaller than this (in bytes) are skipped while resolving the paths.
+    pub min_file_size: Option<u64>,
+    /// Row groups are split into blocks of at most this many rows while reading,
+    /// instead of being deserialized as a whole.
+    pub max_block_rows: Option<usize>,
+    /// The rows of the blocks emitted by each source of a scan, whatever the row groups
+    /// they are read from: the blocks of the row groups are split or merged so that only
+    /// the last block of a source has fewer rows.
+    pub batch_size: Option<usize>,
+    /// Verify the CRC checksums of the pages which carry one.
+    pub verify_checksums: bool,
+    /// Expose the `<column>=<value>` directories of the paths as partition columns,
+    /// the files of partitions not matching the filters are not read.
+    pub hive_partitioning: bool,
+    /// The partition values of the files matching glob patterns, for the files not laid
+    /// out in hive style. Set by `partition_values=<pattern>:<column>=<value>,...;...`, like
+    /// `partition_values=data/us/*:region=us;data/eu/*:region=eu`.
+    pub partition_values: Option<Vec<(String, Vec<(String, String)>)>>,
+    /// Constant columns added to the rows of all the files, after the partition columns.
+    /// Set by `literals=<column>:<value>[::<type>],...`, like `literals=batch:42,source:daily`.
+    /// The type is BIGINT if the value is an integer and STRING otherwise, unless given.
+    pub literals: Option<Vec<(String, String, Option<String>)>>,
+    /// Names of the columns of the files, by position, instead of the ones in the files.
+    pub columns: Option<Vec<String>>,
+    /// Read only the columns whose names, lower cased and transformed, match this SQL
+    /// `LIKE` pattern, where `%` matches any characters and `_` a single one, like
+    /// `columns_like=metric_%`. The columns are matched in the schema of the table, the
+    /// ones of the files are found by name.
+    pub columns_like: Option<String>,
+    /// What to do with the files missing a column of the table, set by
+    /// `on_missing_columns=null` or `on_missing_columns=error`, see [`MissingColumns`]. By
+    /// default the columns matched by `columns_like` are rejected, and the ones merged by
+    /// `merge_by=name` are read as NULL.
+    pub on_missing_columns: Option<MissingColumns>,
+    /// Names of the non-data files skipped while resolving the paths, like the `_SUCCESS`
+    /// markers of Spark. See [`DEFAULT_SIDECAR_FILES`] for the default.
+    pub sidecar_files: Option<Vec<String>>,
+    /// Take the footers of the files from the `_metadata` summary of their directory,
+    /// if there is one, instead of reading them one by one.
+    pub use_metadata_file: bool,
+    /// Provide the null counts of the columns to the optimizer, summed from the page
+    /// level null counts in the column indexes of the files.
+    pub page_null_counts: bool,
+    /// Refuse to read more than this many bytes, as estimated from the footers of the
+    /// files before any of them is scanned.
+    pub max_scan_bytes: Option<usize>,
+    /// Read all the row groups of all the files, without pruning them by the filters.
+    ///
+    /// This is for checking the results of a query against an unpruned scan.
+    pub no_prune: bool,
+    /// Types to cast the columns to while reading, as `<column>:<type>` pairs like
+    /// `id:double,name:string`. The DECIMAL columns, read as FLOAT64 values, can be cast to
+    /// DECIMAL types like `price:decimal(18, 2)`: their values are rounded to the scale by
+    /// [`ParquetTableOptions::decimal_rounding`], and must have at most the precision.
+    pub cast: Option<Vec<(String, String)>>,
+    /// Skip the row groups which fail to decode instead of aborting the query, set by
+    /// `on_error=skip` (the default is `on_error=abort`). A row group decoded in blocks
+    /// (see `max_block_rows`) keeps the blocks read before the failure, only its
+    /// remaining rows are skipped.
+    pub skip_corrupt_row_groups: bool,
+    /// The built-in transform of the column names, applied after they are lower cased.
+    pub name_transform: Option<NameTransform>,
+    /// How the names inferred for the columns are made safe to reference in SQL without
+    /// quotes, applied after the name transform. The names are kept as they are by default
+    /// (`identifier_policy=none`).
+    pub identifier_policy: Option<IdentifierPolicy>,
+    /// Row groups whose projected string columns are larger than this (in bytes, after
+    /// decompression) are decoded block by block, so that no string column is decoded
+    /// into one giant buffer. See [`DEFAULT_MAX_STRING_BLOCK_BYTES`] for the default.
+    pub max_string_block_bytes: Option<usize>,
+    /// Decode the row group of each part in about this many bytes, as estimated from its
+    /// projected column chunks: the compressed chunks are held while it is decoded, and
+    /// the decoded values grow with the rows of the blocks. The row groups above it are
+    /// decoded in smaller blocks, unless `on_memory_budget=error` is set.
+    pub memory_budget: Option<usize>,
+    /// Fail on the row groups exceeding `memory_budget` instead of decoding them in smaller
+    /// blocks, set by `on_memory_budget=error` (the default is `on_memory_budget=split`).
+    /// The row groups whose compressed chunks alone exceed it fail either way.
+    pub error_on_memory_budget: bool,
+    /// Make the nullable columns holding no NULL in any of the files non-nullable in the
+    /// table schema, by the null counts of their footers, set by `nullability=observed`.
+    /// The columns keep the nullability of the files by default (`nullability=declared`).
+    pub observed_nullability: bool,
+    /// Clamp the TIMESTAMP values of milliseconds or nanoseconds out of the range of the
+    /// table timestamps to its bounds, set by `on_timestamp_overflow=saturate`. They fail,
+    /// naming their file and row, by default (`on_timestamp_overflow=error`).
+    pub saturate_timestamps: bool,
+    /// Reject the scans of files whose TIMESTAMP columns have different units, like
+    /// milliseconds in some files and microseconds in others, set by
+    /// `on_mixed_time_units=error`. By default (`on_mixed_time_units=normalize`) the values
+    /// are scaled to the microseconds of the table whatever their unit, and the mix is
+    /// reported as a warning.
+    pub error_on_mixed_time_units: bool,
+    /// Read the DECIMAL values of fixed length byte arrays as little-endian integers, set
+    /// by `decimal_byte_order=little_endian`, for the files of writers not following the
+    /// format. They are big-endian by default (`decimal_byte_order=big_endian`).
+    pub little_endian_decimals: bool,
+    /// The rounding of the DECIMAL values cast to a smaller scale by the option `cast`,
+    /// set by `decimal_rounding=half_up`, `decimal_rounding=half_even` or
+    /// `decimal_rounding=truncate`. There is no default, the values of a larger scale are
+    /// rejected unless it is set.
+    pub decimal_rounding: Option<DecimalRounding>,
+    /// The column the files are sorted by, in the order of their paths, and the row groups
+    /// of every file. The files which may match the predicates on it are found by binary
+    /// searches, the footers of the other files are not read.
+    pub sorted_by: Option<String>,
+    /// The column the files are clustered by, whatever the order of their paths: the row
+    /// groups of every file are sorted by it and the ranges of the files don't overlap. The
+    /// files are read in the order of the column, found from the statistics of all their
+    /// footers, and the column is the cluster key of the table.
+    pub cluster_by: Option<String>,
+    /// Read the files in the order of their paths, without a cluster key, when they turn
+    /// out not to be clustered by `cluster_by`, set by `on_unclustered=warn` (the default
+    /// is `on_unclustered=error`).
+    pub warn_on_unclustered: bool,
+    /// Collect the non-fatal issues, like the coerced columns, the skipped files and the
+    /// row groups skipped by `on_error=skip`, to return them along with the result, set by
+    /// `warnings=collect`. They are only logged by default (`warnings=log`), see
+    /// [`crate::ParquetWarnings`].
+    pub collect_warnings: bool,
+    /// The timezone of the TIMESTAMP columns not adjusted to UTC, whose values are local
+    /// times. The session timezone by default.
+    pub timezone: Option<Tz>,
+    /// How the schemas of the files are merged, set by `merge_by=name` or
+    /// `merge_by=position`. The files must have the same schema if it is not set.
+    pub merge_by: Option<MergeBy>,
+    /// Sort the columns of the table by name, set by `column_order=name`, so that the
+    /// table has the same schema whatever the order of the columns in the files, which are
+    /// found by name. The columns are in the order of the first file by default
+    /// (`column_order=first_file`), the ones only merged from other files by `merge_by=name`
+    /// following in the order they appear.
+    pub sort_columns_by_name: bool,
+    /// Rename the columns named like a previous one once lower cased, by appending `_1`,
+    /// `_2`, ... to their names, set by `on_duplicate=suffix`. They are rejected by default
+    /// (`on_duplicate=error`).
+    pub suffix_duplicate_columns: bool,
+    /// Read the columns annotated only by a converted type, as written before the logical
+    /// types, as their physical types, set by `converted_types=ignore`. Their types are
+    /// taken from the converted types by default (`converted_types=fallback`).
+    pub ignore_converted_types: bool,
+    /// The fewest row groups each source reads ahead at once from the storages without a
+    /// blocking API, see [`crate::ReadAheadWindow`]. `max_read_ahead` by default.
+    pub min_read_ahead: Option<usize>,
+    /// The most row groups each source reads ahead at once, the window between the bounds
+    /// grows when the reads are slower than the decoding. `min_read_ahead` by default, the
+    /// row groups are read one by one if neither is set.
+    pub max_read_ahead: Option<usize>,
+    /// The order the files are read in, which is the order of the rows absent an
+    /// `ORDER BY` if the files are read by a single thread. By default, the files matched
+    /// by each path are sorted by name and the paths are followed in order.
+    pub file_order: Option<FileOrder>,
+    /// The order the parts of a scan are emitted in, by the size of their column chunks,
+    /// set by `part_order=size_desc` or `part_order=size_asc`. The parts follow the order of
+    /// the files and of their row groups by default (`part_order=sequential`).
+    pub part_order: Option<PartOrder>,
+    /// Map the local files in memory to read their column chunks, instead of reading the
+    /// column chunks one by one. The files of other storages, and the local files which
+    /// fail to be mapped, are read as usual. The files must not be modified while read.
+    pub mmap: bool,
+    /// Files whose scanned column chunks are smaller than this (in bytes) are coalesced
+    /// into parts of about this size, so that a glob over many tiny files doesn't make as
+    /// many parts. The files of a part are read one after the other by the same source.
+    pub coalesce_file_size: Option<usize>,
+    /// The path `-` reads a parquet file from the standard input, which is buffered in
+    /// memory as a whole since the footer is at its end. Refuse to buffer more than this
+    /// (in bytes), see [`DEFAULT_MAX_STDIN_SIZE`] for the default.
+    pub max_stdin_size: Option<usize>,
+    /// The most requests to the storages in flight at once, for the backends limiting the
+    /// concurrent requests of a client. Shared by the reads of all the sources of the
+    /// table, the listing of the files is not limited.
+    pub max_concurrent_requests: Option<usize>,
+    /// The most files open at once for reading during a scan, for the storages and systems
+    /// limiting the open files or connections. The readers of the parts of a file share
+    /// it, the other files wait for a file to be done with, and the local files mapped in
+    /// memory are unmapped once done with.
+    pub max_open_files: Option<usize>,
+    /// Read about this fraction of the rows of the files, between 0 (excluded) and 1, set
+    /// by `fraction=0.01`, the option of the table function `read_parquet_sample`. The
+    /// sampling unit is the row group: whole row groups are read, spread evenly over the
+    /// files, whose rows add up to the fraction of the rows within half a row group.
+    pub sample_fraction: Option<f64>,
+    /// Add the virtual column `_row_group_index`, the index of the row group of every row
+    /// in its file, after the partition columns. Each row group is then read as a part.
+    pub row_group_index: bool,
+}
+
+/// The built-in transforms of the column names, set by the option `name_transform`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NameTransform {
+    /// Trim the leading and trailing spaces, set by `name_transform=trim`.
+    Trim,
+    /// Replace the runs of characters other than letters and digits with `_`, and trim the
+    /// leading and trailing ones, set by `name_transform=snake`. `User Id` becomes `user_id`.
+    Snake,
+}
+
+impl NameTransform {
+    pub fn apply(&self, name: &str) -> String {
+        match self {
+            NameTransform::Trim => name.trim().to_string(),
+            NameTransform::Snake => name
+                .split(|c: char| !c.is_alphanumeric())
+                .filter(|word| !word.is_empty())
+                .map(str::to_lowercase)
+                .collect::<Vec<_>>()
+                .join("_"),
+        }
+    }
+}
+
+/// The policies making the names inferred for the columns safe identifiers, set by the
+/// option `identifier_policy`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IdentifierPolicy {
+    /// Prefix with `_` the names which would have to be quoted, like the reserved keywords
+    /// or the names starting with a digit, set by `identifier_policy=prefix`. `select`
+    /// becomes `_select`. The names still to be quoted once prefixed, like the ones with
+    /// spaces, are kept as they are.
+    Prefix,
+}
+
+impl IdentifierPolicy {
+    pub fn apply(&self, name: &str) -> String {
+        match self {
+            IdentifierPolicy::Prefix => {
+                let prefixed = format!("_{}", name);
+                if !is_safe_identifier(name) && is_safe_identifier(&prefixed) {
+                    prefixed
+                } else {
+                    name.to_string()
+                }
+            }
+        }
+    }
+}
+
+/// Whether `name` is referenced in SQL without quotes, as a single identifier which is not
+/// a reserved keyword.
+fn is_safe_identifier(name: &str) -> bool {
+    match tokenize_sql(name).as_deref() {
+        Ok([token, eoi]) if eoi.kind == TokenKind::EOI && token.text() == name => {
+            token.kind == TokenKind::Ident
+                || (token.kind.is_keyword() && !token.kind.is_reserved_ident(false))
+        }
+        _ => false,
+    }
+}
+
+/// The ways to merge the schemas of the files, set by the option `merge_by`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MergeBy {
+    /// Match the columns of the files by name. The table has the union of their columns,
+    /// the ones missing in a file are read as NULL and must be nullable in the others.
+    Name,
+    /// Match the columns of the files by position. The files must have as many columns,
+    /// of the same types, the table takes the names of the first file.
+    Position,
+}
+
+impl FromStr for MergeBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, String> {
+        choose(s, &[
+            ("name", MergeBy::Name),
+            ("position", MergeBy::Position),
+        ])
+    }
+}
+
+/// The policies for the files missing a column of the table, set by the option
+/// `on_missing_columns`. The missing columns must be nullable in the other files either way.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MissingColumns {
+    /// Read the missing columns as NULL, set by `on_missing_columns=null`.
+    Null,
+    /// Reject the files missing a column, set by `on_missing_columns=error`. With
+    /// `merge_by=name` only the columns read by the query are checked, when the files are
+    /// read, so that the queries not reading the missing columns still read the files.
+    Error,
+}
+
+impl FromStr for MissingColumns {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, String> {
+        choose(s, &[
+            ("error", MissingColumns::Error),
+            ("null", MissingColumns::Null),
+        ])
+    }
+}
+
+/// The orders of the files, set by the option `file_order`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileOrder {
+    /// Sort all the files by name, set by `file_order=name`.
+    Name,
+    /// Sort all the files by name in descending order, set by `file_order=name_desc`.
+    NameDesc,
+    /// Sort all the files by modification time, the oldest first, set by
+    /// `file_order=mtime`. The files whose storage reports no modification time come
+    /// first, the ones modified at the same time are sorted by name.
+    Mtime,
+    /// Keep the files in the order they are listed by the storage, following the order
+    /// of the paths, set by `file_order=as_listed`.
+    AsListed,
+}
+
+impl FromStr for FileOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, String> {
+        choose(s, &[
+            ("name", FileOrder::Name),
+            ("name_desc", FileOrder::NameDesc),
+            ("mtime", FileOrder::Mtime),
+            ("as_listed", FileOrder::AsListed),
+        ])
+    }
+}
+
+/// The orders of the parts of a scan by size, set by the option `part_order`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PartOrder {
+    /// The largest parts first, set by `part_order=size_desc`, so that the longest reads
+    /// start early instead of straggling at the end of the scan.
+    SizeDesc,
+    /// The smallest parts first, set by `part_order=size_asc`.
+    SizeAsc,
+}
+
+impl FromStr for DecimalRounding {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, String> {
+        choose(s, &[
+            ("half_up", DecimalRounding::HalfUp),
+            ("half_even", DecimalRounding::HalfEven),
+            ("truncate", DecimalRounding::Truncate),
+        ])
+    }
+}
+
+/// The non-data files written along with parquet files by Spark and Hive.
+pub const DEFAULT_SIDECAR_FILES: [&str; 3] = ["_SUCCESS", "_metadata", "_common_metadata"];
+
+/// The default size of the string columns of a block, 64MB.
+pub const DEFAULT_MAX_STRING_BLOCK_BYTES: usize = 64 * 1024 * 1024;
+
+/// The default size limit of the standard input read by the path `-`, 256MB.
+pub const DEFAULT_MAX_STDIN_SIZE: usize = 256 * 1024 * 1024;
+
+impl ParquetTableOptions {
+    /// Whether the file at `location` is a non-data file to skip.
+    pub fn is_sidecar_file(&self, location: &str) -> bool {
+        let name = location.rsplit('/').next().unwrap_or(location);
+        match &self.sidecar_files {
+            Some(sidecar_files) => sidecar_files.iter().any(|file| file == name),
+            None => DEFAULT_SIDECAR_FILES.contains(&name),
+        }
+    }
+
+    /// The read-ahead window of the sources, if the option `min_read_ahead` or
+    /// `max_read_ahead` is set.
+    pub fn read_ahead_window(&self) -> Option<ReadAheadWindow> {
+        match (self.min_read_ahead, self.max_read_ahead) {
+            (None, None) => None,
+            (Some(min), None) => Some(ReadAheadWindow::create(min, min)),
+            (None, Some(max)) => Some(ReadAheadWindow::create(1, max)),
+            (Some(min), Some(max)) => Some(ReadAheadWindow::create(min, max)),
+        }
+    }
+
+    /// Set the option `name` to `value`.
+    ///
+    /// Returns `false` if `name` is not an option of `read_parquet`.
+    fn set(&mut self, name: &str, value: &str) -> Result<bool> {
+        match name {
+            "min_file_size" => self.min_file_size = Some(parse_option(name, value)?),
+            "max_block_rows" => self.max_block_rows = Some(parse_positive(name, value)?),
+            "batch_size" => self.batch_size = Some(parse_positive(name, value)?),
+            "max_string_block_bytes" => {
+                self.max_string_block_bytes = Some(parse_positive(name, value)?)
+            }
+            "memory_budget" => self.memory_budget = Some(parse_positive(name, value)?),
+            "coalesce_file_size" => self.coalesce_file_size = Some(parse_positive(name, value)?),
+            "max_stdin_size" => self.max_stdin_size = Some(parse_positive(name, value)?),
+            "max_concurrent_requests" => {
+                self.max_concurrent_requests = Some(parse_positive(name, value)?)
+            }
+            "max_open_files" => self.max_open_files = Some(parse_positive(name, value)?),
+            "fraction" => {
+                let fraction: f64 = parse_option(name, value)?;
+                if !(fraction > 0.0 && fraction <= 1.0) {
+                    return Err(ErrorCode::BadArguments(
+                        "read_parquet option 'fraction' must be greater than 0 and at most 1",
+                    ));
+                }
+                self.sample_fraction = Some(fraction);
+            }
+            "min_read_ahead" => self.min_read_ahead = Some(parse_positive(name, value)?),
+            "max_read_ahead" => self.max_read_ahead = Some(parse_positive(name, value)?),
+            "verify_checksums" => self.verify_checksums = parse_option(name, value)?,
+            "mmap" => self.mmap = parse_option(name, value)?,
+            "hive_partitioning" => self.hive_partitioning = parse_option(name, value)?,
+            "row_group_index" => self.row_group_index = parse_option(name, value)?,
+            "partition_values" => {
+                let mapping = value
+                    .split(';')
+                    .map(str::trim)
+                    .filter(|entry| !entry.is_empty())
+                    .map(parse_partition_values)
+                    .collect::<Option<Vec<_>>>();
+                match mapping {
+                    Some(mapping) if !mapping.is_empty() => self.partition_values = Some(mapping),
+                    _ => {
+                        return Err(ErrorCode::BadArguments(format!(
+                            "invalid value '{}' for read_parquet option 'partition_values': expect '<pattern>:<column>=<value>,...' entries separated by ';'",
+                            value
+                        )));
+                    }
+                }
+            }
+            // The secrets would stay in the arguments of the table, which end up in the
+            // plans and in the query log. The value is left out of the error.
+            "credentials" => {
+                return Err(ErrorCode::BadArguments(
+                    "read_parquet option 'credentials' is not supported: the credentials of the path prefixes are set by the config 'storage.parquet_credentials'",
+                ));
+            }
+            "literals" => {
+                let literals = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|literal| !literal.is_empty())
+                    .map(parse_literal)
+                    .collect::<Option<Vec<_>>>();
+                match literals {
+                    Some(literals) if !literals.is_empty() => self.literals = Some(literals),
+                    _ => {
+                        return Err(ErrorCode::BadArguments(format!(
+                            "invalid value '{}' for read_parquet option 'literals': expect '<column>:<value>[::<type>]' entries separated by ','",
+                            value
+                        )));
+                    }
+                }
+            }
+            "columns" => {
+                let columns = value
+                    .split(',')
+                    .map(|column| column.trim().to_lowercase())
+                    .collect::<Vec<_>>();
+                if columns.iter().any(|column| column.is_empty()) {
+                    return Err(ErrorCode::BadArguments(format!(
+                        "invalid value '{}' for read_parquet option 'columns': empty column name",
+                        value
+                    )));
+                }
+                self.columns = Some(columns);
+            }
+            "columns_like" => self.columns_like = Some(value.to_lowercase()),
+            "sidecar_files" => {
+                let sidecar_files = value
+                    .split(',')
+                    .map(|file| file.trim().to_string())
+                    .filter(|file| !file.is_empty())
+                    .collect();
+                self.sidecar_files = Some(sidecar_files);
+            }
+            "use_metadata_file" => self.use_metadata_file = parse_option(name, value)?,
+            "page_null_counts" => self.page_null_counts = parse_option(name, value)?,
+            "max_scan_bytes" => self.max_scan_bytes = Some(parse_option(name, value)?),
+            "sorted_by" => self.sorted_by = Some(value.to_lowercase()),
+            "cluster_by" => self.cluster_by = Some(value.to_lowercase()),
+            "timezone" => self.timezone = Some(parse_option(name, value)?),
+            "no_prune" => self.no_prune = parse_option(name, value)?,
+            "cast" => {
+                let mut casts = vec![];
+                let pairs = split_outside_parentheses(value, ',');
+                for cast in pairs.into_iter().map(str::trim).filter(|c| !c.is_empty()) {
+                    match cast.split_once(':') {
+                        Some((column, data_type))
+                            if !column.trim().is_empty() && !data_type.trim().is_empty() =>
+                        {
+                            casts.push((column.trim().to_lowercase(), data_type.trim().to_string()))
+                        }
+                        _ => {
+                            return Err(ErrorCode::BadArguments(format!(
+                                "invalid value '{}' for read_parquet option 'cast': expect '<column>:<type>' pairs",
+                                value
+                            )));
+                        }
+                    }
+                }
+                self.cast = Some(casts);
+            }
+            "on_error" => {
+                self.skip_corrupt_row_groups =
+                    parse_choice(name, value, &[("abort", false), ("skip", true)])?
+            }
+            "nullability" => {
+                self.observed_nullability =
+                    parse_choice(name, value, &[("declared", false), ("observed", true)])?
+            }
+            "on_memory_budget" => {
+                self.error_on_memory_budget =
+                    parse_choice(name, value, &[("split", false), ("error", true)])?
+            }
+            "on_timestamp_overflow" => {
+                self.saturate_timestamps =
+                    parse_choice(name, value, &[("error", false), ("saturate", true)])?
+            }
+            "on_mixed_time_units" => {
+                self.error_on_mixed_time_units =
+                    parse_choice(name, value, &[("normalize", false), ("error", true)])?
+            }
+            "decimal_byte_order" => {
+                self.little_endian_decimals =
+                    parse_choice(name, value, &[("big_endian", false), ("little_endian", true)])?
+            }
+            "decimal_rounding" => self.decimal_rounding = Some(parse_option(name, value)?),
+            "on_missing_columns" => self.on_missing_columns = Some(parse_option(name, value)?),
+            "on_unclustered" => {
+                self.warn_on_unclustered =
+                    parse_choice(name, value, &[("error", false), ("warn", true)])?
+            }
+            "warnings" => {
+                self.collect_warnings =
+                    parse_choice(name, value, &[("log", false), ("collect", true)])?
+            }
+            "on_duplicate" => {
+                self.suffix_duplicate_columns =
+                    parse_choice(name, value, &[("error", false), ("suffix", true)])?
+            }
+            "converted_types" => {
+                self.ignore_converted_types =
+                    parse_choice(name, value, &[("fallback", false), ("ignore", true)])?
+            }
+            "column_order" => {
+                self.sort_columns_by_name =
+                    parse_choice(name, value, &[("first_file", false), ("name", true)])?
+            }
+            "merge_by" => self.merge_by = Some(parse_option(name, value)?),
+            "file_order" => self.file_order = Some(parse_option(name, value)?),
+            "part_order" => {
+                self.part_order = parse_choice(name, value, &[
+                    ("sequential", None),
+                    ("size_desc", Some(PartOrder::SizeDesc)),
+                    ("size_asc", Some(PartOrder::SizeAsc)),
+                ])?
+            }
+            "name_transform" => {
+                self.name_transform = parse_choice(name, value, &[
+                    ("none", None),
+                    ("trim", Some(NameTransform::Trim)),
+                    ("snake", Some(NameTransform::Snake)),
+                ])?
+            }
+            "identifier_policy" => {
+                self.identifier_policy = parse_choice(name, value, &[
+                    ("none", None),
+                    ("prefix", Some(IdentifierPolicy::Prefix)),
+                ])?
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+/// Split the arguments of `read_parquet` into file path patterns and options.
+pub fn parse_table_args(table_args: &[DataValue]) -> Result<(Vec<String>, ParquetTableOptions)> {
+    let mut patterns = Vec::with_capacity(table_args.len());
+    let mut options = ParquetTableOptions::default();
+
+    for arg in table_args {
+        let arg = match arg {
+            DataValue::String(arg) => String::from_utf8(arg.clone())
+                .map_err(|e| ErrorCode::BadArguments(format!("invalid string argument: {}", e)))?,
+            _ => {
+                return Err(ErrorCode::BadArguments(
+                    "read_parquet only accepts string arguments",
+                ));
+            }
+        };
+
+        let is_option = match arg.split_once('=') {
+            Some((name, value)) => options.set(&name.trim().to_lowercase(), value.trim())?,
+            None => false,
+        };
+        if !is_option {
+            patterns.push(arg);
+        }
+    }
+
+    if let (Some(min), Some(max)) = (options.min_read_ahead, options.max_read_ahead) {
+        if min > max {
+            return Err(ErrorCode::BadArguments(format!(
+                "read_parquet option 'min_read_ahead' ({}) is greater than 'max_read_ahead' ({})",
+                min, max
+            )));
+        }
+    }
+
+    if options.hive_partitioning && options.partition_values.is_some() {
+        return Err(ErrorCode::BadArguments(
+            "read_parquet options 'hive_partitioning' and 'partition_values' can't be used together",
+        ));
+    }
+
+    if options.sorted_by.is_some()
+        && matches!(options.file_order, Some(order) if order != FileOrder::Name)
+    {
+        return Err(ErrorCode::BadArguments(
+            "read_parquet option 'sorted_by' needs the files in the order of their paths, it can only be used with 'file_order=name'",
+        ));
+    }
+
+    if options.cluster_by.is_some()
+        && (options.file_order.is_some() || options.sorted_by.is_some())
+    {
+        return Err(ErrorCode::BadArguments(
+            "read_parquet option 'cluster_by' orders the files by the column, it can't be used with 'file_order' or 'sorted_by'",
+        ));
+    }
+
+    if options.cluster_by.is_some() && options.part_order.is_some() {
+        return Err(ErrorCode::BadArguments(
+            "read_parquet option 'cluster_by' orders the parts by the column, it can't be used with 'part_order'",
+        ));
+    }
+
+    if options.columns_like.is_some() && options.merge_by == Some(MergeBy::Position) {
+        return Err(ErrorCode::BadArguments(
+            "read_parquet option 'columns_like' finds the columns of the files by name, it can't be used with 'merge_by=position'",
+        ));
+    }
+
+    if options.sort_columns_by_name && options.merge_by == Some(MergeBy::Position) {
+        return Err(ErrorCode::BadArguments(
+            "read_parquet option 'column_order=name' finds the columns of the files by name, it can't be used with 'merge_by=position'",
+        ));
+    }
+
+    Ok((patterns, options))
+}
+
+/// Parse an entry `<column>:<value>[::<type>]` of the option `literals`, the column name is
+/// lower cased.
+fn parse_literal(entry: &str) -> Option<(String, String, Option<String>)> {
+    let (name, value) = entry.split_once(':')?;
+    let name = name.trim().to_lowercase();
+    let (value, data_type) = match value.rsplit_once("::") {
+        Some((value, data_type)) if !data_type.trim().is_empty() => {
+            (value, Some(data_type.trim().to_string()))
+        }
+        Some(_) => return None,
+        None => (value, None),
+    };
+    (!name.is_empty()).then(|| (name, value.trim().to_string(), data_type))
+}
+
+/// Parse an entry `<pattern>:<column>=<value>,...` of the option `partition_values`, the
+/// pattern ends at the last `:` before the first `=`. The column names are lower cased.
+fn parse_partition_values(entry: &str) -> Option<(String, Vec<(String, String)>)> {
+    let colon = entry[..entry.find('=')?].rfind(':')?;
+    let pattern = entry[..colon].trim();
+    let partitions = entry[colon + 1..]
+        .split(',')
+        .map(|partition| {
+            let (name, value) = partition.split_once('=')?;
+            let name = name.trim().to_lowercase();
+            (!name.is_empty()).then(|| (name, value.trim().to_string()))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    (!pattern.is_empty()).then(|| (pattern.to_string(), partitions))
+}
+
+/// Split `value` at the `separator`s outside of parentheses, like the ones of the types
+/// `decimal(18, 2)`.
+fn split_outside_parentheses(value: &str, separator: char) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in value.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            c if c == separator && depth == 0 => {
+                parts.push(&value[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&value[start..]);
+    parts
+}
+
+fn parse_option<T>(name: &str, value: &str) -> Result<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    value.parse::<T>().map_err(|e| {
+        ErrorCode::BadArguments(format!(
+            "invalid value '{}' for read_parquet option '{}': {}",
+            value, name, e
+        ))
+    })
+}
+
+/// Parse the value of the option `name`, which must be greater than 0.
+fn parse_positive<T>(name: &str, value: &str) -> Result<T>
+where
+    T: FromStr + Default + PartialEq,
+    T::Err: Display,
+{
+    let value = parse_option(name, value)?;
+    if value == T::default() {
+        return Err(ErrorCode::BadArguments(format!(
+            "read_parquet option '{}' must be greater than 0",
+            name
+        )));
+    }
+    Ok(value)
+}
+
+/// Parse the value of the option `name`, one of the names of `choices`, case insensitive.
+fn parse_choice<T: Copy>(name: &str, value: &str, choices: &[(&str, T)]) -> Result<T> {
+    choose(value, choices).map_err(|e| {
+        ErrorCode::BadArguments(format!(
+            "invalid value '{}' for read_parquet option '{}': {}",
+            value, name, e
+        ))
+    })
+}
+
+/// Find the choice named `value` among `choices`, case insensitive, for [`parse_choice`] and
+/// the [`FromStr`] implementations of the enum options. The error lists the names expected.
+fn choose<T: Copy>(value: &str, choices: &[(&str, T)]) -> std::result::Result<T, String> {
+    let value = value.to_lowercase();
+    if let Some((_, choice)) = choices.iter().find(|(name, _)| *name == value) {
+        return Ok(*choice);
+    }
+    let mut names = choices
+        .iter()
+        .map(|(name, _)| format!("'{}'", name))
+        .collect::<Vec<_>>();
+    let last = names.pop().unwrap_or_default();
+    if names.is_empty() {
+        Err(format!("expect {}", last))
+    } else {
+        Err(format!("expect {} or {}", names.join(", "), last))
+    }
+}
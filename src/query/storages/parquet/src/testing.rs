@@ -0,0 +1,218 @@
+//  Copyright 2022 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+//! Utilities to test `read_parquet` end to end without fixtures on disk, enabled by the
+//! feature `testing`.
+
+use std::sync::Arc;
+
+use common_arrow::arrow::chunk::Chunk;
+use common_arrow::arrow::io::parquet::write::transverse;
+use common_arrow::arrow::io::parquet::write::RowGroupIterator;
+use common_arrow::arrow::io::parquet::write::WriteOptions;
+use common_arrow::parquet::compression::CompressionOptions;
+use common_arrow::parquet::encoding::Encoding;
+use common_arrow::parquet::write::Version;
+use common_arrow::parquet::write::WriteOptions as FileWriteOptions;
+use common_arrow::write_parquet_file;
+use common_datablocks::DataBlock;
+use common_datavalues::DataValue;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use opendal::raw::Accessor;
+use opendal::raw::AccessorCapability;
+use opendal::raw::AccessorMetadata;
+use opendal::raw::BytesReader;
+use opendal::raw::ObjectPager;
+use opendal::raw::RpList;
+use opendal::raw::RpRead;
+use opendal::raw::RpStat;
+use opendal::Layer;
+use opendal::OpList;
+use opendal::OpRead;
+use opendal::OpStat;
+use opendal::Operator;
+
+use crate::ParquetTable;
+use crate::ParquetTableBuilder;
+
+/// Parquet files written in memory from [`DataBlock`]s, read by a [`ParquetTable`].
+///
+/// Each block is written as a row group of its file, so the blocks given to
+/// [`ParquetFixture::with_file`] choose the row group boundaries. The files are written
+/// with statistics, uncompressed and with `DATA_PAGE_V2` pages by default.
+///
+/// ```ignore
+/// let table = ParquetFixture::create()?
+///     .with_file("data/a.parquet", vec![block_1, block_2])?
+///     .with_option("merge_by=name")
+///     .build()?;
+/// ```
+pub struct ParquetFixture {
+    operator: Operator,
+    locations: Vec<String>,
+    options: Vec<String>,
+    write_statistics: bool,
+    compression: CompressionOptions,
+    version: Version,
+}
+
+impl ParquetFixture {
+    pub fn create() -> Result<ParquetFixture> {
+        let operator = Operator::new(opendal::services::memory::Builder::default().build()?);
+        Ok(ParquetFixture {
+            operator,
+            locations: vec![],
+            options: vec![],
+            write_statistics: true,
+            compression: CompressionOptions::Uncompressed,
+            version: Version::V2,
+        })
+    }
+
+    /// Write the statistics of the next files, to test the pruning without them.
+    pub fn with_statistics(mut self, write_statistics: bool) -> Self {
+        self.write_statistics = write_statistics;
+        self
+    }
+
+    /// The compression of the next files.
+    pub fn with_compression(mut self, compression: CompressionOptions) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// The version of the data pages of the next files, `V1` for `DATA_PAGE` and `V2` for
+    /// `DATA_PAGE_V2`.
+    pub fn with_version(mut self, version: Version) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Write a file at `location` with a row group per block of `blocks`, which all have
+    /// the schema of the first one. The table reads the files in the order they are added.
+    pub fn with_file(mut self, location: &str, blocks: Vec<DataBlock>) -> Result<Self> {
+        let buf = self.write_file(blocks)?;
+        self.operator.object(location).blocking_write(buf)?;
+        self.locations.push(location.to_string());
+        Ok(self)
+    }
+
+    /// Add the `read_parquet` option `option`, like `merge_by=name`.
+    pub fn with_option(mut self, option: &str) -> Self {
+        self.options.push(option.to_string());
+        self
+    }
+
+    /// The operator the files are written to, to add other files to it.
+    pub fn operator(&self) -> Operator {
+        self.operator.clone()
+    }
+
+    /// The table reading the files with the options.
+    pub fn build(self) -> Result<Arc<ParquetTable>> {
+        let table_args = self
+            .locations
+            .iter()
+            .chain(self.options.iter())
+            .map(|arg| DataValue::String(arg.as_bytes().to_vec()))
+            .collect();
+        ParquetTableBuilder::create("system", "read_parquet", 1, table_args)
+            .with_operator(self.operator)
+            .build()
+    }
+
+    fn write_file(&self, blocks: Vec<DataBlock>) -> Result<Vec<u8>> {
+        let schema = match blocks.first() {
+            Some(block) => block.schema().clone(),
+            None => {
+                return Err(ErrorCode::BadArguments(
+                    "a parquet fixture file needs at least one block",
+                ));
+            }
+        };
+        let arrow_schema = schema.to_arrow();
+        let chunks = blocks
+            .into_iter()
+            .map(Chunk::try_from)
+            .collect::<Result<Vec<_>>>()?;
+        let encodings = arrow_schema
+            .fields
+            .iter()
+            .map(|field| transverse(&field.data_type, |_| Encoding::Plain))
+            .collect();
+        let options = WriteOptions {
+            write_statistics: self.write_statistics,
+            compression: self.compression,
+            version: self.version,
+        };
+        let row_groups = RowGroupIterator::try_new(
+            chunks.into_iter().map(Ok),
+            &arrow_schema,
+            options,
+            encodings,
+        )?;
+        let file_options = FileWriteOptions {
+            write_statistics: self.write_statistics,
+            version: self.version,
+        };
+        let mut buf = vec![];
+        write_parquet_file(&mut buf, row_groups, arrow_schema, file_options)?;
+        Ok(buf)
+    }
+}
+
+/// A layer hiding the blocking API of an operator, like the ones of s3 and gcs which have
+/// none, to test the reads through the async API. Its blocking calls fail.
+///
+/// ```ignore
+/// let operator = ParquetFixture::create()?.operator().layer(NonBlockingLayer);
+/// assert!(!operator.metadata().can_blocking());
+/// ```
+#[derive(Clone, Debug)]
+pub struct NonBlockingLayer;
+
+impl Layer for NonBlockingLayer {
+    fn layer(&self, inner: Arc<dyn Accessor>) -> Arc<dyn Accessor> {
+        Arc::new(NonBlockingAccessor { inner })
+    }
+}
+
+/// The accessor of [`NonBlockingLayer`]. It doesn't return its inner accessor, so that
+/// the blocking calls are not forwarded to it.
+#[derive(Debug)]
+struct NonBlockingAccessor {
+    inner: Arc<dyn Accessor>,
+}
+
+#[async_trait::async_trait]
+impl Accessor for NonBlockingAccessor {
+    fn metadata(&self) -> AccessorMetadata {
+        let mut meta = self.inner.metadata();
+        meta.set_capabilities(AccessorCapability::Read | AccessorCapability::List);
+        meta
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> opendal::Result<(RpRead, BytesReader)> {
+        self.inner.read(path, args).await
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> opendal::Result<RpStat> {
+        self.inner.stat(path, args).await
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> opendal::Result<(RpList, ObjectPager)> {
+        self.inner.list(path, args).await
+    }
+}
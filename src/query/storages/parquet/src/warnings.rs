@@ -0,0 +1,157 @@
+//  Copyright 2022 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use parking_lot::Mutex;
+use tracing::warn;
+
+/// The kinds of the non-fatal issues of a [`crate::ParquetTable`], see [`ParquetWarning`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WarningKind {
+    /// A column is read as another type than the one of the files, by the option `cast`.
+    Coercion,
+    /// A file matched by the paths is not read, like the ones under `min_file_size`.
+    SkippedFile,
+    /// A row group failing to decode is skipped, by `on_error=skip`.
+    SkippedRowGroup,
+    /// A row group failing its page checksums is skipped, by `on_error=skip`.
+    ChecksumMismatch,
+    /// The files are not clustered by `cluster_by`, by `on_unclustered=warn`.
+    Unclustered,
+    /// A TIMESTAMP column has different units in the files, its values are scaled to the
+    /// same unit, by `on_mixed_time_units=normalize`.
+    MixedTimeUnits,
+}
+
+impl WarningKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WarningKind::Coercion => "coercion",
+            WarningKind::SkippedFile => "skipped_file",
+            WarningKind::SkippedRowGroup => "skipped_row_group",
+            WarningKind::ChecksumMismatch => "checksum_mismatch",
+            WarningKind::Unclustered => "unclustered",
+            WarningKind::MixedTimeUnits => "mixed_time_units",
+        }
+    }
+}
+
+/// A non-fatal issue met while building or scanning a [`crate::ParquetTable`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParquetWarning {
+    pub kind: WarningKind,
+    /// The location of the file it is about, if it is about one.
+    pub location: Option<String>,
+    /// The row group of the file it is about, if it is about one.
+    pub row_group: Option<usize>,
+    /// The column it is about, if it is about one.
+    pub column: Option<String>,
+    pub message: String,
+}
+
+impl ParquetWarning {
+    pub fn create(kind: WarningKind, message: String) -> Self {
+        ParquetWarning {
+            kind,
+            location: None,
+            row_group: None,
+            column: None,
+            message,
+        }
+    }
+
+    pub fn with_location(mut self, location: &str) -> Self {
+        self.location = Some(location.to_string());
+        self
+    }
+
+    pub fn with_row_group(mut self, row_group: usize) -> Self {
+        self.row_group = Some(row_group);
+        self
+    }
+
+    pub fn with_column(mut self, column: &str) -> Self {
+        self.column = Some(column.to_string());
+        self
+    }
+}
+
+/// The warnings of a [`crate::ParquetTable`], shared by the sources of its scans.
+///
+/// The warnings are always logged, and collected in the order they are met if the option
+/// `warnings=collect` is set, to be returned along with the result of the query by
+/// [`crate::ParquetTable::warnings`]. Only the warnings of the sources of the local node are
+/// collected.
+pub struct ParquetWarnings {
+    collect: bool,
+    warnings: Mutex<Vec<ParquetWarning>>,
+}
+
+impl ParquetWarnings {
+    pub fn create(collect: bool) -> Self {
+        ParquetWarnings {
+            collect,
+            warnings: Mutex::new(vec![]),
+        }
+    }
+
+    pub fn add(&self, warning: ParquetWarning) {
+        warn!("read_parquet {}: {}", warning.kind.as_str(), warning.message);
+        if self.collect {
+            self.warnings.lock().push(warning);
+        }
+    }
+
+    pub fn get(&self) -> Vec<ParquetWarning> {
+        self.warnings.lock().clone()
+    }
+
+    /// The schema of the warnings as a result set, see [`ParquetWarnings::to_block`].
+    pub fn schema() -> DataSchemaRef {
+        DataSchemaRefExt::create(vec![
+            DataField::new("kind", Vu8::to_data_type()),
+            DataField::new_nullable("file", Vu8::to_data_type()),
+            DataField::new_nullable("row_group", u64::to_data_type()),
+            DataField::new_nullable("column", Vu8::to_data_type()),
+            DataField::new("message", Vu8::to_data_type()),
+        ])
+    }
+
+    /// The warnings collected so far as a block of [`ParquetWarnings::schema`], one row each.
+    pub fn to_block(&self) -> DataBlock {
+        let warnings = self.warnings.lock();
+        let len = warnings.len();
+        let mut kinds: Vec<Vec<u8>> = Vec::with_capacity(len);
+        let mut files: Vec<Option<Vec<u8>>> = Vec::with_capacity(len);
+        let mut row_groups: Vec<Option<u64>> = Vec::with_capacity(len);
+        let mut columns: Vec<Option<Vec<u8>>> = Vec::with_capacity(len);
+        let mut messages: Vec<Vec<u8>> = Vec::with_capacity(len);
+        for warning in warnings.iter() {
+            kinds.push(warning.kind.as_str().as_bytes().to_vec());
+            files.push(warning.location.clone().map(String::into_bytes));
+            row_groups.push(warning.row_group.map(|row_group| row_group as u64));
+            columns.push(warning.column.clone().map(String::into_bytes));
+            messages.push(warning.message.clone().into_bytes());
+        }
+
+        DataBlock::create(Self::schema(), vec![
+            Series::from_data(kinds),
+            Series::from_data(files),
+            Series::from_data(row_groups),
+            Series::from_data(columns),
+            Series::from_data(messages),
+        ])
+    }
+}
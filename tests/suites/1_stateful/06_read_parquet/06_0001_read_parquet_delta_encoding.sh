@@ -0,0 +1,11 @@
+#!/usr/bin/env bash
+
+CURDIR=$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)
+. "$CURDIR"/../../../shell_env.sh
+
+aws --endpoint-url http://127.0.0.1:9900/ s3 cp s3://testbucket/admin/data/parquet/delta_encoding.parquet /tmp/06_0001_delta_encoding.parquet  > /dev/null 2>&1
+
+# `id` is DELTA_BINARY_PACKED, `name` is DELTA_BYTE_ARRAY.
+echo "select id, name from read_parquet('/tmp/06_0001_delta_encoding.parquet') order by id" | $MYSQL_CLIENT_CONNECT
+
+echo "select count(*) from read_parquet('/tmp/06_0001_delta_encoding.parquet') where name like 'ban%'" | $MYSQL_CLIENT_CONNECT
@@ -0,0 +1,21 @@
+#!/usr/bin/env bash
+
+CURDIR=$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)
+. "$CURDIR"/../../../shell_env.sh
+
+DATA_DIR=/tmp/06_0002_read_parquet_min_file_size
+rm -rf $DATA_DIR && mkdir -p $DATA_DIR
+
+# ontime_200.parquet is 28087 bytes, the tiny files are partial writes that are not valid parquet.
+aws --endpoint-url http://127.0.0.1:9900/ s3 cp s3://testbucket/admin/data/ontime_200.parquet $DATA_DIR/ontime_200.parquet  > /dev/null 2>&1
+echo -n "PAR1" > $DATA_DIR/tiny_1.parquet
+echo -n "partial" > $DATA_DIR/tiny_2.parquet
+
+echo "select count(*) from read_parquet('$DATA_DIR/*', 'min_file_size=1024')" | $MYSQL_CLIENT_CONNECT
+
+# files exactly at the threshold are kept
+echo "select count(*) from read_parquet('$DATA_DIR/*', 'min_file_size=28087')" | $MYSQL_CLIENT_CONNECT
+
+echo "select count(*) from read_parquet('$DATA_DIR/*', 'min_file_size=28088')" | $MYSQL_CLIENT_CONNECT 2>&1 | grep -c "No matched files found"
+
+rm -rf $DATA_DIR
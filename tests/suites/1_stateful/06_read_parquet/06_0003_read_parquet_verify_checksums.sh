@@ -0,0 +1,20 @@
+#!/usr/bin/env bash
+
+CURDIR=$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)
+. "$CURDIR"/../../../shell_env.sh
+
+DATA_DIR=/tmp/06_0003_read_parquet_verify_checksums
+rm -rf $DATA_DIR && mkdir -p $DATA_DIR
+
+# Every page carries a CRC, the last byte of the `name` page in the corrupted file is flipped ('c' -> 'b').
+aws --endpoint-url http://127.0.0.1:9900/ s3 cp s3://testbucket/admin/data/parquet/page_checksum.parquet $DATA_DIR/page_checksum.parquet  > /dev/null 2>&1
+aws --endpoint-url http://127.0.0.1:9900/ s3 cp s3://testbucket/admin/data/parquet/page_checksum_corrupted.parquet $DATA_DIR/page_checksum_corrupted.parquet  > /dev/null 2>&1
+
+echo "select id, name from read_parquet('$DATA_DIR/page_checksum.parquet', 'verify_checksums=true') order by id" | $MYSQL_CLIENT_CONNECT
+
+# checksums are not verified by default
+echo "select id, name from read_parquet('$DATA_DIR/page_checksum_corrupted.parquet') order by id" | $MYSQL_CLIENT_CONNECT
+
+echo "select id, name from read_parquet('$DATA_DIR/page_checksum_corrupted.parquet', 'verify_checksums=true')" | $MYSQL_CLIENT_CONNECT 2>&1 | grep -c "Checksum mismatch of page 0 of column 'name' in row group 0"
+
+rm -rf $DATA_DIR
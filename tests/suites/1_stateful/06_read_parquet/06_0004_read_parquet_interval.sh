@@ -0,0 +1,9 @@
+#!/usr/bin/env bash
+
+CURDIR=$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)
+. "$CURDIR"/../../../shell_env.sh
+
+aws --endpoint-url http://127.0.0.1:9900/ s3 cp s3://testbucket/admin/data/parquet/interval.parquet /tmp/06_0004_interval.parquet  > /dev/null 2>&1
+
+# `iv` is an optional INTERVAL column, read as (months, days, milliseconds).
+echo "select id, iv from read_parquet('/tmp/06_0004_interval.parquet') order by id" | $MYSQL_CLIENT_CONNECT
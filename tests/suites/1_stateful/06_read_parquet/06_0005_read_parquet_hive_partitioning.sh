@@ -0,0 +1,20 @@
+#!/usr/bin/env bash
+
+CURDIR=$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)
+. "$CURDIR"/../../../shell_env.sh
+
+DATA_DIR=/tmp/06_0005_read_parquet_hive_partitioning
+rm -rf $DATA_DIR && mkdir -p $DATA_DIR/year=2022 $DATA_DIR/year=2023
+
+aws --endpoint-url http://127.0.0.1:9900/ s3 cp s3://testbucket/admin/data/parquet/page_checksum.parquet $DATA_DIR/year=2022/data.parquet  > /dev/null 2>&1
+# The file of the other partition is not a parquet file, reading it fails.
+echo "not a parquet file" > $DATA_DIR/year=2023/data.parquet
+
+echo "select id, name, year from read_parquet('$DATA_DIR/*/*.parquet', 'hive_partitioning=true') where year = 2022 order by id" | $MYSQL_CLIENT_CONNECT
+
+# only the partition column is projected
+echo "select year, count(*) from read_parquet('$DATA_DIR/*/*.parquet', 'hive_partitioning=true') where year < 2023 group by year" | $MYSQL_CLIENT_CONNECT
+
+echo "select id, name, year from read_parquet('$DATA_DIR/*/*.parquet', 'hive_partitioning=true')" | $MYSQL_CLIENT_CONNECT 2>&1 | grep -c "year=2023/data.parquet''s meta error"
+
+rm -rf $DATA_DIR
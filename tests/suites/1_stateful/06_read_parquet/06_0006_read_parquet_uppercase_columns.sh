@@ -0,0 +1,16 @@
+#!/usr/bin/env bash
+
+CURDIR=$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)
+. "$CURDIR"/../../../shell_env.sh
+
+DATA_DIR=/tmp/06_0006_read_parquet_uppercase_columns
+rm -rf $DATA_DIR && mkdir -p $DATA_DIR
+
+# Columns `UserId` and `Info` (a tuple of `UserName`).
+aws --endpoint-url http://127.0.0.1:9900/ s3 cp s3://testbucket/admin/data/parquet/uppercase_columns.parquet $DATA_DIR/uppercase_columns.parquet  > /dev/null 2>&1
+
+echo "select UserId, Info:UserName from read_parquet('$DATA_DIR/uppercase_columns.parquet') order by UserId" | $MYSQL_CLIENT_CONNECT
+
+echo "select userid, info:username from read_parquet('$DATA_DIR/uppercase_columns.parquet') where userid > 1 order by userid" | $MYSQL_CLIENT_CONNECT
+
+rm -rf $DATA_DIR
@@ -0,0 +1,13 @@
+#!/usr/bin/env bash
+
+CURDIR=$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)
+. "$CURDIR"/../../../shell_env.sh
+
+aws --endpoint-url http://127.0.0.1:9900/ s3 cp s3://testbucket/admin/data/parquet/mixed_compression.parquet /tmp/06_0007_mixed_compression.parquet  > /dev/null 2>&1
+
+# `id` is compressed by snappy and `name` by zstd.
+echo "select id, name from read_parquet('/tmp/06_0007_mixed_compression.parquet') order by id" | $MYSQL_CLIENT_CONNECT
+
+echo "select name from read_parquet('/tmp/06_0007_mixed_compression.parquet') where id > 2 order by id" | $MYSQL_CLIENT_CONNECT
+
+rm -f /tmp/06_0007_mixed_compression.parquet
@@ -0,0 +1,18 @@
+#!/usr/bin/env bash
+
+CURDIR=$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)
+. "$CURDIR"/../../../shell_env.sh
+
+DATA_DIR=/tmp/06_0008_read_parquet_timestamp_units
+rm -rf $DATA_DIR && mkdir -p $DATA_DIR
+
+# The same `ts` values stored in milliseconds, microseconds and nanoseconds.
+for unit in millis micros nanos; do
+	aws --endpoint-url http://127.0.0.1:9900/ s3 cp s3://testbucket/admin/data/parquet/timestamp_$unit.parquet $DATA_DIR/timestamp_$unit.parquet  > /dev/null 2>&1
+done
+
+for unit in millis micros nanos; do
+	echo "select id, ts from read_parquet('$DATA_DIR/timestamp_$unit.parquet') order by id" | $MYSQL_CLIENT_CONNECT
+done
+
+rm -rf $DATA_DIR